@@ -0,0 +1,169 @@
+//! Derive macro of `lab01_2022_input_validation`, enabled by its `derive` feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::meta::ParseNestedMeta;
+use syn::{parse_macro_input, Data, DeriveInput, LitBool, LitStr, Member};
+
+/// Rule of a field, given by a `#[valid(...)]` attribute.
+enum Rule {
+    /// `url` or `url(whitelist = ".ch,.com")`
+    Url { whitelist: Vec<String> },
+    /// `uuid_v5`
+    UuidV5,
+    /// `file`, or `file(kind = "image", check_extension = false)`
+    File { kind: Option<String>, check_extension: bool },
+}
+
+/// Kinds of files of the `file` rule, and their variant of `FileKind`.
+const FILE_KINDS: [(&str, &str); 6] = [
+    ("image", "Image"),
+    ("video", "Video"),
+    ("audio", "Audio"),
+    ("archive", "Archive"),
+    ("document", "Document"),
+    ("book", "Book"),
+];
+
+/// Derive a `validate(&self) -> Result<(), ValidationErrors>` method checking every annotated
+/// field and collecting all their errors, instead of stopping at the first one.
+///
+/// The rules of a field are listed in its `#[valid(...)]` attributes:
+/// - `url`, or `url(whitelist = ".ch,.com")` to accept only some top level domains, on a field
+///   implementing `AsRef<str>`
+/// - `uuid_v5`, on a field implementing `AsRef<str>`
+/// - `file`, or `file(kind = "image", check_extension = false)`, on a field implementing
+///   `AsRef<Path>`; the extension is checked unless disabled
+///
+/// # Examples
+/// ``` ignore
+/// #[derive(ValidateInput)]
+/// struct Upload {
+///     #[valid(url(whitelist = ".ch,.com"))]
+///     source: String,
+///     #[valid(uuid_v5)]
+///     id: String,
+///     #[valid(file(kind = "image"))]
+///     path: PathBuf,
+/// }
+///
+/// if let Err(errors) = upload.validate() {
+///     println!("{}", errors);
+/// }
+/// ```
+#[proc_macro_derive(ValidateInput, attributes(valid))]
+pub fn derive_validate_input(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => return Err(syn::Error::new_spanned(&input.ident, "ValidateInput can only be derived for structs")),
+    };
+
+    let mut checks = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let (member, name) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.to_string()),
+            None => (Member::Unnamed(index.into()), index.to_string()),
+        };
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("valid")) {
+            let mut rules = Vec::new();
+            attr.parse_nested_meta(|meta| {
+                rules.push(parse_rule(&meta)?);
+                Ok(())
+            })?;
+            for rule in rules {
+                let check = check(&rule, &member);
+                checks.push(quote! {
+                    if let ::core::result::Result::Err(error) = #check {
+                        errors.push(#name, error);
+                    }
+                });
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #ident #type_generics #where_clause {
+            /// Validate the annotated fields, with the errors of every invalid one.
+            pub fn validate(&self) -> ::core::result::Result<(), ::lab01_2022_input_validation::ValidationErrors> {
+                let mut errors = ::lab01_2022_input_validation::ValidationErrors::new();
+                #(#checks)*
+                errors.into_result()
+            }
+        }
+    })
+}
+
+fn parse_rule(meta: &ParseNestedMeta) -> syn::Result<Rule> {
+    if meta.path.is_ident("url") {
+        let mut whitelist = Vec::new();
+        if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+            meta.parse_nested_meta(|option| {
+                if option.path.is_ident("whitelist") {
+                    let value: LitStr = option.value()?.parse()?;
+                    whitelist = value.value().split(',').map(|tld| tld.trim().to_string()).collect();
+                    Ok(())
+                } else {
+                    Err(option.error("unknown option of url, expected `whitelist`"))
+                }
+            })?;
+        }
+        Ok(Rule::Url { whitelist })
+    } else if meta.path.is_ident("uuid_v5") {
+        Ok(Rule::UuidV5)
+    } else if meta.path.is_ident("file") {
+        let mut kind = None;
+        let mut check_extension = true;
+        if !meta.input.is_empty() && !meta.input.peek(syn::Token![,]) {
+            meta.parse_nested_meta(|option| {
+                if option.path.is_ident("kind") {
+                    let value: LitStr = option.value()?.parse()?;
+                    match FILE_KINDS.iter().find(|(name, _)| *name == value.value()) {
+                        Some((_, variant)) => kind = Some(variant.to_string()),
+                        None => return Err(syn::Error::new_spanned(value, "unknown file kind, expected image, video, audio, archive, document or book")),
+                    }
+                    Ok(())
+                } else if option.path.is_ident("check_extension") {
+                    let value: LitBool = option.value()?.parse()?;
+                    check_extension = value.value;
+                    Ok(())
+                } else {
+                    Err(option.error("unknown option of file, expected `kind` or `check_extension`"))
+                }
+            })?;
+        }
+        Ok(Rule::File { kind, check_extension })
+    } else {
+        Err(meta.error("unknown rule, expected `url`, `uuid_v5` or `file`"))
+    }
+}
+
+fn check(rule: &Rule, member: &Member) -> TokenStream2 {
+    match rule {
+        Rule::Url { whitelist } => quote! {
+            ::lab01_2022_input_validation::derive::url(::core::convert::AsRef::<str>::as_ref(&self.#member), &[#(#whitelist),*])
+        },
+        Rule::UuidV5 => quote! {
+            ::lab01_2022_input_validation::derive::uuid_v5(::core::convert::AsRef::<str>::as_ref(&self.#member))
+        },
+        Rule::File { kind, check_extension } => {
+            let kind = match kind {
+                Some(variant) => {
+                    let variant = format_ident!("{}", variant);
+                    quote!(::core::option::Option::Some(::lab01_2022_input_validation::FileKind::#variant))
+                }
+                None => quote!(::core::option::Option::None),
+            };
+            quote! {
+                ::lab01_2022_input_validation::derive::file(::core::convert::AsRef::<::std::path::Path>::as_ref(&self.#member), #kind, #check_extension)
+            }
+        }
+    }
+}
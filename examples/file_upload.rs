@@ -10,17 +10,35 @@ lazy_static! {
     // (String, bool) where the string is the filepath and the bool is true if a movie (is_movie)
     static ref HASHMAP: Mutex<HashMap<Uuid, (String, bool)>> = Mutex::new(HashMap::new());
     static ref NAMESPACE: Uuid = Uuid::parse_str("c7bb890c-a4a8-4d68-85b7-1e1cfe909249").unwrap();
+    // Checks the extensions and hashes the file while validating it
+    static ref VALIDATOR: FileValidator =
+        FileValidator::builder().check_extension(true).reject_dangerous_extensions(true).sha256(true).build().unwrap();
 }
 
+#[allow(clippy::map_entry, clippy::to_string_in_format_args)]
 fn file_upload_handler() {
     loop {
         let filepath = input::<String>().repeat_msg("Please enter the path to an image or video file : ").get();
-        match validate_file(&filepath, true) {
-            Ok(result) => match result {
-                0 => println!("Invalid file contents !"),
-                i => {
-                    // Generate v5 uuid
-                    let key = Uuid::new_v5(&NAMESPACE, filepath.to_lowercase().as_bytes());
+        if let Err(e) = validate_upload_path(&filepath) {
+            println!("{}", e);
+            continue;
+        }
+        match VALIDATOR.validate(&filepath) {
+            Ok(file) => match file.kind {
+                FileKind::Invalid => match file.denied_extension {
+                    Some(extension) => println!("Dangerous extension {} in filename !", extension),
+                    None => println!("Invalid file contents !"),
+                },
+                kind => {
+                    // Generate v5 uuid from the content digest, without reading the file again
+                    let digest = file.sha256.expect("the validator hashes the files");
+                    let key = match dedup_key_for(&NAMESPACE, DedupSource::Sha256(&digest), DedupStrategy::Sha256) {
+                        Ok(key) => key,
+                        Err(e) => {
+                            println!("{}", e);
+                            continue;
+                        }
+                    };
 
                     // Check that the file is not already present => break if so
                     let mut map = HASHMAP.lock().unwrap();
@@ -28,9 +46,8 @@ fn file_upload_handler() {
                         println!("This file is already uploaded.\n");
                         break;
                     } else {
-                        // true for videos (2), false for images (1)
-                        map.insert(key, (filepath, i == 2));
-                        println!("File uploaded successfully, UUID : {}\n", key.to_string());
+                        map.insert(key, (filepath, kind == FileKind::Video));
+                        println!("File uploaded successfully, UUID : {} (short id : {})\n", key.to_string(), encode_uuid(&key, UuidEncoding::Base64Url));
                         break;
                     }
                 }
@@ -42,11 +59,11 @@ fn file_upload_handler() {
 
 fn file_verify_handler() {
     loop {
-        let uuid = input::<String>().repeat_msg("Please enter the UUID to check : ").get();
-        if validate_uuid(&uuid) {
+        let uuid = input::<String>().repeat_msg("Please enter the UUID or short id to check : ").get();
+        if let Ok(parsed) = parse_uuid_v5(&uuid).or_else(|_| parse_short_uuid_v5(&uuid, UuidEncoding::Base64Url)) {
             let map = HASHMAP.lock().unwrap();
 
-            match map.get(&Uuid::parse_str(&uuid).unwrap()) {
+            match map.get(&parsed) {
                 None => println!("File {} doesn't exist.\n", uuid),
                 Some((_, is_video)) => {
                     if *is_video {
@@ -65,11 +82,11 @@ fn file_verify_handler() {
 
 fn get_url_handler() {
     loop {
-        let uuid = input::<String>().repeat_msg("Please enter the UUID to get : ").get();
-        if validate_uuid(&uuid) {
+        let uuid = input::<String>().repeat_msg("Please enter the UUID or short id to get : ").get();
+        if let Ok(parsed) = parse_uuid_v5(&uuid).or_else(|_| parse_short_uuid_v5(&uuid, UuidEncoding::Base64Url)) {
             let map = HASHMAP.lock().unwrap();
 
-            match map.get(&Uuid::parse_str(&uuid).unwrap()) {
+            match map.get(&parsed) {
                 None => println!("File {} doesn't exist.\n", uuid),
                 Some((filepath, is_video)) => {
                     // Generate url
@@ -0,0 +1,4 @@
+//! Shared (`liblab01.so`) and static (`liblab01.a`) C library of the validators, exporting the
+//! functions of the `ffi` module of `lab01_2022_input_validation` declared in `include/lab01.h`.
+
+pub use lab01_2022_input_validation::ffi::*;
@@ -0,0 +1,75 @@
+#ifndef LAB01_H
+#define LAB01_H
+
+/* Generated by cbindgen from src/ffi.rs, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// The input is valid
+#define LAB01_VALID 1
+
+// The input is invalid
+#define LAB01_INVALID 0
+
+// A pointer argument is null
+#define LAB01_ERROR_NULL_POINTER -1
+
+// A string argument is not UTF-8
+#define LAB01_ERROR_INVALID_UTF8 -2
+
+// An option is invalid, e.g. a top level domain of the whitelist
+#define LAB01_ERROR_INVALID_OPTION -3
+
+// The file could not be opened or read
+#define LAB01_ERROR_IO -4
+
+// The type of the file is unknown
+#define LAB01_ERROR_UNKNOWN_FILE_TYPE -5
+
+// The file is not a regular file, e.g. a directory
+#define LAB01_ERROR_NOT_A_FILE -6
+
+// The validation panicked
+#define LAB01_ERROR_PANIC -7
+
+
+
+// Validate an url, see [`validate_url`], accepting only the `whitelist_length` top level domains
+// of the whitelist if it is not null.
+//
+// # Safety
+// `url` is a NUL-terminated string, and `whitelist` is null or points to `whitelist_length`
+// NUL-terminated strings.
+int32_t lab01_validate_url(const char *url, const char *const *whitelist, size_t whitelist_length);
+
+// Validate a version-5 uuid, see [`validate_uuid`].
+//
+// # Safety
+// `uuid` is a NUL-terminated string.
+int32_t lab01_validate_uuid(const char *uuid);
+
+// Validate a file on disk as an image or a video, see [`validate_file`].
+//
+// # Safety
+// `path` is a NUL-terminated string.
+int32_t lab01_validate_file(const char *path, bool check_extension);
+
+// Validate a file from its `length` bytes of contents, see [`validate_file_bytes`], its
+// extension checked on its declared name if not null. Empty contents may be null.
+//
+// # Safety
+// `contents` points to `length` bytes unless `length` is 0, and `declared_name` is null or a
+// NUL-terminated string.
+int32_t lab01_validate_file_bytes(const uint8_t *contents,
+                                  size_t length,
+                                  const char *declared_name,
+                                  bool check_extension);
+
+// Description of a result code, a static NUL-terminated string.
+const char *lab01_error_message(int32_t code);
+
+#endif  /* LAB01_H */
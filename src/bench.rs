@@ -0,0 +1,336 @@
+//! Throughput measurement of the validators, enabled with the `bench` feature.
+//!
+//! The corpora are generated deterministically from a seed so that two runs on the same machine
+//! validate the same inputs. A run produces [`Metrics`] that can be stored as a baseline and
+//! compared against later runs to guard against throughput regressions.
+//!
+//! # Examples
+//! ``` ignore
+//! let baseline = Metrics::from_baseline(&std::fs::read_to_string("baseline.txt")?)?;
+//! run_suite().assert_within(&baseline, 0.2);
+//! ```
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use uuid::Uuid;
+
+use crate::{validate_uuid, validate_uuid_regex, UrlValidator, ValidationError};
+
+const DEFAULT_SEED: u64 = 0x5EC1_AB01;
+const DEFAULT_CORPUS_SIZE: usize = 10_000;
+
+/// Deterministic pseudo random generator (splitmix64) used to build the corpora.
+#[derive(Debug, Clone)]
+pub struct CorpusRng {
+    state: u64,
+}
+
+impl CorpusRng {
+    /// Create a generator from a seed. The same seed always gives the same sequence.
+    pub fn new(seed: u64) -> Self {
+        CorpusRng { state: seed }
+    }
+
+    /// Next pseudo random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Next pseudo random number in `0..bound`.
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn pick<'a>(&mut self, choices: &[&'a str]) -> &'a str {
+        choices[self.below(choices.len())]
+    }
+
+    fn word(&mut self, charset: &[u8], min: usize, max: usize) -> String {
+        let len = min + self.below(max - min + 1);
+        (0..len).map(|_| charset[self.below(charset.len())] as char).collect()
+    }
+}
+
+/// Generate `size` urls from a seed, about half of them valid for the default [`UrlValidator`].
+pub fn url_corpus(seed: u64, size: usize) -> Vec<String> {
+    const ALNUM: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    const ALPHA: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    const INVALID: &[u8] = b"_ !$%&*+,;=";
+
+    let mut rng = CorpusRng::new(seed);
+    (0..size)
+        .map(|_| {
+            let scheme = rng.pick(&["", "http://", "https://", "ftp://", "http:/", "://"]);
+            let mut host = rng.word(ALNUM, 1, 20);
+            if rng.below(4) == 0 {
+                host.insert(rng.below(host.len()), INVALID[rng.below(INVALID.len())] as char);
+            }
+            let tld = match rng.below(5) {
+                0 => String::new(),
+                1 => format!(".{}", rng.word(ALNUM, 1, 4)),
+                _ => format!(".{}", rng.word(ALPHA, 2, 6)),
+            };
+            let tail = rng.pick(&["", "/", "/index.html", "#top", "?q=1", "/a/b/c?x=y#z"]);
+            format!("{}{}{}{}", scheme, host, tld, tail)
+        })
+        .collect()
+}
+
+/// Generate `size` uuids from a seed, about half of them valid version-5 uuids.
+pub fn uuid_corpus(seed: u64, size: usize) -> Vec<String> {
+    let mut rng = CorpusRng::new(seed);
+    (0..size)
+        .map(|_| {
+            let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, &rng.next_u64().to_le_bytes()).to_string();
+            match rng.below(4) {
+                0 => uuid.to_uppercase(),
+                1 => uuid,
+                2 => uuid.replacen('5', "4", 1),
+                _ => uuid[1..].to_string(),
+            }
+        })
+        .collect()
+}
+
+/// Measurement of one validator over one corpus.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// Name of the measured validator
+    pub name: String,
+    /// Number of validated inputs
+    pub inputs: usize,
+    /// Number of accepted inputs
+    pub accepted: usize,
+    /// Total time spent validating
+    pub duration: Duration,
+}
+
+impl Measurement {
+    /// Validated inputs per second.
+    pub fn throughput(&self) -> f64 {
+        self.inputs as f64 / self.duration.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Measurements of a suite run.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    measurements: Vec<Measurement>,
+}
+
+impl Metrics {
+    /// All the measurements of the run.
+    pub fn measurements(&self) -> &[Measurement] {
+        &self.measurements
+    }
+
+    /// The measurement of a validator by name.
+    pub fn get(&self, name: &str) -> Option<&Measurement> {
+        self.measurements.iter().find(|measurement| measurement.name == name)
+    }
+
+    /// Serialize the metrics as a baseline: one `name inputs accepted nanoseconds` line per
+    /// measurement.
+    pub fn to_baseline(&self) -> String {
+        let mut baseline = String::new();
+        for m in &self.measurements {
+            writeln!(baseline, "{} {} {} {}", m.name, m.inputs, m.accepted, m.duration.as_nanos()).unwrap();
+        }
+        baseline
+    }
+
+    /// Parse metrics serialized with [`Metrics::to_baseline`].
+    ///
+    /// # Errors
+    /// If a line is malformed, an error telling the line number is returned.
+    pub fn from_baseline(baseline: &str) -> Result<Metrics, ValidationError> {
+        let mut measurements = Vec::new();
+        for (index, line) in baseline.lines().enumerate().filter(|(_, line)| !line.trim().is_empty()) {
+            let error = || ValidationError::InvalidOption(format!("Invalid baseline at line {}.", index + 1));
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(error());
+            }
+
+            measurements.push(Measurement {
+                name: fields[0].to_string(),
+                inputs: fields[1].parse().map_err(|_| error())?,
+                accepted: fields[2].parse().map_err(|_| error())?,
+                duration: Duration::from_nanos(fields[3].parse().map_err(|_| error())?),
+            });
+        }
+        Ok(Metrics { measurements })
+    }
+
+    /// List the measurements whose throughput dropped by more than `tolerance` (e.g. `0.1` for
+    /// 10%) compared to the baseline, or which are missing. Returns their names.
+    pub fn regressions(&self, baseline: &Metrics, tolerance: f64) -> Vec<String> {
+        baseline
+            .measurements
+            .iter()
+            .filter(|expected| match self.get(&expected.name) {
+                None => true,
+                Some(actual) => actual.throughput() < expected.throughput() * (1.0 - tolerance),
+            })
+            .map(|expected| expected.name.clone())
+            .collect()
+    }
+
+    /// Assert that no throughput dropped by more than `tolerance` compared to the baseline.
+    ///
+    /// # Panics
+    /// If at least one measurement regressed, see [`Metrics::regressions`].
+    pub fn assert_within(&self, baseline: &Metrics, tolerance: f64) {
+        let regressions = self.regressions(baseline, tolerance);
+        assert!(regressions.is_empty(), "Throughput regression in: {}", regressions.join(", "));
+    }
+}
+
+/// Suite of validators run over user provided corpora.
+#[derive(Debug, Clone, Default)]
+pub struct Suite {
+    urls: Vec<String>,
+    uuids: Vec<String>,
+    url_validators: Vec<(String, UrlValidator)>,
+}
+
+impl Suite {
+    /// Create an empty suite.
+    pub fn new() -> Self {
+        Suite::default()
+    }
+
+    /// Set the urls given to the url validators.
+    pub fn url_corpus(mut self, urls: Vec<String>) -> Self {
+        self.urls = urls;
+        self
+    }
+
+    /// Set the uuids given to the uuid validator.
+    pub fn uuid_corpus(mut self, uuids: Vec<String>) -> Self {
+        self.uuids = uuids;
+        self
+    }
+
+    /// Measure an url validator under the given name.
+    pub fn url_validator(mut self, name: &str, validator: UrlValidator) -> Self {
+        self.url_validators.push((name.to_string(), validator));
+        self
+    }
+
+    /// Run every validator over its corpus. Validators without corpus are skipped.
+    pub fn run(&self) -> Metrics {
+        let mut measurements = Vec::new();
+
+        if !self.urls.is_empty() {
+            for (name, validator) in &self.url_validators {
+                measurements.push(measure(name, &self.urls, |url| validator.validate(url)));
+            }
+        }
+
+        if !self.uuids.is_empty() {
+            measurements.push(measure("validate_uuid", &self.uuids, |uuid| validate_uuid(uuid).is_ok()));
+            measurements.push(measure("validate_uuid_regex", &self.uuids, validate_uuid_regex));
+        }
+
+        Metrics { measurements }
+    }
+}
+
+fn measure<F: Fn(&str) -> bool>(name: &str, inputs: &[String], validate: F) -> Measurement {
+    let start = Instant::now();
+    let accepted = inputs.iter().filter(|input| validate(input)).count();
+    Measurement {
+        name: name.to_string(),
+        inputs: inputs.len(),
+        accepted,
+        duration: start.elapsed(),
+    }
+}
+
+/// Run the default suite: the default and a whitelisted url validator and the uuid validator,
+/// with its former regex matching, over deterministic corpora.
+pub fn run_suite() -> Metrics {
+    Suite::new()
+        .url_corpus(url_corpus(DEFAULT_SEED, DEFAULT_CORPUS_SIZE))
+        .uuid_corpus(uuid_corpus(DEFAULT_SEED, DEFAULT_CORPUS_SIZE))
+        .url_validator("validate_url", UrlValidator::builder().build().unwrap())
+        .url_validator(
+            "validate_url_whitelist",
+            UrlValidator::builder().top_level_whitelist([".com", ".ch", ".org"]).build().unwrap(),
+        )
+        .run()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::bench::{run_suite, url_corpus, uuid_corpus, Measurement, Metrics};
+
+    fn metrics(throughputs: &[(&str, u64)]) -> Metrics {
+        Metrics {
+            measurements: throughputs
+                .iter()
+                .map(|&(name, nanos)| Measurement {
+                    name: name.to_string(),
+                    inputs: 1000,
+                    accepted: 500,
+                    duration: Duration::from_nanos(nanos),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn deterministic_corpora() {
+        assert_eq!(url_corpus(1, 100), url_corpus(1, 100));
+        assert_ne!(url_corpus(1, 100), url_corpus(2, 100));
+        assert_eq!(uuid_corpus(1, 100), uuid_corpus(1, 100));
+        assert_eq!(url_corpus(1, 42).len(), 42);
+    }
+
+    #[test]
+    fn suite_accepts_part_of_the_corpora() {
+        let metrics = run_suite();
+        for measurement in metrics.measurements() {
+            assert!(measurement.accepted > 0 && measurement.accepted < measurement.inputs);
+        }
+        assert!(metrics.get("validate_url").is_some());
+        assert_eq!(metrics.get("validate_uuid").unwrap().accepted, metrics.get("validate_uuid_regex").unwrap().accepted);
+    }
+
+    #[test]
+    fn baseline_round_trip() {
+        let metrics = metrics(&[("a", 1000), ("b", 2000)]);
+        assert_eq!(Metrics::from_baseline(&metrics.to_baseline()).unwrap(), metrics);
+
+        assert_eq!(Metrics::from_baseline("a 1 2").unwrap_err().to_string(), "Invalid baseline at line 1.");
+        assert_eq!(Metrics::from_baseline("a 1 2 3\nb x 2 3").unwrap_err().to_string(), "Invalid baseline at line 2.");
+    }
+
+    #[test]
+    fn regressions() {
+        let baseline = metrics(&[("a", 1000), ("b", 1000)]);
+
+        // b is twice as slow
+        let current = metrics(&[("a", 1050), ("b", 2000)]);
+        assert_eq!(current.regressions(&baseline, 0.1), vec!["b"]);
+        assert!(current.regressions(&baseline, 0.6).is_empty());
+        current.assert_within(&baseline, 0.6);
+
+        // missing measurement
+        assert_eq!(metrics(&[("a", 1000)]).regressions(&baseline, 0.1), vec!["b"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Throughput regression in: b")]
+    fn assert_regression() {
+        metrics(&[("a", 1000), ("b", 2000)]).assert_within(&metrics(&[("a", 1000), ("b", 1000)]), 0.1);
+    }
+}
@@ -0,0 +1,86 @@
+//! Drop-in wrappers keeping the original signatures of the validators.
+//!
+//! The crate root API can evolve, these functions keep behaving as they did in the lab and are
+//! implemented on top of the current validators.
+
+use std::io::{Error, ErrorKind};
+
+use lazy_static::lazy_static;
+
+use crate::{FileKind, UrlValidator, ValidationError};
+
+/// Validate an url providing an optional top level whitelist.
+///
+/// See [`crate::UrlValidator`] for the rules. The top level domains inside the whitelist are
+/// case sensitive.
+///
+/// # Errors
+/// If the whitelist is empty or at least one top level domain inside is invalid, an error will
+/// be returned.
+pub fn validate_url(url: &str, top_level_whitelist: Option<&Vec<&str>>) -> Result<bool, String> {
+    match top_level_whitelist {
+        None => {
+            lazy_static! {
+                static ref VALIDATOR: UrlValidator = UrlValidator::builder().build().unwrap();
+            }
+            Ok(VALIDATOR.validate(url))
+        }
+
+        Some(whitelist) => {
+            let validator = UrlValidator::builder().top_level_whitelist(whitelist).build().map_err(|e| e.to_string())?;
+            Ok(validator.validate(url))
+        }
+    }
+}
+
+/// Validate a file by checking that it is an image or a video. And check his filename extension
+/// if requested.
+///
+/// The result is an unsigned integer telling:
+///     0 - The file is invalid (not a video or image or the extension is invalid)
+///     1 - The file is valid and it is an image
+///     2 - The file is valid and it a video
+///
+/// # Errors
+/// If the filename could not be found or opened. Also return an error if the file type is unknown
+/// (cf. crate infer).
+pub fn validate_file(filename: &str, check_extension: bool) -> Result<u8, Error> {
+    let file = crate::validate_file(filename, check_extension).map_err(|e| match e {
+        ValidationError::Io(e) => e,
+        e @ ValidationError::FileType(_) => Error::new(ErrorKind::InvalidInput, e),
+        e @ ValidationError::UnknownFileType => Error::other(e),
+        e => Error::new(ErrorKind::InvalidData, e),
+    })?;
+    Ok(match file.kind {
+        FileKind::Image => 1,
+        FileKind::Video => 2,
+        _ => 0,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::compat::{validate_file, validate_url};
+
+    const TEST_DIR: &str = "test_files";
+
+    #[test]
+    fn same_url_results() {
+        assert!(validate_url("https://test.com/path", None).unwrap());
+        assert!(!validate_url("test.com?", None).unwrap());
+        assert!(validate_url("test.ch.com", Some(&vec![".com"])).unwrap());
+        assert!(!validate_url("test.COM", Some(&vec![".com"])).unwrap());
+
+        assert_eq!(validate_url("", Some(&vec![])).unwrap_err(), "The white list is empty.");
+        assert_eq!(validate_url("", Some(&vec![".a"])).unwrap_err(), "Invalid top level domain in white list.");
+    }
+
+    #[test]
+    fn same_file_results() {
+        assert_eq!(validate_file(&format!("{}/valid_image.jpg", TEST_DIR), true).unwrap(), 1);
+        assert_eq!(validate_file(&format!("{}/valid_video.avi", TEST_DIR), true).unwrap(), 2);
+        assert_eq!(validate_file(&format!("{}/valid_image.webp", TEST_DIR), true).unwrap(), 1);
+        assert_eq!(validate_file(&format!("{}/invalid_ext_image_jpg.png", TEST_DIR), true).unwrap(), 0);
+        assert_eq!(validate_file("Cargo.toml", false).unwrap_err().to_string(), "File type is unknown.");
+    }
+}
@@ -0,0 +1,352 @@
+//! Acceptance testing of the validators against labeled corpora.
+//!
+//! A corpus lists inputs expected to be accepted and inputs expected to be rejected, so that
+//! security requirements can be written as data and checked again when upgrading the crate.
+//!
+//! On disk, a corpus is a directory with an `accept.txt` and a `reject.txt` file holding one
+//! input per line. Empty lines are ignored, the other lines are kept as written. A directory of
+//! such directories is a set of named corpora, see [`CorpusRunner::from_dir`].
+//!
+//! With the `toml` feature, named corpora can also be listed in a single TOML manifest, a table
+//! per corpus holding an `accept` and a `reject` array, see [`CorpusRunner::from_toml`].
+//!
+//! # Examples
+//! ``` ignore
+//! let report = CorpusRunner::from_dir("tests/corpus/urls")?.run(|url| validator.validate(url));
+//! report.assert_passes();
+//! ```
+
+use std::fmt::{self, Display, Formatter};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::ValidationError;
+
+const ACCEPT_FILE: &str = "accept.txt";
+const REJECT_FILE: &str = "reject.txt";
+
+/// Inputs expected to be accepted and rejected by a validator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Corpus {
+    accept: Vec<String>,
+    reject: Vec<String>,
+}
+
+impl Corpus {
+    /// Create an empty corpus.
+    pub fn new() -> Self {
+        Corpus::default()
+    }
+
+    /// Add inputs expected to be accepted.
+    pub fn accept<I, S>(mut self, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.accept.extend(inputs.into_iter().map(|input| input.as_ref().to_string()));
+        self
+    }
+
+    /// Add inputs expected to be rejected.
+    pub fn reject<I, S>(mut self, inputs: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.reject.extend(inputs.into_iter().map(|input| input.as_ref().to_string()));
+        self
+    }
+
+    /// Create a corpus from the contents of an accept and a reject list, one input per line.
+    pub fn from_strings(accept: &str, reject: &str) -> Self {
+        Corpus::new().accept(lines(accept)).reject(lines(reject))
+    }
+
+    /// Read a corpus from a directory holding an `accept.txt` and a `reject.txt` file. A
+    /// missing file is an empty list.
+    ///
+    /// # Errors
+    /// If the directory holds neither file or one of them could not be read.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Corpus, ValidationError> {
+        let path = path.as_ref();
+        let read = |name| match std::fs::read_to_string(path.join(name)) {
+            Ok(list) => Ok(Some(list)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        match (read(ACCEPT_FILE)?, read(REJECT_FILE)?) {
+            (None, None) => Err(Error::new(ErrorKind::NotFound, format!("No corpus in {}.", path.display())).into()),
+            (accept, reject) => Ok(Corpus::from_strings(&accept.unwrap_or_default(), &reject.unwrap_or_default())),
+        }
+    }
+
+    /// The inputs expected to be accepted.
+    pub fn accepted(&self) -> &[String] {
+        &self.accept
+    }
+
+    /// The inputs expected to be rejected.
+    pub fn rejected(&self) -> &[String] {
+        &self.reject
+    }
+}
+
+fn lines(list: &str) -> impl Iterator<Item = &str> {
+    list.lines().filter(|line| !line.is_empty())
+}
+
+/// Named corpora run against a validator.
+#[derive(Debug, Clone, Default)]
+pub struct CorpusRunner {
+    corpora: Vec<(String, Corpus)>,
+}
+
+impl CorpusRunner {
+    /// Create a runner without corpus.
+    pub fn new() -> Self {
+        CorpusRunner::default()
+    }
+
+    /// Add a corpus under the given name.
+    pub fn corpus(mut self, name: &str, corpus: Corpus) -> Self {
+        self.corpora.push((name.to_string(), corpus));
+        self
+    }
+
+    /// Read the corpora of a directory: `path` itself if it is a corpus, otherwise each of its
+    /// sub-directories holding a corpus, named after them and sorted by name.
+    ///
+    /// # Errors
+    /// If the directory could not be read or holds no corpus.
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<CorpusRunner, ValidationError> {
+        let path = path.as_ref();
+        if let Ok(corpus) = Corpus::from_dir(path) {
+            let name = path.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+            return Ok(CorpusRunner::new().corpus(&name, corpus));
+        }
+
+        let mut directories = Vec::new();
+        for entry in std::fs::read_dir(path)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                directories.push(entry.path());
+            }
+        }
+        directories.sort();
+
+        let mut runner = CorpusRunner::new();
+        for directory in directories {
+            match Corpus::from_dir(&directory) {
+                Ok(corpus) => runner = runner.corpus(&directory.file_name().unwrap().to_string_lossy(), corpus),
+                Err(ValidationError::Io(e)) if e.kind() == ErrorKind::NotFound => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if runner.corpora.is_empty() {
+            return Err(Error::new(ErrorKind::NotFound, format!("No corpus in {}.", path.display())).into());
+        }
+        Ok(runner)
+    }
+
+    /// Read named corpora from a TOML manifest: a table per corpus, named after it, with an
+    /// `accept` and a `reject` array of strings. A missing array is an empty list. The corpora
+    /// are sorted by name.
+    ///
+    /// ``` toml
+    /// [urls]
+    /// accept = ["https://test.com", "test.org#top"]
+    /// reject = ["https://a_b.com"]
+    /// ```
+    ///
+    /// # Errors
+    /// If the manifest is not valid TOML, holds no corpus, or a corpus has another key or a
+    /// list which is not an array of strings.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(manifest: &str) -> Result<CorpusRunner, ValidationError> {
+        let invalid = ValidationError::InvalidOption;
+        let manifest: toml::Table = manifest.parse().map_err(|e: toml::de::Error| invalid(e.message().to_string()))?;
+
+        let mut runner = CorpusRunner::new();
+        for (name, corpus) in &manifest {
+            let corpus = corpus.as_table().ok_or_else(|| invalid(format!("Invalid corpus {}.", name)))?;
+            if let Some(key) = corpus.keys().find(|&key| key != "accept" && key != "reject") {
+                return Err(invalid(format!("Invalid key {} in corpus {}.", key, name)));
+            }
+
+            let list = |key: &str| match corpus.get(key) {
+                None => Ok(Vec::new()),
+                Some(list) => list
+                    .as_array()
+                    .and_then(|list| list.iter().map(toml::Value::as_str).collect::<Option<Vec<_>>>())
+                    .ok_or_else(|| invalid(format!("Invalid {} list in corpus {}.", key, name))),
+            };
+            runner = runner.corpus(name, Corpus::new().accept(list("accept")?).reject(list("reject")?));
+        }
+
+        if runner.corpora.is_empty() {
+            return Err(ValidationError::option("No corpus in the manifest."));
+        }
+        Ok(runner)
+    }
+
+    /// Read the named corpora of a TOML manifest file, see [`CorpusRunner::from_toml`].
+    ///
+    /// # Errors
+    /// If the file could not be read or is not a valid manifest.
+    #[cfg(feature = "toml")]
+    pub fn from_manifest<P: AsRef<Path>>(path: P) -> Result<CorpusRunner, ValidationError> {
+        CorpusRunner::from_toml(&std::fs::read_to_string(path)?)
+    }
+
+    /// Run a validator over every corpus.
+    pub fn run<F: Fn(&str) -> bool>(&self, validate: F) -> CorpusReport {
+        let mut report = CorpusReport::default();
+        for (name, corpus) in &self.corpora {
+            let expectations = corpus.accept.iter().map(|input| (input, true)).chain(corpus.reject.iter().map(|input| (input, false)));
+            for (input, expected) in expectations {
+                report.checked += 1;
+                if validate(input) != expected {
+                    report.mismatches.push(Mismatch {
+                        corpus: name.clone(),
+                        input: input.clone(),
+                        expected,
+                    });
+                }
+            }
+        }
+        report
+    }
+}
+
+/// Input for which a validator disagrees with its corpus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// Name of the corpus of the input
+    pub corpus: String,
+    /// The input as listed in the corpus
+    pub input: String,
+    /// Whether the input was expected to be accepted
+    pub expected: bool,
+}
+
+/// Result of a [`CorpusRunner::run`].
+///
+/// It displays as a diff between the corpora and the validator: a `-` line for each input
+/// expected to be accepted but rejected, a `+` line for each input expected to be rejected but
+/// accepted, grouped by corpus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CorpusReport {
+    checked: usize,
+    mismatches: Vec<Mismatch>,
+}
+
+impl CorpusReport {
+    /// Number of checked inputs.
+    pub fn checked(&self) -> usize {
+        self.checked
+    }
+
+    /// The inputs for which the validator disagrees with its corpus, in corpus order.
+    pub fn mismatches(&self) -> &[Mismatch] {
+        &self.mismatches
+    }
+
+    /// Whether the validator agrees with every corpus.
+    pub fn passed(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// Assert that the validator agrees with every corpus.
+    ///
+    /// # Panics
+    /// If at least one input mismatches, with the report as message.
+    pub fn assert_passes(&self) {
+        assert!(self.passed(), "Corpus mismatches:\n{}", self);
+    }
+}
+
+impl Display for CorpusReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut corpus = None;
+        for mismatch in &self.mismatches {
+            if corpus != Some(&mismatch.corpus) {
+                writeln!(f, "--- {}", mismatch.corpus)?;
+                corpus = Some(&mismatch.corpus);
+            }
+            writeln!(f, "{} {}", if mismatch.expected { '-' } else { '+' }, mismatch.input)?;
+        }
+        write!(f, "{} of {} inputs mismatch", self.mismatches.len(), self.checked)
+    }
+}
+
+#[cfg(all(test, feature = "url", feature = "uuid"))]
+mod tests {
+    use crate::corpus::{Corpus, CorpusRunner};
+    use crate::{validate_url, validate_uuid};
+
+    const CORPUS_DIR: &str = "test_files/corpus";
+
+    #[test]
+    fn load_corpora() {
+        let corpus = Corpus::from_dir(format!("{}/urls", CORPUS_DIR)).unwrap();
+        assert_eq!(corpus.accepted(), ["https://test.com", "www.test.ch/path", "test.org#top"]);
+        assert_eq!(corpus.rejected(), ["test", "https://a_b.com", ".com"]);
+
+        assert_eq!(Corpus::from_strings("a\n\nb\n", ""), Corpus::new().accept(["a", "b"]));
+        assert!(Corpus::from_dir(CORPUS_DIR).is_err());
+        assert!(CorpusRunner::from_dir("missing").is_err());
+    }
+
+    #[test]
+    fn run_corpora() {
+        let report = CorpusRunner::from_dir(format!("{}/urls", CORPUS_DIR)).unwrap().run(|url| validate_url(url, None).unwrap());
+        assert_eq!(report.checked(), 6);
+        report.assert_passes();
+
+        let report = CorpusRunner::from_dir(CORPUS_DIR).unwrap().run(|uuid| validate_uuid(uuid).is_ok());
+        assert_eq!(report.checked(), 8);
+        assert_eq!(report.mismatches().len(), 3);
+        assert_eq!(report.to_string(), "--- urls\n- https://test.com\n- www.test.ch/path\n- test.org#top\n3 of 8 inputs mismatch");
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_manifests() {
+        let runner = CorpusRunner::from_manifest(format!("{}/corpus.toml", CORPUS_DIR)).unwrap();
+        let report = runner.run(|input| validate_url(input, None).unwrap() || validate_uuid(input).is_ok());
+        assert_eq!(report.checked(), 8);
+        report.assert_passes();
+
+        // same corpora as the directory
+        let report = runner.run(|url| validate_url(url, None).unwrap());
+        let directory_report = CorpusRunner::from_dir(CORPUS_DIR).unwrap().run(|url| validate_url(url, None).unwrap());
+        assert_eq!(report.mismatches(), directory_report.mismatches());
+
+        let runner = CorpusRunner::from_toml("[a]\naccept = [\"1\"]\n\n[b]\n").unwrap();
+        assert_eq!(runner.run(|_| true).checked(), 1);
+
+        for manifest in ["", "a = 1", "[a]\naccept = \"1\"", "[a]\naccept = [1]", "[a]\nrejct = [\"1\"]", "[a"] {
+            assert!(CorpusRunner::from_toml(manifest).is_err(), "{}", manifest);
+        }
+        assert!(CorpusRunner::from_manifest("missing.toml").is_err());
+    }
+
+    #[test]
+    fn diff_report() {
+        let runner = CorpusRunner::new()
+            .corpus("a", Corpus::new().accept(["1", "2"]).reject(["3"]))
+            .corpus("b", Corpus::new().reject(["4"]));
+        let report = runner.run(|input| input != "2");
+        assert!(!report.passed());
+        assert_eq!(report.to_string(), "--- a\n- 2\n+ 3\n--- b\n+ 4\n3 of 4 inputs mismatch");
+    }
+
+    #[test]
+    #[should_panic(expected = "Corpus mismatches:\n--- a\n+ 1")]
+    fn assert_mismatch() {
+        CorpusRunner::new().corpus("a", Corpus::new().reject(["1"])).run(|_| true).assert_passes();
+    }
+}
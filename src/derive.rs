@@ -0,0 +1,78 @@
+//! Checks called by the code generated by the derive macro [`crate::ValidateInput`].
+
+use std::path::Path;
+
+use crate::{parse_uuid_v5, validate_kind, validate_url, validate_url_with_whitelist, FileKind, UrlError, ValidationError};
+
+/// Rule `url`, with the top level domains of its whitelist if any.
+pub fn url(url: &str, whitelist: &[&str]) -> Result<(), ValidationError> {
+    let valid = if whitelist.is_empty() {
+        validate_url(url, None)?
+    } else {
+        validate_url_with_whitelist(url, whitelist)?
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(UrlError::Invalid.into())
+    }
+}
+
+/// Rule `uuid_v5`.
+pub fn uuid_v5(uuid: &str) -> Result<(), ValidationError> {
+    parse_uuid_v5(uuid)?;
+    Ok(())
+}
+
+/// Rule `file`, accepting only one kind of file if given.
+pub fn file(path: &Path, kind: Option<FileKind>, check_extension: bool) -> Result<(), ValidationError> {
+    validate_kind(path, kind, check_extension)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{UuidError, ValidateInput, ValidationError};
+
+    #[derive(ValidateInput)]
+    struct Upload {
+        #[valid(url(whitelist = ".ch, .com"))]
+        source: String,
+        #[valid(uuid_v5)]
+        id: &'static str,
+        #[valid(file(kind = "image"))]
+        path: PathBuf,
+        #[valid(file(check_extension = false))]
+        attachment: String,
+        #[allow(dead_code)]
+        comment: String,
+    }
+
+    #[derive(ValidateInput)]
+    struct Link<'a>(#[valid(url)] &'a str, #[valid(url, uuid_v5)] String);
+
+    #[test]
+    fn derived_validation() {
+        let mut upload = Upload {
+            source: String::from("https://heig-vd.ch/upload"),
+            id: "c70dc454-1c7d-5c59-8fed-3a321e6a4a49",
+            path: PathBuf::from("test_files/valid_image.png"),
+            attachment: String::from("test_files/invalid_ext_video_avi.mp4"),
+            comment: String::new(),
+        };
+        assert!(upload.validate().is_ok());
+
+        upload.source = String::from("https://docs.rs");
+        upload.id = "c70dc454";
+        upload.path = PathBuf::from("test_files/valid_video.mov");
+        let errors = upload.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(errors.field("id").next(), Some(ValidationError::Uuid(UuidError::Malformed))));
+        assert_eq!(errors.to_string(), "source: The url is invalid.\nid: Invalid uuid.\npath: The type video/quicktime is not allowed.");
+
+        let errors = Link("docs.rs", String::from("https://docs.rs")).validate().unwrap_err();
+        assert_eq!(errors.errors().iter().map(|(field, _)| field.as_str()).collect::<Vec<_>>(), ["1"]);
+    }
+}
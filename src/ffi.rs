@@ -0,0 +1,194 @@
+//! C interface of the validators, enabled by the `ffi` feature, so that services in other
+//! languages reuse the same rules. The declarations are in `include/lab01.h`, generated with
+//! `cbindgen --config cbindgen.toml --output include/lab01.h`, and the shared and static
+//! libraries are built by the `ffi` crate of the workspace with `cargo build --release -p
+//! lab01_2022_input_validation_ffi`.
+//!
+//! The strings are NUL-terminated UTF-8. The functions return [`LAB01_VALID`] or
+//! [`LAB01_INVALID`], or a negative error code described by [`lab01_error_message`].
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, UnwindSafe};
+use std::slice;
+
+use crate::{validate_file, validate_file_bytes, validate_url, validate_url_with_whitelist, validate_uuid, ValidationError};
+
+/// The input is valid
+pub const LAB01_VALID: i32 = 1;
+/// The input is invalid
+pub const LAB01_INVALID: i32 = 0;
+/// A pointer argument is null
+pub const LAB01_ERROR_NULL_POINTER: i32 = -1;
+/// A string argument is not UTF-8
+pub const LAB01_ERROR_INVALID_UTF8: i32 = -2;
+/// An option is invalid, e.g. a top level domain of the whitelist
+pub const LAB01_ERROR_INVALID_OPTION: i32 = -3;
+/// The file could not be opened or read
+pub const LAB01_ERROR_IO: i32 = -4;
+/// The type of the file is unknown
+pub const LAB01_ERROR_UNKNOWN_FILE_TYPE: i32 = -5;
+/// The file is not a regular file, e.g. a directory
+pub const LAB01_ERROR_NOT_A_FILE: i32 = -6;
+/// The validation panicked
+pub const LAB01_ERROR_PANIC: i32 = -7;
+
+/// Code of an error.
+fn error_code(error: ValidationError) -> i32 {
+    match error {
+        ValidationError::EmptyWhitelist { .. } | ValidationError::InvalidTld { .. } | ValidationError::InvalidOption(_) => LAB01_ERROR_INVALID_OPTION,
+        ValidationError::UnknownFileType => LAB01_ERROR_UNKNOWN_FILE_TYPE,
+        ValidationError::FileType(_) => LAB01_ERROR_NOT_A_FILE,
+        ValidationError::Url(_)
+        | ValidationError::Uuid(_)
+        | ValidationError::Rejected(_)
+        | ValidationError::Contents(_)
+        | ValidationError::InvalidContentType(_)
+        | ValidationError::Path(_) => LAB01_INVALID,
+        ValidationError::Io(_) | ValidationError::Scanner(_) => LAB01_ERROR_IO,
+    }
+}
+
+/// Run a validation, catching its panics which must not unwind into C.
+fn run<F: FnOnce() -> Result<bool, i32> + UnwindSafe>(validation: F) -> i32 {
+    match catch_unwind(validation) {
+        Ok(Ok(true)) => LAB01_VALID,
+        Ok(Ok(false)) => LAB01_INVALID,
+        Ok(Err(code)) => code,
+        Err(_) => LAB01_ERROR_PANIC,
+    }
+}
+
+/// Borrow a C string.
+///
+/// # Safety
+/// The pointer is null or points to a NUL-terminated string valid for `'a`.
+unsafe fn string<'a>(pointer: *const c_char) -> Result<&'a str, i32> {
+    if pointer.is_null() {
+        return Err(LAB01_ERROR_NULL_POINTER);
+    }
+    CStr::from_ptr(pointer).to_str().map_err(|_| LAB01_ERROR_INVALID_UTF8)
+}
+
+/// Validate an url, see [`validate_url`], accepting only the `whitelist_length` top level domains
+/// of the whitelist if it is not null.
+///
+/// # Safety
+/// `url` is a NUL-terminated string, and `whitelist` is null or points to `whitelist_length`
+/// NUL-terminated strings.
+#[no_mangle]
+pub unsafe extern "C" fn lab01_validate_url(url: *const c_char, whitelist: *const *const c_char, whitelist_length: usize) -> i32 {
+    run(|| {
+        let url = string(url)?;
+        if whitelist.is_null() {
+            return validate_url(url, None).map_err(error_code);
+        }
+        let whitelist = slice::from_raw_parts(whitelist, whitelist_length).iter().map(|&tld| string(tld)).collect::<Result<Vec<_>, _>>()?;
+        validate_url_with_whitelist(url, whitelist).map_err(error_code)
+    })
+}
+
+/// Validate a version-5 uuid, see [`validate_uuid`].
+///
+/// # Safety
+/// `uuid` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lab01_validate_uuid(uuid: *const c_char) -> i32 {
+    run(|| Ok(validate_uuid(string(uuid)?).is_ok()))
+}
+
+/// Validate a file on disk as an image or a video, see [`validate_file`].
+///
+/// # Safety
+/// `path` is a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lab01_validate_file(path: *const c_char, check_extension: bool) -> i32 {
+    run(|| {
+        let file = validate_file(string(path)?, check_extension).map_err(error_code)?;
+        Ok(file.is_valid())
+    })
+}
+
+/// Validate a file from its `length` bytes of contents, see [`validate_file_bytes`], its
+/// extension checked on its declared name if not null. Empty contents may be null.
+///
+/// # Safety
+/// `contents` points to `length` bytes unless `length` is 0, and `declared_name` is null or a
+/// NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn lab01_validate_file_bytes(contents: *const u8, length: usize, declared_name: *const c_char, check_extension: bool) -> i32 {
+    run(|| {
+        let contents = match length {
+            0 => &[][..],
+            _ if contents.is_null() => return Err(LAB01_ERROR_NULL_POINTER),
+            _ => slice::from_raw_parts(contents, length),
+        };
+        let declared_name = if declared_name.is_null() { None } else { Some(string(declared_name)?) };
+        let file = validate_file_bytes(contents, declared_name, check_extension).map_err(error_code)?;
+        Ok(file.is_valid())
+    })
+}
+
+/// Description of a result code, a static NUL-terminated string.
+#[no_mangle]
+pub extern "C" fn lab01_error_message(code: i32) -> *const c_char {
+    let message: &'static CStr = match code {
+        LAB01_VALID => c"The input is valid.",
+        LAB01_INVALID => c"The input is invalid.",
+        LAB01_ERROR_NULL_POINTER => c"A pointer argument is null.",
+        LAB01_ERROR_INVALID_UTF8 => c"A string argument is not UTF-8.",
+        LAB01_ERROR_INVALID_OPTION => c"An option is invalid.",
+        LAB01_ERROR_IO => c"The file could not be opened or read.",
+        LAB01_ERROR_UNKNOWN_FILE_TYPE => c"File type is unknown.",
+        LAB01_ERROR_NOT_A_FILE => c"The file is not a regular file.",
+        LAB01_ERROR_PANIC => c"The validation failed unexpectedly.",
+        _ => c"Unknown result code.",
+    };
+    message.as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+    use std::ptr;
+
+    use super::*;
+
+    #[test]
+    fn c_validators() {
+        let url = CString::new("https://heig-vd.ch").unwrap();
+        let whitelist = [c".ch".as_ptr(), c".com".as_ptr()];
+        unsafe {
+            assert_eq!(lab01_validate_url(url.as_ptr(), ptr::null(), 0), LAB01_VALID);
+            assert_eq!(lab01_validate_url(url.as_ptr(), whitelist.as_ptr(), 2), LAB01_VALID);
+            assert_eq!(lab01_validate_url(c"https://docs.rs".as_ptr(), whitelist.as_ptr(), 2), LAB01_INVALID);
+            assert_eq!(lab01_validate_url(url.as_ptr(), [c"ch".as_ptr()].as_ptr(), 1), LAB01_ERROR_INVALID_OPTION);
+            assert_eq!(lab01_validate_url(ptr::null(), ptr::null(), 0), LAB01_ERROR_NULL_POINTER);
+            assert_eq!(lab01_validate_url(c"https://\xff.ch".as_ptr(), ptr::null(), 0), LAB01_ERROR_INVALID_UTF8);
+
+            assert_eq!(lab01_validate_uuid(c"c70dc454-1c7d-5c59-8fed-3a321e6a4a49".as_ptr()), LAB01_VALID);
+            assert_eq!(lab01_validate_uuid(c"c70dc454".as_ptr()), LAB01_INVALID);
+
+            assert_eq!(lab01_validate_file(c"test_files/valid_image.png".as_ptr(), true), LAB01_VALID);
+            assert_eq!(lab01_validate_file(c"test_files/invalid_ext_image_jpg.png".as_ptr(), true), LAB01_INVALID);
+            assert_eq!(lab01_validate_file(c"test_files/missing.png".as_ptr(), true), LAB01_ERROR_IO);
+            assert_eq!(lab01_validate_file(c"test_files".as_ptr(), true), LAB01_ERROR_NOT_A_FILE);
+            assert_eq!(lab01_validate_file(c"Cargo.toml".as_ptr(), false), LAB01_ERROR_UNKNOWN_FILE_TYPE);
+
+            let image = std::fs::read("test_files/valid_image.png").unwrap();
+            assert_eq!(lab01_validate_file_bytes(image.as_ptr(), image.len(), c"image.png".as_ptr(), true), LAB01_VALID);
+            assert_eq!(lab01_validate_file_bytes(image.as_ptr(), image.len(), ptr::null(), true), LAB01_INVALID);
+            assert_eq!(lab01_validate_file_bytes(ptr::null(), 1, ptr::null(), false), LAB01_ERROR_NULL_POINTER);
+
+            // empty contents, whatever the pointer
+            assert_eq!(lab01_validate_file_bytes(ptr::null(), 0, ptr::null(), false), LAB01_ERROR_UNKNOWN_FILE_TYPE);
+            assert_eq!(lab01_validate_file_bytes(image.as_ptr(), 0, ptr::null(), false), LAB01_ERROR_UNKNOWN_FILE_TYPE);
+        }
+    }
+
+    #[test]
+    fn error_messages() {
+        let message = |code| unsafe { CStr::from_ptr(lab01_error_message(code)) }.to_str().unwrap();
+        assert_eq!(message(LAB01_ERROR_IO), "The file could not be opened or read.");
+        assert_eq!(message(42), "Unknown result code.");
+    }
+}
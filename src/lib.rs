@@ -1,4 +1,24 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 extern crate core;
+// The code generated by the derive macro names the crate, also in its own tests
+#[cfg(feature = "derive")]
+extern crate self as lab01_2022_input_validation;
 
+#[cfg(feature = "bench")]
+pub mod bench;
+#[cfg(all(feature = "url", feature = "file"))]
+pub mod compat;
+#[cfg(feature = "std")]
+pub mod corpus;
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub mod derive;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod prelude;
 mod validators;
 pub use validators::*;
+#[cfg(feature = "derive")]
+pub use lab01_2022_input_validation_derive::ValidateInput;
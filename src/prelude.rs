@@ -0,0 +1,50 @@
+//! The commonly used validators, builders and errors of the enabled features, imported at once.
+//!
+//! # Examples
+//! ``` ignore
+//! use lab01_2022_input_validation::prelude::*;
+//!
+//! fn check_upload(url: &str, path: &str, id: &str) -> Result<(), ValidationError> {
+//!     validate_url_with_whitelist(url, [".ch", ".com"])?;
+//!     validate_file(path, true)?;
+//!     validate_uuid(id)?;
+//!     Ok(())
+//! }
+//! ```
+
+pub use crate::{ValidationError, ValidationErrors};
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+pub use crate::{rule, Validate, ValidationReport};
+
+#[cfg(feature = "url")]
+pub use crate::{validate_url, validate_url_with_whitelist, UrlValidator, UrlValidatorBuilder};
+#[cfg(all(feature = "url", feature = "std"))]
+pub use crate::{is_dangerous_url, Url, UrlError, ValidUrl};
+
+#[cfg(feature = "file")]
+pub use crate::{
+    sanitize_filename, validate_file, validate_upload_path, FileKind, FileTypeError, FileValidator, FileValidatorBuilder, ImageFile,
+    PathError, ValidatedFile, VideoFile,
+};
+
+#[cfg(feature = "uuid")]
+pub use crate::{generate_uuid_v5, validate_uuid, validate_uuid_version, UuidError, UuidValidator, UuidValidatorBuilder, UuidVersion};
+#[cfg(all(feature = "uuid", feature = "std"))]
+pub use crate::{parse_uuid_v5, FileUuid};
+
+#[cfg(feature = "std")]
+pub use crate::ValidatedInput;
+#[cfg(feature = "derive")]
+pub use crate::ValidateInput;
+
+#[cfg(all(test, feature = "url", feature = "file", feature = "uuid"))]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn imported_validators() {
+        assert!(validate_url_with_whitelist("https://heig-vd.ch", [".ch"]).unwrap());
+        assert_eq!(validate_file("test_files/valid_image.png", true).unwrap().kind, FileKind::Image);
+        assert!(matches!(validate_uuid("c70dc454"), Err(ValidationError::Uuid(UuidError::Malformed))));
+    }
+}
@@ -0,0 +1,389 @@
+//! Inspection of the entries of the archives, enabled with the `deep` feature.
+//!
+//! The magic numbers of an archive tell nothing about its entries: a small ZIP archive can
+//! decompress to terabytes (a zip bomb), overlap its entries to multiply their size, nest other
+//! archives, or hold entries of any type. The central directory of a ZIP archive is checked
+//! against size limits before any entry is decompressed, and the decompression stops at the
+//! size declared by the archive.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::byte_reader::{Reader, Truncated};
+use super::image_header::crc32;
+use super::inflate::{inflate, InflateError};
+
+/// Mime type of the ZIP archives.
+pub(crate) const ZIP_MIME_TYPE: &str = "application/zip";
+
+/// Mime types of the archives inspected in deep mode, the ZIP archives being the only ones whose
+/// entries can be read.
+pub(crate) const INSPECTED_MIME_TYPES: [&str; 3] = [ZIP_MIME_TYPE, "application/vnd.rar", "application/x-7z-compressed"];
+
+const END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+const CENTRAL_DIRECTORY_HEADER: &[u8] = b"PK\x01\x02";
+const LOCAL_FILE_HEADER: &[u8] = b"PK\x03\x04";
+
+/// Length of the end of central directory record without its comment.
+const END_OF_CENTRAL_DIRECTORY_LENGTH: usize = 22;
+
+/// Entry of an archive inspected by the deep validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    /// Name of the entry, with its directories
+    pub name: String,
+    /// Size of the entry in the archive
+    pub compressed_size: u64,
+    /// Size of the entry once decompressed
+    pub size: u64,
+    /// Mime type detected from the contents, `None` for a directory
+    pub mime_type: Option<&'static str>,
+}
+
+/// Archive inspected by the deep validation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveInfo {
+    /// Entries of the archive, in the order of its central directory
+    pub entries: Vec<ArchiveEntry>,
+}
+
+impl ArchiveInfo {
+    /// Size of the entries once decompressed.
+    pub fn size(&self) -> u64 {
+        self.entries.iter().map(|entry| entry.size).sum()
+    }
+}
+
+/// Limit of the inspection of an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ArchiveLimit {
+    /// Number of entries of an archive, see [`FileValidatorBuilder::max_archive_entries`](crate::FileValidatorBuilder::max_archive_entries)
+    Entries,
+    /// Size of the entries of an archive once decompressed, see [`FileValidatorBuilder::max_archive_size`](crate::FileValidatorBuilder::max_archive_size)
+    Size,
+    /// Compression ratio of an entry, see [`FileValidatorBuilder::max_compression_ratio`](crate::FileValidatorBuilder::max_compression_ratio)
+    Ratio,
+    /// Nesting depth of the archives, see [`FileValidatorBuilder::max_archive_depth`](crate::FileValidatorBuilder::max_archive_depth)
+    Depth,
+}
+
+/// Limits of the inspection of the archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ArchiveLimits {
+    pub(crate) max_entries: usize,
+    pub(crate) max_size: u64,
+    pub(crate) max_ratio: u64,
+    pub(crate) max_depth: usize,
+}
+
+impl Default for ArchiveLimits {
+    fn default() -> Self {
+        ArchiveLimits { max_entries: 10_000, max_size: 1 << 30, max_ratio: 100, max_depth: 2 }
+    }
+}
+
+impl ArchiveLimits {
+    /// Check a value against the maximum of a limit.
+    pub(crate) fn check(&self, limit: ArchiveLimit, actual: u64) -> Result<(), ArchiveError> {
+        let max = match limit {
+            ArchiveLimit::Entries => self.max_entries as u64,
+            ArchiveLimit::Size => self.max_size,
+            ArchiveLimit::Ratio => self.max_ratio,
+            ArchiveLimit::Depth => self.max_depth as u64,
+        };
+        if actual > max {
+            return Err(ArchiveError::TooLarge { limit, actual, max });
+        }
+        Ok(())
+    }
+}
+
+/// Error of the inspection of an archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// The archive is malformed or truncated, for the given reason
+    Invalid(String),
+    /// The archive or one of its entries can't be inspected, for the given reason, e.g. a RAR
+    /// archive or an encrypted entry
+    Unsupported(String),
+    /// The archive exceeds a limit, e.g. a zip bomb
+    TooLarge {
+        /// The exceeded limit
+        limit: ArchiveLimit,
+        /// The value of the archive
+        actual: u64,
+        /// The configured maximum
+        max: u64,
+    },
+    /// An entry is not allowed by the validator, for the given reason
+    Entry {
+        /// Name of the entry
+        name: String,
+        /// Why the entry is not allowed
+        reason: String,
+    },
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ArchiveError::Invalid(reason) => write!(f, "Invalid archive: {}.", reason),
+            ArchiveError::Unsupported(reason) => write!(f, "The archive can't be inspected: {}.", reason),
+            ArchiveError::TooLarge { limit: ArchiveLimit::Entries, actual, max } => {
+                write!(f, "The archive has too many entries ({}, at most {}).", actual, max)
+            }
+            ArchiveError::TooLarge { limit: ArchiveLimit::Size, actual, max } => {
+                write!(f, "The archive is too large once decompressed ({} bytes, at most {}).", actual, max)
+            }
+            ArchiveError::TooLarge { limit: ArchiveLimit::Ratio, actual, max } => {
+                write!(f, "An archive entry is too compressed (ratio of {}, at most {}).", actual, max)
+            }
+            ArchiveError::TooLarge { limit: ArchiveLimit::Depth, actual, max } => {
+                write!(f, "The archives are nested too deep ({} levels, at most {}).", actual, max)
+            }
+            ArchiveError::Entry { name, reason } => write!(f, "Invalid archive entry {}: {}", name, reason),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {}
+
+impl From<Truncated> for ArchiveError {
+    fn from(_: Truncated) -> Self {
+        invalid("truncated file")
+    }
+}
+
+fn invalid(reason: &str) -> ArchiveError {
+    ArchiveError::Invalid(reason.to_string())
+}
+
+/// Entry of a ZIP archive, read from its central directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ZipEntry<'a> {
+    pub(crate) name: String,
+    pub(crate) compressed_size: u64,
+    pub(crate) size: u64,
+    method: u16,
+    crc32: u32,
+    data: &'a [u8],
+}
+
+impl ZipEntry<'_> {
+    /// Whether the entry is a directory.
+    pub(crate) fn is_dir(&self) -> bool {
+        self.name.ends_with('/')
+    }
+
+    /// Decompress the entry, stopping at its declared size, and check its checksum.
+    pub(crate) fn decompress(&self) -> Result<Vec<u8>, ArchiveError> {
+        let contents = match self.method {
+            0 => self.data.to_vec(),
+            8 => inflate(self.data, self.size as usize).map_err(|InflateError(reason)| ArchiveError::Invalid(format!("entry {}: {}", self.name, reason)))?,
+            method => return Err(ArchiveError::Unsupported(format!("compression method {} of entry {}", method, self.name))),
+        };
+        if contents.len() as u64 != self.size {
+            return Err(ArchiveError::Invalid(format!("entry {}: size mismatch", self.name)));
+        }
+        if crc32(&contents) != self.crc32 {
+            return Err(ArchiveError::Invalid(format!("entry {}: bad checksum", self.name)));
+        }
+        Ok(contents)
+    }
+}
+
+/// Read the central directory of a ZIP archive and check it against the limits, without
+/// decompressing the entries.
+pub(crate) fn read_zip<'a>(contents: &'a [u8], limits: &ArchiveLimits) -> Result<Vec<ZipEntry<'a>>, ArchiveError> {
+    // The end of central directory record is followed by a comment of at most 65535 bytes
+    let search_start = contents.len().saturating_sub(END_OF_CENTRAL_DIRECTORY_LENGTH + u16::MAX as usize);
+    let end = contents[search_start..]
+        .windows(END_OF_CENTRAL_DIRECTORY.len())
+        .rposition(|window| window == END_OF_CENTRAL_DIRECTORY)
+        .map(|position| search_start + position)
+        .ok_or_else(|| invalid("no end of central directory"))?;
+
+    let mut reader = Reader::new(&contents[end + 4..]);
+    let (disk, directory_disk) = (reader.le_u16()?, reader.le_u16()?);
+    let (disk_entries, entry_count) = (reader.le_u16()?, reader.le_u16()?);
+    let (directory_size, directory_offset) = (reader.le_u32()?, reader.le_u32()?);
+    if disk != 0 || directory_disk != 0 || disk_entries != entry_count {
+        return Err(ArchiveError::Unsupported(String::from("multi-disk archive")));
+    }
+    if entry_count == u16::MAX || directory_size == u32::MAX || directory_offset == u32::MAX {
+        return Err(ArchiveError::Unsupported(String::from("ZIP64 archive")));
+    }
+    limits.check(ArchiveLimit::Entries, entry_count as u64)?;
+
+    let directory = contents
+        .get(directory_offset as usize..)
+        .and_then(|directory| directory.get(..directory_size as usize))
+        .ok_or_else(|| invalid("central directory out of the file"))?;
+    let mut reader = Reader::new(directory);
+    let mut entries = Vec::with_capacity(entry_count as usize);
+    let mut total_size = 0u64;
+    for _ in 0..entry_count {
+        let entry = read_entry(contents, &mut reader)?;
+        total_size += entry.size;
+        limits.check(ArchiveLimit::Size, total_size)?;
+        if entry.size > 0 {
+            limits.check(ArchiveLimit::Ratio, entry.size / entry.compressed_size.max(1))?;
+        }
+        entries.push(entry);
+    }
+
+    // Overlapping entries multiply the decompressed size of the same bytes
+    let mut ranges: Vec<(usize, usize)> = entries
+        .iter()
+        .filter(|entry| !entry.data.is_empty())
+        .map(|entry| {
+            let start = entry.data.as_ptr() as usize - contents.as_ptr() as usize;
+            (start, start + entry.data.len())
+        })
+        .collect();
+    ranges.sort_unstable();
+    if ranges.windows(2).any(|pair| pair[1].0 < pair[0].1) {
+        return Err(invalid("overlapping entries"));
+    }
+    Ok(entries)
+}
+
+/// Read an entry of the central directory and locate its data after its local file header.
+fn read_entry<'a>(contents: &'a [u8], reader: &mut Reader) -> Result<ZipEntry<'a>, ArchiveError> {
+    if reader.take(4)? != CENTRAL_DIRECTORY_HEADER {
+        return Err(invalid("bad central directory header"));
+    }
+    reader.skip(4)?;
+    let flags = reader.le_u16()?;
+    let method = reader.le_u16()?;
+    reader.skip(4)?;
+    let crc32 = reader.le_u32()?;
+    let (compressed_size, size) = (reader.le_u32()?, reader.le_u32()?);
+    let (name_length, extra_length, comment_length) = (reader.le_u16()?, reader.le_u16()?, reader.le_u16()?);
+    reader.skip(8)?;
+    let local_offset = reader.le_u32()?;
+    let name = String::from_utf8_lossy(reader.take(name_length as usize)?).into_owned();
+    reader.skip(extra_length as usize + comment_length as usize)?;
+
+    if flags & 1 != 0 {
+        return Err(ArchiveError::Unsupported(format!("encrypted entry {}", name)));
+    }
+    if compressed_size == u32::MAX || size == u32::MAX || local_offset == u32::MAX {
+        return Err(ArchiveError::Unsupported(String::from("ZIP64 archive")));
+    }
+
+    let mut local = Reader::new(contents.get(local_offset as usize..).ok_or_else(|| invalid("local file header out of the file"))?);
+    if local.take(4)? != LOCAL_FILE_HEADER {
+        return Err(invalid("bad local file header"));
+    }
+    local.skip(22)?;
+    let (local_name_length, local_extra_length) = (local.le_u16()?, local.le_u16()?);
+    local.skip(local_name_length as usize + local_extra_length as usize)?;
+    let data = local.take(compressed_size as usize)?;
+
+    Ok(ZipEntry { name, compressed_size: compressed_size as u64, size: size as u64, method, crc32, data })
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{read_zip, ArchiveError, ArchiveLimit, ArchiveLimits};
+    use crate::validators::image_header::crc32;
+
+    /// Build a ZIP archive of entries given by name, compression method, declared size and data.
+    pub(crate) fn zip(entries: &[(&str, u16, u32, &[u8])]) -> Vec<u8> {
+        let mut archive = Vec::new();
+        let mut directory = Vec::new();
+        for &(name, method, size, data) in entries {
+            let contents = if method == 0 { data.to_vec() } else { crate::validators::inflate::inflate(data, size as usize).unwrap_or_default() };
+            let mut header = Vec::new();
+            header.extend_from_slice(&20u16.to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+            header.extend_from_slice(&method.to_le_bytes());
+            header.extend_from_slice(&0u32.to_le_bytes());
+            header.extend_from_slice(&crc32(&contents).to_le_bytes());
+            header.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            header.extend_from_slice(&size.to_le_bytes());
+            header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&0u16.to_le_bytes());
+
+            directory.extend_from_slice(b"PK\x01\x02\x14\x00");
+            directory.extend_from_slice(&header);
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&(archive.len() as u32).to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+
+            archive.extend_from_slice(b"PK\x03\x04");
+            archive.extend_from_slice(&header);
+            archive.extend_from_slice(name.as_bytes());
+            archive.extend_from_slice(data);
+        }
+        let offset = archive.len() as u32;
+        archive.extend_from_slice(&directory);
+        archive.extend_from_slice(b"PK\x05\x06\x00\x00\x00\x00");
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+        archive.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        archive.extend_from_slice(&offset.to_le_bytes());
+        archive.extend_from_slice(&[0, 0]);
+        archive
+    }
+
+    #[test]
+    fn zip_entries() {
+        let archive = zip(&[("a/", 0, 0, b""), ("a/b.txt", 0, 3, b"abc"), ("c.txt", 8, 9, b"\x4b\x4c\x4a\x4e\x04\x23\x00")]);
+        let entries = read_zip(&archive, &ArchiveLimits::default()).unwrap();
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["a/", "a/b.txt", "c.txt"]);
+        assert!(entries[0].is_dir());
+        assert_eq!(entries[1].decompress().unwrap(), b"abc");
+        assert_eq!((entries[2].compressed_size, entries[2].size), (7, 9));
+        assert_eq!(entries[2].decompress().unwrap(), b"abcabcabc");
+
+        // empty archive
+        assert!(read_zip(&zip(&[]), &ArchiveLimits::default()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn zip_bombs() {
+        let limits = ArchiveLimits { max_entries: 2, max_size: 100, max_ratio: 10, max_depth: 1 };
+        let error = read_zip(&zip(&[("a", 0, 1, b"a"), ("b", 0, 1, b"b"), ("c", 0, 1, b"c")]), &limits).unwrap_err();
+        assert_eq!(error, ArchiveError::TooLarge { limit: ArchiveLimit::Entries, actual: 3, max: 2 });
+        assert_eq!(error.to_string(), "The archive has too many entries (3, at most 2).");
+
+        let error = read_zip(&zip(&[("a", 0, 60, &[0; 60]), ("b", 0, 60, &[0; 60])]), &limits).unwrap_err();
+        assert_eq!(error, ArchiveError::TooLarge { limit: ArchiveLimit::Size, actual: 120, max: 100 });
+
+        // declared size much larger than the compressed data
+        let error = read_zip(&zip(&[("a", 8, 90, b"\x4b\x4c\x4a\x4e\x04\x23\x00")]), &limits).unwrap_err();
+        assert_eq!(error, ArchiveError::TooLarge { limit: ArchiveLimit::Ratio, actual: 12, max: 10 });
+
+        // data larger than declared
+        let archive = zip(&[("a", 8, 3, b"\x4b\x4c\x4a\x4e\x04\x23\x00")]);
+        let entries = read_zip(&archive, &ArchiveLimits::default()).unwrap();
+        assert_eq!(entries[0].decompress().unwrap_err().to_string(), "Invalid archive: entry a: output too large.");
+
+        // two entries sharing the same data
+        let mut archive = zip(&[("a", 0, 3, b"abc"), ("b", 0, 3, b"abc")]);
+        let second = archive.windows(4).rposition(|window| window == b"PK\x01\x02").unwrap();
+        archive[second + 42..second + 46].copy_from_slice(&0u32.to_le_bytes());
+        assert_eq!(read_zip(&archive, &ArchiveLimits::default()).unwrap_err(), ArchiveError::Invalid(String::from("overlapping entries")));
+    }
+
+    #[test]
+    fn invalid_zips() {
+        let limits = ArchiveLimits::default();
+        assert_eq!(read_zip(b"PK\x03\x04", &limits).unwrap_err().to_string(), "Invalid archive: no end of central directory.");
+
+        let archive = zip(&[("a", 0, 3, b"abc")]);
+        assert!(read_zip(&archive[20..], &limits).is_err());
+
+        let mut encrypted = archive.clone();
+        encrypted[6] = 1;
+        let directory = encrypted.windows(4).position(|window| window == b"PK\x01\x02").unwrap();
+        encrypted[directory + 8] = 1;
+        assert_eq!(read_zip(&encrypted, &limits).unwrap_err().to_string(), "The archive can't be inspected: encrypted entry a.");
+
+        let mut corrupted = archive.clone();
+        corrupted[31] = b'x';
+        assert_eq!(read_zip(&corrupted, &limits).unwrap()[0].decompress().unwrap_err().to_string(), "Invalid archive: entry a: bad checksum.");
+    }
+}
@@ -0,0 +1,163 @@
+//! Validation of domestic bank account details: US routing numbers, UK sort codes and accounts,
+//! and the account numbers of the countries of [`account_number_length`].
+
+use std::ops::RangeInclusive;
+
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use super::ValidationError;
+
+/// Validate a US ABA routing transit number: 9 digits with a valid Federal Reserve prefix and
+/// checksum.
+///
+/// The checksum is `3 * (d1 + d4 + d7) + 7 * (d2 + d5 + d8) + (d3 + d6 + d9)` modulo 10, which
+/// must be 0.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_aba_routing("011000015"));
+/// assert!(!validate_aba_routing("011000016"));
+/// ```
+pub fn validate_aba_routing(routing: &str) -> bool {
+    if routing.len() != 9 || !routing.bytes().all(|c| c.is_ascii_digit()) {
+        return false;
+    }
+
+    let digits: Vec<u32> = routing.bytes().map(|c| (c - b'0') as u32).collect();
+
+    // Federal Reserve routing symbols, thrift institutions and electronic transactions
+    let prefix = digits[0] * 10 + digits[1];
+    if !matches!(prefix, 0..=12 | 21..=32 | 61..=72 | 80) {
+        return false;
+    }
+
+    let checksum = 3 * (digits[0] + digits[3] + digits[6])
+        + 7 * (digits[1] + digits[4] + digits[7])
+        + (digits[2] + digits[5] + digits[8]);
+    checksum.is_multiple_of(10)
+}
+
+/// Validate a UK sort code: 6 digits, optionally grouped by pairs with hyphens or spaces
+/// (`12-34-56`, `12 34 56` or `123456`).
+///
+/// Only the format is checked, the sort code may not be assigned to a bank.
+pub fn validate_uk_sort_code(sort_code: &str) -> bool {
+    lazy_static! {
+        static ref REGEX: Regex = Regex::new(r"^([0-9]{2}-[0-9]{2}-[0-9]{2}|[0-9]{2} [0-9]{2} [0-9]{2}|[0-9]{6})$").unwrap();
+    }
+    REGEX.is_match(sort_code)
+}
+
+/// Allowed number of digits of a domestic account number by ISO 3166 alpha-2 country code.
+const ACCOUNT_NUMBER_LENGTHS: &[(&str, RangeInclusive<usize>)] = &[
+    ("AU", 6..=10),
+    ("CA", 7..=12),
+    ("CN", 16..=19),
+    ("IN", 9..=18),
+    ("JP", 7..=7),
+    ("NZ", 15..=16),
+    ("SG", 9..=12),
+    ("US", 4..=17),
+    ("ZA", 9..=11),
+];
+
+/// Allowed number of digits of a domestic account number in a country (ISO 3166 alpha-2 code,
+/// not case sensitive), or `None` if the country is unknown.
+pub fn account_number_length(country: &str) -> Option<RangeInclusive<usize>> {
+    ACCOUNT_NUMBER_LENGTHS
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(country))
+        .map(|(_, length)| length.clone())
+}
+
+/// Validate a domestic account number of a country: only digits, with the length allowed in
+/// the country. Spaces and hyphens used to group the digits are ignored.
+///
+/// The UK is not listed as its account numbers are 8 digits identified with a sort code, see
+/// [`validate_uk_account`].
+///
+/// # Errors
+/// If the country is unknown, a [`ValidationError::InvalidOption`] is returned.
+pub fn validate_account_number(country: &str, account: &str) -> Result<bool, ValidationError> {
+    let length = account_number_length(country).ok_or_else(|| ValidationError::option("Unknown country."))?;
+
+    let digits: String = account.chars().filter(|&c| c != ' ' && c != '-').collect();
+    Ok(digits.bytes().all(|c| c.is_ascii_digit()) && length.contains(&digits.len()))
+}
+
+/// Validate a UK domestic account: a sort code (see [`validate_uk_sort_code`]) and an 8 digits
+/// account number.
+pub fn validate_uk_account(sort_code: &str, account: &str) -> bool {
+    validate_uk_sort_code(sort_code) && account.len() == 8 && account.bytes().all(|c| c.is_ascii_digit())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::banking::*;
+    use crate::ValidationError;
+
+    #[test]
+    fn valid_aba_routings() {
+        assert!(validate_aba_routing("011000015"));
+        assert!(validate_aba_routing("021000021"));
+        assert!(validate_aba_routing("122105155"));
+        assert!(validate_aba_routing("322271627"));
+    }
+
+    #[test]
+    fn invalid_aba_routings() {
+        // checksum
+        assert!(!validate_aba_routing("011000016"));
+        assert!(!validate_aba_routing("021000012"));
+
+        // invalid prefixes
+        assert!(!validate_aba_routing("130000001"));
+        assert!(!validate_aba_routing("900000000"));
+
+        // only 9 digits
+        assert!(!validate_aba_routing(""));
+        assert!(!validate_aba_routing("01100001"));
+        assert!(!validate_aba_routing("0110000155"));
+        assert!(!validate_aba_routing("01100001a"));
+        assert!(!validate_aba_routing("011-000-015"));
+    }
+
+    #[test]
+    fn uk_sort_codes() {
+        assert!(validate_uk_sort_code("12-34-56"));
+        assert!(validate_uk_sort_code("12 34 56"));
+        assert!(validate_uk_sort_code("123456"));
+
+        assert!(!validate_uk_sort_code("12-34 56"));
+        assert!(!validate_uk_sort_code("1234567"));
+        assert!(!validate_uk_sort_code("12-34-5a"));
+        assert!(!validate_uk_sort_code(""));
+    }
+
+    #[test]
+    fn uk_accounts() {
+        assert!(validate_uk_account("12-34-56", "31926819"));
+        assert!(!validate_uk_account("12-34-56", "3192681"));
+        assert!(!validate_uk_account("12-34-5", "31926819"));
+    }
+
+    #[test]
+    fn account_numbers() {
+        assert!(validate_account_number("US", "123456789").unwrap());
+        assert!(validate_account_number("us", "1234").unwrap());
+        assert!(validate_account_number("IN", "1234 5678 9012").unwrap());
+        assert!(validate_account_number("JP", "1234567").unwrap());
+
+        // length
+        assert!(!validate_account_number("US", "123").unwrap());
+        assert!(!validate_account_number("JP", "12345678").unwrap());
+
+        // only digits
+        assert!(!validate_account_number("US", "12345678a").unwrap());
+
+        let error = validate_account_number("XX", "1234").unwrap_err();
+        assert!(matches!(error, ValidationError::InvalidOption(ref message) if message == "Unknown country."));
+        assert_eq!(account_number_length("ca"), Some(7..=12));
+    }
+}
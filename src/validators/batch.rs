@@ -0,0 +1,270 @@
+#[cfg(feature = "url")]
+use std::io::{self, BufRead, Lines};
+
+#[cfg(feature = "uuid")]
+use super::validate_uuid;
+#[cfg(feature = "url")]
+use super::{UrlError, UrlValidator};
+
+/// Validate many urls with a single validator, e.g. the urls of a log file.
+///
+/// Each result is `Ok(true)` for a valid url, `Ok(false)` for an invalid one and an error if the
+/// url exceeds a length limit of the validator (see [`UrlValidator::check`]). The results are in
+/// the order of the urls.
+///
+/// # Examples
+/// ``` ignore
+/// let validator = UrlValidator::builder().max_length(2048).build()?;
+/// let results = validate_urls(log.lines(), &validator);
+/// ```
+#[cfg(feature = "url")]
+pub fn validate_urls<I, S>(urls: I, validator: &UrlValidator) -> Vec<Result<bool, UrlError>>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    urls.into_iter().map(|url| check(validator, url.as_ref())).collect()
+}
+
+/// Validate many urls in parallel with a single validator, enabled with the `parallel` feature.
+///
+/// The urls are split in one chunk per available cpu, each validated by its own thread. The
+/// results are the same as with [`validate_urls`], in the order of the urls. Use
+/// [`crate::RegexStrategy::ThreadLocal`] to avoid contention on the regexes of the validator.
+#[cfg(all(feature = "url", feature = "parallel"))]
+pub fn par_validate_urls<S>(urls: &[S], validator: &UrlValidator) -> Vec<Result<bool, UrlError>>
+where
+    S: AsRef<str> + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = urls.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = urls
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || validate_urls(chunk, validator)))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+/// Validate many version-5 uuids lazily as by [`validate_uuid`], e.g. the ids of an ingested
+/// list, yielding the index of each uuid with its result.
+///
+/// # Examples
+/// ``` ignore
+/// let invalid: Vec<usize> = validate_uuids(&ids).filter(|&(_, valid)| !valid).map(|(index, _)| index).collect();
+/// ```
+#[cfg(feature = "uuid")]
+pub fn validate_uuids<I, S>(uuids: I) -> impl Iterator<Item = (usize, bool)>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    uuids.into_iter().enumerate().map(|(index, uuid)| (index, validate_uuid(uuid.as_ref()).is_ok()))
+}
+
+/// Validate many version-5 uuids in parallel, enabled with the `parallel` feature.
+///
+/// The uuids are split in one chunk per available cpu as by [`par_validate_urls`]. The results
+/// are the same as with [`validate_uuids`], in the order of the uuids.
+#[cfg(all(feature = "uuid", feature = "parallel"))]
+pub fn par_validate_uuids<S>(uuids: &[S]) -> Vec<(usize, bool)>
+where
+    S: AsRef<str> + Sync,
+{
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = uuids.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = uuids
+            .chunks(chunk_size)
+            .enumerate()
+            .map(|(chunk_index, chunk)| {
+                scope.spawn(move || validate_uuids(chunk).map(|(index, valid)| (chunk_index * chunk_size + index, valid)).collect::<Vec<_>>())
+            })
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+/// Url read from a list, see [`validate_url_lines`].
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedUrl {
+    /// Line number of the url in the list, starting at 1
+    pub line: usize,
+    /// The url as written
+    pub url: String,
+    /// The result of the validation, see [`validate_urls`]
+    pub result: Result<bool, UrlError>,
+}
+
+/// Counts of the urls validated by an [`UrlLines`].
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UrlSummary {
+    /// Number of valid urls
+    pub valid: usize,
+    /// Number of invalid urls
+    pub invalid: usize,
+    /// Number of urls exceeding a length limit of the validator
+    pub too_long: usize,
+}
+
+#[cfg(feature = "url")]
+impl UrlSummary {
+    /// Number of validated urls.
+    pub fn total(&self) -> usize {
+        self.valid + self.invalid + self.too_long
+    }
+}
+
+/// Iterator validating the urls of a reader line by line, see [`validate_url_lines`].
+#[cfg(feature = "url")]
+#[derive(Debug)]
+pub struct UrlLines<'a, R> {
+    lines: Lines<R>,
+    line: usize,
+    validator: &'a UrlValidator,
+    summary: UrlSummary,
+}
+
+#[cfg(feature = "url")]
+impl<R> UrlLines<'_, R> {
+    /// Counts of the urls validated so far, of the whole list once the iterator is exhausted.
+    pub fn summary(&self) -> UrlSummary {
+        self.summary
+    }
+}
+
+#[cfg(feature = "url")]
+impl<R: BufRead> Iterator for UrlLines<'_, R> {
+    type Item = io::Result<ValidatedUrl>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let url = match self.lines.next()? {
+                Ok(url) => url,
+                Err(e) => return Some(Err(e)),
+            };
+            self.line += 1;
+            if url.is_empty() {
+                continue;
+            }
+
+            let result = check(self.validator, &url);
+            match result {
+                Ok(true) => self.summary.valid += 1,
+                Ok(false) => self.summary.invalid += 1,
+                Err(_) => self.summary.too_long += 1,
+            }
+            return Some(Ok(ValidatedUrl { line: self.line, url, result }));
+        }
+    }
+}
+
+/// Validate a list of newline separated urls lazily, e.g. a huge dump read from a file, without
+/// loading it in memory. Empty lines are skipped. A line which is not valid utf-8 or a read
+/// failure yields an error.
+///
+/// # Examples
+/// ``` ignore
+/// let mut lines = validate_url_lines(BufReader::new(File::open("urls.txt")?), &validator);
+/// for url in &mut lines {
+///     let url = url?;
+///     if url.result != Ok(true) {
+///         println!("line {}: {}", url.line, url.url);
+///     }
+/// }
+/// println!("{} valid urls of {}", lines.summary().valid, lines.summary().total());
+/// ```
+#[cfg(feature = "url")]
+pub fn validate_url_lines<R: BufRead>(reader: R, validator: &UrlValidator) -> UrlLines<'_, R> {
+    UrlLines {
+        lines: reader.lines(),
+        line: 0,
+        validator,
+        summary: UrlSummary::default(),
+    }
+}
+
+#[cfg(feature = "url")]
+fn check(validator: &UrlValidator, url: &str) -> Result<bool, UrlError> {
+    match validator.check(url) {
+        Ok(_) => Ok(true),
+        Err(UrlError::Invalid) => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+#[cfg(all(test, feature = "url", feature = "uuid"))]
+mod tests {
+    use crate::{validate_url_lines, validate_urls, validate_uuids, UrlError, UrlLimit, UrlSummary, UrlValidator, ValidatedUrl};
+
+    #[test]
+    fn batch_results() {
+        let validator = UrlValidator::builder().max_length(20).build().unwrap();
+        let results = validate_urls(["test.com", "test", "https://www.test.com/long"], &validator);
+        assert_eq!(results, [
+            Ok(true),
+            Ok(false),
+            Err(UrlError::TooLong { limit: UrlLimit::Length, actual: 25, max: 20 }),
+        ]);
+
+        let owned = vec![String::from("test.ch")];
+        assert_eq!(validate_urls(&owned, &validator), [Ok(true)]);
+        assert!(validate_urls(Vec::<String>::new(), &validator).is_empty());
+    }
+
+    #[test]
+    fn url_lines() {
+        let validator = UrlValidator::builder().max_length(20).build().unwrap();
+        let list = "test.com\r\ntest\n\nhttps://www.test.com/long\ntest.ch";
+
+        let mut lines = validate_url_lines(list.as_bytes(), &validator);
+        assert_eq!(lines.next().unwrap().unwrap(), ValidatedUrl { line: 1, url: String::from("test.com"), result: Ok(true) });
+        assert_eq!(lines.summary().total(), 1);
+
+        let rest: Vec<_> = lines.by_ref().map(|url| url.unwrap()).collect();
+        assert_eq!(rest.iter().map(|url| url.line).collect::<Vec<_>>(), [2, 4, 5]);
+        assert_eq!(lines.summary(), UrlSummary { valid: 2, invalid: 1, too_long: 1 });
+
+        // invalid utf-8
+        let mut lines = validate_url_lines(&b"test.com\n\xff\n"[..], &validator);
+        assert!(lines.next().unwrap().is_ok());
+        assert!(lines.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn uuid_batches() {
+        let uuids = ["c70dc454-1c7d-5c59-8fed-3a321e6a4a49", "c70dc454-1c7d-4c59-8fed-3a321e6a4a49", "", "b267fe9e-6e37-5bed-a2c5-e44943802a91"];
+        assert_eq!(validate_uuids(uuids).collect::<Vec<_>>(), [(0, true), (1, false), (2, false), (3, true)]);
+
+        let invalid: Vec<usize> = validate_uuids(&uuids).filter(|&(_, valid)| !valid).map(|(index, _)| index).collect();
+        assert_eq!(invalid, [1, 2]);
+        assert_eq!(validate_uuids(Vec::<String>::new()).count(), 0);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_uuid_batches() {
+        use crate::par_validate_uuids;
+
+        let uuids: Vec<String> = (0..1000).map(|i| format!("c70dc454-1c7d-{}c59-8fed-3a321e6a{:04}", i % 6, i)).collect();
+        assert_eq!(par_validate_uuids(&uuids), validate_uuids(&uuids).collect::<Vec<_>>());
+        assert_eq!(par_validate_uuids(&uuids).iter().filter(|&&(_, valid)| valid).count(), 166);
+        assert!(par_validate_uuids::<&str>(&[]).is_empty());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_results() {
+        use crate::par_validate_urls;
+
+        let validator = UrlValidator::builder().build().unwrap();
+        let urls: Vec<String> = (0..1000).map(|i| if i % 3 == 0 { format!("test{}", i) } else { format!("test{}.com", i) }).collect();
+        assert_eq!(par_validate_urls(&urls, &validator), validate_urls(&urls, &validator));
+        assert!(par_validate_urls::<&str>(&[], &validator).is_empty());
+    }
+}
@@ -0,0 +1,271 @@
+use std::io::{self, Write};
+
+/// Initial chaining value, the same as the initial state of SHA-256.
+const IV: [u32; 8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+/// Permutation of the message words between the rounds.
+const MESSAGE_PERMUTATION: [usize; 16] = [2, 6, 3, 10, 7, 0, 4, 13, 1, 11, 12, 5, 9, 14, 15, 8];
+
+const BLOCK_LENGTH: usize = 64;
+const CHUNK_LENGTH: usize = 1024;
+
+// Domain separation flags
+const CHUNK_START: u32 = 1 << 0;
+const CHUNK_END: u32 = 1 << 1;
+const PARENT: u32 = 1 << 2;
+const ROOT: u32 = 1 << 3;
+
+/// Mixing function, on two columns or diagonals of the state.
+fn g(state: &mut [u32; 16], (a, b, c, d): (usize, usize, usize, usize), x: u32, y: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(x);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(y);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+/// Compression of a block, returning the whole state, the chaining value in its first 8 words.
+fn compress(chaining_value: &[u32; 8], block: &[u32; 16], counter: u64, block_length: u32, flags: u32) -> [u32; 16] {
+    #[rustfmt::skip]
+    let mut state = [
+        chaining_value[0], chaining_value[1], chaining_value[2], chaining_value[3],
+        chaining_value[4], chaining_value[5], chaining_value[6], chaining_value[7],
+        IV[0], IV[1], IV[2], IV[3],
+        counter as u32, (counter >> 32) as u32, block_length, flags,
+    ];
+    let mut message = *block;
+    for round in 0..7 {
+        g(&mut state, (0, 4, 8, 12), message[0], message[1]);
+        g(&mut state, (1, 5, 9, 13), message[2], message[3]);
+        g(&mut state, (2, 6, 10, 14), message[4], message[5]);
+        g(&mut state, (3, 7, 11, 15), message[6], message[7]);
+        g(&mut state, (0, 5, 10, 15), message[8], message[9]);
+        g(&mut state, (1, 6, 11, 12), message[10], message[11]);
+        g(&mut state, (2, 7, 8, 13), message[12], message[13]);
+        g(&mut state, (3, 4, 9, 14), message[14], message[15]);
+        if round < 6 {
+            message = MESSAGE_PERMUTATION.map(|index| message[index]);
+        }
+    }
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+    state
+}
+
+/// The little endian words of a block.
+fn words(block: &[u8; BLOCK_LENGTH]) -> [u32; 16] {
+    let mut words = [0; 16];
+    for (word, bytes) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(bytes.try_into().unwrap());
+    }
+    words
+}
+
+/// The first 8 words of a compressed state.
+fn chaining_value(state: [u32; 16]) -> [u32; 8] {
+    state[..8].try_into().unwrap()
+}
+
+/// Last compression of a chunk or of a parent node, which is the root one of the hash.
+struct Output {
+    chaining_value: [u32; 8],
+    block: [u32; 16],
+    counter: u64,
+    block_length: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        chaining_value(compress(&self.chaining_value, &self.block, self.counter, self.block_length, self.flags))
+    }
+
+    fn root_digest(&self) -> [u8; 32] {
+        let state = compress(&self.chaining_value, &self.block, 0, self.block_length, self.flags | ROOT);
+        let mut digest = [0; 32];
+        for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+            bytes.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+
+    /// Parent node of two chaining values.
+    fn parent(left: [u32; 8], right: [u32; 8]) -> Output {
+        let mut block = [0; 16];
+        block[..8].copy_from_slice(&left);
+        block[8..].copy_from_slice(&right);
+        Output { chaining_value: IV, block, counter: 0, block_length: BLOCK_LENGTH as u32, flags: PARENT }
+    }
+}
+
+/// Chunk of 1024 bytes being hashed, block by block.
+#[derive(Debug, Clone)]
+struct Chunk {
+    chaining_value: [u32; 8],
+    counter: u64,
+    block: [u8; BLOCK_LENGTH],
+    block_length: usize,
+    blocks_compressed: usize,
+}
+
+impl Chunk {
+    fn new(counter: u64) -> Self {
+        Chunk { chaining_value: IV, counter, block: [0; BLOCK_LENGTH], block_length: 0, blocks_compressed: 0 }
+    }
+
+    fn len(&self) -> usize {
+        self.blocks_compressed * BLOCK_LENGTH + self.block_length
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    /// Add bytes to the chunk, at most the bytes left in it. The last block is only compressed
+    /// with the next bytes, as it is the one of the output if there are none.
+    fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            if self.block_length == BLOCK_LENGTH {
+                let state = compress(&self.chaining_value, &words(&self.block), self.counter, BLOCK_LENGTH as u32, self.start_flag());
+                self.chaining_value = chaining_value(state);
+                self.blocks_compressed += 1;
+                self.block = [0; BLOCK_LENGTH];
+                self.block_length = 0;
+            }
+            let taken = bytes.len().min(BLOCK_LENGTH - self.block_length);
+            self.block[self.block_length..self.block_length + taken].copy_from_slice(&bytes[..taken]);
+            self.block_length += taken;
+            bytes = &bytes[taken..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            chaining_value: self.chaining_value,
+            block: words(&self.block),
+            counter: self.counter,
+            block_length: self.block_length as u32,
+            flags: self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// Incremental BLAKE3 hasher, with the default 32 bytes output, fed through [`Write`] or
+/// [`Blake3::update`]. Port of the single-threaded reference implementation of the
+/// [specification](https://github.com/BLAKE3-team/BLAKE3-specs).
+#[derive(Debug, Clone)]
+pub(crate) struct Blake3 {
+    chunk: Chunk,
+    /// Chaining values of the complete subtrees, merged as the chunks are added
+    stack: Vec<[u32; 8]>,
+}
+
+impl Blake3 {
+    pub(crate) fn new() -> Self {
+        Blake3 { chunk: Chunk::new(0), stack: Vec::new() }
+    }
+
+    pub(crate) fn update(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            // A full chunk is only finalized with the next bytes, as it is the root one if there are none
+            if self.chunk.len() == CHUNK_LENGTH {
+                let mut chaining_value = self.chunk.output().chaining_value();
+                let mut chunks = self.chunk.counter + 1;
+                while chunks & 1 == 0 {
+                    chaining_value = Output::parent(self.stack.pop().unwrap(), chaining_value).chaining_value();
+                    chunks >>= 1;
+                }
+                self.stack.push(chaining_value);
+                self.chunk = Chunk::new(self.chunk.counter + 1);
+            }
+            let taken = bytes.len().min(CHUNK_LENGTH - self.chunk.len());
+            self.chunk.update(&bytes[..taken]);
+            bytes = &bytes[taken..];
+        }
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 32] {
+        let mut output = self.chunk.output();
+        for &left in self.stack.iter().rev() {
+            output = Output::parent(left, output.chaining_value());
+        }
+        output.root_digest()
+    }
+}
+
+impl Write for Blake3 {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.update(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// BLAKE3 digest of a content, e.g. to compare it with [`ValidatedFile::blake3`](crate::ValidatedFile::blake3).
+pub fn blake3(contents: &[u8]) -> [u8; 32] {
+    let mut hasher = Blake3::new();
+    hasher.update(contents);
+    hasher.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{blake3, Blake3};
+
+    fn hex(digest: [u8; 32]) -> String {
+        digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Input of the official test vectors: the bytes 0 to 250, repeated.
+    fn input(length: usize) -> Vec<u8> {
+        (0..length).map(|i| (i % 251) as u8).collect()
+    }
+
+    #[test]
+    fn known_digests() {
+        assert_eq!(hex(blake3(b"")), "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262");
+        assert_eq!(hex(blake3(b"abc")), "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85");
+
+        // Official test vectors, with the default output length
+        let vectors = [
+            (1, "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213"),
+            (1023, "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11"),
+            (1024, "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7"),
+            (1025, "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444"),
+            (2048, "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a"),
+            (2049, "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030"),
+            (3072, "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2"),
+            (4097, "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995"),
+            (8192, "aae792484c8efe4f19e2ca7d371d8c467ffb10748d8a5a1ae579948f718a2a63"),
+            (8193, "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b"),
+            (31744, "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47"),
+            (102400, "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085"),
+        ];
+        for (length, digest) in vectors {
+            assert_eq!(hex(blake3(&input(length))), digest, "{}", length);
+        }
+    }
+
+    #[test]
+    fn incremental_digests() {
+        let contents = input(5000);
+        for split in [0, 1, 63, 64, 65, 1023, 1024, 1025, 2048, 4999] {
+            let mut hasher = Blake3::new();
+            hasher.update(&contents[..split]);
+            hasher.update(&contents[split..]);
+            assert_eq!(hasher.finalize(), blake3(&contents));
+        }
+    }
+}
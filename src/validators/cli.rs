@@ -0,0 +1,132 @@
+use std::ffi::OsStr;
+use std::marker::PhantomData;
+
+use clap::builder::{TypedValueParser, ValueParserFactory};
+use clap::error::ErrorKind;
+use clap::{Arg, Command, Error};
+
+use super::{FileUuid, ImageFile, ValidUrl, ValidatedInput, VideoFile};
+
+/// Value parser of clap validating a command-line argument by its type, e.g. an existing image
+/// with `ValidatedParser::<ImageFile>::new()`. An invalid argument is rejected at parse time with
+/// the error of the validator.
+///
+/// The validated types are also their own value parsers, so that the derive API of clap infers
+/// them from the type of the field.
+///
+/// # Examples
+/// ``` ignore
+/// let command = Command::new("upload")
+///     .arg(Arg::new("image").value_parser(ValidatedParser::<ImageFile>::new()))
+///     .arg(Arg::new("id").long("id").value_parser(ValidatedParser::<FileUuid>::new()));
+///
+/// #[derive(Parser)]
+/// struct Share {
+///     url: ValidUrl,
+/// }
+/// ```
+#[derive(Debug)]
+pub struct ValidatedParser<T>(PhantomData<fn() -> T>);
+
+impl<T> ValidatedParser<T> {
+    /// Create a parser.
+    pub fn new() -> Self {
+        ValidatedParser(PhantomData)
+    }
+}
+
+impl<T> Default for ValidatedParser<T> {
+    fn default() -> Self {
+        ValidatedParser::new()
+    }
+}
+
+impl<T> Clone for ValidatedParser<T> {
+    fn clone(&self) -> Self {
+        ValidatedParser::new()
+    }
+}
+
+impl<T: ValidatedInput + Clone + Send + Sync + 'static> TypedValueParser for ValidatedParser<T> {
+    type Value = T;
+
+    fn parse_ref(&self, command: &Command, arg: Option<&Arg>, value: &OsStr) -> Result<T, Error> {
+        let name = arg.map_or_else(|| String::from("..."), |arg| arg.to_string());
+        let value = value.to_str().ok_or_else(|| Error::raw(ErrorKind::InvalidUtf8, format!("invalid UTF-8 value for '{}'\n", name)).with_cmd(command))?;
+        T::parse_input(value).map_err(|e| Error::raw(ErrorKind::ValueValidation, format!("invalid value '{}' for '{}': {}\n", value, name, e)).with_cmd(command))
+    }
+}
+
+impl ValueParserFactory for ValidUrl {
+    type Parser = ValidatedParser<ValidUrl>;
+
+    fn value_parser() -> Self::Parser {
+        ValidatedParser::new()
+    }
+}
+
+impl ValueParserFactory for FileUuid {
+    type Parser = ValidatedParser<FileUuid>;
+
+    fn value_parser() -> Self::Parser {
+        ValidatedParser::new()
+    }
+}
+
+impl ValueParserFactory for ImageFile {
+    type Parser = ValidatedParser<ImageFile>;
+
+    fn value_parser() -> Self::Parser {
+        ValidatedParser::new()
+    }
+}
+
+impl ValueParserFactory for VideoFile {
+    type Parser = ValidatedParser<VideoFile>;
+
+    fn value_parser() -> Self::Parser {
+        ValidatedParser::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::error::ErrorKind;
+    use clap::{Arg, Command, Parser};
+
+    use crate::{FileUuid, ImageFile, ValidUrl, ValidatedParser, VideoFile};
+
+    #[test]
+    fn parsed_arguments() {
+        let command = || {
+            Command::new("upload")
+                .arg(Arg::new("image").value_parser(ValidatedParser::<ImageFile>::new()))
+                .arg(Arg::new("id").long("id").value_parser(ValidatedParser::<FileUuid>::new()))
+        };
+        let matches = command().try_get_matches_from(["upload", "test_files/valid_image.png", "--id", "C70DC454-1C7D-5C59-8FED-3A321E6A4A49"]).unwrap();
+        assert_eq!(matches.get_one::<ImageFile>("image").unwrap().file().mime_type, "image/png");
+        assert_eq!(matches.get_one::<FileUuid>("id").unwrap().to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+
+        let error = command().try_get_matches_from(["upload", "test_files/valid_video.mov"]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().contains("invalid value 'test_files/valid_video.mov' for '[image]': The type video/quicktime is not allowed."));
+        let error = command().try_get_matches_from(["upload", "--id", "c70dc454"]).unwrap_err();
+        assert!(error.to_string().contains("for '--id <id>': Invalid uuid."));
+    }
+
+    #[derive(Parser)]
+    struct Share {
+        url: ValidUrl,
+        #[arg(long)]
+        video: Option<VideoFile>,
+    }
+
+    #[test]
+    fn derived_arguments() {
+        let share = Share::try_parse_from(["share", "https://docs.rs", "--video", "test_files/valid_video.mov"]).unwrap();
+        assert_eq!(share.url.as_str(), "https://docs.rs");
+        assert!(share.video.is_some());
+        assert!(Share::try_parse_from(["share", "https://docs .rs"]).is_err());
+        assert!(Share::try_parse_from(["share", "https://docs.rs", "--video", "test_files/valid_image.png"]).is_err());
+    }
+}
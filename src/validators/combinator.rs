@@ -0,0 +1,244 @@
+#[cfg(feature = "file")]
+use std::path::Path;
+
+#[cfg(feature = "file")]
+use super::validate_file::rejection_reason;
+#[cfg(feature = "file")]
+use super::{FileValidator, ValidationError};
+#[cfg(all(feature = "url", feature = "std"))]
+use super::{UrlError, UrlValidator};
+#[cfg(feature = "uuid")]
+use super::{UuidError, UuidValidator};
+
+/// Validator of an input of type `T`, implemented by the configured validators and composed
+/// with the combinators into compound rules, reusable across a codebase.
+///
+/// # Examples
+/// ``` ignore
+/// let validator = UrlValidator::builder().allowed_hosts(["*.example.com"]).build()?;
+/// let url_rule = validator
+///     .and(rule(|url: &str| url.len() < 2048, UrlError::Invalid))
+///     .and(rule(|url: &str| !url.contains("@"), UrlError::Invalid))
+///     .map_err(ValidationError::from);
+///
+/// assert!(url_rule.is_valid("https://www.example.com/upload"));
+/// ```
+pub trait Validate<T: ?Sized> {
+    /// Error of an invalid input
+    type Error;
+
+    /// Check an input.
+    ///
+    /// # Errors
+    /// If the input is invalid.
+    fn check(&self, input: &T) -> Result<(), Self::Error>;
+
+    /// Whether an input is valid.
+    fn is_valid(&self, input: &T) -> bool {
+        self.check(input).is_ok()
+    }
+
+    /// Valid if both validators accept the input, the error of the first failing one otherwise.
+    fn and<V>(self, other: V) -> And<Self, V>
+    where
+        Self: Sized,
+        V: Validate<T, Error = Self::Error>,
+    {
+        And(self, other)
+    }
+
+    /// Valid if either validator accepts the input, the error of the second one otherwise.
+    fn or<V>(self, other: V) -> Or<Self, V>
+    where
+        Self: Sized,
+        V: Validate<T, Error = Self::Error>,
+    {
+        Or(self, other)
+    }
+
+    /// Valid if the validator rejects the input, the given error otherwise.
+    fn not<E: Clone>(self, error: E) -> Not<Self, E>
+    where
+        Self: Sized,
+    {
+        Not(self, error)
+    }
+
+    /// Map the error of the validator, e.g. to the error of another validator to combine them.
+    fn map_err<E, F>(self, f: F) -> MapErr<Self, F>
+    where
+        Self: Sized,
+        F: Fn(Self::Error) -> E,
+    {
+        MapErr(self, f)
+    }
+}
+
+/// Validator of [`Validate::and`].
+#[derive(Debug, Clone)]
+pub struct And<A, B>(A, B);
+
+impl<T: ?Sized, A: Validate<T>, B: Validate<T, Error = A::Error>> Validate<T> for And<A, B> {
+    type Error = A::Error;
+
+    fn check(&self, input: &T) -> Result<(), Self::Error> {
+        self.0.check(input)?;
+        self.1.check(input)
+    }
+}
+
+/// Validator of [`Validate::or`].
+#[derive(Debug, Clone)]
+pub struct Or<A, B>(A, B);
+
+impl<T: ?Sized, A: Validate<T>, B: Validate<T, Error = A::Error>> Validate<T> for Or<A, B> {
+    type Error = A::Error;
+
+    fn check(&self, input: &T) -> Result<(), Self::Error> {
+        self.0.check(input).or_else(|_| self.1.check(input))
+    }
+}
+
+/// Validator of [`Validate::not`].
+#[derive(Debug, Clone)]
+pub struct Not<V, E>(V, E);
+
+impl<T: ?Sized, V: Validate<T>, E: Clone> Validate<T> for Not<V, E> {
+    type Error = E;
+
+    fn check(&self, input: &T) -> Result<(), E> {
+        match self.0.check(input) {
+            Ok(()) => Err(self.1.clone()),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+/// Validator of [`Validate::map_err`].
+#[derive(Debug, Clone)]
+pub struct MapErr<V, F>(V, F);
+
+impl<T: ?Sized, V: Validate<T>, E, F: Fn(V::Error) -> E> Validate<T> for MapErr<V, F> {
+    type Error = E;
+
+    fn check(&self, input: &T) -> Result<(), E> {
+        self.0.check(input).map_err(&self.1)
+    }
+}
+
+/// Validator of a predicate, see [`rule`].
+#[derive(Debug, Clone)]
+pub struct Rule<F, E> {
+    predicate: F,
+    error: E,
+}
+
+impl<T: ?Sized, F: Fn(&T) -> bool, E: Clone> Validate<T> for Rule<F, E> {
+    type Error = E;
+
+    fn check(&self, input: &T) -> Result<(), E> {
+        if (self.predicate)(input) {
+            Ok(())
+        } else {
+            Err(self.error.clone())
+        }
+    }
+}
+
+/// Validator accepting the inputs matching a predicate, rejected with the given error.
+///
+/// # Examples
+/// ``` ignore
+/// let short = rule(|url: &str| url.len() < 2048, UrlError::Invalid);
+/// ```
+pub fn rule<T: ?Sized, F: Fn(&T) -> bool, E: Clone>(predicate: F, error: E) -> Rule<F, E> {
+    Rule { predicate, error }
+}
+
+/// See [`UrlValidator::check`].
+#[cfg(all(feature = "url", feature = "std"))]
+impl Validate<str> for UrlValidator {
+    type Error = UrlError;
+
+    fn check(&self, url: &str) -> Result<(), UrlError> {
+        UrlValidator::check(self, url).map(|_| ())
+    }
+}
+
+/// See [`UuidValidator::parse`].
+#[cfg(feature = "uuid")]
+impl Validate<str> for UuidValidator {
+    type Error = UuidError;
+
+    fn check(&self, uuid: &str) -> Result<(), UuidError> {
+        self.parse(uuid).map(|_| ())
+    }
+}
+
+/// See [`FileValidator::validate`], a rejected file is a [`ValidationError::Rejected`].
+#[cfg(feature = "file")]
+impl Validate<Path> for FileValidator {
+    type Error = ValidationError;
+
+    fn check(&self, path: &Path) -> Result<(), ValidationError> {
+        let file = self.validate(path)?;
+        if file.is_valid() {
+            Ok(())
+        } else {
+            Err(ValidationError::Rejected(rejection_reason(&file)))
+        }
+    }
+}
+
+#[cfg(all(test, feature = "url", feature = "file", feature = "uuid"))]
+mod tests {
+    use std::path::Path;
+
+    use crate::{rule, FileValidator, UrlError, UrlValidator, UuidError, UuidValidator, UuidVersion, Validate, ValidationError};
+
+    #[test]
+    fn combined_urls() {
+        let validator = UrlValidator::builder().top_level_whitelist([".com", ".ch"]).build().unwrap();
+        let rules = validator.clone().and(rule(|url: &str| url.len() < 20, UrlError::Invalid));
+        assert!(rules.is_valid("https://test.com"));
+        assert!(!rules.is_valid("https://test.org"));
+        assert_eq!(rules.check("https://www.test.com/long"), Err(UrlError::Invalid));
+
+        let http = rule(|url: &str| url.starts_with("http://"), UrlError::Invalid);
+        let insecure = validator.clone().and(http.clone());
+        let secure = validator.and(insecure.not(UrlError::Invalid));
+        assert!(secure.is_valid("https://test.ch"));
+        assert!(!secure.is_valid("http://test.ch"));
+        assert!(!secure.is_valid("https://test.org"));
+
+        let either = http.or(rule(|url: &str| url.starts_with("ftp://"), UrlError::Invalid));
+        assert!(either.is_valid("ftp://test.ch") && either.is_valid("http://test.ch"));
+        assert!(!either.is_valid("https://test.ch"));
+    }
+
+    #[test]
+    fn mapped_errors() {
+        let uuid = UuidValidator::builder().version(UuidVersion::V4).build();
+        let v4_or_v5 = uuid.or(UuidValidator::builder().build());
+        assert!(v4_or_v5.is_valid("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04"));
+        assert!(v4_or_v5.is_valid("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"));
+        assert_eq!(v4_or_v5.check("017f22e2-79b0-7cc3-98c4-dc0c0c07398f"), Err(UuidError::Version { expected: UuidVersion::V5, actual: 7 }));
+
+        let mapped = v4_or_v5.map_err(ValidationError::from);
+        assert!(matches!(mapped.check("c70dc454"), Err(ValidationError::Uuid(UuidError::Malformed))));
+    }
+
+    #[test]
+    fn combined_files() {
+        let images = FileValidator::builder().allow_mime(["image/png", "image/jpeg"]).build().unwrap();
+        let extension = rule(|path: &Path| path.extension().is_some_and(|extension| extension == extension.to_ascii_lowercase().as_os_str()), String::from("Invalid extension."));
+        let rules = images.and(extension.map_err(ValidationError::Rejected));
+        assert!(rules.is_valid(Path::new("test_files/valid_image.png")));
+        assert!(rules.check(Path::new("test_files/valid_image.jpg")).is_ok());
+        let error = rules.check(Path::new("test_files/valid_video.mov")).unwrap_err();
+        assert_eq!(error.to_string(), "The type video/quicktime is not allowed.");
+        let error = rules.check(Path::new("test_files/valid_ext_image.JpG")).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid extension.");
+        assert!(matches!(rules.check(Path::new("test_files/missing.png")), Err(ValidationError::Io(_))));
+    }
+}
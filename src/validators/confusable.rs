@@ -0,0 +1,166 @@
+use super::punycode;
+
+/// Characters of other scripts, or of the Latin script, that look like a Latin letter or digit,
+/// with the Latin character they can be confused with. Uppercase letters are lowercased before
+/// the lookup.
+const CONFUSABLES: &[(char, char)] = &[
+    // Cyrillic
+    ('а', 'a'), ('в', 'b'), ('с', 'c'), ('ԁ', 'd'), ('е', 'e'), ('һ', 'h'), ('н', 'h'), ('і', 'i'),
+    ('ј', 'j'), ('к', 'k'), ('ӏ', 'l'), ('м', 'm'), ('о', 'o'), ('р', 'p'), ('ԛ', 'q'), ('ѕ', 's'),
+    ('т', 't'), ('ԝ', 'w'), ('х', 'x'), ('у', 'y'), ('ү', 'y'),
+    // Greek
+    ('α', 'a'), ('ε', 'e'), ('ι', 'i'), ('κ', 'k'), ('ν', 'v'), ('ο', 'o'), ('ρ', 'p'), ('τ', 't'),
+    ('υ', 'u'), ('χ', 'x'),
+    // Armenian
+    ('օ', 'o'), ('ս', 'u'), ('հ', 'h'), ('ց', 'g'),
+    // Latin and digits
+    ('ı', 'i'), ('ɑ', 'a'), ('ɡ', 'g'), ('ℓ', 'l'), ('0', 'o'), ('1', 'l'),
+];
+
+/// Sequences of Latin letters that look like a single letter.
+const CONFUSABLE_SEQUENCES: &[(&str, &str)] = &[("rn", "m"), ("vv", "w")];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+}
+
+/// Script of a letter among the ones easily confused with each other. Digits, hyphens and the
+/// letters of the other scripts have none.
+fn script(c: char) -> Option<Script> {
+    match c as u32 {
+        0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F | 0x1E00..=0x1EFF => Some(Script::Latin),
+        0x370..=0x3FF | 0x1F00..=0x1FFF => Some(Script::Greek),
+        0x400..=0x52F => Some(Script::Cyrillic),
+        0x530..=0x58F => Some(Script::Armenian),
+        _ => None,
+    }
+}
+
+/// Suspicious host detected by [`detect_confusable_host`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfusableHost {
+    /// A label mixes the Latin, Greek, Cyrillic or Armenian scripts
+    pub mixed_script: bool,
+    /// The skeleton of the host, see [`host_skeleton`]
+    pub skeleton: String,
+    /// The trusted host which looks the same but differs, if any
+    pub lookalike: Option<String>,
+}
+
+/// Skeleton of a host: its unicode form lowercased, with every confusable character replaced by
+/// the Latin character it looks like. Two hosts with the same skeleton look the same, e.g.
+/// `pаypal.com` with a Cyrillic `а` and `paypal.com`. Punycode labels are decoded first.
+///
+/// The skeleton is only meant to be compared with other skeletons, it is not a valid host.
+pub fn host_skeleton(host: &str) -> String {
+    let host = punycode::host_to_unicode(host).unwrap_or_else(|| host.to_string());
+
+    let mut skeleton: String = host
+        .to_lowercase()
+        .chars()
+        .map(|c| CONFUSABLES.iter().find(|(confusable, _)| *confusable == c).map_or(c, |&(_, latin)| latin))
+        .collect();
+    for (sequence, latin) in CONFUSABLE_SEQUENCES {
+        skeleton = skeleton.replace(sequence, latin);
+    }
+    skeleton
+}
+
+/// Check whether a label of a host mixes letters of the Latin, Greek, Cyrillic or Armenian
+/// scripts, e.g. `pаypal.com` with a Cyrillic `а`. Punycode labels are decoded first.
+pub fn is_mixed_script_host(host: &str) -> bool {
+    let host = punycode::host_to_unicode(host).unwrap_or_else(|| host.to_string());
+
+    host.split('.').any(|label| {
+        let mut scripts = label.chars().filter_map(script);
+        match scripts.next() {
+            None => false,
+            Some(first) => scripts.any(|script| script != first),
+        }
+    })
+}
+
+/// Detect a suspicious host: a label mixes scripts (see [`is_mixed_script_host`]) or the host
+/// looks like one of the trusted hosts without being it (same [`host_skeleton`]). Returns `None`
+/// if the host is not suspicious.
+///
+/// # Examples
+/// ``` ignore
+/// let suspicious = detect_confusable_host("xn--pypal-4ve.com", &["paypal.com"]).unwrap();
+/// assert_eq!(suspicious.lookalike.as_deref(), Some("paypal.com"));
+/// ```
+pub fn detect_confusable_host<S: AsRef<str>>(host: &str, trusted_hosts: &[S]) -> Option<ConfusableHost> {
+    let skeleton = host_skeleton(host);
+    let unicode_host = punycode::host_to_unicode(host).unwrap_or_else(|| host.to_string()).to_lowercase();
+
+    let lookalike = trusted_hosts
+        .iter()
+        .map(AsRef::as_ref)
+        .find(|trusted| {
+            let trusted_host = punycode::host_to_unicode(trusted).unwrap_or_else(|| trusted.to_string());
+            trusted_host.to_lowercase() != unicode_host && host_skeleton(trusted) == skeleton
+        })
+        .map(String::from);
+
+    let mixed_script = is_mixed_script_host(host);
+    if !mixed_script && lookalike.is_none() {
+        return None;
+    }
+    Some(ConfusableHost { mixed_script, skeleton, lookalike })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{detect_confusable_host, host_skeleton, is_mixed_script_host};
+
+    #[test]
+    fn skeletons() {
+        assert_eq!(host_skeleton("pаypal.com"), "paypal.com");
+        assert_eq!(host_skeleton("PAYPAL.COM"), "paypal.com");
+        assert_eq!(host_skeleton("раураӏ.com"), "paypal.com");
+        assert_eq!(host_skeleton("paypa1.com"), "paypal.com");
+        assert_eq!(host_skeleton("rnicrosoft.com"), host_skeleton("microsoft.com"));
+        assert_eq!(host_skeleton("xn--pypal-4ve.com"), "paypal.com");
+    }
+
+    #[test]
+    fn mixed_scripts() {
+        assert!(is_mixed_script_host("pаypal.com"));
+        assert!(is_mixed_script_host("xn--pypal-4ve.com"));
+        assert!(is_mixed_script_host("www.gοogle.com"));
+
+        assert!(!is_mixed_script_host("paypal.com"));
+        assert!(!is_mixed_script_host("раураӏ.com"));
+        assert!(!is_mixed_script_host("пример.рф"));
+        assert!(!is_mixed_script_host("münchen.de"));
+        assert!(!is_mixed_script_host("漢字test.com"));
+    }
+
+    #[test]
+    fn confusable_hosts() {
+        let trusted = ["paypal.com", "google.com"];
+
+        let suspicious = detect_confusable_host("pаypal.com", &trusted).unwrap();
+        assert!(suspicious.mixed_script);
+        assert_eq!(suspicious.skeleton, "paypal.com");
+        assert_eq!(suspicious.lookalike.as_deref(), Some("paypal.com"));
+
+        // whole script confusables are only detected against the trusted hosts
+        let suspicious = detect_confusable_host("раураӏ.com", &trusted).unwrap();
+        assert!(!suspicious.mixed_script);
+        assert_eq!(suspicious.lookalike.as_deref(), Some("paypal.com"));
+        assert_eq!(detect_confusable_host("раураӏ.com", &[] as &[&str]), None);
+
+        assert!(detect_confusable_host("g00gle.com", &trusted).is_some());
+        assert!(detect_confusable_host("pаypal.com", &[] as &[&str]).is_some());
+
+        // trusted and unrelated hosts
+        assert_eq!(detect_confusable_host("PayPal.com", &trusted), None);
+        assert_eq!(detect_confusable_host("test.com", &trusted), None);
+        assert_eq!(detect_confusable_host("münchen.de", &trusted), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Cross-check of a declared `Content-Type` against the type sniffed from the contents, following
+//! the rules of the [WHATWG MIME Sniffing](https://mimesniff.spec.whatwg.org/) standard.
+
+use super::ValidationError;
+
+/// Bytes skipped before the HTML patterns.
+const WHITESPACE: &[u8] = b"\t\n\x0c\r ";
+
+/// Tags starting an HTML document when followed by a space or `>`, case insensitive.
+const HTML_PATTERNS: [&[u8]; 17] = [
+    b"<!DOCTYPE HTML", b"<HTML", b"<HEAD", b"<SCRIPT", b"<IFRAME", b"<H1", b"<DIV", b"<FONT", b"<TABLE", b"<A", b"<STYLE",
+    b"<TITLE", b"<B", b"<BODY", b"<BR", b"<P", b"<!--",
+];
+
+/// Mime types a browser runs scripts in, or renders as an active document.
+const SCRIPTABLE_MIME_TYPES: [&str; 9] = [
+    "text/html",
+    "application/xhtml+xml",
+    "text/xml",
+    "application/xml",
+    "image/svg+xml",
+    "application/pdf",
+    "application/javascript",
+    "text/javascript",
+    "application/x-shockwave-flash",
+];
+
+/// Other names of the sniffed mime types, each group naming the same format.
+const MIME_ALIASES: &[&[&str]] = &[
+    &["image/jpeg", "image/jpg", "image/pjpeg"],
+    &["image/png", "image/x-png", "image/apng"],
+    &["image/bmp", "image/x-bmp", "image/x-ms-bmp"],
+    &["image/x-icon", "image/vnd.microsoft.icon", "image/ico"],
+    &["image/tiff", "image/tif"],
+    &["image/heif", "image/heic", "image/heif-sequence", "image/heic-sequence"],
+    &["audio/mpeg", "audio/mp3", "audio/x-mpeg"],
+    &["audio/x-wav", "audio/wav", "audio/wave", "audio/vnd.wave"],
+    &["audio/x-flac", "audio/flac"],
+    &["video/x-msvideo", "video/avi", "video/msvideo"],
+    &["application/zip", "application/x-zip-compressed", "application/x-zip"],
+    &["application/gzip", "application/x-gzip"],
+    &["text/xml", "application/xml"],
+    &["application/javascript", "text/javascript", "application/x-javascript"],
+];
+
+/// Category of the difference between a declared and a sniffed mime type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ContentTypeMatch {
+    /// The declared type is the sniffed one
+    Exact,
+    /// The declared type is another name of the sniffed one, e.g. `image/jpg` for `image/jpeg`,
+    /// or a text type for a text file
+    Alias,
+    /// The declared type is `application/octet-stream` and the file is not scriptable
+    Generic,
+    /// The declared type differs from the sniffed one, which is not scriptable, e.g. a PNG image
+    /// declared as a JPEG image
+    Mismatch,
+    /// The sniffed type runs scripts in a browser but not the declared one, e.g. an HTML page
+    /// declared as `text/plain` or as an image
+    Escalation,
+}
+
+/// Result of [`check_content_type`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentTypeCheck {
+    /// The declared mime type, lowercase and without its parameters
+    pub declared: String,
+    /// The mime type sniffed from the contents, see [`sniff_mime_type`]
+    pub sniffed: &'static str,
+    /// How the types differ
+    pub category: ContentTypeMatch,
+}
+
+impl ContentTypeCheck {
+    /// Whether the declared type hides a scriptable file.
+    pub fn is_dangerous(&self) -> bool {
+        self.category == ContentTypeMatch::Escalation
+    }
+}
+
+/// Sniff the mime type of a file like a browser does for a resource of unknown type.
+///
+/// The scriptable types come first: HTML documents (a known tag after optional whitespace),
+/// XML documents (`<?xml`) and PDF documents (`%PDF-`). The other types are detected from their
+/// magic numbers with infer. A file without binary bytes is `text/plain`, any other file is
+/// `application/octet-stream`. SVG images are not sniffed, as by browsers.
+///
+/// # Examples
+/// ``` ignore
+/// assert_eq!(sniff_mime_type(b"  <html><body>"), "text/html");
+/// assert_eq!(sniff_mime_type(b"Hello"), "text/plain");
+/// ```
+pub fn sniff_mime_type(contents: &[u8]) -> &'static str {
+    let start = contents.iter().position(|byte| !WHITESPACE.contains(byte)).unwrap_or(contents.len());
+    let trimmed = &contents[start..];
+    let is_html = HTML_PATTERNS.iter().any(|pattern| {
+        trimmed.len() > pattern.len() && trimmed[..pattern.len()].eq_ignore_ascii_case(pattern) && matches!(trimmed[pattern.len()], b' ' | b'>')
+    });
+    if is_html {
+        return "text/html";
+    }
+    if contents.starts_with(b"<?xml") {
+        return "text/xml";
+    }
+    if contents.starts_with(b"%PDF-") {
+        return "application/pdf";
+    }
+
+    // A text file with a byte order mark
+    if contents.starts_with(b"\xef\xbb\xbf") || contents.starts_with(b"\xfe\xff") || contents.starts_with(b"\xff\xfe") {
+        return "text/plain";
+    }
+    if let Some(kind) = infer::get(contents).filter(|kind| kind.matcher_type() != infer::MatcherType::Text) {
+        return kind.mime_type();
+    }
+
+    let is_binary = |byte: &u8| matches!(byte, 0x00..=0x08 | 0x0B | 0x0E..=0x1A | 0x1C..=0x1F);
+    if contents.iter().any(is_binary) {
+        "application/octet-stream"
+    } else {
+        "text/plain"
+    }
+}
+
+/// Cross-check the `Content-Type` declared for an upload against the type sniffed from its
+/// contents with [`sniff_mime_type`], e.g. to refuse an HTML page uploaded as an image, which a
+/// browser could run if it sniffs the file.
+///
+/// The parameters of the declared type, such as the charset, are ignored and its case is
+/// irrelevant.
+///
+/// # Errors
+/// If the declared type is not a `type/subtype` mime type, a
+/// [`ValidationError::InvalidContentType`] is returned.
+///
+/// # Examples
+/// ``` ignore
+/// let check = check_content_type("image/png", &upload.bytes)?;
+/// if check.is_dangerous() {
+///     println!("Rejected upload: {} declared as {}", check.sniffed, check.declared);
+/// }
+/// ```
+pub fn check_content_type(declared: &str, contents: &[u8]) -> Result<ContentTypeCheck, ValidationError> {
+    let essence = declared.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    let is_token = |token: &str| !token.is_empty() && token.chars().all(|c| c.is_ascii_alphanumeric() || "!#$&-^_.+".contains(c));
+    match essence.split_once('/') {
+        Some((kind, subtype)) if is_token(kind) && is_token(subtype) => {}
+        _ => return Err(ValidationError::InvalidContentType(declared.to_string())),
+    }
+
+    let sniffed = sniff_mime_type(contents);
+    let is_scriptable = |mime: &str| SCRIPTABLE_MIME_TYPES.contains(&mime);
+    let are_aliases = |a: &str, b: &str| MIME_ALIASES.iter().any(|group| group.contains(&a) && group.contains(&b));
+
+    let category = if essence == sniffed {
+        ContentTypeMatch::Exact
+    } else if are_aliases(&essence, sniffed) {
+        ContentTypeMatch::Alias
+    } else if is_scriptable(sniffed) {
+        ContentTypeMatch::Escalation
+    } else if sniffed == "text/plain" && essence.starts_with("text/") && !is_scriptable(&essence) {
+        ContentTypeMatch::Alias
+    } else if essence == "application/octet-stream" {
+        ContentTypeMatch::Generic
+    } else {
+        ContentTypeMatch::Mismatch
+    };
+    Ok(ContentTypeCheck { declared: essence, sniffed, category })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{check_content_type, sniff_mime_type, ContentTypeMatch, ValidationError};
+
+    const TEST_DIR: &str = "test_files";
+
+    fn category(declared: &str, contents: &[u8]) -> ContentTypeMatch {
+        check_content_type(declared, contents).unwrap().category
+    }
+
+    #[test]
+    fn sniffed_types() {
+        assert_eq!(sniff_mime_type(b"<!doctype html><p>"), "text/html");
+        assert_eq!(sniff_mime_type(b"\r\n  <SCRIPT>alert(1)</SCRIPT>"), "text/html");
+        assert_eq!(sniff_mime_type(b"<!-- comment -->"), "text/html");
+        assert_eq!(sniff_mime_type(b"<?xml version=\"1.0\"?>"), "text/xml");
+        assert_eq!(sniff_mime_type(b"%PDF-1.7"), "application/pdf");
+        assert_eq!(sniff_mime_type(b"Hello <b>world</b>"), "text/plain");
+        assert_eq!(sniff_mime_type(b"<bold>"), "text/plain");
+        assert_eq!(sniff_mime_type(b"\x00\x01\x02"), "application/octet-stream");
+        assert_eq!(sniff_mime_type(b""), "text/plain");
+
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(sniff_mime_type(&image), "image/png");
+    }
+
+    #[test]
+    fn content_type_categories() {
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        let check = check_content_type("Image/JPEG; charset=binary", &image).unwrap();
+        assert_eq!((check.declared.as_str(), check.sniffed, check.category), ("image/jpeg", "image/jpeg", ContentTypeMatch::Exact));
+        assert!(!check.is_dangerous());
+
+        assert_eq!(category("image/jpg", &image), ContentTypeMatch::Alias);
+        assert_eq!(category("text/csv", b"a,b\n1,2"), ContentTypeMatch::Alias);
+        assert_eq!(category("application/octet-stream", &image), ContentTypeMatch::Generic);
+        assert_eq!(category("image/png", &image), ContentTypeMatch::Mismatch);
+        assert_eq!(category("text/html", b"Hello"), ContentTypeMatch::Mismatch);
+    }
+
+    #[test]
+    fn escalations() {
+        let page = b"<html><script>alert(document.cookie)</script></html>";
+        assert!(check_content_type("text/plain", page).unwrap().is_dangerous());
+        assert_eq!(category("image/png", page), ContentTypeMatch::Escalation);
+        assert_eq!(category("application/octet-stream", page), ContentTypeMatch::Escalation);
+        assert_eq!(category("image/jpeg", b"%PDF-1.4"), ContentTypeMatch::Escalation);
+        assert_eq!(category("application/xml", b"<?xml version=\"1.0\"?><a/>"), ContentTypeMatch::Alias);
+
+        assert_eq!(check_content_type("image", page).unwrap_err().to_string(), "Invalid content type.");
+        assert!(matches!(check_content_type("image/", page), Err(ValidationError::InvalidContentType(declared)) if declared == "image/"));
+        assert_eq!(check_content_type("", page).unwrap_err().code(), "invalid_content_type");
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::{Component, Path, PathBuf};
+
+use uuid::Uuid;
+
+use crate::{generate_file_uuid, generate_uuid_v5, normalize_url, sha256, ValidationError};
+
+/// Input from which a deduplication key is derived, see [`dedup_key_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DedupSource<'a> {
+    /// Path to a file
+    Path(&'a Path),
+    /// In-memory content
+    Bytes(&'a [u8]),
+    /// Url
+    Url(&'a str),
+    /// SHA-256 digest of a content, e.g. [`crate::ValidatedFile::sha256`]
+    Sha256(&'a [u8; 32]),
+}
+
+/// How a deduplication key is derived, see [`dedup_key_for`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DedupStrategy {
+    /// Hash of the content, read from the file for a path. Two sources collide only if they have
+    /// the same bytes, whatever their names. Accepts paths and bytes.
+    ContentHash,
+    /// Lexically normalized path: `.` components are removed, `..` components are resolved and
+    /// redundant separators are dropped. The case is preserved, so `A.png` and `a.png` don't
+    /// collide even on case insensitive file systems, and links to the same file don't collide
+    /// either. The file is not read. Accepts paths.
+    NormalizedPath,
+    /// Url normalized with [`crate::normalize_url`]: equivalent urls collide, e.g. the same urls
+    /// with and without their default port. Urls differing in the case of their path, query or
+    /// fragment don't collide. Accepts urls.
+    NormalizedUrl,
+    /// SHA-256 digest of the content, read from the file for a path. Collides like
+    /// [`DedupStrategy::ContentHash`] but the digest computed while validating a file can be used
+    /// instead of reading it twice, see [`crate::FileValidatorBuilder::sha256`]. Accepts paths,
+    /// bytes and digests.
+    Sha256,
+}
+
+/// Derive a deduplication key from a source with a strategy.
+///
+/// The key is a version-5 uuid in the given namespace of the normalized source, so keys from
+/// different namespaces never collide. With [`DedupStrategy::ContentHash`], the key of a file
+/// is the one checked by [`crate::validate_file_uuid`].
+///
+/// # Errors
+/// If the strategy doesn't accept the source, the file could not be read or the url is invalid.
+///
+/// # Examples
+/// ``` ignore
+/// let key = dedup_key_for(&namespace, DedupSource::Path(Path::new("a/./b.png")), DedupStrategy::NormalizedPath)?;
+/// assert_eq!(key, dedup_key_for(&namespace, DedupSource::Path(Path::new("a/b.png")), DedupStrategy::NormalizedPath)?);
+/// ```
+pub fn dedup_key_for(namespace: &Uuid, source: DedupSource, strategy: DedupStrategy) -> Result<Uuid, ValidationError> {
+    match (strategy, source) {
+        (DedupStrategy::ContentHash, DedupSource::Path(path)) => generate_file_uuid(namespace, path),
+
+        (DedupStrategy::ContentHash, DedupSource::Bytes(bytes)) => Ok(generate_uuid_v5(namespace, bytes)),
+
+        (DedupStrategy::Sha256, DedupSource::Path(path)) => Ok(generate_uuid_v5(namespace, &sha256(&std::fs::read(path)?))),
+
+        (DedupStrategy::Sha256, DedupSource::Bytes(bytes)) => Ok(generate_uuid_v5(namespace, &sha256(bytes))),
+
+        (DedupStrategy::Sha256, DedupSource::Sha256(digest)) => Ok(generate_uuid_v5(namespace, digest)),
+
+        (DedupStrategy::NormalizedPath, DedupSource::Path(path)) => {
+            let path = normalize_path(path);
+            Ok(generate_uuid_v5(namespace, path.to_string_lossy().as_bytes()))
+        }
+
+        (DedupStrategy::NormalizedUrl, DedupSource::Url(url)) => {
+            let url = normalize_url(url)?;
+            Ok(generate_uuid_v5(namespace, url.as_bytes()))
+        }
+
+        _ => Err(ValidationError::option("The strategy doesn't accept this source.")),
+    }
+}
+
+/// Normalize a path without accessing the file system.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => match normalized.components().next_back() {
+                // Can't go above the root
+                Some(Component::RootDir) | Some(Component::Prefix(_)) => {}
+                Some(Component::Normal(_)) => {
+                    normalized.pop();
+                }
+                _ => normalized.push(".."),
+            },
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use uuid::Uuid;
+
+    use crate::{dedup_key_for, sha256, validate_file_uuid, DedupSource, DedupStrategy, FileValidator};
+
+    const TEST_DIR: &str = "test_files";
+
+    fn path_key(path: &str) -> Uuid {
+        dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Path(Path::new(path)), DedupStrategy::NormalizedPath).unwrap()
+    }
+
+    fn url_key(url: &str) -> Uuid {
+        dedup_key_for(&Uuid::NAMESPACE_URL, DedupSource::Url(url), DedupStrategy::NormalizedUrl).unwrap()
+    }
+
+    #[test]
+    fn content_hash_keys() {
+        let path = format!("{}/valid_image.jpg", TEST_DIR);
+        let content = std::fs::read(&path).unwrap();
+
+        let key = dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Path(Path::new(&path)), DedupStrategy::ContentHash).unwrap();
+        assert_eq!(key, dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Bytes(&content), DedupStrategy::ContentHash).unwrap());
+        assert!(validate_file_uuid(&Uuid::NAMESPACE_OID, &content, &key));
+
+        // same content with another name
+        let copy = dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Path(Path::new(&format!("{}/valid_ext_image.JpG", TEST_DIR))),
+                                 DedupStrategy::ContentHash).unwrap();
+        assert_eq!(key, copy);
+
+        // missing file
+        assert!(dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Path(Path::new("missing")), DedupStrategy::ContentHash).is_err());
+    }
+
+    #[test]
+    fn sha256_keys() {
+        let path = format!("{}/valid_image.jpg", TEST_DIR);
+        let content = std::fs::read(&path).unwrap();
+        let key = |source: DedupSource| dedup_key_for(&Uuid::NAMESPACE_OID, source, DedupStrategy::Sha256).unwrap();
+
+        let digest = FileValidator::builder().sha256(true).build().unwrap().validate(&path).unwrap().sha256.unwrap();
+        assert_eq!(key(DedupSource::Sha256(&digest)), key(DedupSource::Path(Path::new(&path))));
+        assert_eq!(key(DedupSource::Sha256(&digest)), key(DedupSource::Bytes(&content)));
+        assert_eq!(key(DedupSource::Sha256(&sha256(b"a"))), key(DedupSource::Bytes(b"a")));
+        assert_ne!(key(DedupSource::Bytes(b"a")), key(DedupSource::Bytes(b"b")));
+    }
+
+    #[test]
+    fn normalized_path_keys() {
+        assert_eq!(path_key("a/b.png"), path_key("a/./b.png"));
+        assert_eq!(path_key("a/b.png"), path_key("a//b.png"));
+        assert_eq!(path_key("a/b.png"), path_key("a/c/../b.png"));
+        assert_eq!(path_key("/b.png"), path_key("/../b.png"));
+        assert_eq!(path_key("../b.png"), path_key("a/../../b.png"));
+
+        // case is preserved
+        assert_ne!(path_key("a/b.png"), path_key("A/b.png"));
+        assert_ne!(path_key("a/b.png"), path_key("/a/b.png"));
+    }
+
+    #[test]
+    fn normalized_url_keys() {
+        assert_eq!(url_key("https://test.com/a"), url_key("HTTPS://Test.COM/a"));
+        assert_ne!(url_key("https://test.com/a"), url_key("https://test.com/A"));
+        assert_ne!(url_key("https://test.com/a"), url_key("http://test.com/a"));
+        assert_eq!(url_key("test.com:8080?x=1"), url_key("TEST.com:8080?x=1"));
+        assert_ne!(url_key("test.com/a://B"), url_key("test.com/a://b"));
+        assert_eq!(url_key("https://test.com:443/a/./b"), url_key("https://test.com/a/b"));
+
+        assert!(dedup_key_for(&Uuid::NAMESPACE_URL, DedupSource::Url("test"), DedupStrategy::NormalizedUrl).is_err());
+    }
+
+    #[test]
+    fn unsupported_sources() {
+        assert!(dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Bytes(b"a"), DedupStrategy::NormalizedPath).is_err());
+        assert!(dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Url("test.com"), DedupStrategy::ContentHash).is_err());
+        assert!(dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Path(Path::new("a")), DedupStrategy::NormalizedUrl).is_err());
+        assert!(dedup_key_for(&Uuid::NAMESPACE_OID, DedupSource::Sha256(&[0; 32]), DedupStrategy::ContentHash).is_err());
+    }
+}
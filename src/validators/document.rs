@@ -0,0 +1,403 @@
+//! Structural validation of the PDF and office documents, enabled with the `deep` feature.
+//!
+//! A document is checked to be complete, a PDF document with its header, its cross-reference
+//! table and its end of file marker, an office document with the parts of its ZIP package, and
+//! its active content is reported: scripts, macros and embedded files, the usual vectors of the
+//! malicious documents.
+
+use std::fmt::{self, Display, Formatter};
+use std::path::Path;
+
+use super::archive::{read_zip, ArchiveError, ArchiveLimits};
+use super::inflate::inflate;
+use super::ValidationError;
+
+/// Length of the end of a PDF document holding its end of file marker.
+const PDF_TRAILER_LENGTH: usize = 1024;
+
+/// Maximum length of an object stream of a PDF document once decompressed.
+const MAX_OBJECT_STREAM_LENGTH: usize = 16 << 20;
+
+/// Main parts of the office documents, telling their format.
+const OFFICE_MAIN_PARTS: [(&str, DocumentFormat); 3] =
+    [("word/document.xml", DocumentFormat::Docx), ("xl/workbook.xml", DocumentFormat::Xlsx), ("ppt/presentation.xml", DocumentFormat::Pptx)];
+
+/// Format of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DocumentFormat {
+    /// PDF document
+    Pdf,
+    /// Word document, macro-enabled included
+    Docx,
+    /// Excel workbook, macro-enabled included
+    Xlsx,
+    /// PowerPoint presentation, macro-enabled included
+    Pptx,
+}
+
+impl DocumentFormat {
+    /// Mime type of the format.
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            DocumentFormat::Pdf => "application/pdf",
+            DocumentFormat::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            DocumentFormat::Xlsx => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+            DocumentFormat::Pptx => "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+        }
+    }
+}
+
+/// Active content of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ActiveContent {
+    /// JavaScript code of a PDF document
+    JavaScript,
+    /// Launch action of a PDF document, running a program
+    LaunchAction,
+    /// VBA macros of an office document
+    Macros,
+    /// ActiveX controls of an office document
+    ActiveX,
+    /// Embedded files, PDF attachments or office OLE objects
+    EmbeddedFile,
+}
+
+impl Display for ActiveContent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ActiveContent::JavaScript => "JavaScript code",
+            ActiveContent::LaunchAction => "launch action",
+            ActiveContent::Macros => "macros",
+            ActiveContent::ActiveX => "ActiveX controls",
+            ActiveContent::EmbeddedFile => "embedded files",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Document checked by [`validate_document`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentInfo {
+    /// Format of the document
+    pub format: DocumentFormat,
+    /// Active content found in the document, sorted
+    pub active_content: Vec<ActiveContent>,
+}
+
+impl DocumentInfo {
+    /// Whether the document has no active content.
+    pub fn is_passive(&self) -> bool {
+        self.active_content.is_empty()
+    }
+}
+
+/// Error of the validation of a document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentError {
+    /// The document is malformed or truncated, for the given reason
+    Invalid(String),
+    /// The format of the document can't be checked, for the given reason
+    Unsupported(String),
+    /// The ZIP package of an office document is invalid or exceeds a limit
+    Archive(ArchiveError),
+}
+
+impl Display for DocumentError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DocumentError::Invalid(reason) => write!(f, "Invalid document: {}.", reason),
+            DocumentError::Unsupported(reason) => write!(f, "The document can't be checked: {}.", reason),
+            DocumentError::Archive(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for DocumentError {}
+
+impl From<ArchiveError> for DocumentError {
+    fn from(error: ArchiveError) -> Self {
+        DocumentError::Archive(error)
+    }
+}
+
+fn invalid(reason: &str) -> DocumentError {
+    DocumentError::Invalid(reason.to_string())
+}
+
+/// Validate a document from its path, see [`validate_document_bytes`].
+///
+/// # Errors
+/// If the file could not be read, or the document is invalid ([`ValidationError::Contents`]
+/// wrapping a [`DocumentError`]).
+///
+/// # Examples
+/// ``` ignore
+/// let document = validate_document("uploads/report.pdf")?;
+/// if !document.is_passive() {
+///     println!("Rejected document with {:?}", document.active_content);
+/// }
+/// ```
+pub fn validate_document<P: AsRef<Path>>(path: P) -> Result<DocumentInfo, ValidationError> {
+    validate_document_bytes(&std::fs::read(path)?).map_err(ValidationError::contents)
+}
+
+/// Validate a PDF document or an office document (DOCX, XLSX or PPTX) from its contents, and
+/// report its active content.
+///
+/// A PDF document must start with its header, end with its end of file marker and point to its
+/// cross-reference table. The names of its objects are searched for scripts, launch actions and
+/// attachments, the compressed object streams included, the names hidden with `#` escapes too.
+///
+/// An office document must be a valid ZIP package, checked against the limits of the archive
+/// inspection, with its content types, its relationships and its main part. Its parts are
+/// searched for macros, ActiveX controls and embedded objects.
+///
+/// # Errors
+/// If the document is invalid, or is not a PDF or an office document, e.g. a legacy Word
+/// document.
+pub fn validate_document_bytes(contents: &[u8]) -> Result<DocumentInfo, DocumentError> {
+    let (format, mut active_content) = if contents.starts_with(b"%PDF-") {
+        (DocumentFormat::Pdf, check_pdf(contents)?)
+    } else if contents.starts_with(b"PK\x03\x04") {
+        check_office(contents)?
+    } else if contents.starts_with(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1") {
+        return Err(DocumentError::Unsupported(String::from("legacy office document")));
+    } else {
+        return Err(DocumentError::Unsupported(String::from("unknown document format")));
+    };
+
+    active_content.sort_unstable();
+    active_content.dedup();
+    Ok(DocumentInfo { format, active_content })
+}
+
+/// Check the structure of a PDF document and return its active content.
+fn check_pdf(contents: &[u8]) -> Result<Vec<ActiveContent>, DocumentError> {
+    let version = contents.get(5..8).ok_or_else(|| invalid("truncated file"))?;
+    if !(version[0].is_ascii_digit() && version[1] == b'.' && version[2].is_ascii_digit()) {
+        return Err(invalid("bad PDF header"));
+    }
+
+    let trailer = &contents[contents.len().saturating_sub(PDF_TRAILER_LENGTH)..];
+    let end = rfind(trailer, b"%%EOF").ok_or_else(|| invalid("missing PDF end of file marker"))?;
+    let start_xref = rfind(&trailer[..end], b"startxref").ok_or_else(|| invalid("missing PDF cross-reference table"))?;
+    let offset = std::str::from_utf8(&trailer[start_xref + 9..end])
+        .ok()
+        .and_then(|offset| offset.trim().parse::<usize>().ok())
+        .ok_or_else(|| invalid("bad PDF cross-reference offset"))?;
+    // A cross-reference table, or a cross-reference stream object
+    let table = contents.get(offset..).unwrap_or_default();
+    if !table.starts_with(b"xref") && !table.first().is_some_and(u8::is_ascii_digit) {
+        return Err(invalid("bad PDF cross-reference offset"));
+    }
+
+    let mut names = pdf_names(contents);
+    for stream in object_streams(contents) {
+        names.extend(pdf_names(&stream?));
+    }
+    let active_content = names
+        .iter()
+        .filter_map(|name| match name.as_slice() {
+            b"JavaScript" | b"JS" => Some(ActiveContent::JavaScript),
+            b"Launch" => Some(ActiveContent::LaunchAction),
+            b"EmbeddedFile" | b"EmbeddedFiles" => Some(ActiveContent::EmbeddedFile),
+            _ => None,
+        })
+        .collect();
+    Ok(active_content)
+}
+
+/// Offset of the last occurrence of a pattern.
+fn rfind(bytes: &[u8], pattern: &[u8]) -> Option<usize> {
+    bytes.windows(pattern.len()).rposition(|window| window == pattern)
+}
+
+/// Whether a byte ends a PDF name.
+fn is_pdf_delimiter(byte: u8) -> bool {
+    byte.is_ascii_whitespace() || b"()<>[]{}/%\0".contains(&byte)
+}
+
+/// Names of the objects of a PDF document, without their solidus and with their `#` escapes
+/// decoded.
+fn pdf_names(bytes: &[u8]) -> Vec<Vec<u8>> {
+    let mut names = Vec::new();
+    let mut position = 0;
+    while let Some(start) = bytes[position..].iter().position(|&byte| byte == b'/') {
+        let start = position + start + 1;
+        let length = bytes[start..].iter().position(|&byte| is_pdf_delimiter(byte)).unwrap_or(bytes.len() - start);
+        let raw = &bytes[start..start + length];
+
+        let mut name = Vec::with_capacity(raw.len());
+        let mut index = 0;
+        while index < raw.len() {
+            let escaped = raw.get(index + 1..index + 3).and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+            match (raw[index], escaped) {
+                (b'#', Some(byte)) => {
+                    name.push(byte);
+                    index += 3;
+                }
+                (byte, _) => {
+                    name.push(byte);
+                    index += 1;
+                }
+            }
+        }
+        names.push(name);
+        position = start + length;
+    }
+    names
+}
+
+/// Decompressed object streams of a PDF document, whose objects can hide names.
+fn object_streams(contents: &[u8]) -> impl Iterator<Item = Result<Vec<u8>, DocumentError>> + '_ {
+    let mut position = 0;
+    std::iter::from_fn(move || loop {
+        let start = position + contents.get(position..)?.windows(6).position(|window| window == b"stream")?;
+        position = start + 6;
+        // The keyword of the end of a stream, or a stream without dictionary
+        if contents[..start].ends_with(b"end") {
+            continue;
+        }
+        let Some(object) = rfind(&contents[..start], b"obj") else { continue };
+        let dictionary = pdf_names(&contents[object..start]);
+        if !dictionary.iter().any(|name| name == b"ObjStm") || !dictionary.iter().any(|name| name == b"FlateDecode") {
+            continue;
+        }
+
+        // The data follows an end of line, and a zlib header
+        let data = &contents[position..];
+        let data = data.strip_prefix(b"\r\n").or_else(|| data.strip_prefix(b"\n")).unwrap_or(data);
+        let length = rfind(data, b"endstream").unwrap_or(data.len());
+        let stream = data.get(2..length).unwrap_or_default();
+        return Some(inflate(stream, MAX_OBJECT_STREAM_LENGTH).map_err(|e| DocumentError::Invalid(format!("PDF object stream: {}", e.0))));
+    })
+}
+
+/// Check the ZIP package of an office document and return its format and active content.
+fn check_office(contents: &[u8]) -> Result<(DocumentFormat, Vec<ActiveContent>), DocumentError> {
+    let entries = read_zip(contents, &ArchiveLimits::default())?;
+    let entry = |name: &str| entries.iter().find(|entry| entry.name == name);
+
+    let content_types = entry("[Content_Types].xml").ok_or_else(|| invalid("missing office content types"))?.decompress()?;
+    if entry("_rels/.rels").is_none() {
+        return Err(invalid("missing office relationships"));
+    }
+    let format = OFFICE_MAIN_PARTS
+        .iter()
+        .find(|(part, _)| entry(part).is_some())
+        .map(|&(_, format)| format)
+        .ok_or_else(|| DocumentError::Unsupported(String::from("unknown office document")))?;
+
+    let mut active_content = Vec::new();
+    let content_types = String::from_utf8_lossy(&content_types).to_ascii_lowercase();
+    if content_types.contains("macroenabled") || content_types.contains("vbaproject") {
+        active_content.push(ActiveContent::Macros);
+    }
+    for entry in &entries {
+        let name = entry.name.to_ascii_lowercase();
+        if name.ends_with("vbaproject.bin") || name.ends_with("vbadata.xml") {
+            active_content.push(ActiveContent::Macros);
+        } else if name.contains("/activex/") {
+            active_content.push(ActiveContent::ActiveX);
+        } else if name.contains("/embeddings/") {
+            active_content.push(ActiveContent::EmbeddedFile);
+        }
+    }
+    Ok((format, active_content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pdf_names, validate_document, validate_document_bytes, ActiveContent, DocumentError, DocumentFormat};
+    use crate::validators::archive::tests::zip;
+    use crate::{ArchiveError, ValidationError};
+
+    const TEST_DIR: &str = "test_files";
+
+    /// PDF document of a catalog, with an object and the end of its trailer.
+    fn pdf(object: &str) -> Vec<u8> {
+        let body = format!("%PDF-1.7\n1 0 obj\n<< /Type /Catalog {} >>\nendobj\n", object);
+        format!("{}xref\n0 2\ntrailer\n<< /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n", body, body.len()).into_bytes()
+    }
+
+    fn office(parts: &[&str], content_types: &str) -> Vec<u8> {
+        let mut entries: Vec<(&str, u16, u32, &[u8])> = vec![("[Content_Types].xml", 0, content_types.len() as u32, content_types.as_bytes()), ("_rels/.rels", 0, 0, b"")];
+        entries.extend(parts.iter().map(|&part| (part, 0, 0, &b""[..])));
+        zip(&entries)
+    }
+
+    #[test]
+    fn pdf_documents() {
+        let document = validate_document_bytes(&pdf("/Pages 2 0 R")).unwrap();
+        assert_eq!(document.format, DocumentFormat::Pdf);
+        assert!(document.is_passive());
+
+        let document = validate_document_bytes(&pdf("/OpenAction << /S /JavaScript /JS (app.alert(1)) >> /Names << /EmbeddedFiles 3 0 R >>")).unwrap();
+        assert_eq!(document.active_content, [ActiveContent::JavaScript, ActiveContent::EmbeddedFile]);
+        // escaped names
+        let document = validate_document_bytes(&pdf("/OpenAction << /S /J#61vaScr#69pt /#4aS 4 0 R >>")).unwrap();
+        assert_eq!(document.active_content, [ActiveContent::JavaScript]);
+        assert_eq!(pdf_names(b"/A#20B/C<</D"), [b"A B".to_vec(), b"C".to_vec(), b"D".to_vec()]);
+
+        // a PDF file is invalid for the file validator, not as a document
+        let document = validate_document(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap();
+        assert_eq!(document.format, DocumentFormat::Pdf);
+        assert!(matches!(validate_document(format!("{}/valid_image.png", TEST_DIR)), Err(ValidationError::Contents(_))));
+    }
+
+    #[test]
+    fn pdf_object_streams() {
+        // "<< /S /JavaScript /JS (app.alert(1)) >>" compressed with zlib
+        let compressed = b"\x78\x9c\xb3\xb1\x51\xd0\x0f\x56\xd0\xf7\x4a\x2c\x4b\x0c\x4e\x2e\xca\x2c\x28\x01\xb2\x83\x15\x34\x12\x0b\x0a\xf4\x12\x73\x52\x8b\x4a\x34\x0c\x35\x35\x15\xec\xec\x00\xe5\x44\x0b\x63";
+        let mut document = b"%PDF-1.5\n2 0 obj\n<< /Type /ObjStm /N 1 /First 4 /Filter /FlateDecode >>\nstream\n".to_vec();
+        document.extend_from_slice(compressed);
+        document.extend_from_slice(b"\nendstream\nendobj\n");
+        let offset = document.len();
+        document.extend_from_slice(format!("3 0 obj\n<< /Type /XRef >>\nendobj\nstartxref\n{}\n%%EOF", offset).as_bytes());
+        assert_eq!(validate_document_bytes(&document).unwrap().active_content, [ActiveContent::JavaScript]);
+
+        // a corrupted stream
+        let position = document.windows(2).position(|window| window == b"\x78\x9c").unwrap() + 2;
+        document[position] = 0xFF;
+        assert!(matches!(validate_document_bytes(&document), Err(DocumentError::Invalid(_))));
+    }
+
+    #[test]
+    fn invalid_pdf_documents() {
+        let document = pdf("");
+        let error = |document: &[u8]| validate_document_bytes(document).unwrap_err().to_string();
+        assert_eq!(error(&document[..document.len() - 7]), "Invalid document: missing PDF end of file marker.");
+        assert_eq!(error(b"%PDF-x.y\n%%EOF"), "Invalid document: bad PDF header.");
+        assert_eq!(error(b"%PDF-1.7\n%%EOF"), "Invalid document: missing PDF cross-reference table.");
+        assert_eq!(error(b"%PDF-1.7\nstartxref\n9999\n%%EOF"), "Invalid document: bad PDF cross-reference offset.");
+        assert_eq!(error(b"%PDF-"), "Invalid document: truncated file.");
+    }
+
+    #[test]
+    fn office_documents() {
+        let document = validate_document_bytes(&office(&["word/document.xml"], "<Types/>")).unwrap();
+        assert_eq!((document.format, document.is_passive()), (DocumentFormat::Docx, true));
+        assert_eq!(document.format.mime_type(), "application/vnd.openxmlformats-officedocument.wordprocessingml.document");
+
+        let macros = "<Override ContentType=\"application/vnd.ms-excel.sheet.macroEnabled.main+xml\"/>";
+        let document = validate_document_bytes(&office(&["xl/workbook.xml", "xl/vbaProject.bin", "xl/embeddings/oleObject1.bin"], macros)).unwrap();
+        assert_eq!(document.format, DocumentFormat::Xlsx);
+        assert_eq!(document.active_content, [ActiveContent::Macros, ActiveContent::EmbeddedFile]);
+        let document = validate_document_bytes(&office(&["ppt/presentation.xml", "ppt/activeX/activeX1.xml"], "<Types/>")).unwrap();
+        assert_eq!((document.format, document.active_content), (DocumentFormat::Pptx, vec![ActiveContent::ActiveX]));
+
+        let error = |document: &[u8]| validate_document_bytes(document).unwrap_err().to_string();
+        assert_eq!(error(&zip(&[("_rels/.rels", 0, 0, b"")])), "Invalid document: missing office content types.");
+        assert_eq!(error(&office(&["content.xml"], "<Types/>")), "The document can't be checked: unknown office document.");
+        let document = office(&["word/document.xml"], "<Types/>");
+        assert_eq!(validate_document_bytes(&document[..document.len() - 22]).unwrap_err(), DocumentError::Archive(ArchiveError::Invalid(String::from("no end of central directory"))));
+    }
+
+    #[test]
+    fn unsupported_documents() {
+        let ppt = std::fs::read(format!("{}/invalid_file.ppt", TEST_DIR)).unwrap();
+        assert_eq!(validate_document_bytes(&ppt).unwrap_err().to_string(), "The document can't be checked: legacy office document.");
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(validate_document_bytes(&image).unwrap_err().to_string(), "The document can't be checked: unknown document format.");
+    }
+}
@@ -0,0 +1,337 @@
+#[cfg(feature = "file")]
+use alloc::boxed::Box;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "file")]
+use super::FileTypeError;
+#[cfg(feature = "file")]
+use super::PathError;
+#[cfg(all(feature = "url", feature = "std"))]
+use super::UrlError;
+#[cfg(feature = "uuid")]
+use super::UuidError;
+
+/// Error of the validators, returned by the builders and the validation functions, and
+/// converted from the errors of the typed parsers so that a caller can handle them all with `?`.
+///
+/// The exceptions are the typed parsers themselves, e.g. [`crate::decode_uuid`] returning a
+/// [`UuidError`], whose errors convert into this one, the integer codes of the legacy
+/// [`crate::compat`] functions and of the C library, and the `JsError` of the wasm bindings.
+///
+/// # Examples
+/// ``` ignore
+/// fn check_upload(url: &str, path: &str, id: &str) -> Result<(), ValidationError> {
+///     validate_url_with_whitelist(url, [".ch", ".com"])?;
+///     validate_file(path, true)?;
+///     validate_uuid(id)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub enum ValidationError {
+    /// A list of a builder is empty, given by its name, e.g. `"scheme white list"`
+    EmptyWhitelist {
+        /// Name of the list
+        list: &'static str,
+    },
+    /// A top level domain of a list of a builder is invalid
+    InvalidTld {
+        /// The invalid top level domain
+        value: String,
+        /// Name of the list, e.g. `"white list"`
+        list: &'static str,
+    },
+    /// Another option of a builder is invalid, e.g. a mime type unknown to infer
+    InvalidOption(String),
+    /// The type of a file could not be detected from its contents
+    UnknownFileType,
+    /// The file is not a regular file
+    #[cfg(feature = "file")]
+    FileType(FileTypeError),
+    /// The contents of a file are invalid, e.g. a dangerous SVG image, or an image over a limit
+    /// of the deep validation whose [`crate::ImageError`] is the source
+    #[cfg(feature = "file")]
+    Contents(Box<dyn core::error::Error + Send + Sync>),
+    /// A content scanner of a [`crate::FileValidator`] failed, with its message
+    #[cfg(feature = "file")]
+    Scanner(String),
+    /// The declared content type of an upload is not a `type/subtype` mime type
+    #[cfg(feature = "file")]
+    InvalidContentType(String),
+    /// The path of an upload is unsafe
+    #[cfg(feature = "file")]
+    Path(PathError),
+    /// The url exceeds a length limit or is invalid
+    #[cfg(all(feature = "url", feature = "std"))]
+    Url(UrlError),
+    /// The uuid is invalid
+    #[cfg(feature = "uuid")]
+    Uuid(UuidError),
+    /// A file could not be read
+    #[cfg(feature = "std")]
+    Io(io::Error),
+    /// The file was rejected by a [`crate::FileValidator`], with the reason
+    Rejected(String),
+}
+
+impl ValidationError {
+    #[cfg(feature = "std")]
+    #[cfg_attr(not(any(feature = "url", feature = "file", feature = "banking", feature = "toml")), allow(dead_code))]
+    pub(crate) fn option(message: &str) -> Self {
+        ValidationError::InvalidOption(String::from(message))
+    }
+
+    #[cfg(feature = "file")]
+    pub(crate) fn contents<E: Into<Box<dyn core::error::Error + Send + Sync>>>(error: E) -> Self {
+        ValidationError::Contents(error.into())
+    }
+
+    /// Machine-readable code of the error, e.g. `invalid_url` or `uuid_version`, stable across
+    /// the changes of the messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::EmptyWhitelist { .. } => "empty_whitelist",
+            ValidationError::InvalidTld { .. } => "invalid_tld",
+            ValidationError::InvalidOption(_) => "invalid_option",
+            ValidationError::UnknownFileType => "unknown_file_type",
+            #[cfg(feature = "file")]
+            ValidationError::FileType(_) => "file_type",
+            #[cfg(feature = "file")]
+            ValidationError::Contents(_) => "invalid_contents",
+            #[cfg(feature = "file")]
+            ValidationError::Scanner(_) => "scanner_error",
+            #[cfg(feature = "file")]
+            ValidationError::InvalidContentType(_) => "invalid_content_type",
+            #[cfg(feature = "file")]
+            ValidationError::Path(_) => "unsafe_path",
+            #[cfg(all(feature = "url", feature = "std"))]
+            ValidationError::Url(UrlError::TooLong { .. }) => "url_too_long",
+            #[cfg(all(feature = "url", feature = "std"))]
+            ValidationError::Url(UrlError::Dangerous) => "dangerous_url",
+            #[cfg(all(feature = "url", feature = "std"))]
+            ValidationError::Url(UrlError::Invalid) => "invalid_url",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Malformed) => "invalid_uuid",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Variant) => "uuid_variant",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Nil) => "nil_uuid",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Max) => "max_uuid",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Version { .. }) => "uuid_version",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Expired) => "uuid_expired",
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(UuidError::Future) => "uuid_future",
+            #[cfg(feature = "std")]
+            ValidationError::Io(e) if e.kind() == io::ErrorKind::NotFound => "not_found",
+            #[cfg(feature = "std")]
+            ValidationError::Io(_) => "io",
+            ValidationError::Rejected(_) => "rejected_file",
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::EmptyWhitelist { list } => write!(f, "The {} is empty.", list),
+            ValidationError::InvalidTld { list, .. } => write!(f, "Invalid top level domain in {}.", list),
+            ValidationError::InvalidOption(message) => write!(f, "{}", message),
+            ValidationError::UnknownFileType => write!(f, "File type is unknown."),
+            #[cfg(feature = "file")]
+            ValidationError::FileType(e) => write!(f, "{}", e),
+            #[cfg(feature = "file")]
+            ValidationError::Contents(e) => write!(f, "{}", e),
+            #[cfg(feature = "file")]
+            ValidationError::Scanner(message) => write!(f, "{}", message),
+            #[cfg(feature = "file")]
+            ValidationError::InvalidContentType(_) => write!(f, "Invalid content type."),
+            #[cfg(feature = "file")]
+            ValidationError::Path(e) => write!(f, "{}", e),
+            #[cfg(all(feature = "url", feature = "std"))]
+            ValidationError::Url(e) => write!(f, "{}", e),
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(e) => write!(f, "{}", e),
+            #[cfg(feature = "std")]
+            ValidationError::Io(e) => write!(f, "{}", e),
+            ValidationError::Rejected(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl core::error::Error for ValidationError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "file")]
+            ValidationError::FileType(e) => Some(e),
+            #[cfg(feature = "file")]
+            ValidationError::Contents(e) => Some(e.as_ref()),
+            #[cfg(feature = "file")]
+            ValidationError::Path(e) => Some(e),
+            #[cfg(all(feature = "url", feature = "std"))]
+            ValidationError::Url(e) => Some(e),
+            #[cfg(feature = "uuid")]
+            ValidationError::Uuid(e) => Some(e),
+            #[cfg(feature = "std")]
+            ValidationError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "file")]
+impl From<FileTypeError> for ValidationError {
+    fn from(e: FileTypeError) -> Self {
+        ValidationError::FileType(e)
+    }
+}
+
+#[cfg(feature = "file")]
+impl From<PathError> for ValidationError {
+    fn from(e: PathError) -> Self {
+        ValidationError::Path(e)
+    }
+}
+
+#[cfg(all(feature = "url", feature = "std"))]
+impl From<UrlError> for ValidationError {
+    fn from(e: UrlError) -> Self {
+        ValidationError::Url(e)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl From<UuidError> for ValidationError {
+    fn from(e: UuidError) -> Self {
+        ValidationError::Uuid(e)
+    }
+}
+
+/// An io error wrapping a validation error, e.g. of a file validated by a function returning io
+/// errors, is unwrapped.
+#[cfg(feature = "std")]
+impl From<io::Error> for ValidationError {
+    fn from(e: io::Error) -> Self {
+        e.downcast::<ValidationError>().unwrap_or_else(ValidationError::Io)
+    }
+}
+
+/// Errors of several fields, e.g. of a struct validated by the derive macro `ValidateInput`, with
+/// the name of the field of each error, in the order of the fields.
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    errors: Vec<(String, ValidationError)>,
+}
+
+impl ValidationErrors {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        ValidationErrors::default()
+    }
+
+    /// Add the error of a field.
+    pub fn push(&mut self, field: &str, error: ValidationError) {
+        self.errors.push((String::from(field), error));
+    }
+
+    /// Whether no field is invalid.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Number of errors.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// The errors, with the name of their field.
+    pub fn errors(&self) -> &[(String, ValidationError)] {
+        &self.errors
+    }
+
+    /// The errors of a field.
+    pub fn field<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a ValidationError> + 'a {
+        self.errors.iter().filter(move |(name, _)| name == field).map(|(_, error)| error)
+    }
+
+    /// `Ok` if no field is invalid, the errors otherwise.
+    ///
+    /// # Errors
+    /// If at least one field is invalid.
+    pub fn into_result(self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// One line per error, e.g. `id: Invalid uuid.`
+impl Display for ValidationErrors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, (field, error)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", field, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ValidationErrors {}
+
+#[cfg(all(test, feature = "url", feature = "file", feature = "uuid"))]
+mod tests {
+    use std::error::Error;
+    use std::io;
+
+    use crate::{validate_file, FileTypeError, UrlError, UrlValidator, UuidError, ValidationError, ValidationErrors};
+
+    #[test]
+    fn error_messages() {
+        assert_eq!(ValidationError::EmptyWhitelist { list: "port white list" }.to_string(), "The port white list is empty.");
+        assert_eq!(ValidationError::InvalidTld { value: String::from("ch"), list: "black list" }.to_string(), "Invalid top level domain in black list.");
+        assert_eq!(ValidationError::from(UrlError::Invalid).to_string(), "The url is invalid.");
+        assert!(ValidationError::from(UrlError::Invalid).source().is_some());
+        assert_eq!(ValidationError::UnknownFileType.to_string(), "File type is unknown.");
+        assert_eq!(ValidationError::from(UuidError::Nil).code(), "nil_uuid");
+        assert_eq!(ValidationError::from(io::Error::from(io::ErrorKind::NotFound)).code(), "not_found");
+    }
+
+    #[test]
+    fn converted_errors() {
+        let error = UrlValidator::builder().top_level_whitelist([".ch", "com"]).build().unwrap_err();
+        assert!(matches!(error, ValidationError::InvalidTld { ref value, list: "white list" } if value == "com"));
+
+        assert!(matches!(validate_file("Cargo.toml", false), Err(ValidationError::UnknownFileType)));
+        assert!(matches!(validate_file("test_files", false), Err(ValidationError::FileType(FileTypeError::Directory))));
+        let error = validate_file("test_files/missing", false).unwrap_err();
+        assert!(matches!(error, ValidationError::Io(ref e) if e.kind() == io::ErrorKind::NotFound));
+
+        // a validation error wrapped in an io error is unwrapped
+        let error = ValidationError::from(io::Error::other(ValidationError::UnknownFileType));
+        assert!(matches!(error, ValidationError::UnknownFileType));
+    }
+
+    #[test]
+    fn field_errors() {
+        let mut errors = ValidationErrors::new();
+        assert!(errors.is_empty());
+        errors.push("source", ValidationError::from(UrlError::Invalid));
+        errors.push("id", ValidationError::from(UuidError::Malformed));
+        errors.push("source", ValidationError::UnknownFileType);
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors.field("source").count(), 2);
+        assert_eq!(errors.to_string(), "source: The url is invalid.\nid: Invalid uuid.\nsource: File type is unknown.");
+        assert!(errors.into_result().is_err());
+        assert!(ValidationErrors::new().into_result().is_ok());
+    }
+}
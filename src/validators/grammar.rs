@@ -0,0 +1,184 @@
+//! Hand-written matching of the url grammar, used instead of the regexes with the `handwritten`
+//! feature and without the `url_regex` feature. It matches the same urls without backtracking
+//! nor allocation.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Characters allowed in a top level domain, see [`crate::UrlValidatorBuilder::top_level_charset`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum TopLevelCharset {
+    /// Ascii letters only, as in the lab (the default).
+    #[default]
+    Alphabetic,
+    /// Ascii letters and digits.
+    Alphanumeric,
+    /// Ascii letters, digits and hyphens, as in the punycode top level domains such as
+    /// `xn--p1ai`. The last character can't be a hyphen.
+    Punycode,
+}
+
+impl TopLevelCharset {
+    pub(crate) fn contains(self, c: char) -> bool {
+        match self {
+            TopLevelCharset::Alphabetic => c.is_ascii_alphabetic(),
+            TopLevelCharset::Alphanumeric => c.is_ascii_alphanumeric(),
+            TopLevelCharset::Punycode => c.is_ascii_alphanumeric() || c == '-',
+        }
+    }
+
+    /// Whether a character can end a top level domain.
+    #[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+    fn contains_last(self, c: u8) -> bool {
+        match self {
+            TopLevelCharset::Alphabetic => c.is_ascii_alphabetic(),
+            _ => c.is_ascii_alphanumeric(),
+        }
+    }
+}
+
+/// End of the scheme of an url, after its `://`, as matched by `^[[:alnum:]]+://`.
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+pub(crate) fn scheme_end(url: &str) -> Option<usize> {
+    let length = url.bytes().take_while(u8::is_ascii_alphanumeric).count();
+    if length > 0 && url[length..].starts_with("://") {
+        Some(length + "://".len())
+    } else {
+        None
+    }
+}
+
+/// Grammar of the hosts of an url validator.
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+#[derive(Debug, Clone)]
+pub(crate) enum HostGrammar {
+    /// Sub level domain followed by a top level domain following the rules
+    Rules { charset: TopLevelCharset, min_length: usize },
+    /// Sub level domain followed by one of the top level domains, including their full stop
+    Whitelist(Vec<String>),
+}
+
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+impl HostGrammar {
+    /// Check whether a host matches the grammar.
+    pub(crate) fn matches(&self, host: &str) -> bool {
+        match self {
+            HostGrammar::Rules { charset, min_length } => matches_rules(host, *charset, *min_length),
+            HostGrammar::Whitelist(whitelist) => whitelist.iter().any(|tld| {
+                host.strip_suffix(tld.as_str()).is_some_and(|sub| !sub.is_empty() && sub.bytes().all(is_sub_level_byte))
+            }),
+        }
+    }
+}
+
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+fn is_sub_level_byte(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'.' || c == b'-'
+}
+
+/// Match `^[[:alnum:].-]+(\.[CHARSET.]{min_length - 1,}[LAST])$`.
+///
+/// The charsets of the top level domain are included in the one of the sub level domain, so the
+/// host matches if all its characters are valid, it ends with a valid last character and a full
+/// stop inside its longest suffix of top level characters leaves enough characters after it.
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+pub(crate) fn matches_rules(host: &str, charset: TopLevelCharset, min_length: usize) -> bool {
+    let bytes = host.as_bytes();
+    let last_valid = bytes.last().is_some_and(|&last| charset.contains_last(last));
+    if !last_valid || !bytes.iter().all(|&c| is_sub_level_byte(c)) {
+        return false;
+    }
+
+    // Start of the longest suffix which can be part of the top level domain
+    let suffix_length = bytes.iter().rev().take_while(|&&c| c == b'.' || charset.contains(c as char)).count();
+    let suffix_start = (bytes.len() - suffix_length).max(1);
+
+    // The first full stop leaves the most characters to the top level domain
+    match bytes[suffix_start..].iter().position(|&c| c == b'.') {
+        None => false,
+        Some(index) => bytes.len() - (suffix_start + index) - 2 >= min_length - 1,
+    }
+}
+
+/// Match a top level domain of a list, `^\.[CHARSET.]{min_length - 1,}[LAST]$`.
+#[cfg_attr(all(feature = "url_regex", not(feature = "handwritten")), allow(dead_code))]
+pub(crate) fn is_top_level(tld: &str, charset: TopLevelCharset, min_length: usize) -> bool {
+    match tld.as_bytes().split_first() {
+        Some((b'.', rest)) => {
+            rest.len() >= min_length
+                && rest.last().is_some_and(|&last| charset.contains_last(last))
+                && rest.iter().all(|&c| c == b'.' || charset.contains(c as char))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    #[cfg(feature = "url_regex")]
+    use regex::Regex;
+
+    #[cfg(feature = "url_regex")]
+    use super::super::validate_url::top_level_pattern;
+    use super::HostGrammar;
+    #[cfg(feature = "url_regex")]
+    use super::{is_top_level, scheme_end};
+    #[cfg(feature = "url_regex")]
+    use crate::TopLevelCharset;
+
+    #[test]
+    #[cfg(feature = "url_regex")]
+    fn same_schemes_as_the_regex() {
+        let regex = Regex::new(r"^[[:alnum:]]+://").unwrap();
+        for url in ["https://test.com", "://test.com", "h_s://test.com", "https:/test.com", "", "a://", "é://"] {
+            assert_eq!(scheme_end(url), regex.find(url).map(|found| found.end()), "{}", url);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "url_regex")]
+    fn same_hosts_as_the_regex() {
+        let hosts = [
+            "test.com", "a.b.c.ch", "test", ".com", "test.", "test.c", "test..c", "t.co.", "a-b.c-d", "test.c0m", "test.xn--p1ai",
+            "test.c.m", "a.bc", "-.ab", "a_b.com", "a..b", "1.2.3.4", "test.com-", "é.com", "", "a.b-c", "a.-bc", "a.b.-", "ab.c1d",
+        ];
+        let charsets = [TopLevelCharset::Alphabetic, TopLevelCharset::Alphanumeric, TopLevelCharset::Punycode];
+
+        for charset in charsets {
+            for min_length in 1..5 {
+                let grammar = HostGrammar::Rules { charset, min_length };
+                let regex = Regex::new(&format!("{}{}$", r"^([[:alnum:].-]+)", top_level_pattern(charset, min_length))).unwrap();
+                for host in hosts {
+                    assert_eq!(grammar.matches(host), regex.is_match(host), "{} {:?} {}", host, charset, min_length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "url_regex")]
+    fn same_top_level_domains_as_the_regex() {
+        let tlds = [".ch", ".co.uk", "ch", ".c", ".", "..", ".c.", ".c1", ".xn--p1ai", ".c-", ".com-", ".é", "", ".c_h", "..ch"];
+        let charsets = [TopLevelCharset::Alphabetic, TopLevelCharset::Alphanumeric, TopLevelCharset::Punycode];
+
+        for charset in charsets {
+            for min_length in 1..5 {
+                let regex = Regex::new(&format!("^{}$", top_level_pattern(charset, min_length))).unwrap();
+                for tld in tlds {
+                    assert_eq!(is_top_level(tld, charset, min_length), regex.is_match(tld), "{} {:?} {}", tld, charset, min_length);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn whitelist_hosts() {
+        let grammar = HostGrammar::Whitelist(vec![String::from(".com"), String::from(".co.uk")]);
+        assert!(grammar.matches("test.com"));
+        assert!(grammar.matches("www.test.co.uk"));
+        assert!(!grammar.matches(".com"));
+        assert!(!grammar.matches("test.coXuk"));
+        assert!(!grammar.matches("test.ch"));
+        assert!(!grammar.matches("a_b.com"));
+    }
+}
@@ -0,0 +1,586 @@
+//! Structural validation of the common image formats, enabled with the `deep` feature.
+//!
+//! The magic numbers of a file only tell its first bytes, the headers and the structure of the
+//! image are parsed here so that truncated or crafted images are rejected. The pixels are not
+//! decoded.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::byte_reader::{Reader, Truncated};
+
+pub(crate) const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Format of an image checked by the deep validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    WebP,
+}
+
+/// Header of an image checked by the deep validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageInfo {
+    /// Format of the image
+    pub format: ImageFormat,
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Length in bytes up to the end of the image, any data after it is not part of the image
+    pub length: usize,
+    /// Number of frames, more than one for an animated GIF, PNG (APNG) or WebP image
+    pub frames: u32,
+}
+
+impl ImageInfo {
+    /// Whether the image is animated, e.g. to accept only static avatars.
+    pub fn is_animated(&self) -> bool {
+        self.frames > 1
+    }
+}
+
+/// Size limit of the deep validation of an image.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ImageLimit {
+    /// Width of the image, see [`FileValidatorBuilder::max_image_width`](crate::FileValidatorBuilder::max_image_width)
+    Width,
+    /// Height of the image, see [`FileValidatorBuilder::max_image_height`](crate::FileValidatorBuilder::max_image_height)
+    Height,
+    /// Number of pixels of the image, see [`FileValidatorBuilder::max_image_pixels`](crate::FileValidatorBuilder::max_image_pixels)
+    Pixels,
+}
+
+/// Size limits of the images, none by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct ImageLimits {
+    pub(crate) max_width: Option<u32>,
+    pub(crate) max_height: Option<u32>,
+    pub(crate) max_pixels: Option<u64>,
+}
+
+impl ImageLimits {
+    /// Check the size of an image against the limits.
+    fn check(&self, image: &ImageInfo) -> Result<(), ImageError> {
+        let pixels = image.width as u64 * image.height as u64;
+        let limits = [
+            (ImageLimit::Width, image.width as u64, self.max_width.map(u64::from)),
+            (ImageLimit::Height, image.height as u64, self.max_height.map(u64::from)),
+            (ImageLimit::Pixels, pixels, self.max_pixels),
+        ];
+        match limits.into_iter().find(|&(_, actual, max)| max.is_some_and(|max| actual > max)) {
+            Some((limit, actual, max)) => Err(ImageError::TooLarge { limit, actual, max: max.unwrap() }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Error of the deep validation of an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImageError {
+    /// The image is malformed or truncated, for the given reason
+    Invalid(String),
+    /// The format of the image, given by its mime type, can't be checked
+    Unsupported(String),
+    /// The image exceeds a size limit, e.g. a decompression bomb
+    TooLarge {
+        /// The exceeded limit
+        limit: ImageLimit,
+        /// The size of the image
+        actual: u64,
+        /// The configured maximum
+        max: u64,
+    },
+}
+
+impl Display for ImageError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Invalid(reason) => write!(f, "Invalid image: {}.", reason),
+            ImageError::Unsupported(mime_type) => write!(f, "Unsupported image format {}.", mime_type),
+            ImageError::TooLarge { limit: ImageLimit::Width, actual, max } => {
+                write!(f, "The image is too wide ({} pixels, at most {}).", actual, max)
+            }
+            ImageError::TooLarge { limit: ImageLimit::Height, actual, max } => {
+                write!(f, "The image is too high ({} pixels, at most {}).", actual, max)
+            }
+            ImageError::TooLarge { limit: ImageLimit::Pixels, actual, max } => {
+                write!(f, "The image has too many pixels ({}, at most {}).", actual, max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<Truncated> for ImageError {
+    fn from(_: Truncated) -> Self {
+        invalid("truncated file")
+    }
+}
+
+fn invalid(reason: &str) -> ImageError {
+    ImageError::Invalid(reason.to_string())
+}
+
+/// Parse the header and check the structure and the size of an image of the given mime type.
+pub(crate) fn parse_image(contents: &[u8], mime_type: &str, limits: &ImageLimits) -> Result<ImageInfo, ImageError> {
+    let (format, (width, height, length, frames)) = match mime_type {
+        "image/png" => (ImageFormat::Png, parse_png(contents)?),
+        "image/jpeg" => (ImageFormat::Jpeg, parse_jpeg(contents)?),
+        "image/gif" => (ImageFormat::Gif, parse_gif(contents)?),
+        "image/bmp" => (ImageFormat::Bmp, parse_bmp(contents)?),
+        "image/webp" => (ImageFormat::WebP, parse_webp(contents)?),
+        _ => return Err(ImageError::Unsupported(mime_type.to_string())),
+    };
+
+    if width == 0 || height == 0 {
+        return Err(invalid("empty image"));
+    }
+    let image = ImageInfo { format, width, height, length, frames };
+    limits.check(&image)?;
+    Ok(image)
+}
+
+/// Check the chunks of a PNG image up to its `IEND` chunk, with their checksums, and return its
+/// size, length and number of frames, from the animation control chunk of an APNG image.
+fn parse_png(bytes: &[u8]) -> Result<(u32, u32, usize, u32), ImageError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(PNG_SIGNATURE.len())? != PNG_SIGNATURE {
+        return Err(invalid("bad PNG signature"));
+    }
+
+    let mut size = None;
+    let mut image_data = false;
+    let mut frames = 1;
+    loop {
+        let length = reader.be_u32()? as usize;
+        let chunk = reader.take(4 + length)?;
+        if crc32(chunk) != reader.be_u32()? {
+            return Err(invalid("bad PNG chunk checksum"));
+        }
+
+        let (kind, data) = chunk.split_at(4);
+        match kind {
+            b"IHDR" if size.is_none() && length == 13 => {
+                let mut header = Reader::new(data);
+                let (width, height) = (header.be_u32()?, header.be_u32()?);
+                let (bit_depth, color_type) = (header.u8()?, header.u8()?);
+                let valid_depth = match color_type {
+                    0 => matches!(bit_depth, 1 | 2 | 4 | 8 | 16),
+                    3 => matches!(bit_depth, 1 | 2 | 4 | 8),
+                    2 | 4 | 6 => matches!(bit_depth, 8 | 16),
+                    _ => false,
+                };
+                // Compression, filter and interlace methods
+                let methods = header.take(3)?;
+                if !valid_depth || methods[0] != 0 || methods[1] != 0 || methods[2] > 1 || width > i32::MAX as u32 || height > i32::MAX as u32 {
+                    return Err(invalid("bad PNG header"));
+                }
+                size = Some((width, height));
+            }
+            _ if size.is_none() => return Err(invalid("missing PNG header")),
+            b"IHDR" => return Err(invalid("bad PNG header")),
+            b"IDAT" => image_data = true,
+            // Animation control, before the image data
+            b"acTL" if !image_data && length == 8 => {
+                frames = Reader::new(data).be_u32()?;
+                if frames == 0 {
+                    return Err(invalid("bad APNG animation control"));
+                }
+            }
+            b"IEND" if image_data => {
+                let (width, height) = size.unwrap();
+                return Ok((width, height, reader.position, frames));
+            }
+            b"IEND" => return Err(invalid("missing PNG image data")),
+            _ => {}
+        }
+    }
+}
+
+/// CRC-32 checksum of the PNG chunks.
+pub(crate) fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Check the segments of a JPEG image up to its end of image marker, and return its size,
+/// length and single frame.
+fn parse_jpeg(bytes: &[u8]) -> Result<(u32, u32, usize, u32), ImageError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(2)? != [0xFF, 0xD8] {
+        return Err(invalid("bad JPEG signature"));
+    }
+
+    let mut size = None;
+    loop {
+        if reader.u8()? != 0xFF {
+            return Err(invalid("bad JPEG marker"));
+        }
+        // Markers can be preceded by fill bytes
+        let mut marker = reader.u8()?;
+        while marker == 0xFF {
+            marker = reader.u8()?;
+        }
+
+        match marker {
+            // End of image
+            0xD9 => {
+                let (width, height) = size.ok_or_else(|| invalid("missing JPEG frame"))?;
+                return Ok((width, height, reader.position, 1));
+            }
+            // Markers without segment
+            0x01 | 0xD0..=0xD7 => {}
+            0x00 => return Err(invalid("bad JPEG marker")),
+            _ => {
+                let length = reader.be_u16()? as usize;
+                if length < 2 {
+                    return Err(invalid("bad JPEG segment"));
+                }
+                let segment = reader.take(length - 2)?;
+
+                match marker {
+                    // Start of frame
+                    0xC0..=0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF => {
+                        let mut frame = Reader::new(segment);
+                        frame.skip(1)?;
+                        let height = frame.be_u16()? as u32;
+                        let width = frame.be_u16()? as u32;
+                        size = Some((width, height));
+                    }
+                    // Start of scan, followed by the entropy coded data
+                    0xDA => {
+                        if size.is_none() {
+                            return Err(invalid("missing JPEG frame"));
+                        }
+                        skip_entropy_coded_data(&mut reader)?;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Skip the entropy coded data of a JPEG scan, up to the next marker.
+fn skip_entropy_coded_data(reader: &mut Reader) -> Result<(), ImageError> {
+    loop {
+        if reader.u8()? != 0xFF {
+            continue;
+        }
+        match reader.u8()? {
+            // Escaped 0xFF and restart markers are part of the data
+            0x00 | 0xD0..=0xD7 => {}
+            0xFF => reader.position -= 1,
+            _ => {
+                reader.position -= 2;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Check the blocks of a GIF image up to its trailer, and return its size, length and number of
+/// images.
+fn parse_gif(bytes: &[u8]) -> Result<(u32, u32, usize, u32), ImageError> {
+    let mut reader = Reader::new(bytes);
+    let signature = reader.take(6)?;
+    if signature != b"GIF87a" && signature != b"GIF89a" {
+        return Err(invalid("bad GIF signature"));
+    }
+
+    let width = reader.le_u16()? as u32;
+    let height = reader.le_u16()? as u32;
+    let flags = reader.u8()?;
+    // Background color and pixel aspect ratio
+    reader.skip(2)?;
+    if flags & 0x80 != 0 {
+        reader.skip(3 << ((flags & 0x07) + 1))?;
+    }
+
+    let mut images = 0;
+    loop {
+        match reader.u8()? {
+            // Image descriptor
+            0x2C => {
+                reader.skip(8)?;
+                let flags = reader.u8()?;
+                if flags & 0x80 != 0 {
+                    reader.skip(3 << ((flags & 0x07) + 1))?;
+                }
+                let code_size = reader.u8()?;
+                if !(1..=11).contains(&code_size) {
+                    return Err(invalid("bad GIF image data"));
+                }
+                skip_sub_blocks(&mut reader)?;
+                images += 1;
+            }
+            // Extension
+            0x21 => {
+                reader.skip(1)?;
+                skip_sub_blocks(&mut reader)?;
+            }
+            // Trailer
+            0x3B if images > 0 => return Ok((width, height, reader.position, images)),
+            0x3B => return Err(invalid("missing GIF image")),
+            _ => return Err(invalid("bad GIF block")),
+        }
+    }
+}
+
+/// Skip data sub-blocks of a GIF image, up to the block terminator.
+fn skip_sub_blocks(reader: &mut Reader) -> Result<(), ImageError> {
+    loop {
+        match reader.u8()? {
+            0 => return Ok(()),
+            size => reader.skip(size as usize)?,
+        }
+    }
+}
+
+/// Check the headers of a BMP image and that its pixel data is complete, and return its size,
+/// length and single frame.
+fn parse_bmp(bytes: &[u8]) -> Result<(u32, u32, usize, u32), ImageError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(2)? != b"BM" {
+        return Err(invalid("bad BMP signature"));
+    }
+    let file_size = reader.le_u32()? as usize;
+    reader.skip(4)?;
+    let data_offset = reader.le_u32()? as usize;
+
+    let (width, height, planes, bits_per_pixel, compression) = match reader.le_u32()? {
+        12 => (reader.le_u16()? as i64, reader.le_u16()? as i64, reader.le_u16()?, reader.le_u16()?, 0),
+        40 | 52 | 56 | 108 | 124 => (
+            reader.le_u32()? as i32 as i64,
+            reader.le_u32()? as i32 as i64,
+            reader.le_u16()?,
+            reader.le_u16()?,
+            reader.le_u32()?,
+        ),
+        _ => return Err(invalid("bad BMP header")),
+    };
+
+    if planes != 1 || !matches!(bits_per_pixel, 1 | 4 | 8 | 16 | 24 | 32) || width <= 0 || height == 0 {
+        return Err(invalid("bad BMP header"));
+    }
+    if file_size > bytes.len() || data_offset > bytes.len() {
+        return Err(invalid("truncated file"));
+    }
+
+    // The size of the uncompressed pixel data is known, the file size of the header is used
+    // otherwise, the whole file if unset
+    let (width, height) = (width as u64, height.unsigned_abs());
+    let length = if compression == 0 || compression == 3 {
+        let row_size = (bits_per_pixel as u64 * width).div_ceil(32) * 4;
+        let data_size = row_size.saturating_mul(height);
+        if data_size > (bytes.len() - data_offset) as u64 {
+            return Err(invalid("truncated file"));
+        }
+        data_offset + data_size as usize
+    } else if file_size == 0 {
+        bytes.len()
+    } else {
+        file_size
+    };
+
+    let width = u32::try_from(width).map_err(|_| invalid("bad BMP header"))?;
+    let height = u32::try_from(height).map_err(|_| invalid("bad BMP header"))?;
+    Ok((width, height, length, 1))
+}
+
+/// Check the RIFF container and the header of the first chunk of a WebP image, and return its
+/// size, length and number of frames, the animation frame chunks of an animated image.
+fn parse_webp(bytes: &[u8]) -> Result<(u32, u32, usize, u32), ImageError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(4)? != b"RIFF" {
+        return Err(invalid("bad WebP signature"));
+    }
+    let riff_size = reader.le_u32()? as usize;
+    if reader.take(4)? != b"WEBP" {
+        return Err(invalid("bad WebP signature"));
+    }
+    // The RIFF chunk is padded to an even length
+    let length = riff_size.saturating_add(8 + riff_size % 2).min(bytes.len());
+    if riff_size.saturating_add(8) > bytes.len() {
+        return Err(invalid("truncated file"));
+    }
+
+    let kind = reader.take(4)?;
+    let chunk_length = reader.le_u32()? as usize;
+    let mut chunk = Reader::new(reader.take(chunk_length)?);
+
+    match kind {
+        // Extended format, the canvas size is stored minus one on 24 bits
+        b"VP8X" => {
+            let animated = chunk.u8()? & 0x02 != 0;
+            chunk.skip(3)?;
+            let size = chunk.take(6)?;
+            let width = u32::from_le_bytes([size[0], size[1], size[2], 0]) + 1;
+            let height = u32::from_le_bytes([size[3], size[4], size[5], 0]) + 1;
+            if !animated {
+                return Ok((width, height, length, 1));
+            }
+
+            // The chunks following the header, padded to an even length
+            reader.skip(chunk_length % 2)?;
+            let mut frames = 0;
+            while reader.position + 8 <= length {
+                let kind = reader.take(4)?;
+                let chunk_length = reader.le_u32()? as usize;
+                reader.skip(chunk_length)?;
+                reader.position = (reader.position + chunk_length % 2).min(length);
+                if kind == b"ANMF" {
+                    frames += 1;
+                }
+            }
+            if frames == 0 {
+                return Err(invalid("missing WebP animation frame"));
+            }
+            Ok((width, height, length, frames))
+        }
+        // Lossless, the size is stored minus one on 14 bits each
+        b"VP8L" => {
+            if chunk.u8()? != 0x2F {
+                return Err(invalid("bad WebP lossless signature"));
+            }
+            let bits = chunk.le_u32()?;
+            Ok(((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1, length, 1))
+        }
+        // Lossy, after the frame tag and the start code
+        b"VP8 " => {
+            chunk.skip(3)?;
+            if chunk.take(3)? != [0x9D, 0x01, 0x2A] {
+                return Err(invalid("bad WebP lossy start code"));
+            }
+            let width = (chunk.le_u16()? & 0x3FFF) as u32;
+            let height = (chunk.le_u16()? & 0x3FFF) as u32;
+            Ok((width, height, length, 1))
+        }
+        _ => Err(invalid("bad WebP chunk")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{crc32, parse_image, ImageError, ImageFormat, ImageInfo, ImageLimit, ImageLimits, PNG_SIGNATURE};
+
+    const TEST_DIR: &str = "test_files";
+
+    /// Smallest GIF image: a single pixel with a global color table of two colors.
+    const GIF: &[u8] = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b";
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut chunk = (data.len() as u32).to_be_bytes().to_vec();
+        let body = [kind, data].concat();
+        chunk.extend_from_slice(&body);
+        chunk.extend_from_slice(&crc32(&body).to_be_bytes());
+        chunk
+    }
+
+    fn png(width: u32, height: u32, chunks: &[Vec<u8>]) -> Vec<u8> {
+        let header = [&width.to_be_bytes()[..], &height.to_be_bytes(), &[8, 2, 0, 0, 0]].concat();
+        let mut png = [PNG_SIGNATURE.to_vec(), png_chunk(b"IHDR", &header)].concat();
+        for chunk in chunks {
+            png.extend_from_slice(chunk);
+        }
+        png
+    }
+
+    #[test]
+    fn valid_images() {
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap().format, ImageFormat::Png);
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        assert_eq!(parse_image(&image, "image/jpeg", &ImageLimits::default()).unwrap().format, ImageFormat::Jpeg);
+
+        let image = png(3, 2, &[png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap(), ImageInfo { format: ImageFormat::Png, width: 3, height: 2, length: image.len(), frames: 1 });
+        assert_eq!(parse_image(GIF, "image/gif", &ImageLimits::default()).unwrap(), ImageInfo { format: ImageFormat::Gif, width: 1, height: 1, length: GIF.len(), frames: 1 });
+    }
+
+    #[test]
+    fn animated_images() {
+        assert!(!parse_image(GIF, "image/gif", &ImageLimits::default()).unwrap().is_animated());
+        // a second frame after the graphic control extension of the first one
+        let gif = [&GIF[..GIF.len() - 1], b"\x21\xf9\x04\x00\x0a\x00\x00\x00", &GIF[19..]].concat();
+        let image = parse_image(&gif, "image/gif", &ImageLimits::default()).unwrap();
+        assert_eq!((image.frames, image.is_animated()), (2, true));
+
+        let animation = png_chunk(b"acTL", &[0, 0, 0, 3, 0, 0, 0, 0]);
+        let image = png(3, 2, &[animation, png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap().frames, 3);
+        let image = png(3, 2, &[png_chunk(b"acTL", &[0; 8]), png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: bad APNG animation control.");
+
+        // extended WebP header of a 2x2 canvas, with the animation flag
+        let webp = |chunks: &[u8]| {
+            let body = [b"WEBPVP8X\x0a\x00\x00\x00\x02\x00\x00\x00\x01\x00\x00\x01\x00\x00".as_slice(), chunks].concat();
+            [b"RIFF".as_slice(), &(body.len() as u32).to_le_bytes(), &body].concat()
+        };
+        let frame = b"ANMF\x03\x00\x00\x00abc\x00";
+        let image = parse_image(&webp(&[b"ANIM\x06\x00\x00\x00\x00\x00\x00\x00\x00\x00".as_slice(), frame, frame].concat()), "image/webp", &ImageLimits::default()).unwrap();
+        assert_eq!((image.width, image.height, image.frames), (2, 2, 2));
+        assert_eq!(parse_image(&webp(b""), "image/webp", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: missing WebP animation frame.");
+    }
+
+    #[test]
+    fn truncated_images() {
+        for name in ["valid_image.png", "valid_image.jpg"] {
+            let image = std::fs::read(format!("{}/{}", TEST_DIR, name)).unwrap();
+            let mime_type = if name.ends_with("png") { "image/png" } else { "image/jpeg" };
+            for length in [4, image.len() / 2, image.len() - 1] {
+                assert_eq!(parse_image(&image[..length], mime_type, &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: truncated file.");
+            }
+        }
+        assert!(parse_image(&GIF[..GIF.len() - 1], "image/gif", &ImageLimits::default()).is_err());
+    }
+
+    #[test]
+    fn crafted_images() {
+        // magic numbers followed by garbage
+        let image = [PNG_SIGNATURE, b"garbage garbage garbage"].concat();
+        assert!(parse_image(&image, "image/png", &ImageLimits::default()).is_err());
+        assert_eq!(parse_image(b"\xff\xd8\xff\xe0garbage", "image/jpeg", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: truncated file.");
+
+        let mut image = png(3, 2, &[png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        let last = image.len() - 1;
+        image[last] ^= 1;
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: bad PNG chunk checksum.");
+
+        let image = png(3, 2, &[png_chunk(b"IEND", b"")]);
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: missing PNG image data.");
+        let image = png(0, 2, &[png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        assert_eq!(parse_image(&image, "image/png", &ImageLimits::default()).unwrap_err().to_string(), "Invalid image: empty image.");
+
+        assert_eq!(parse_image(b"II*\x00", "image/tiff", &ImageLimits::default()).unwrap_err(), ImageError::Unsupported(String::from("image/tiff")));
+    }
+
+    #[test]
+    fn image_limits() {
+        let bomb = png(100_000, 100_000, &[png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")]);
+        assert!(parse_image(&bomb, "image/png", &ImageLimits::default()).is_ok());
+
+        let limits = ImageLimits { max_pixels: Some(50_000_000), ..ImageLimits::default() };
+        let error = parse_image(&bomb, "image/png", &limits).unwrap_err();
+        assert_eq!(error, ImageError::TooLarge { limit: ImageLimit::Pixels, actual: 10_000_000_000, max: 50_000_000 });
+        assert_eq!(error.to_string(), "The image has too many pixels (10000000000, at most 50000000).");
+
+        let limits = ImageLimits { max_width: Some(4096), max_height: Some(100_000), max_pixels: None };
+        assert_eq!(parse_image(&bomb, "image/png", &limits).unwrap_err().to_string(), "The image is too wide (100000 pixels, at most 4096).");
+        let limits = ImageLimits { max_width: Some(100_000), max_height: Some(4096), max_pixels: None };
+        assert_eq!(parse_image(&bomb, "image/png", &limits).unwrap_err().to_string(), "The image is too high (100000 pixels, at most 4096).");
+
+        let limits = ImageLimits { max_width: Some(1), max_height: Some(1), max_pixels: Some(1) };
+        assert!(parse_image(GIF, "image/gif", &limits).is_ok());
+    }
+}
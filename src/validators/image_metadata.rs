@@ -0,0 +1,298 @@
+//! Removal of the metadata of the JPEG, PNG and WebP images, enabled with the `deep` feature.
+//!
+//! The metadata of a photo can tell where and when it was taken and with which device, it is
+//! removed before storing an upload. The pixels and the color profile are kept as is.
+
+use std::fs;
+use std::path::Path;
+
+use super::byte_reader::Reader;
+use super::image_header::{self, ImageError, ImageLimits, PNG_SIGNATURE};
+use super::ValidationError;
+
+/// Kind of metadata removed by [`strip_metadata`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetadataKind {
+    /// EXIF data, with the GPS location, the date and the device
+    Exif,
+    /// XMP packet
+    Xmp,
+    /// IPTC data of Photoshop
+    Iptc,
+    /// Textual comments and annotations
+    Text,
+    /// Other application data, e.g. the modification time of a PNG image
+    Other,
+}
+
+/// Metadata removed from an image.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metadata {
+    /// Kind of the metadata
+    pub kind: MetadataKind,
+    /// Contents of the JPEG segment or of the PNG or WebP chunk, without its header
+    pub data: Vec<u8>,
+}
+
+/// Image without its metadata, returned by [`extract_metadata`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrippedImage {
+    /// Contents of the image without its metadata
+    pub contents: Vec<u8>,
+    /// Metadata removed from the image, in the order of the file
+    pub metadata: Vec<Metadata>,
+}
+
+/// Remove the EXIF (GPS location included), XMP, IPTC and textual metadata of a JPEG, PNG or
+/// WebP image, see [`extract_metadata`] to audit them.
+///
+/// The image is checked as in deep mode and any data after its end is removed too. The
+/// orientation stored in the EXIF data is lost.
+///
+/// # Errors
+/// If the image is malformed or of another format.
+///
+/// # Examples
+/// ``` ignore
+/// let contents = strip_metadata(&upload.bytes)?;
+/// std::fs::write(storage_path, contents)?;
+/// ```
+pub fn strip_metadata(contents: &[u8]) -> Result<Vec<u8>, ImageError> {
+    extract_metadata(contents).map(|image| image.contents)
+}
+
+/// Remove the metadata of an image file, see [`strip_metadata`]. The file is left unchanged.
+///
+/// # Errors
+/// If the file could not be read, the error of the file. If the image is malformed or of
+/// another format, [`ValidationError::Contents`] wrapping the [`ImageError`].
+pub fn strip_file_metadata<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, ValidationError> {
+    strip_metadata(&fs::read(path)?).map_err(ValidationError::contents)
+}
+
+/// Remove the metadata of a JPEG, PNG or WebP image and return them, e.g. to log what was
+/// removed, see [`strip_metadata`].
+///
+/// # Errors
+/// If the image is malformed or of another format.
+pub fn extract_metadata(contents: &[u8]) -> Result<StrippedImage, ImageError> {
+    let mime_type = infer::get(contents).map_or("unknown", |kind| kind.mime_type());
+    let image = match mime_type {
+        "image/jpeg" | "image/png" | "image/webp" => image_header::parse_image(contents, mime_type, &ImageLimits::default())?,
+        _ => return Err(ImageError::Unsupported(mime_type.to_string())),
+    };
+    // The image is valid, its structure can be walked without further checks
+    let contents = &contents[..image.length];
+
+    let mut stripped = StrippedImage { contents: Vec::with_capacity(contents.len()), metadata: Vec::new() };
+    match mime_type {
+        "image/jpeg" => strip_jpeg(contents, &mut stripped)?,
+        "image/png" => strip_png(contents, &mut stripped)?,
+        _ => strip_webp(contents, &mut stripped)?,
+    }
+    Ok(stripped)
+}
+
+const EXIF_IDENTIFIER: &[u8] = b"Exif\0";
+const XMP_IDENTIFIER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+const EXTENDED_XMP_IDENTIFIER: &[u8] = b"http://ns.adobe.com/xmp/extension/\0";
+
+/// Remove the application segments but the JFIF header, the color profile and the Adobe color
+/// transform, and the comments of a JPEG image.
+fn strip_jpeg(bytes: &[u8], stripped: &mut StrippedImage) -> Result<(), ImageError> {
+    let mut reader = Reader::new(bytes);
+    stripped.contents.extend_from_slice(reader.take(2)?);
+    loop {
+        let start = reader.position;
+        // Markers can be preceded by fill bytes
+        reader.skip(1)?;
+        let mut marker = reader.u8()?;
+        while marker == 0xFF {
+            marker = reader.u8()?;
+        }
+        if matches!(marker, 0x01 | 0xD0..=0xD7) {
+            stripped.contents.extend_from_slice(&bytes[start..reader.position]);
+            continue;
+        }
+        // The scan and the rest of the image are kept
+        if matches!(marker, 0xD9 | 0xDA) {
+            stripped.contents.extend_from_slice(&bytes[start..]);
+            return Ok(());
+        }
+
+        let length = reader.be_u16()? as usize;
+        let data = reader.take(length - 2)?;
+        let kind = match marker {
+            0xE1 if data.starts_with(EXIF_IDENTIFIER) => Some(MetadataKind::Exif),
+            0xE1 if data.starts_with(XMP_IDENTIFIER) || data.starts_with(EXTENDED_XMP_IDENTIFIER) => Some(MetadataKind::Xmp),
+            0xED => Some(MetadataKind::Iptc),
+            0xFE => Some(MetadataKind::Text),
+            0xE2 if data.starts_with(b"ICC_PROFILE\0") => None,
+            0xEE if data.starts_with(b"Adobe") => None,
+            0xE1..=0xEF => Some(MetadataKind::Other),
+            _ => None,
+        };
+        match kind {
+            Some(kind) => stripped.metadata.push(Metadata { kind, data: data.to_vec() }),
+            None => stripped.contents.extend_from_slice(&bytes[start..reader.position]),
+        }
+    }
+}
+
+/// Remove the EXIF, textual and time chunks of a PNG image.
+fn strip_png(bytes: &[u8], stripped: &mut StrippedImage) -> Result<(), ImageError> {
+    let mut reader = Reader::new(bytes);
+    stripped.contents.extend_from_slice(reader.take(PNG_SIGNATURE.len())?);
+    while reader.remaining() > 0 {
+        let start = reader.position;
+        let length = reader.be_u32()? as usize;
+        let kind = reader.take(4)?;
+        let data = reader.take(length)?;
+        reader.skip(4)?;
+
+        let metadata = match kind {
+            b"eXIf" => Some(MetadataKind::Exif),
+            b"iTXt" if data.starts_with(b"XML:com.adobe.xmp\0") => Some(MetadataKind::Xmp),
+            b"tEXt" | b"zTXt" | b"iTXt" => Some(MetadataKind::Text),
+            b"tIME" => Some(MetadataKind::Other),
+            _ => None,
+        };
+        match metadata {
+            Some(kind) => stripped.metadata.push(Metadata { kind, data: data.to_vec() }),
+            None => stripped.contents.extend_from_slice(&bytes[start..reader.position]),
+        }
+    }
+    Ok(())
+}
+
+/// Remove the EXIF and XMP chunks of a WebP image, and their flags in its extended header.
+fn strip_webp(bytes: &[u8], stripped: &mut StrippedImage) -> Result<(), ImageError> {
+    let mut reader = Reader::new(bytes);
+    // The RIFF size is set once the chunks are removed
+    reader.skip(8)?;
+    stripped.contents.extend_from_slice(b"RIFF\0\0\0\0");
+    stripped.contents.extend_from_slice(reader.take(4)?);
+    while reader.remaining() > 0 {
+        let start = reader.position;
+        let kind = reader.take(4)?;
+        let length = reader.le_u32()? as usize;
+        let data = reader.take(length)?;
+        // The chunks are padded to an even length
+        let padding = (length % 2).min(reader.remaining());
+        reader.skip(padding)?;
+
+        match kind {
+            b"EXIF" => stripped.metadata.push(Metadata { kind: MetadataKind::Exif, data: data.to_vec() }),
+            b"XMP " => stripped.metadata.push(Metadata { kind: MetadataKind::Xmp, data: data.to_vec() }),
+            b"VP8X" => {
+                let flags = stripped.contents.len() + 8;
+                stripped.contents.extend_from_slice(&bytes[start..reader.position]);
+                // Flags of the EXIF and XMP chunks
+                stripped.contents[flags] &= !0x0C;
+            }
+            _ => stripped.contents.extend_from_slice(&bytes[start..reader.position]),
+        }
+    }
+
+    let riff_size = (stripped.contents.len() - 8) as u32;
+    stripped.contents[4..8].copy_from_slice(&riff_size.to_le_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{extract_metadata, strip_file_metadata, strip_metadata, Metadata, MetadataKind};
+    use crate::{ImageError, ValidationError};
+
+    const TEST_DIR: &str = "test_files";
+
+    fn jpeg_segment(marker: u8, data: &[u8]) -> Vec<u8> {
+        [&[0xFF, marker][..], &(data.len() as u16 + 2).to_be_bytes(), data].concat()
+    }
+
+    /// JPEG image of 1x1 pixel with the given segments before its frame.
+    fn jpeg(segments: &[Vec<u8>]) -> Vec<u8> {
+        let frame = jpeg_segment(0xC0, b"\x08\x00\x01\x00\x01\x01\x01\x11\x00");
+        let scan = [jpeg_segment(0xDA, b"\x01\x01\x00\x00\x3f\x00"), b"\x12\x34\xff\x00\xff\xd9".to_vec()].concat();
+        [b"\xff\xd8".to_vec(), segments.concat(), frame, scan].concat()
+    }
+
+    fn png_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let body = [kind, data].concat();
+        [&(data.len() as u32).to_be_bytes()[..], &body, &crate::validators::image_header::crc32(&body).to_be_bytes()].concat()
+    }
+
+    fn png(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let header = png_chunk(b"IHDR", b"\x00\x00\x00\x01\x00\x00\x00\x01\x08\x02\x00\x00\x00");
+        let end = [png_chunk(b"IDAT", b"data"), png_chunk(b"IEND", b"")].concat();
+        [b"\x89PNG\r\n\x1a\n".to_vec(), header, chunks.concat(), end].concat()
+    }
+
+    fn webp_chunk(kind: &[u8], data: &[u8]) -> Vec<u8> {
+        let padding: &[u8] = if data.len() % 2 == 1 { b"\0" } else { b"" };
+        [kind, &(data.len() as u32).to_le_bytes(), data, padding].concat()
+    }
+
+    fn webp(chunks: &[Vec<u8>]) -> Vec<u8> {
+        let chunks = chunks.concat();
+        [b"RIFF", &(chunks.len() as u32 + 4).to_le_bytes()[..], b"WEBP", &chunks].concat()
+    }
+
+    #[test]
+    fn jpeg_metadata() {
+        let exif = b"Exif\0\0MM\0*GPS";
+        let xmp = b"http://ns.adobe.com/xap/1.0/\0<x:xmpmeta/>";
+        let jfif = jpeg_segment(0xE0, b"JFIF\0\x01\x01\0\0\x01\0\x01\0\0");
+        let icc = jpeg_segment(0xE2, b"ICC_PROFILE\0\x01\x01profile");
+        let image = jpeg(&[jfif.clone(), jpeg_segment(0xE1, exif), icc.clone(), jpeg_segment(0xE1, xmp), jpeg_segment(0xFE, b"comment")]);
+
+        let stripped = extract_metadata(&image).unwrap();
+        assert_eq!(stripped.contents, jpeg(&[jfif, icc]));
+        let kinds: Vec<MetadataKind> = stripped.metadata.iter().map(|metadata| metadata.kind).collect();
+        assert_eq!(kinds, [MetadataKind::Exif, MetadataKind::Xmp, MetadataKind::Text]);
+        assert_eq!(stripped.metadata[0], Metadata { kind: MetadataKind::Exif, data: exif.to_vec() });
+
+        // trailing data is removed too
+        let appended = [&image[..], b"<?php ?>"].concat();
+        assert_eq!(strip_metadata(&appended).unwrap(), stripped.contents);
+
+        let photo = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        let stripped = strip_file_metadata(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        assert!(stripped.len() <= photo.len());
+        assert_eq!(strip_metadata(&stripped).unwrap(), stripped);
+    }
+
+    #[test]
+    fn png_metadata() {
+        let image = png(&[png_chunk(b"eXIf", b"MM\0*"), png_chunk(b"tEXt", b"Author\0me"), png_chunk(b"iTXt", b"XML:com.adobe.xmp\0\0\0\0\0<x/>")]);
+        let stripped = extract_metadata(&image).unwrap();
+        assert_eq!(stripped.contents, png(&[]));
+        let kinds: Vec<MetadataKind> = stripped.metadata.iter().map(|metadata| metadata.kind).collect();
+        assert_eq!(kinds, [MetadataKind::Exif, MetadataKind::Text, MetadataKind::Xmp]);
+
+        let photo = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        let stripped = strip_metadata(&photo).unwrap();
+        assert_eq!(crate::scan_polyglot(&stripped).trailing_data, None);
+        assert_eq!(strip_metadata(&stripped).unwrap(), stripped);
+    }
+
+    #[test]
+    fn webp_metadata() {
+        let header = |flags: u8| webp_chunk(b"VP8X", &[flags, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        let frame = webp_chunk(b"VP8L", b"\x2f\x00\x00\x00\x00");
+        let image = webp(&[header(0x0C), frame.clone(), webp_chunk(b"EXIF", b"MM\0*GPS"), webp_chunk(b"XMP ", b"<x/>")]);
+
+        let stripped = extract_metadata(&image).unwrap();
+        assert_eq!(stripped.contents, webp(&[header(0), frame]));
+        assert_eq!(stripped.metadata[0], Metadata { kind: MetadataKind::Exif, data: b"MM\0*GPS".to_vec() });
+        assert_eq!(stripped.metadata[1].kind, MetadataKind::Xmp);
+    }
+
+    #[test]
+    fn unsupported_images() {
+        assert_eq!(strip_metadata(b"GIF89a").unwrap_err(), ImageError::Unsupported(String::from("image/gif")));
+        assert_eq!(strip_metadata(b"text").unwrap_err(), ImageError::Unsupported(String::from("unknown")));
+        assert_eq!(strip_metadata(b"\xff\xd8\xff\xe0\x00").unwrap_err().to_string(), "Invalid image: truncated file.");
+        assert!(matches!(strip_file_metadata("Cargo.toml"), Err(ValidationError::Contents(_))));
+    }
+}
@@ -0,0 +1,289 @@
+//! Decoder of raw DEFLATE streams (RFC 1951), used to inspect the entries of the ZIP archives.
+//!
+//! The output is bounded, so that a decompression bomb fails as soon as it exceeds the size
+//! declared by its archive instead of exhausting the memory.
+
+/// The stream is malformed or its output exceeds the maximum, for the given reason.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct InflateError(pub(crate) &'static str);
+
+/// Base lengths of the length symbols 257 to 285, and their extra bits.
+const LENGTH_BASES: [u16; 29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA_BITS: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+
+/// Base distances of the distance symbols 0 to 29, and their extra bits.
+const DISTANCE_BASES: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193,
+    12289, 16385, 24577,
+];
+const DISTANCE_EXTRA_BITS: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+
+/// Order of the code lengths of the code length alphabet in a dynamic block.
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+const MAX_BITS: usize = 15;
+
+/// Reader of the bits of a stream, least significant bit first.
+struct Bits<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    buffer: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Bits { bytes, position: 0, buffer: 0, count: 0 }
+    }
+
+    fn bits(&mut self, count: u32) -> Result<u32, InflateError> {
+        while self.count < count {
+            let byte = *self.bytes.get(self.position).ok_or(InflateError("truncated stream"))?;
+            self.position += 1;
+            self.buffer |= (byte as u32) << self.count;
+            self.count += 8;
+        }
+        let value = self.buffer & ((1u32 << count) - 1);
+        self.buffer = self.buffer.checked_shr(count).unwrap_or(0);
+        self.count -= count;
+        Ok(value)
+    }
+
+    /// Drop the bits left in the current byte.
+    fn align(&mut self) {
+        self.buffer = 0;
+        self.count = 0;
+    }
+
+    fn take(&mut self, length: usize) -> Result<&'a [u8], InflateError> {
+        let end = self.position.checked_add(length).filter(|&end| end <= self.bytes.len()).ok_or(InflateError("truncated stream"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+}
+
+/// Canonical Huffman code: the number of codes of each length and the symbols by code.
+struct Huffman {
+    counts: [u16; MAX_BITS + 1],
+    symbols: Vec<u16>,
+    /// Whether every code of the lengths is used
+    complete: bool,
+}
+
+impl Huffman {
+    /// Build the code from the code length of each symbol, 0 for the unused ones.
+    fn new(lengths: &[u8]) -> Result<Self, InflateError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &length in lengths {
+            counts[length as usize] += 1;
+        }
+
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(InflateError("over-subscribed code"));
+            }
+        }
+        let used = lengths.len() - counts[0] as usize;
+
+        let mut offsets = [0u16; MAX_BITS + 1];
+        for length in 1..MAX_BITS {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = vec![0; used];
+        for (symbol, &length) in lengths.iter().enumerate() {
+            if length != 0 {
+                symbols[offsets[length as usize] as usize] = symbol as u16;
+                offsets[length as usize] += 1;
+            }
+        }
+        Ok(Huffman { counts, symbols, complete: left == 0 })
+    }
+
+    /// Reject an incomplete code, unless it has a single symbol.
+    fn complete_or_single(self) -> Result<Self, InflateError> {
+        if self.complete || self.symbols.len() <= 1 {
+            Ok(self)
+        } else {
+            Err(InflateError("incomplete code"))
+        }
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Result<u16, InflateError> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(InflateError("invalid code"))
+    }
+}
+
+/// Decompress a raw DEFLATE stream.
+///
+/// # Errors
+/// If the stream is malformed or truncated, or its output exceeds `max_length` bytes.
+pub(crate) fn inflate(bytes: &[u8], max_length: usize) -> Result<Vec<u8>, InflateError> {
+    let mut bits = Bits::new(bytes);
+    let mut output = Vec::new();
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => stored_block(&mut bits, &mut output)?,
+            1 => {
+                let (lengths, distances) = fixed_codes()?;
+                compressed_block(&mut bits, &mut output, &lengths, &distances, max_length)?;
+            }
+            2 => {
+                let (lengths, distances) = dynamic_codes(&mut bits)?;
+                compressed_block(&mut bits, &mut output, &lengths, &distances, max_length)?;
+            }
+            _ => return Err(InflateError("invalid block type")),
+        }
+        if output.len() > max_length {
+            return Err(InflateError("output too large"));
+        }
+        if last {
+            return Ok(output);
+        }
+    }
+}
+
+fn stored_block(bits: &mut Bits, output: &mut Vec<u8>) -> Result<(), InflateError> {
+    bits.align();
+    let header = bits.take(4)?;
+    let length = u16::from_le_bytes([header[0], header[1]]);
+    if length != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(InflateError("bad stored block length"));
+    }
+    output.extend_from_slice(bits.take(length as usize)?);
+    Ok(())
+}
+
+fn fixed_codes() -> Result<(Huffman, Huffman), InflateError> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    // The fixed distance code is incomplete, its codes 30 and 31 being unused
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5; 30])?))
+}
+
+fn dynamic_codes(bits: &mut Bits) -> Result<(Huffman, Huffman), InflateError> {
+    let length_count = bits.bits(5)? as usize + 257;
+    let distance_count = bits.bits(5)? as usize + 1;
+    let code_length_count = bits.bits(4)? as usize + 4;
+    if length_count > 286 || distance_count > 30 {
+        return Err(InflateError("too many codes"));
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..code_length_count] {
+        code_lengths[index] = bits.bits(3)? as u8;
+    }
+    let code_length_code = Huffman::new(&code_lengths)?;
+    if !code_length_code.complete {
+        return Err(InflateError("incomplete code"));
+    }
+
+    let mut lengths = vec![0u8; length_count + distance_count];
+    let mut index = 0;
+    while index < lengths.len() {
+        let symbol = code_length_code.decode(bits)?;
+        let (length, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let previous = *lengths[..index].last().ok_or(InflateError("repeat without length"))?;
+                (previous, 3 + bits.bits(2)? as usize)
+            }
+            17 => (0, 3 + bits.bits(3)? as usize),
+            _ => (0, 11 + bits.bits(7)? as usize),
+        };
+        if index + repeat > lengths.len() {
+            return Err(InflateError("too many lengths"));
+        }
+        lengths[index..index + repeat].fill(length);
+        index += repeat;
+    }
+    if lengths[256] == 0 {
+        return Err(InflateError("no end of block code"));
+    }
+
+    let length_code = Huffman::new(&lengths[..length_count])?.complete_or_single()?;
+    let distance_code = Huffman::new(&lengths[length_count..])?.complete_or_single()?;
+    Ok((length_code, distance_code))
+}
+
+fn compressed_block(bits: &mut Bits, output: &mut Vec<u8>, lengths: &Huffman, distances: &Huffman, max_length: usize) -> Result<(), InflateError> {
+    loop {
+        let symbol = lengths.decode(bits)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let symbol = symbol - 257;
+                if symbol >= LENGTH_BASES.len() {
+                    return Err(InflateError("invalid length symbol"));
+                }
+                let length = LENGTH_BASES[symbol] as usize + bits.bits(LENGTH_EXTRA_BITS[symbol] as u32)? as usize;
+
+                let symbol = distances.decode(bits)? as usize;
+                if symbol >= DISTANCE_BASES.len() {
+                    return Err(InflateError("invalid distance symbol"));
+                }
+                let distance = DISTANCE_BASES[symbol] as usize + bits.bits(DISTANCE_EXTRA_BITS[symbol] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(InflateError("distance too far back"));
+                }
+                let start = output.len() - distance;
+                for i in 0..length {
+                    output.push(output[start + i]);
+                }
+            }
+        }
+        if output.len() > max_length {
+            return Err(InflateError("output too large"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{inflate, InflateError};
+
+    #[test]
+    fn inflate_blocks() {
+        // stored block
+        assert_eq!(inflate(b"\x01\x03\x00\xfc\xffabc", 10).unwrap(), b"abc");
+        // fixed codes
+        assert_eq!(inflate(b"\x4b\x4c\x4a\x06\x00", 10).unwrap(), b"abc");
+        assert_eq!(inflate(b"\x03\x00", 10).unwrap(), b"");
+        // fixed codes with a back reference
+        assert_eq!(inflate(b"\x4b\x4c\x4a\x4e\x04\x23\x00", 100).unwrap(), b"abcabcabc");
+
+        // dynamic codes
+        let text: Vec<u8> = (0..20).flat_map(|i| format!("The {} quick brown foxes jump over the {} lazy dogs. ", i, i * 7).into_bytes()).collect();
+        let compressed = b"\x8d\xd2\xc9\x11\xc2\x40\x0c\x44\xd1\x54\x14\x01\xe5\x9e\xb1\x67\xc9\xc3\x09\xb0\x98\x1d\x06\x6c\xcc\x16\x3d\x14\x37\xa4\x43\xfb\xaa\xd2\x3f\xf5\x6b\xb7\x9d\x54\x72\x1d\x77\xcb\x83\x2c\xfa\xf2\x38\xcb\xba\x3c\xbb\x41\xf6\xe3\xe9\x22\xe5\xde\xf5\x72\xfb\x7d\x1c\xe7\xef\x97\xac\xca\x66\x98\x49\xfb\x3d\x80\x26\x51\x27\x8e\x26\xa8\x75\xe3\x69\xe3\xa0\x9b\x9a\x37\x49\x37\x0d\x6d\x7c\xa3\x9b\x40\x9b\xda\xe9\x26\xf2\x26\xeb\x26\xd1\xa6\x09\xba\xc9\xb4\x09\xde\x4c\xca\x19\x44\xeb\x60\x02\x04\x23\x01\x9c\x42\x32\x14\xc0\x2d\x64\x63\x01\x1c\x43\x36\x18\xc0\x35\xa0\x32\x1c\xc0\x3d\x00\x06\x04\xe2\x84\xca\x90\x00\x37\x01\x67\x50\x80\xab\x80\xff\x63\xf1\x01";
+        assert_eq!(inflate(compressed, text.len()).unwrap(), text);
+        assert_eq!(inflate(compressed, text.len() - 1), Err(InflateError("output too large")));
+        assert_eq!(inflate(&compressed[..100], text.len()), Err(InflateError("truncated stream")));
+    }
+
+    #[test]
+    fn invalid_streams() {
+        assert_eq!(inflate(b"", 10), Err(InflateError("truncated stream")));
+        assert_eq!(inflate(b"\x07", 10), Err(InflateError("invalid block type")));
+        assert_eq!(inflate(b"\x01\x03\x00\xfc\xfeabc", 10), Err(InflateError("bad stored block length")));
+        assert_eq!(inflate(b"\x01\x03\x00\xfc\xffab", 10), Err(InflateError("truncated stream")));
+        assert_eq!(inflate(b"\x4b\x4c\x4a\x06\x00", 2), Err(InflateError("output too large")));
+    }
+}
@@ -0,0 +1,78 @@
+use std::fs::File;
+use std::io::{Error, ErrorKind};
+use std::ops::Deref;
+use std::os::unix::io::AsRawFd;
+
+/// Read-only private mapping of a whole file, unmapped when dropped.
+pub(crate) struct Mmap {
+    pointer: *mut libc::c_void,
+    length: usize,
+}
+
+impl Mmap {
+    /// Map a regular file in memory.
+    ///
+    /// # Safety
+    /// The file must be neither truncated nor written while mapped, e.g. by another process:
+    /// reading a truncated page raises `SIGBUS`, and a written page changes the contents
+    /// borrowed from the mapping.
+    pub(crate) unsafe fn map(file: &File) -> Result<Mmap, Error> {
+        let length = usize::try_from(file.metadata()?.len()).map_err(|_| Error::new(ErrorKind::InvalidInput, "The file is too large to be mapped."))?;
+        // An empty mapping is invalid
+        if length == 0 {
+            return Ok(Mmap { pointer: std::ptr::null_mut(), length });
+        }
+
+        // SAFETY: a new mapping of a valid descriptor, checked below
+        let pointer = unsafe { libc::mmap(std::ptr::null_mut(), length, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0) };
+        if pointer == libc::MAP_FAILED {
+            return Err(Error::last_os_error());
+        }
+        Ok(Mmap { pointer, length })
+    }
+}
+
+impl Deref for Mmap {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        if self.length == 0 {
+            return &[];
+        }
+        // SAFETY: the mapping is readable and lives as long as self, its file is unchanged per
+        // the contract of map
+        unsafe { std::slice::from_raw_parts(self.pointer as *const u8, self.length) }
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        if self.length != 0 {
+            // SAFETY: the mapping was created by map and is not used anymore
+            unsafe { libc::munmap(self.pointer, self.length) };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, File};
+
+    use super::Mmap;
+
+    const TEST_DIR: &str = "test_files";
+
+    #[test]
+    fn mapped_files() {
+        let path = format!("{}/valid_image.png", TEST_DIR);
+        // SAFETY: the test file is not modified
+        let map = unsafe { Mmap::map(&File::open(&path).unwrap()) }.unwrap();
+        assert_eq!(&map[..], fs::read(&path).unwrap());
+
+        let empty = std::env::temp_dir().join(format!("mmap_empty_{}", std::process::id()));
+        // SAFETY: the file is removed, not truncated, while mapped
+        let map = unsafe { Mmap::map(&File::create(&empty).unwrap()) }.unwrap();
+        fs::remove_file(&empty).unwrap();
+        assert!(map.is_empty());
+    }
+}
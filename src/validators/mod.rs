@@ -1,7 +1,175 @@
+#[cfg(feature = "deep")]
+mod archive;
+#[cfg(feature = "banking")]
+pub mod banking;
+#[cfg(all(feature = "std", any(feature = "url", feature = "uuid")))]
+mod batch;
+#[cfg(feature = "file")]
+mod blake3;
+#[cfg(feature = "deep")]
+mod byte_reader;
+#[cfg(feature = "clap")]
+mod cli;
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+mod combinator;
+#[cfg(all(feature = "url", feature = "std"))]
+mod confusable;
+#[cfg(feature = "file")]
+mod content_type;
+#[cfg(all(feature = "url", feature = "file", feature = "uuid"))]
+mod dedup;
+#[cfg(feature = "deep")]
+mod document;
+mod error;
+#[cfg(feature = "url")]
+mod grammar;
+#[cfg(feature = "deep")]
+mod image_header;
+#[cfg(feature = "deep")]
+mod image_metadata;
+#[cfg(feature = "deep")]
+mod inflate;
+#[cfg(all(feature = "mmap", unix))]
+mod mmap;
+#[cfg(all(feature = "file", feature = "uuid"))]
+mod namespace;
+#[cfg(feature = "psl")]
+mod public_suffix;
+#[cfg(feature = "deep")]
+mod payload;
+#[cfg(feature = "deep")]
+mod polyglot;
+#[cfg(all(feature = "url", feature = "std"))]
+mod punycode;
+#[cfg(feature = "file")]
+mod quarantine;
+#[cfg(all(feature = "url", feature = "std"))]
+mod regex_strategy;
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+mod report;
+#[cfg(feature = "rocket")]
+mod rocket_guards;
+#[cfg(feature = "file")]
+mod scanner;
+#[cfg(feature = "serde")]
+mod serde_support;
+#[cfg(feature = "file")]
+mod sha256;
+#[cfg(all(feature = "uuid", feature = "std"))]
+mod short_uuid;
+#[cfg(feature = "iana")]
+mod tld_list;
+#[cfg(all(feature = "url", feature = "std"))]
+mod tld_whitelist;
+#[cfg(feature = "file")]
+mod validate_dir;
+#[cfg(feature = "file")]
 mod validate_file;
+#[cfg(all(feature = "url", feature = "std"))]
+mod validate_mailto;
+#[cfg(feature = "file")]
+mod validate_path;
+#[cfg(feature = "file")]
+mod validate_svg;
+#[cfg(all(feature = "url", feature = "std"))]
+mod validate_tel;
+#[cfg(feature = "template")]
+mod validate_template;
+#[cfg(all(feature = "url", feature = "std"))]
 mod validate_url;
+#[cfg(all(feature = "url", any(not(feature = "std"), test)))]
+mod validate_url_no_std;
+#[cfg(feature = "uuid")]
 mod validate_uuid;
+#[cfg(feature = "std")]
+mod validated;
+#[cfg(feature = "deep")]
+mod video_container;
+#[cfg(feature = "wasm")]
+mod wasm;
+#[cfg(any(feature = "axum", feature = "actix"))]
+mod web;
 
+#[cfg(feature = "deep")]
+pub use archive::{ArchiveEntry, ArchiveError, ArchiveInfo, ArchiveLimit};
+#[cfg(all(feature = "std", any(feature = "url", feature = "uuid")))]
+pub use batch::*;
+#[cfg(feature = "file")]
+pub use blake3::blake3;
+#[cfg(feature = "clap")]
+pub use cli::ValidatedParser;
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+pub use combinator::*;
+#[cfg(all(feature = "url", feature = "std"))]
+pub use confusable::*;
+#[cfg(feature = "file")]
+pub use content_type::*;
+#[cfg(all(feature = "url", feature = "file", feature = "uuid"))]
+pub use dedup::*;
+#[cfg(feature = "deep")]
+pub use document::*;
+pub use error::{ValidationError, ValidationErrors};
+#[cfg(feature = "url")]
+pub use grammar::TopLevelCharset;
+#[cfg(feature = "deep")]
+pub use image_header::{ImageError, ImageFormat, ImageInfo, ImageLimit};
+#[cfg(feature = "deep")]
+pub use image_metadata::*;
+#[cfg(all(feature = "file", feature = "uuid"))]
+pub use namespace::*;
+#[cfg(feature = "deep")]
+pub use payload::*;
+#[cfg(feature = "deep")]
+pub use polyglot::*;
+#[cfg(feature = "psl")]
+pub use public_suffix::*;
+#[cfg(feature = "file")]
+pub use quarantine::*;
+#[cfg(all(feature = "url", feature = "std"))]
+pub use regex_strategy::RegexStrategy;
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+pub use report::*;
+#[cfg(feature = "rocket")]
+pub use rocket_guards::ValidatedUpload;
+#[cfg(feature = "file")]
+pub use scanner::*;
+#[cfg(feature = "file")]
+pub use sha256::sha256;
+#[cfg(all(feature = "uuid", feature = "std"))]
+pub use short_uuid::*;
+#[cfg(feature = "iana")]
+pub use tld_list::*;
+#[cfg(all(feature = "url", feature = "std"))]
+pub use tld_whitelist::*;
+#[cfg(feature = "file")]
+pub use validate_dir::*;
+#[cfg(feature = "file")]
 pub use validate_file::*;
+#[cfg(all(feature = "url", feature = "std"))]
+pub use validate_mailto::*;
+#[cfg(feature = "file")]
+pub use validate_path::*;
+#[cfg(feature = "file")]
+pub use validate_svg::*;
+#[cfg(all(feature = "url", feature = "std"))]
+pub use validate_tel::*;
+#[cfg(feature = "template")]
+pub use validate_template::*;
+#[cfg(all(feature = "url", feature = "std"))]
 pub use validate_url::*;
+#[cfg(all(feature = "url", not(feature = "std")))]
+pub use validate_url_no_std::*;
+#[cfg(feature = "uuid")]
 pub use validate_uuid::*;
+#[cfg(feature = "bench")]
+pub(crate) use validate_uuid::validate_uuid_regex;
+#[cfg(feature = "std")]
+pub use validated::*;
+#[cfg(feature = "derive")]
+pub(crate) use validated::validate_kind;
+#[cfg(feature = "deep")]
+pub use video_container::{VideoError, VideoFormat, VideoInfo};
+#[cfg(feature = "wasm")]
+pub use wasm::FileCheck;
+#[cfg(any(feature = "axum", feature = "actix"))]
+pub use web::*;
@@ -0,0 +1,120 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use uuid::Uuid;
+
+use super::{generate_file_uuid, generate_uuid_v5, validate_file_uuid, ValidationError};
+
+/// Namespaces of the version-5 uuids of an application, registered once under a key, e.g.
+/// `"uploads"` or `"users"`, instead of uuid constants scattered across the code.
+///
+/// # Examples
+/// ``` ignore
+/// let mut registry = NamespaceRegistry::new();
+/// registry.register("uploads", Uuid::parse_str("c7bb890c-a4a8-4d68-85b7-1e1cfe909249")?)?;
+///
+/// let uuid = registry.generate_file_uuid("uploads", "myDir/myImage.png")?;
+/// assert!(registry.validate_file_uuid_path("uploads", "myDir/myImage.png", &uuid)?);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NamespaceRegistry {
+    namespaces: HashMap<String, Uuid>,
+}
+
+impl NamespaceRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        NamespaceRegistry::default()
+    }
+
+    /// Register a namespace under a key.
+    ///
+    /// # Errors
+    /// If the key is empty or already registered, or the namespace is the nil uuid.
+    pub fn register(&mut self, key: &str, namespace: Uuid) -> Result<(), ValidationError> {
+        if key.is_empty() {
+            return Err(ValidationError::option("The namespace key is empty."));
+        }
+        if namespace.is_nil() {
+            return Err(ValidationError::option("Invalid namespace."));
+        }
+        if self.namespaces.contains_key(key) {
+            return Err(ValidationError::InvalidOption(format!("The namespace {} is already registered.", key)));
+        }
+        self.namespaces.insert(String::from(key), namespace);
+        Ok(())
+    }
+
+    /// Namespace registered under a key.
+    pub fn namespace(&self, key: &str) -> Option<&Uuid> {
+        self.namespaces.get(key)
+    }
+
+    /// Generate the version-5 uuid of a name in a registered namespace, see [`generate_uuid_v5`],
+    /// `None` if the key is not registered.
+    pub fn generate_uuid_v5(&self, key: &str, name: &[u8]) -> Option<Uuid> {
+        Some(generate_uuid_v5(self.namespace(key)?, name))
+    }
+
+    /// Generate the version-5 uuid of a file in a registered namespace, see [`generate_file_uuid`].
+    ///
+    /// # Errors
+    /// If the key is not registered ([`ValidationError::InvalidOption`]), or the file could not
+    /// be opened or read.
+    pub fn generate_file_uuid<P: AsRef<Path>>(&self, key: &str, path: P) -> Result<Uuid, ValidationError> {
+        generate_file_uuid(self.resolve(key)?, path)
+    }
+
+    /// Check that a version-5 uuid corresponds to a file in a registered namespace, see
+    /// [`validate_file_uuid`]. A key which is not registered is invalid.
+    pub fn validate_file_uuid(&self, key: &str, file: &[u8], uuid: &Uuid) -> bool {
+        self.namespace(key).is_some_and(|namespace| validate_file_uuid(namespace, file, uuid))
+    }
+
+    /// Check that a version-5 uuid corresponds to a file on disk in a registered namespace, see
+    /// [`crate::validate_file_uuid_path`].
+    ///
+    /// # Errors
+    /// If the key is not registered ([`ValidationError::InvalidOption`]), or the file could not
+    /// be opened or read.
+    pub fn validate_file_uuid_path<P: AsRef<Path>>(&self, key: &str, path: P, uuid: &Uuid) -> Result<bool, ValidationError> {
+        Ok(self.generate_file_uuid(key, path)? == *uuid)
+    }
+
+    fn resolve(&self, key: &str) -> Result<&Uuid, ValidationError> {
+        self.namespace(key).ok_or_else(|| ValidationError::InvalidOption(format!("Unknown namespace {}.", key)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{NamespaceRegistry, ValidationError};
+
+    #[test]
+    fn registered_namespaces() {
+        let uploads = Uuid::parse_str("c7bb890c-a4a8-4d68-85b7-1e1cfe909249").unwrap();
+        let mut registry = NamespaceRegistry::new();
+        registry.register("uploads", uploads).unwrap();
+        registry.register("users", Uuid::NAMESPACE_OID).unwrap();
+        assert_eq!(registry.register("uploads", Uuid::NAMESPACE_DNS).unwrap_err().to_string(), "The namespace uploads is already registered.");
+        assert!(registry.register("", Uuid::NAMESPACE_DNS).is_err());
+        assert!(registry.register("empty", Uuid::nil()).is_err());
+        assert_eq!(registry.namespace("uploads"), Some(&uploads));
+        assert_eq!(registry.namespace("unknown"), None);
+
+        assert_eq!(registry.generate_uuid_v5("users", b"42"), Some(Uuid::new_v5(&Uuid::NAMESPACE_OID, b"42")));
+        assert_eq!(registry.generate_uuid_v5("unknown", b"42"), None);
+        assert!(registry.validate_file_uuid("users", b"42", &Uuid::new_v5(&Uuid::NAMESPACE_OID, b"42")));
+        assert!(!registry.validate_file_uuid("uploads", b"42", &Uuid::new_v5(&Uuid::NAMESPACE_OID, b"42")));
+        assert!(!registry.validate_file_uuid("unknown", b"42", &Uuid::new_v5(&Uuid::NAMESPACE_OID, b"42")));
+
+        let path = "test_files/valid_image.png";
+        let uuid = registry.generate_file_uuid("uploads", path).unwrap();
+        assert_eq!(uuid, Uuid::new_v5(&uploads, &std::fs::read(path).unwrap()));
+        assert!(registry.validate_file_uuid_path("uploads", path, &uuid).unwrap());
+        assert!(!registry.validate_file_uuid_path("users", path, &uuid).unwrap());
+        assert!(matches!(registry.generate_file_uuid("unknown", path), Err(ValidationError::InvalidOption(_))));
+    }
+}
@@ -0,0 +1,278 @@
+//! Heuristics for the payloads hidden in media files, enabled with the `deep` feature.
+//!
+//! An executable smuggled inside an image, or a steganographic payload encrypted in the pixels
+//! of an uncompressed image, leaves the structure of the file intact. Such files are only
+//! suspected here, from the headers of executables and from regions of random looking bytes,
+//! and the findings are advisory.
+
+use std::fmt::{self, Display, Formatter};
+
+/// Length of the windows whose entropy is computed.
+pub const ENTROPY_WINDOW: usize = 4096;
+
+/// Entropy in bits per byte above which a window of an uncompressed file looks encrypted or
+/// compressed.
+pub const HIGH_ENTROPY: f64 = 7.5;
+
+/// Mime types of the formats storing their data uncompressed, whose entropy is meaningful.
+const UNCOMPRESSED_MIME_TYPES: &[&str] = &["image/bmp", "image/tiff", "image/vnd.adobe.photoshop", "image/vnd.microsoft.icon", "audio/x-wav", "audio/x-aiff"];
+
+/// CPU types of the Mach-O headers: x86, x86-64, ARM, ARM64 and PowerPC.
+const MACH_O_CPU_TYPES: [u32; 5] = [7, 0x0100_0007, 12, 0x0100_000C, 18];
+
+/// Format of an executable embedded in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExecutableKind {
+    /// Windows executable or library, a DOS header pointing to a PE header
+    Pe,
+    /// Linux executable or library
+    Elf,
+    /// macOS executable or library
+    MachO,
+}
+
+impl Display for ExecutableKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ExecutableKind::Pe => "PE executable",
+            ExecutableKind::Elf => "ELF executable",
+            ExecutableKind::MachO => "Mach-O executable",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Executable header found in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmbeddedExecutable {
+    /// Format of the executable
+    pub kind: ExecutableKind,
+    /// Offset of its header in the file
+    pub offset: usize,
+}
+
+/// Region of a file with a high entropy.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct EntropyRegion {
+    /// Offset of the region in the file
+    pub offset: usize,
+    /// Length of the region, a multiple of [`ENTROPY_WINDOW`] except at the end of the file
+    pub length: usize,
+    /// Highest entropy of the windows of the region, in bits per byte
+    pub entropy: f64,
+}
+
+/// Result of [`scan_payload`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PayloadReport {
+    /// Entropy of the whole file in bits per byte, from 0 for a single repeated byte to 8 for
+    /// random bytes
+    pub entropy: f64,
+    /// Regions of high entropy, only searched in the formats storing their data uncompressed
+    pub high_entropy: Vec<EntropyRegion>,
+    /// Executable headers, by offset
+    pub executables: Vec<EmbeddedExecutable>,
+}
+
+impl PayloadReport {
+    /// Whether the file probably hides a payload.
+    pub fn is_suspicious(&self) -> bool {
+        !self.high_entropy.is_empty() || !self.executables.is_empty()
+    }
+}
+
+impl Display for PayloadReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.is_suspicious() {
+            return write!(f, "No payload suspected.");
+        }
+        let mut findings: Vec<String> = self.executables.iter().map(|executable| format!("{} at {}", executable.kind, executable.offset)).collect();
+        for region in &self.high_entropy {
+            findings.push(format!("high entropy ({:.2}) at {}", region.entropy, region.offset));
+        }
+        write!(f, "Suspected payload: {}.", findings.join(", "))
+    }
+}
+
+/// Shannon entropy of bytes, in bits per byte.
+pub fn entropy(bytes: &[u8]) -> f64 {
+    let mut counts = [0usize; 256];
+    for &byte in bytes {
+        counts[byte as usize] += 1;
+    }
+    let length = bytes.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count > 0)
+        .map(|&count| {
+            let probability = count as f64 / length;
+            -probability * probability.log2()
+        })
+        .sum()
+}
+
+/// Format of the executable whose header starts at an offset, checked past its magic number to
+/// avoid matching random bytes.
+fn executable_at(contents: &[u8], offset: usize) -> Option<ExecutableKind> {
+    let header = &contents[offset..];
+    if header.starts_with(b"MZ") {
+        // The DOS header points to the PE header
+        let pe_offset = u32::from_le_bytes(header.get(0x3C..0x40)?.try_into().ok()?) as usize;
+        return (pe_offset >= 0x40 && header.get(pe_offset..pe_offset.checked_add(4)?)? == b"PE\0\0").then_some(ExecutableKind::Pe);
+    }
+    if header.starts_with(b"\x7fELF") {
+        // Class and byte order
+        let identification = header.get(4..7)?;
+        return (matches!(identification[0], 1 | 2) && matches!(identification[1], 1 | 2) && identification[2] == 1).then_some(ExecutableKind::Elf);
+    }
+
+    let magic = u32::from_be_bytes(header.get(..4)?.try_into().ok()?);
+    let cpu_type = header.get(4..8)?.try_into().ok()?;
+    let cpu_type = match magic {
+        0xFEED_FACE | 0xFEED_FACF => u32::from_be_bytes(cpu_type),
+        0xCEFA_EDFE | 0xCFFA_EDFE => u32::from_le_bytes(cpu_type),
+        _ => return None,
+    };
+    MACH_O_CPU_TYPES.contains(&cpu_type).then_some(ExecutableKind::MachO)
+}
+
+/// Scan a file for executables (PE, ELF and Mach-O) after its start and, for the formats storing
+/// their data uncompressed (BMP, TIFF, PSD, ICO, WAV and AIFF), for regions whose entropy is
+/// above [`HIGH_ENTROPY`], e.g. an encrypted payload hidden in the pixels of an image.
+///
+/// The compressed formats, e.g. JPEG or PNG images, look random by design and are only given
+/// their entropy. An executable at the start of the file is the format of the file itself, and
+/// is not reported.
+///
+/// # Examples
+/// ``` ignore
+/// let report = scan_payload(&upload.bytes);
+/// if report.is_suspicious() {
+///     println!("Upload quarantined: {}", report);
+/// }
+/// ```
+pub fn scan_payload(contents: &[u8]) -> PayloadReport {
+    let executables = (1..contents.len())
+        .filter(|&offset| matches!(contents[offset], b'M' | 0x7F | 0xFE | 0xCE | 0xCF))
+        .filter_map(|offset| Some(EmbeddedExecutable { kind: executable_at(contents, offset)?, offset }))
+        .collect();
+
+    let uncompressed = infer::get(contents).is_some_and(|kind| UNCOMPRESSED_MIME_TYPES.contains(&kind.mime_type()));
+    let mut high_entropy: Vec<EntropyRegion> = Vec::new();
+    if uncompressed {
+        for (index, window) in contents.chunks(ENTROPY_WINDOW).enumerate() {
+            // The entropy of a short window is not significant
+            let window_entropy = entropy(window);
+            if window.len() < ENTROPY_WINDOW / 4 || window_entropy <= HIGH_ENTROPY {
+                continue;
+            }
+            let offset = index * ENTROPY_WINDOW;
+            match high_entropy.last_mut() {
+                Some(region) if region.offset + region.length == offset => {
+                    region.length += window.len();
+                    region.entropy = region.entropy.max(window_entropy);
+                }
+                _ => high_entropy.push(EntropyRegion { offset, length: window.len(), entropy: window_entropy }),
+            }
+        }
+    }
+
+    PayloadReport { entropy: entropy(contents), high_entropy, executables }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{entropy, scan_payload, EmbeddedExecutable, ExecutableKind, ENTROPY_WINDOW};
+
+    const TEST_DIR: &str = "test_files";
+
+    /// Header of a 64x64 BMP image of 32 bits per pixel, followed by its pixels.
+    fn bmp(pixels: &[u8]) -> Vec<u8> {
+        let size = (54 + pixels.len() as u32).to_le_bytes();
+        let header = [
+            b"BM".as_slice(),
+            &size,
+            &[0; 4],
+            &54u32.to_le_bytes(),
+            &40u32.to_le_bytes(),
+            &64u32.to_le_bytes(),
+            &64u32.to_le_bytes(),
+            &1u16.to_le_bytes(),
+            &32u16.to_le_bytes(),
+            &[0; 24],
+        ]
+        .concat();
+        [&header, pixels].concat()
+    }
+
+    /// Pseudo-random bytes of a linear congruential generator.
+    fn random(length: usize) -> Vec<u8> {
+        let mut state = 0x2545_F491_4F6C_DD1Du64;
+        (0..length)
+            .map(|_| {
+                state = state.wrapping_mul(6_364_136_223_846_793_005).wrapping_add(1_442_695_040_888_963_407);
+                (state >> 56) as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn entropies() {
+        assert_eq!(entropy(b""), 0.0);
+        assert_eq!(entropy(b"aaaa"), 0.0);
+        assert_eq!(entropy(b"abab"), 1.0);
+        assert_eq!(entropy(&(0..=255).collect::<Vec<u8>>()), 8.0);
+        assert!(entropy(&random(ENTROPY_WINDOW)) > 7.9);
+    }
+
+    #[test]
+    fn embedded_executables() {
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        let report = scan_payload(&image);
+        assert!(!report.is_suspicious());
+        assert_eq!(report.to_string(), "No payload suspected.");
+
+        let mut pe = b"MZ".to_vec();
+        pe.resize(0x3C, 0);
+        pe.extend_from_slice(&0x80u32.to_le_bytes());
+        pe.resize(0x80, 0);
+        pe.extend_from_slice(b"PE\0\0");
+        let elf = b"\x7fELF\x02\x01\x01\x00";
+        let mach_o = b"\xcf\xfa\xed\xfe\x0c\x00\x00\x01";
+        let smuggled = [&image[..], &pe, elf, mach_o].concat();
+        let report = scan_payload(&smuggled);
+        let offset = image.len();
+        assert_eq!(
+            report.executables,
+            [
+                EmbeddedExecutable { kind: ExecutableKind::Pe, offset },
+                EmbeddedExecutable { kind: ExecutableKind::Elf, offset: offset + pe.len() },
+                EmbeddedExecutable { kind: ExecutableKind::MachO, offset: offset + pe.len() + elf.len() },
+            ]
+        );
+        assert_eq!(report.to_string(), format!("Suspected payload: PE executable at {}, ELF executable at {}, Mach-O executable at {}.", offset, offset + 0x84, offset + 0x8C));
+
+        // magic numbers alone are not executables
+        assert!(scan_payload(&[&image[..], b"MZ\x7fELF\xfe\xed\xfa\xce"].concat()).executables.is_empty());
+    }
+
+    #[test]
+    fn high_entropy_regions() {
+        // smooth gradient pixels
+        let pixels: Vec<u8> = (0..64 * 64 * 4).map(|index| (index / 64) as u8).collect();
+        let report = scan_payload(&bmp(&pixels));
+        assert!(!report.is_suspicious());
+
+        // encrypted data in the middle of the pixels
+        let mut stego = pixels.clone();
+        stego[ENTROPY_WINDOW - 54..3 * ENTROPY_WINDOW - 54].copy_from_slice(&random(2 * ENTROPY_WINDOW));
+        let report = scan_payload(&bmp(&stego));
+        assert!(report.is_suspicious());
+        assert_eq!(report.high_entropy.len(), 1);
+        assert_eq!((report.high_entropy[0].offset, report.high_entropy[0].length), (ENTROPY_WINDOW, 2 * ENTROPY_WINDOW));
+
+        // compressed formats look random by design
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert!(scan_payload(&image).high_entropy.is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+//! Detection of the files valid as several formats, enabled with the `deep` feature.
+//!
+//! A polyglot file passes the magic numbers check as an image while being read as an archive or
+//! a script by another program, e.g. a GIF image followed by a JAR archive, or a PHP payload
+//! appended after the end marker of a JPEG image.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::image_header::{self, ImageLimits};
+
+/// Format of content embedded in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmbeddedKind {
+    /// ZIP archive, JAR, APK and office documents included
+    Zip,
+    /// RAR archive
+    Rar,
+    /// 7z archive
+    SevenZip,
+    /// PDF document
+    Pdf,
+    /// ELF executable
+    Elf,
+    /// PHP code
+    Php,
+    /// HTML script
+    Script,
+}
+
+impl EmbeddedKind {
+    /// Signatures of the format, any of them being enough to be read by some program.
+    fn signatures(&self) -> &'static [&'static [u8]] {
+        match self {
+            // Local file header and end of central directory, read from the end of the file
+            EmbeddedKind::Zip => &[b"PK\x03\x04", b"PK\x05\x06"],
+            EmbeddedKind::Rar => &[b"Rar!\x1a\x07"],
+            EmbeddedKind::SevenZip => &[b"7z\xbc\xaf\x27\x1c"],
+            EmbeddedKind::Pdf => &[b"%PDF-"],
+            EmbeddedKind::Elf => &[b"\x7fELF"],
+            EmbeddedKind::Php => &[b"<?php"],
+            EmbeddedKind::Script => &[b"<script"],
+        }
+    }
+
+    /// Whether the signatures are not case sensitive.
+    fn ignores_case(&self) -> bool {
+        matches!(self, EmbeddedKind::Php | EmbeddedKind::Script)
+    }
+}
+
+impl Display for EmbeddedKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            EmbeddedKind::Zip => "ZIP archive",
+            EmbeddedKind::Rar => "RAR archive",
+            EmbeddedKind::SevenZip => "7z archive",
+            EmbeddedKind::Pdf => "PDF document",
+            EmbeddedKind::Elf => "ELF executable",
+            EmbeddedKind::Php => "PHP code",
+            EmbeddedKind::Script => "HTML script",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+const EMBEDDED_KINDS: [EmbeddedKind; 7] = [
+    EmbeddedKind::Zip,
+    EmbeddedKind::Rar,
+    EmbeddedKind::SevenZip,
+    EmbeddedKind::Pdf,
+    EmbeddedKind::Elf,
+    EmbeddedKind::Php,
+    EmbeddedKind::Script,
+];
+
+/// Content of another format found in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct EmbeddedContent {
+    /// Format of the content
+    pub kind: EmbeddedKind,
+    /// Offset of its first signature in the file
+    pub offset: usize,
+}
+
+/// Result of [`scan_polyglot`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PolyglotReport {
+    /// Offset of the data following the end marker of an image, `None` if the image ends the
+    /// file or the file is not an image with a known structure
+    pub trailing_data: Option<usize>,
+    /// Contents of other formats than the one of the file, by offset
+    pub embedded: Vec<EmbeddedContent>,
+}
+
+impl PolyglotReport {
+    /// Whether the file may be read as another format, or hides data after its image.
+    pub fn is_polyglot(&self) -> bool {
+        self.trailing_data.is_some() || !self.embedded.is_empty()
+    }
+}
+
+impl Display for PolyglotReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if !self.is_polyglot() {
+            return write!(f, "No embedded content.");
+        }
+        let mut findings: Vec<String> = self.embedded.iter().map(|content| format!("{} at {}", content.kind, content.offset)).collect();
+        if let Some(offset) = self.trailing_data {
+            findings.push(format!("data after the end of the image at {}", offset));
+        }
+        write!(f, "Embedded content: {}.", findings.join(", "))
+    }
+}
+
+/// Offset of the first occurrence of a signature in a file.
+fn find_signature(contents: &[u8], signature: &[u8], ignore_case: bool) -> Option<usize> {
+    contents.windows(signature.len()).position(|window| {
+        if ignore_case {
+            window.eq_ignore_ascii_case(signature)
+        } else {
+            window == signature
+        }
+    })
+}
+
+/// Scan a file for content of other formats and, for the images with a known structure (PNG,
+/// JPEG, GIF, BMP and WebP), for data after their end marker.
+///
+/// The format of the file itself, given by the signature at its start, is not reported, e.g.
+/// the local file headers of a ZIP archive.
+///
+/// # Examples
+/// ``` ignore
+/// let report = scan_polyglot(&upload.bytes);
+/// if report.is_polyglot() {
+///     println!("Rejected upload: {}", report);
+/// }
+/// ```
+pub fn scan_polyglot(contents: &[u8]) -> PolyglotReport {
+    let trailing_data = infer::get(contents)
+        .and_then(|kind| image_header::parse_image(contents, kind.mime_type(), &ImageLimits::default()).ok())
+        .map(|image| image.length)
+        .filter(|&length| length < contents.len());
+
+    let mut embedded: Vec<EmbeddedContent> = EMBEDDED_KINDS
+        .iter()
+        .filter_map(|&kind| {
+            let offset = kind
+                .signatures()
+                .iter()
+                .filter_map(|signature| find_signature(contents, signature, kind.ignores_case()))
+                .min()?;
+            (offset > 0).then_some(EmbeddedContent { kind, offset })
+        })
+        .collect();
+    embedded.sort_by_key(|content| content.offset);
+
+    PolyglotReport { trailing_data, embedded }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{scan_polyglot, EmbeddedContent, EmbeddedKind, PolyglotReport};
+
+    const TEST_DIR: &str = "test_files";
+
+    /// Smallest GIF image: a single pixel with a global color table of two colors.
+    const GIF: &[u8] = b"GIF89a\x01\x00\x01\x00\x80\x00\x00\x00\x00\x00\xff\xff\xff\x2c\x00\x00\x00\x00\x01\x00\x01\x00\x00\x02\x02\x44\x01\x00\x3b";
+
+    /// Empty ZIP archive: its end of central directory.
+    const ZIP: &[u8] = b"PK\x05\x06\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00";
+
+    #[test]
+    fn clean_files() {
+        for name in ["valid_image.png", "valid_image.jpg"] {
+            let image = std::fs::read(format!("{}/{}", TEST_DIR, name)).unwrap();
+            assert_eq!(scan_polyglot(&image), PolyglotReport::default());
+        }
+        assert!(!scan_polyglot(GIF).is_polyglot());
+        assert_eq!(scan_polyglot(GIF).to_string(), "No embedded content.");
+
+        // the format of the file itself is not embedded
+        assert!(!scan_polyglot(ZIP).is_polyglot());
+    }
+
+    #[test]
+    fn appended_content() {
+        let polyglot = [GIF, ZIP].concat();
+        let report = scan_polyglot(&polyglot);
+        assert_eq!(report.trailing_data, Some(GIF.len()));
+        assert_eq!(report.embedded, [EmbeddedContent { kind: EmbeddedKind::Zip, offset: GIF.len() }]);
+        assert_eq!(report.to_string(), "Embedded content: ZIP archive at 35, data after the end of the image at 35.");
+
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        let polyglot = [&image[..], b"<?PHP system($_GET['cmd']); ?>"].concat();
+        let report = scan_polyglot(&polyglot);
+        assert!(report.is_polyglot());
+        assert_eq!(report.trailing_data, Some(image.len()));
+        assert_eq!(report.embedded, [EmbeddedContent { kind: EmbeddedKind::Php, offset: image.len() }]);
+
+        // trailing data without known signature
+        assert_eq!(scan_polyglot(&[GIF, b"\x00\x01"].concat()).trailing_data, Some(GIF.len()));
+    }
+
+    #[test]
+    fn embedded_content() {
+        // script in the comment extension of a GIF image
+        let polyglot = [&GIF[..19], b"\x21\xfe\x12<script>x</script>\x00", &GIF[19..]].concat();
+        let report = scan_polyglot(&polyglot);
+        assert_eq!(report.trailing_data, None);
+        assert_eq!(report.embedded, [EmbeddedContent { kind: EmbeddedKind::Script, offset: 22 }]);
+
+        let report = scan_polyglot(b"%PDF-1.7 <?php ?> PK\x03\x04");
+        let kinds: Vec<EmbeddedKind> = report.embedded.iter().map(|content| content.kind).collect();
+        assert_eq!(kinds, [EmbeddedKind::Php, EmbeddedKind::Zip]);
+    }
+}
@@ -0,0 +1,172 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::punycode;
+use super::ValidationError;
+
+/// Rules of the [Mozilla Public Suffix List](https://publicsuffix.org), enabled with the `psl`
+/// feature.
+///
+/// The list is loaded at runtime from its official text format, so that it can be updated
+/// without a new release of the crate.
+///
+/// # Examples
+/// ``` ignore
+/// let list = PublicSuffixList::from_file("public_suffix_list.dat")?;
+/// assert_eq!(list.registrable_domain("www.test.co.uk"), Some(String::from("test.co.uk")));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PublicSuffixList {
+    rules: HashSet<String>,
+    wildcards: HashSet<String>,
+    exceptions: HashSet<String>,
+}
+
+impl PublicSuffixList {
+    /// Parse a list in the official format: one rule per line, `//` comments, `*.` wildcard
+    /// rules and `!` exception rules. Unicode rules are converted to punycode.
+    ///
+    /// # Errors
+    /// If a rule is invalid, an error telling the line number is returned.
+    pub fn parse(list: &str) -> Result<PublicSuffixList, ValidationError> {
+        let mut psl = PublicSuffixList::default();
+
+        for (index, line) in list.lines().enumerate() {
+            // Only the first word of a line is considered
+            let rule = match line.split_whitespace().next() {
+                None => continue,
+                Some(rule) if rule.starts_with("//") => continue,
+                Some(rule) => rule,
+            };
+
+            let error = || ValidationError::InvalidOption(format!("Invalid public suffix rule at line {}.", index + 1));
+            let (set, rule) = if let Some(rule) = rule.strip_prefix('!') {
+                (&mut psl.exceptions, rule)
+            } else if let Some(rule) = rule.strip_prefix("*.") {
+                (&mut psl.wildcards, rule)
+            } else {
+                (&mut psl.rules, rule)
+            };
+
+            let rule = punycode::host_to_ascii(rule).ok_or_else(error)?.to_lowercase();
+            if rule.split('.').any(|label| label.is_empty() || label.contains('*') || label.contains('!')) {
+                return Err(error());
+            }
+            set.insert(rule);
+        }
+
+        Ok(psl)
+    }
+
+    /// Read and parse a list from a file, see [`PublicSuffixList::parse`].
+    ///
+    /// # Errors
+    /// If the file could not be read or a rule is invalid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<PublicSuffixList, ValidationError> {
+        let list = std::fs::read_to_string(path)?;
+        PublicSuffixList::parse(&list)
+    }
+
+    /// The public suffix of an ascii host, e.g. `co.uk` for `www.test.co.uk`. Returns `None` if
+    /// no rule of the list matches (the implicit `*` rule is not applied) or a label is empty.
+    pub fn public_suffix(&self, host: &str) -> Option<String> {
+        let host = host.to_lowercase();
+        let labels: Vec<&str> = host.split('.').collect();
+        if labels.iter().any(|label| label.is_empty()) {
+            return None;
+        }
+
+        // Number of labels of the prevailing rule, the longest one unless an exception matches
+        let mut suffix_labels = 0;
+        for start in 0..labels.len() {
+            let suffix = labels[start..].join(".");
+            let count = labels.len() - start;
+
+            if self.exceptions.contains(&suffix) {
+                suffix_labels = count - 1;
+                break;
+            }
+
+            if self.rules.contains(&suffix) {
+                suffix_labels = suffix_labels.max(count);
+            }
+
+            if start + 1 < labels.len() && self.wildcards.contains(&labels[start + 1..].join(".")) {
+                suffix_labels = suffix_labels.max(count);
+            }
+        }
+
+        if suffix_labels == 0 {
+            return None;
+        }
+        Some(labels[labels.len() - suffix_labels..].join("."))
+    }
+
+    /// The registrable domain of an ascii host: its public suffix and one more label, e.g.
+    /// `test.co.uk` for `www.test.co.uk`. Returns `None` if the host has no public suffix or is a
+    /// public suffix itself.
+    pub fn registrable_domain(&self, host: &str) -> Option<String> {
+        let suffix = self.public_suffix(host)?;
+        let host = host.to_lowercase();
+        let rest = host.strip_suffix(&suffix)?.strip_suffix('.')?;
+        let label = rest.rsplit('.').next()?;
+        Some(format!("{}.{}", label, suffix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::PublicSuffixList;
+
+    const LIST: &str = "// ===BEGIN ICANN DOMAINS===
+com
+ch
+uk
+co.uk
+
+// wildcard and exception
+*.ck
+!www.ck
+рф
+";
+
+    #[test]
+    fn parse_lists() {
+        assert!(PublicSuffixList::parse(LIST).is_ok());
+        assert!(PublicSuffixList::parse("").is_ok());
+
+        assert_eq!(PublicSuffixList::parse("com\nco..uk").unwrap_err().to_string(), "Invalid public suffix rule at line 2.");
+        assert!(PublicSuffixList::parse("a.*.com").is_err());
+        assert!(PublicSuffixList::parse("!").is_err());
+    }
+
+    #[test]
+    fn public_suffixes() {
+        let list = PublicSuffixList::parse(LIST).unwrap();
+        assert_eq!(list.public_suffix("test.com").unwrap(), "com");
+        assert_eq!(list.public_suffix("www.test.co.uk").unwrap(), "co.uk");
+        assert_eq!(list.public_suffix("test.uk").unwrap(), "uk");
+        assert_eq!(list.public_suffix("a.b.ck").unwrap(), "b.ck");
+        assert_eq!(list.public_suffix("www.ck").unwrap(), "ck");
+        assert_eq!(list.public_suffix("test.xn--p1ai").unwrap(), "xn--p1ai");
+        assert_eq!(list.public_suffix("TEST.COM").unwrap(), "com");
+
+        // unknown or malformed
+        assert_eq!(list.public_suffix("test.zzz"), None);
+        assert_eq!(list.public_suffix("test..com"), None);
+    }
+
+    #[test]
+    fn registrable_domains() {
+        let list = PublicSuffixList::parse(LIST).unwrap();
+        assert_eq!(list.registrable_domain("www.test.com").unwrap(), "test.com");
+        assert_eq!(list.registrable_domain("a.b.test.co.uk").unwrap(), "test.co.uk");
+        assert_eq!(list.registrable_domain("a.b.ck").unwrap(), "a.b.ck");
+        assert_eq!(list.registrable_domain("a.www.ck").unwrap(), "www.ck");
+
+        // public suffixes themselves have no registrable domain
+        assert_eq!(list.registrable_domain("co.uk"), None);
+        assert_eq!(list.registrable_domain("b.ck"), None);
+        assert_eq!(list.registrable_domain("com"), None);
+    }
+}
@@ -0,0 +1,156 @@
+//! Conversion of internationalized domain names between their unicode and ascii compatible
+//! (punycode) forms, with [idna](https://docs.rs/idna) ([UTS #46](https://www.unicode.org/reports/tr46/)).
+//!
+//! Without the `idna` feature, the non ascii hosts can't be converted and the labels prefixed with
+//! `xn--` are decoded with the punycode algorithm of [RFC 3492](https://www.rfc-editor.org/rfc/rfc3492),
+//! without the UTS #46 mapping and checks.
+
+/// Convert a host to its ascii compatible form.
+///
+/// The host is mapped and lowercased as in UTS #46, and its non ascii labels are encoded with
+/// punycode and prefixed with `xn--`. Returns `None` if a label can't be encoded.
+#[cfg(feature = "idna")]
+pub(crate) fn host_to_ascii(host: &str) -> Option<String> {
+    idna::domain_to_ascii(host).ok()
+}
+
+/// Convert a host to its ascii compatible form.
+///
+/// The host is lowercased. Returns `None` if it isn't ascii or if a label prefixed with `xn--`
+/// can't be decoded.
+#[cfg(not(feature = "idna"))]
+pub(crate) fn host_to_ascii(host: &str) -> Option<String> {
+    if !host.is_ascii() || host_to_unicode(host).is_none() {
+        return None;
+    }
+    Some(host.to_ascii_lowercase())
+}
+
+/// Convert a host to its unicode form: the labels prefixed with `xn--` are decoded, the other
+/// labels are left as is. Returns `None` if a label can't be decoded.
+#[cfg(feature = "idna")]
+pub(crate) fn host_to_unicode(host: &str) -> Option<String> {
+    match idna::domain_to_unicode(host) {
+        (unicode, Ok(())) => Some(unicode),
+        (_, Err(_)) => None,
+    }
+}
+
+/// Convert a host to its unicode form: the labels prefixed with `xn--` are decoded, the other
+/// labels are left as is. Returns `None` if a label can't be decoded.
+#[cfg(not(feature = "idna"))]
+pub(crate) fn host_to_unicode(host: &str) -> Option<String> {
+    let labels = host
+        .split('.')
+        .map(|label| match label.get(..4) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("xn--") => decode_label(&label[4..]),
+            _ => Some(label.to_string()),
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(labels.join("."))
+}
+
+#[cfg(not(feature = "idna"))]
+const BASE: u32 = 36;
+#[cfg(not(feature = "idna"))]
+const T_MIN: u32 = 1;
+#[cfg(not(feature = "idna"))]
+const T_MAX: u32 = 26;
+
+/// Decode a punycode label, without its `xn--` prefix (RFC 3492, section 6.2).
+#[cfg(not(feature = "idna"))]
+fn decode_label(encoded: &str) -> Option<String> {
+    let (basic, extended) = match encoded.rfind('-') {
+        Some(delimiter) => (&encoded[..delimiter], &encoded[delimiter + 1..]),
+        None => ("", encoded),
+    };
+    if !basic.is_ascii() || extended.is_empty() {
+        return None;
+    }
+
+    let mut output: Vec<char> = basic.chars().collect();
+    let (mut code_point, mut index, mut bias) = (128u32, 0u32, 72u32);
+    let mut digits = extended.bytes();
+    let mut next = digits.next();
+    while next.is_some() {
+        let old_index = index;
+        let mut weight = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = match next? {
+                byte @ b'a'..=b'z' => byte - b'a',
+                byte @ b'A'..=b'Z' => byte - b'A',
+                byte @ b'0'..=b'9' => byte - b'0' + 26,
+                _ => return None,
+            } as u32;
+            next = digits.next();
+            index = index.checked_add(digit.checked_mul(weight)?)?;
+            let threshold = k.saturating_sub(bias).clamp(T_MIN, T_MAX);
+            if digit < threshold {
+                break;
+            }
+            weight = weight.checked_mul(BASE - threshold)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(index - old_index, length, old_index == 0);
+        code_point = code_point.checked_add(index / length)?;
+        index %= length;
+        output.insert(index as usize, char::from_u32(code_point).filter(|c| !c.is_ascii())?);
+        index += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+/// Adapt the bias after a decoded code point (RFC 3492, section 6.1).
+#[cfg(not(feature = "idna"))]
+fn adapt(delta: u32, length: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / length;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + 38)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{host_to_ascii, host_to_unicode};
+
+    #[test]
+    #[cfg(feature = "idna")]
+    fn convert_hosts() {
+        assert_eq!(host_to_ascii("münchen.de").unwrap(), "xn--mnchen-3ya.de");
+        assert_eq!(host_to_ascii("漢字.jp").unwrap(), "xn--p8s937b.jp");
+        assert_eq!(host_to_ascii("пример.рф").unwrap(), "xn--e1afmkfd.xn--p1ai");
+
+        // labels are lowercased
+        assert_eq!(host_to_ascii("MÜNCHEN.de").unwrap(), "xn--mnchen-3ya.de");
+        assert_eq!(host_to_ascii("www.Test.com").unwrap(), "www.test.com");
+    }
+
+    #[test]
+    #[cfg(not(feature = "idna"))]
+    fn convert_hosts() {
+        assert_eq!(host_to_ascii("www.Test.com").unwrap(), "www.test.com");
+        assert_eq!(host_to_ascii("xn--mnchen-3ya.de").unwrap(), "xn--mnchen-3ya.de");
+
+        // not converted without idna
+        assert_eq!(host_to_ascii("münchen.de"), None);
+        assert_eq!(host_to_ascii("xn--zzzzzzzzzzzz.de"), None);
+    }
+
+    #[test]
+    fn decode_hosts() {
+        assert_eq!(host_to_unicode("www.xn--mnchen-3ya.de").unwrap(), "www.münchen.de");
+        assert_eq!(host_to_unicode("xn--p8s937b.jp").unwrap(), "漢字.jp");
+        assert_eq!(host_to_unicode("www.test.com").unwrap(), "www.test.com");
+        assert_eq!(host_to_unicode("xn--e1afmkfd.xn--p1ai").unwrap(), "пример.рф");
+
+        // malformed
+        assert_eq!(host_to_unicode("xn--mnchen-3y!.de"), None);
+        assert_eq!(host_to_unicode("xn--zzzzzzzzzzzz.de"), None);
+    }
+}
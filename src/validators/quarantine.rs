@@ -0,0 +1,165 @@
+use std::fs;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::ValidationError;
+
+/// Extension of the quarantined files, so that no server or desktop opens them by their type.
+pub const QUARANTINE_EXTENSION: &str = "quarantine";
+
+/// Number of files quarantined by the process, part of the quarantined names.
+static QUARANTINED: AtomicU64 = AtomicU64::new(0);
+
+/// How a rejected file is put in quarantine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuarantineMode {
+    /// The file is moved, copied then removed if the quarantine directory is on another
+    /// filesystem
+    Move,
+    /// The file is copied and left in place
+    Copy,
+}
+
+/// Quarantine directory of the rejected files, configured with
+/// [`FileValidatorBuilder::quarantine`](crate::FileValidatorBuilder::quarantine).
+///
+/// Each file is stored under a unique name with the [`QUARANTINE_EXTENSION`], next to a JSON
+/// sidecar of the same name describing the rejection:
+///
+/// ``` json
+/// {"path": "uploads/invoice.php.jpg", "reason": "Dangerous extension php in filename.", "mime_type": "image/jpeg", "time": 1650000000, "mode": "move"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quarantine {
+    dir: PathBuf,
+    mode: QuarantineMode,
+}
+
+/// File put in quarantine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuarantinedFile {
+    /// Path of the file in the quarantine directory
+    pub path: PathBuf,
+    /// Path of its JSON sidecar
+    pub sidecar: PathBuf,
+}
+
+impl Quarantine {
+    /// Create a quarantine in a directory, created with its parents if missing when a file is
+    /// put in quarantine.
+    pub fn new<P: AsRef<Path>>(dir: P, mode: QuarantineMode) -> Quarantine {
+        Quarantine { dir: dir.as_ref().to_path_buf(), mode }
+    }
+
+    /// Directory of the quarantine.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Put a file in quarantine for a reason, with the detected mime type if known.
+    ///
+    /// The copies of the file and its sidecar are written under hidden temporary names then
+    /// renamed, so that the quarantine directory never holds a partial file or sidecar.
+    ///
+    /// # Errors
+    /// If the directory could not be created, or the file could not be moved or copied.
+    pub fn quarantine<P: AsRef<Path>>(&self, path: P, reason: &str, mime_type: Option<&str>) -> Result<QuarantinedFile, ValidationError> {
+        let path = path.as_ref();
+        fs::create_dir_all(&self.dir)?;
+
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+        let id = format!("{}-{}-{}", time.as_nanos(), std::process::id(), QUARANTINED.fetch_add(1, Ordering::Relaxed));
+        let quarantined = self.dir.join(format!("{}.{}", id, QUARANTINE_EXTENSION));
+        let sidecar = self.dir.join(format!("{}.json", id));
+        let temporary = self.dir.join(format!(".{}.tmp", id));
+
+        // A rename is atomic on the same filesystem only
+        let moved = self.mode == QuarantineMode::Move && fs::rename(path, &quarantined).is_ok();
+        if !moved {
+            fs::copy(path, &temporary)?;
+            fs::rename(&temporary, &quarantined)?;
+        }
+
+        let mode = match self.mode {
+            QuarantineMode::Move => "move",
+            QuarantineMode::Copy => "copy",
+        };
+        let json = format!(
+            "{{\"path\": {}, \"reason\": {}, \"mime_type\": {}, \"time\": {}, \"mode\": \"{}\"}}\n",
+            json_string(&path.to_string_lossy()),
+            json_string(reason),
+            mime_type.map_or_else(|| String::from("null"), json_string),
+            time.as_secs(),
+            mode
+        );
+        fs::write(&temporary, json)?;
+        fs::rename(&temporary, &sidecar)?;
+
+        if self.mode == QuarantineMode::Move && !moved {
+            fs::remove_file(path).map_err(|e| Error::other(format!("The quarantined file could not be removed: {}.", e)))?;
+        }
+        Ok(QuarantinedFile { path: quarantined, sidecar })
+    }
+}
+
+/// JSON string literal of a text.
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::json_string;
+    use crate::{Quarantine, QuarantineMode};
+
+    #[test]
+    fn json_strings() {
+        assert_eq!(json_string("image/png"), "\"image/png\"");
+        assert_eq!(json_string("a \"b\"\\c\n\u{1}"), "\"a \\\"b\\\"\\\\c\\n\\u0001\"");
+    }
+
+    #[test]
+    fn quarantined_files() {
+        let dir = std::env::temp_dir().join(format!("quarantine_{}", std::process::id()));
+        let upload = std::env::temp_dir().join(format!("quarantine_upload_{}.php", std::process::id()));
+        fs::write(&upload, "<?php echo 1; ?>").unwrap();
+
+        let copy = Quarantine::new(dir.join("copies"), QuarantineMode::Copy).quarantine(&upload, "Bad \"file\".", None).unwrap();
+        assert!(upload.exists());
+        assert_eq!(fs::read_to_string(&copy.path).unwrap(), "<?php echo 1; ?>");
+        assert!(copy.path.extension().is_some_and(|extension| extension == "quarantine"));
+
+        let quarantine = Quarantine::new(&dir, QuarantineMode::Move);
+        let moved = quarantine.quarantine(&upload, "The type text/x-php is not allowed.", Some("text/x-php")).unwrap();
+        assert!(!upload.exists());
+        assert_ne!(moved.path, copy.path);
+        let sidecar = fs::read_to_string(&moved.sidecar).unwrap();
+        assert!(sidecar.starts_with(&format!("{{\"path\": {}, \"reason\": \"The type text/x-php is not allowed.\", \"mime_type\": \"text/x-php\", \"time\": ", json_string(&upload.to_string_lossy()))));
+        assert!(sidecar.ends_with(", \"mode\": \"move\"}\n"));
+        assert!(fs::read_to_string(&copy.sidecar).unwrap().contains("\"reason\": \"Bad \\\"file\\\".\", \"mime_type\": null"));
+
+        // no temporary file is left
+        let names: Vec<_> = fs::read_dir(quarantine.dir()).unwrap().map(|entry| entry.unwrap().file_name()).collect();
+        assert_eq!(names.len(), 3);
+        assert!(quarantine.quarantine(&upload, "missing", None).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,114 @@
+// Only the strategy is used with the hand-written grammar
+#![cfg_attr(feature = "handwritten", allow(dead_code))]
+
+#[cfg(feature = "url_regex")]
+use std::cell::RefCell;
+#[cfg(feature = "url_regex")]
+use std::collections::HashMap;
+#[cfg(feature = "url_regex")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "url_regex")]
+use std::sync::Arc;
+
+#[cfg(feature = "url_regex")]
+use regex::Regex;
+
+/// Strategy used by a validator to share its compiled regexes between threads.
+///
+/// A compiled regex keeps a pool of matching caches which is contended when many threads use
+/// the same regex at once. Highly concurrent servers can trade memory for throughput by giving
+/// each thread or each validator its own copy.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RegexStrategy {
+    /// Every thread and every clone of the validator use the same regexes (the default).
+    #[default]
+    Shared,
+    /// Each thread uses its own copy of the regexes, created on first use. The copies live as
+    /// long as the thread, so build such validators once and reuse them.
+    ThreadLocal,
+    /// Each validator owns its regexes, a clone of the validator gets its own copy.
+    Owned,
+}
+
+// Unique identifiers of the regexes copied in each thread
+#[cfg(feature = "url_regex")]
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+#[cfg(feature = "url_regex")]
+thread_local! {
+    static THREAD_REGEXES: RefCell<HashMap<u64, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Compiled regex used according to a [`RegexStrategy`].
+#[cfg(feature = "url_regex")]
+#[derive(Debug, Clone)]
+pub(crate) enum CompiledRegex {
+    Shared(Arc<Regex>),
+    ThreadLocal(u64, Arc<Regex>),
+    Owned(Regex),
+}
+
+#[cfg(feature = "url_regex")]
+impl CompiledRegex {
+    pub(crate) fn new(regex: Arc<Regex>, strategy: RegexStrategy) -> CompiledRegex {
+        match strategy {
+            RegexStrategy::Shared => CompiledRegex::Shared(regex),
+            RegexStrategy::ThreadLocal => CompiledRegex::ThreadLocal(NEXT_ID.fetch_add(1, Ordering::Relaxed), regex),
+            RegexStrategy::Owned => CompiledRegex::Owned(Regex::clone(&regex)),
+        }
+    }
+
+    /// Run a function with the regex to use in the current thread.
+    pub(crate) fn with<T, F: FnOnce(&Regex) -> T>(&self, f: F) -> T {
+        match self {
+            CompiledRegex::Shared(regex) => f(regex),
+            CompiledRegex::Owned(regex) => f(regex),
+            CompiledRegex::ThreadLocal(id, regex) => THREAD_REGEXES.with(|regexes| {
+                match regexes.try_borrow_mut() {
+                    Ok(mut regexes) => f(regexes.entry(*id).or_insert_with(|| Regex::clone(regex))),
+                    // Already in use by the caller, fall back to the shared regex
+                    Err(_) => f(regex),
+                }
+            }),
+        }
+    }
+
+    pub(crate) fn is_match(&self, text: &str) -> bool {
+        self.with(|regex| regex.is_match(text))
+    }
+}
+
+#[cfg(all(test, feature = "url_regex"))]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use regex::Regex;
+
+    use super::{CompiledRegex, RegexStrategy};
+
+    #[test]
+    fn same_results_for_all_strategies() {
+        let regex = Arc::new(Regex::new(r"^[a-z]+$").unwrap());
+        for strategy in [RegexStrategy::Shared, RegexStrategy::ThreadLocal, RegexStrategy::Owned] {
+            let compiled = CompiledRegex::new(regex.clone(), strategy);
+            assert!(compiled.is_match("abc"));
+            assert!(!compiled.is_match("ABC"));
+
+            let compiled = Arc::new(compiled);
+            let handles: Vec<_> = (0..4)
+                .map(|_| {
+                    let compiled = compiled.clone();
+                    thread::spawn(move || compiled.is_match("abc") && !compiled.is_match("1"))
+                })
+                .collect();
+            assert!(handles.into_iter().all(|handle| handle.join().unwrap()));
+        }
+    }
+
+    #[test]
+    fn nested_thread_local_use() {
+        let compiled = CompiledRegex::new(Arc::new(Regex::new("a").unwrap()), RegexStrategy::ThreadLocal);
+        assert!(compiled.with(|outer| outer.is_match("a") && compiled.is_match("a")));
+    }
+}
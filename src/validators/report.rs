@@ -0,0 +1,180 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+
+#[cfg(all(feature = "url", feature = "std"))]
+use super::Violation;
+use super::{Validate, ValidationError, ValidationErrors};
+
+/// Failed rule of a [`ValidationReport`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    /// Path of the invalid field, e.g. `upload.files[2].path`
+    pub path: String,
+    /// Machine-readable code of the failure, see [`ValidationError::code`], or the rule of an
+    /// url [`Violation`], e.g. `sub_level_domain`
+    pub code: &'static str,
+    /// Description of the failure
+    pub message: String,
+}
+
+impl Display for Failure {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Report collecting every failed rule of several inputs, or of several rules on one input,
+/// instead of stopping at the first one, e.g. for a web frontend to highlight every invalid field
+/// of a form at once.
+///
+/// # Examples
+/// ``` ignore
+/// let mut report = ValidationReport::new();
+/// report.check("source", validate_url_with_whitelist(&form.source, [".ch"]));
+/// let id = report.check("id", parse_uuid_v5(&form.id));
+/// for (index, path) in form.files.iter().enumerate() {
+///     report.check(&format!("files[{}]", index), ImageFile::new(path));
+/// }
+///
+/// if !report.is_valid() {
+///     return Err(report);
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    failures: Vec<Failure>,
+}
+
+impl ValidationReport {
+    /// Create an empty report.
+    pub fn new() -> Self {
+        ValidationReport::default()
+    }
+
+    /// Add the error of a field.
+    pub fn add<E: Into<ValidationError>>(&mut self, path: &str, error: E) {
+        let error = error.into();
+        self.failures.push(Failure { path: String::from(path), code: error.code(), message: error.to_string() });
+    }
+
+    /// Add the error of a field if any, the value otherwise.
+    pub fn check<T, E: Into<ValidationError>>(&mut self, path: &str, result: Result<T, E>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.add(path, error);
+                None
+            }
+        }
+    }
+
+    /// Check a field with a validator, see [`Validate`], and whether it is valid.
+    pub fn validate<T: ?Sized, V>(&mut self, path: &str, validator: &V, input: &T) -> bool
+    where
+        V: Validate<T>,
+        V::Error: Into<ValidationError>,
+    {
+        self.check(path, validator.check(input)).is_some()
+    }
+
+    /// Add every rule violated by an url, see [`crate::UrlValidator::report`].
+    #[cfg(all(feature = "url", feature = "std"))]
+    pub fn add_violations(&mut self, path: &str, violations: &[Violation]) {
+        for violation in violations {
+            self.failures.push(Failure { path: String::from(path), code: violation.rule, message: violation.message.clone() });
+        }
+    }
+
+    /// Add the errors of the fields of a struct, e.g. validated by the derive macro
+    /// `ValidateInput`, their paths under a prefix if not empty.
+    pub fn add_errors(&mut self, prefix: &str, errors: &ValidationErrors) {
+        for (field, error) in errors.errors() {
+            let path = if prefix.is_empty() { field.clone() } else { format!("{}.{}", prefix, field) };
+            self.failures.push(Failure { path, code: error.code(), message: error.to_string() });
+        }
+    }
+
+    /// Whether no rule failed.
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// The failures, in the order of the checks.
+    pub fn failures(&self) -> &[Failure] {
+        &self.failures
+    }
+
+    /// The failures of a field.
+    pub fn field<'a>(&'a self, path: &'a str) -> impl Iterator<Item = &'a Failure> + 'a {
+        self.failures.iter().filter(move |failure| failure.path == path)
+    }
+
+    /// `Ok` if no rule failed, the report otherwise.
+    ///
+    /// # Errors
+    /// If at least one rule failed.
+    pub fn into_result(self) -> Result<(), ValidationReport> {
+        if self.is_valid() {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+/// One line per failure, e.g. `id: Invalid uuid.`
+impl Display for ValidationReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, failure) in self.failures.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for ValidationReport {}
+
+#[cfg(all(test, feature = "url", feature = "file", feature = "uuid"))]
+mod tests {
+    use crate::{parse_uuid_v5, rule, ImageFile, UrlError, UrlValidator, Validate, ValidationError, ValidationErrors, ValidationReport};
+
+    #[test]
+    fn collected_failures() {
+        let mut report = ValidationReport::new();
+        assert_eq!(report.check("id", parse_uuid_v5("c70dc454-1c7d-5c59-8fed-3a321e6a4a49")).map(|uuid| uuid.get_version_num()), Some(5));
+        assert!(report.is_valid());
+
+        assert_eq!(report.check("id", parse_uuid_v5("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04")), None);
+        for (index, path) in ["test_files/valid_image.png", "test_files/valid_video.mov", "test_files/missing.png"].iter().enumerate() {
+            report.check(&format!("files[{}]", index), ImageFile::new(path));
+        }
+        let validator = UrlValidator::builder().build().unwrap();
+        let short = validator.clone().and(rule(|url: &str| url.len() < 16, UrlError::Invalid));
+        assert!(report.validate("source", &short, "https://docs.rs"));
+        assert!(!report.validate("source", &short, "https://docs.rs/lazy_static"));
+        report.add_violations("link", &validator.report("a_b.com"));
+
+        let codes: Vec<_> = report.failures().iter().map(|failure| (failure.path.as_str(), failure.code)).collect();
+        assert_eq!(codes, [("id", "uuid_version"), ("files[1]", "rejected_file"), ("files[2]", "not_found"), ("source", "invalid_url"), ("link", "sub_level_domain")]);
+        assert_eq!(report.field("id").next().unwrap().to_string(), "id: Invalid uuid version 4, expected 5.");
+        assert!(report.into_result().is_err());
+    }
+
+    #[test]
+    fn merged_errors() {
+        let mut errors = ValidationErrors::new();
+        errors.push("source", ValidationError::from(UrlError::Invalid));
+        errors.push("path", ValidationError::UnknownFileType);
+        let mut report = ValidationReport::new();
+        report.add_errors("upload", &errors);
+        report.add_errors("", &errors);
+        assert_eq!(report.to_string(), "upload.source: The url is invalid.\nupload.path: File type is unknown.\nsource: The url is invalid.\npath: File type is unknown.");
+        assert_eq!(report.failures()[1].code, "unknown_file_type");
+        assert!(ValidationReport::new().into_result().is_ok());
+    }
+}
@@ -0,0 +1,125 @@
+use rocket::data::{Data, FromData, Limits, Outcome};
+use rocket::form::{self, FromFormField, ValueField};
+use rocket::http::Status;
+use rocket::request::Request;
+use rocket::response::{self, Responder};
+use rocket::serde::json::Json;
+
+use super::validated::validate_upload;
+use super::{FileUuid, ValidUrl, ValidatedFile, ValidatedInput, ValidationError, ValidationReport};
+
+/// Validate a form field, the error of the validator being the message of the form error.
+fn form_field<'v, T: ValidatedInput>(field: ValueField<'v>) -> form::Result<'v, T> {
+    T::parse_input(field.value).map_err(|e| form::Error::validation(e.to_string()).into())
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for ValidUrl {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        form_field(field)
+    }
+}
+
+#[rocket::async_trait]
+impl<'v> FromFormField<'v> for FileUuid {
+    fn from_value(field: ValueField<'v>) -> form::Result<'v, Self> {
+        form_field(field)
+    }
+}
+
+/// `400 Bad Request` with the report as JSON.
+impl<'r> Responder<'r, 'static> for ValidationReport {
+    fn respond_to(self, request: &'r Request<'_>) -> response::Result<'static> {
+        (Status::BadRequest, Json(self)).respond_to(request)
+    }
+}
+
+/// Body of a request validated as a file from its contents, see [`crate::validate_file_bytes`],
+/// read up to the `file` limit of Rocket. The body has no name, so its extension is not checked.
+///
+/// An invalid body fails the guard with a `400 Bad Request`, or a `413 Payload Too Large` over
+/// the limit, and the [`ValidationReport`] of the body, under the path `body`; a route taking a
+/// `Result<ValidatedUpload, ValidationReport>` can respond with the report.
+///
+/// # Examples
+/// ``` ignore
+/// #[post("/upload", data = "<upload>")]
+/// fn upload(upload: Result<ValidatedUpload, ValidationReport>) -> Result<String, ValidationReport> {
+///     Ok(upload?.file.mime_type.to_string())
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedUpload {
+    /// Contents of the file
+    pub contents: Vec<u8>,
+    /// Details of the validation of the file
+    pub file: ValidatedFile,
+}
+
+#[rocket::async_trait]
+impl<'r> FromData<'r> for ValidatedUpload {
+    type Error = ValidationReport;
+
+    async fn from_data(request: &'r Request<'_>, data: Data<'r>) -> Outcome<'r, Self> {
+        let mut report = ValidationReport::new();
+        let limit = request.limits().get("file").unwrap_or(Limits::FILE);
+        let contents = match data.open(limit).into_bytes().await {
+            Ok(contents) if contents.is_complete() => contents.into_inner(),
+            Ok(_) => {
+                report.add("body", ValidationError::option(&format!("The file exceeds the limit of {}.", limit)));
+                return Outcome::Error((Status::PayloadTooLarge, report));
+            }
+            Err(e) => {
+                report.add("body", e);
+                return Outcome::Error((Status::BadRequest, report));
+            }
+        };
+        match report.check("body", validate_upload(&contents, None)) {
+            Some(file) => Outcome::Success(ValidatedUpload { contents, file }),
+            None => Outcome::Error((Status::BadRequest, report)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::form::Form;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::blocking::Client;
+    use rocket::{post, routes, FromForm};
+
+    use crate::{FileUuid, ValidUrl, ValidatedUpload, ValidationReport};
+
+    #[derive(FromForm)]
+    struct Share {
+        url: ValidUrl,
+        id: FileUuid,
+    }
+
+    #[post("/share", data = "<share>")]
+    fn share(share: Form<Share>) -> String {
+        format!("{} {}", share.url, share.id)
+    }
+
+    #[post("/upload", data = "<upload>")]
+    fn upload(upload: Result<ValidatedUpload, ValidationReport>) -> Result<String, ValidationReport> {
+        Ok(upload?.file.mime_type.to_string())
+    }
+
+    #[test]
+    fn rocket_guards() {
+        let client = Client::tracked(rocket::build().mount("/", routes![share, upload])).unwrap();
+
+        let response = client.post("/share").header(ContentType::Form).body("url=https://docs.rs&id=C70DC454-1C7D-5C59-8FED-3A321E6A4A49").dispatch();
+        assert_eq!(response.into_string().unwrap(), "https://docs.rs c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        let response = client.post("/share").header(ContentType::Form).body("url=https://docs.rs&id=c70dc454").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+
+        let image = std::fs::read("test_files/valid_image.png").unwrap();
+        let response = client.post("/upload").body(&image).dispatch();
+        assert_eq!(response.into_string().unwrap(), "image/png");
+        let response = client.post("/upload").body(std::fs::read("test_files/invalid_file.pdf").unwrap()).dispatch();
+        assert_eq!(response.status(), Status::BadRequest);
+        assert!(response.into_string().unwrap().starts_with(r#"{"errors":[{"path":"body","code":"rejected_file","#));
+    }
+}
@@ -0,0 +1,68 @@
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Verdict of a [`ContentScanner`] on the contents of a file.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScanVerdict {
+    /// Nothing was detected
+    Clean,
+    /// A threat was detected, given by its name, e.g. `Eicar-Signature`
+    Infected(String),
+    /// The file is suspected for the given reason, without certainty
+    Suspicious(String),
+    /// The file could not be scanned, for the given reason, e.g. an unreachable daemon
+    Error(String),
+}
+
+impl ScanVerdict {
+    /// Whether nothing was detected.
+    pub fn is_clean(&self) -> bool {
+        *self == ScanVerdict::Clean
+    }
+}
+
+impl Display for ScanVerdict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanVerdict::Clean => write!(f, "No threat detected."),
+            ScanVerdict::Infected(threat) => write!(f, "Threat detected: {}.", threat),
+            ScanVerdict::Suspicious(reason) => write!(f, "Suspicious file: {}.", reason),
+            ScanVerdict::Error(reason) => write!(f, "The file could not be scanned: {}.", reason),
+        }
+    }
+}
+
+/// External scanner of the contents of the files, e.g. an antivirus such as ClamAV, run by the
+/// validators configured with [`FileValidatorBuilder::content_scanner`](crate::FileValidatorBuilder::content_scanner).
+///
+/// # Examples
+/// ``` ignore
+/// #[derive(Debug)]
+/// struct Eicar;
+///
+/// impl ContentScanner for Eicar {
+///     fn scan(&self, bytes: &[u8]) -> ScanVerdict {
+///         if bytes.starts_with(b"X5O!P%@AP[4\\PZX54(P^)7CC)7}$EICAR") {
+///             ScanVerdict::Infected(String::from("Eicar-Signature"))
+///         } else {
+///             ScanVerdict::Clean
+///         }
+///     }
+/// }
+/// ```
+pub trait ContentScanner: Debug + Send + Sync {
+    /// Scan the whole contents of a file.
+    fn scan(&self, bytes: &[u8]) -> ScanVerdict;
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ScanVerdict;
+
+    #[test]
+    fn verdicts() {
+        assert!(ScanVerdict::Clean.is_clean());
+        assert!(!ScanVerdict::Suspicious(String::from("packed executable")).is_clean());
+        assert_eq!(ScanVerdict::Infected(String::from("Eicar-Signature")).to_string(), "Threat detected: Eicar-Signature.");
+        assert_eq!(ScanVerdict::Error(String::from("connection refused")).to_string(), "The file could not be scanned: connection refused.");
+    }
+}
@@ -0,0 +1,128 @@
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{Failure, FileUuid, ImageFile, ValidUrl, ValidationReport, VideoFile};
+
+// The validated types are deserialized from strings, running their validation, and serialized to
+// their canonical form: the url as given, the path of the file, and the lowercase hyphenated
+// uuid. The errors of the validators are the messages of the serde errors.
+
+impl<'de> Deserialize<'de> for ValidUrl {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let url = String::deserialize(deserializer)?;
+        ValidUrl::new(&url).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for ValidUrl {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ImageFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        ImageFile::new(path).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for ImageFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_str().ok_or_else(|| S::Error::custom("The path is not valid UTF-8."))?)
+    }
+}
+
+impl<'de> Deserialize<'de> for VideoFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let path = String::deserialize(deserializer)?;
+        VideoFile::new(path).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for VideoFile {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_str().ok_or_else(|| S::Error::custom("The path is not valid UTF-8."))?)
+    }
+}
+
+impl<'de> Deserialize<'de> for FileUuid {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let uuid = String::deserialize(deserializer)?;
+        FileUuid::new(&uuid).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for FileUuid {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+/// `{"path": "id", "code": "invalid_uuid", "message": "Invalid uuid."}`
+impl Serialize for Failure {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut failure = serializer.serialize_struct("Failure", 3)?;
+        failure.serialize_field("path", &self.path)?;
+        failure.serialize_field("code", self.code)?;
+        failure.serialize_field("message", &self.message)?;
+        failure.end()
+    }
+}
+
+/// `{"errors": [...]}`, see [`Failure`].
+impl Serialize for ValidationReport {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut report = serializer.serialize_struct("ValidationReport", 1)?;
+        report.serialize_field("errors", self.failures())?;
+        report.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{parse_uuid_v5, FileUuid, ImageFile, ValidUrl, ValidationReport, VideoFile};
+
+    #[test]
+    fn deserialized_types() {
+        let urls: Vec<ValidUrl> = serde_json::from_str(r#"["https://docs.rs/lazy_static", "heig-vd.ch"]"#).unwrap();
+        assert_eq!(urls[1].as_str(), "heig-vd.ch");
+        let error = serde_json::from_str::<Vec<ValidUrl>>(r#"["https://docs.rs", "https://docs .rs"]"#).unwrap_err();
+        assert!(error.to_string().starts_with("The url is invalid."));
+
+        let request: HashMap<String, FileUuid> = serde_json::from_str(r#"{"id": "{C70DC454-1C7D-5C59-8FED-3A321E6A4A49}"}"#).unwrap();
+        assert_eq!(request["id"].to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        let error = serde_json::from_str::<FileUuid>(r#""9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04""#).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid uuid version 4, expected 5."));
+        assert!(serde_json::from_str::<FileUuid>("42").is_err());
+
+        let image: ImageFile = serde_json::from_str(r#""test_files/valid_image.png""#).unwrap();
+        assert_eq!(image.file().mime_type, "image/png");
+        let error = serde_json::from_str::<ImageFile>(r#""test_files/valid_video.mov""#).unwrap_err();
+        assert!(error.to_string().starts_with("The type video/quicktime is not allowed."));
+        assert!(serde_json::from_str::<VideoFile>(r#""test_files/valid_video.mov""#).is_ok());
+    }
+
+    #[test]
+    fn serialized_types() {
+        let uuid = FileUuid::new("{C70DC454-1C7D-5C59-8FED-3A321E6A4A49}").unwrap();
+        assert_eq!(serde_json::to_string(&uuid).unwrap(), r#""c70dc454-1c7d-5c59-8fed-3a321e6a4a49""#);
+        let url = ValidUrl::new("https://docs.rs/lazy_static").unwrap();
+        assert_eq!(serde_json::to_string(&url).unwrap(), r#""https://docs.rs/lazy_static""#);
+        let video = VideoFile::new("test_files/valid_video.mov").unwrap();
+        assert_eq!(serde_json::to_string(&video).unwrap(), r#""test_files/valid_video.mov""#);
+        let image = ImageFile::new("test_files/valid_image.jpg").unwrap();
+        assert_eq!(serde_json::from_str::<ImageFile>(&serde_json::to_string(&image).unwrap()).unwrap(), image);
+    }
+
+    #[test]
+    fn serialized_reports() {
+        let mut report = ValidationReport::new();
+        report.check("id", parse_uuid_v5("c70dc454"));
+        assert_eq!(serde_json::to_string(&report).unwrap(), r#"{"errors":[{"path":"id","code":"invalid_uuid","message":"Invalid uuid."}]}"#);
+    }
+}
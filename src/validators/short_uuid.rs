@@ -0,0 +1,181 @@
+use uuid::Uuid;
+
+use super::{UuidError, UuidValidator, ValidationError};
+
+/// Url-safe alphabet of base64 (RFC 4648), without padding.
+const BASE64URL_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Bitcoin alphabet of base58, without the look-alike `0`, `O`, `I` and `l`.
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Length of the short forms of a uuid, in both encodings.
+pub const SHORT_UUID_LENGTH: usize = 22;
+
+/// Compact encoding of a uuid, e.g. for the urls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UuidEncoding {
+    /// The 16 bytes in url-safe base64 without padding, e.g. `xw3EVBx9XFmP7ToyHmpKSQ`
+    Base64Url,
+    /// The 128-bit number in base58, left-padded with `1`, the zero digit, e.g.
+    /// `Rae9iTk37mYLkR5T7mnZSQ`
+    Base58,
+}
+
+/// Encode a uuid in its short form of [`SHORT_UUID_LENGTH`] characters.
+///
+/// # Examples
+/// ``` ignore
+/// let id = encode_uuid(&uuid, UuidEncoding::Base64Url);
+/// println!("sec.upload/f/{}", id);
+/// ```
+pub fn encode_uuid(uuid: &Uuid, encoding: UuidEncoding) -> String {
+    match encoding {
+        UuidEncoding::Base64Url => {
+            let mut bits = 0u32;
+            let mut bit_count = 0;
+            let mut encoded = String::with_capacity(SHORT_UUID_LENGTH);
+            for &byte in uuid.as_bytes() {
+                bits = (bits << 8) | byte as u32;
+                bit_count += 8;
+                while bit_count >= 6 {
+                    bit_count -= 6;
+                    encoded.push(BASE64URL_ALPHABET[(bits >> bit_count) as usize & 0x3F] as char);
+                }
+            }
+            // The last 2 bits, padded with zeros
+            encoded.push(BASE64URL_ALPHABET[(bits << (6 - bit_count)) as usize & 0x3F] as char);
+            encoded
+        }
+
+        UuidEncoding::Base58 => {
+            let mut value = u128::from_be_bytes(*uuid.as_bytes());
+            let mut digits = [BASE58_ALPHABET[0]; SHORT_UUID_LENGTH];
+            for digit in digits.iter_mut().rev() {
+                *digit = BASE58_ALPHABET[(value % 58) as usize];
+                value /= 58;
+            }
+            digits.iter().map(|&digit| digit as char).collect()
+        }
+    }
+}
+
+/// Decode a uuid from its short form, of any version.
+///
+/// Only the canonical form produced by [`encode_uuid`] is accepted, so that a uuid has a single
+/// short form: in base64url the padding bits must be zeros, and in base58 the value must fit in
+/// 128 bits.
+///
+/// # Errors
+/// If the short form is not [`SHORT_UUID_LENGTH`] characters of the alphabet of the encoding, or
+/// not canonical ([`UuidError::Malformed`]).
+pub fn decode_uuid(encoded: &str, encoding: UuidEncoding) -> Result<Uuid, UuidError> {
+    if encoded.len() != SHORT_UUID_LENGTH {
+        return Err(UuidError::Malformed);
+    }
+    let value = |alphabet: &[u8], byte: u8| alphabet.iter().position(|&digit| digit == byte).ok_or(UuidError::Malformed);
+
+    match encoding {
+        UuidEncoding::Base64Url => {
+            let mut bytes = [0; 16];
+            let mut bits = 0u32;
+            let mut bit_count = 0;
+            let mut index = 0;
+            for byte in encoded.bytes() {
+                bits = (bits << 6) | value(BASE64URL_ALPHABET, byte)? as u32;
+                bit_count += 6;
+                if bit_count >= 8 && index < bytes.len() {
+                    bit_count -= 8;
+                    bytes[index] = (bits >> bit_count) as u8;
+                    index += 1;
+                }
+            }
+            if bits & ((1 << bit_count) - 1) != 0 {
+                return Err(UuidError::Malformed);
+            }
+            Ok(Uuid::from_bytes(bytes))
+        }
+
+        UuidEncoding::Base58 => {
+            let mut number = 0u128;
+            for byte in encoded.bytes() {
+                let digit = value(BASE58_ALPHABET, byte)? as u128;
+                number = number.checked_mul(58).and_then(|number| number.checked_add(digit)).ok_or(UuidError::Malformed)?;
+            }
+            Ok(Uuid::from_bytes(number.to_be_bytes()))
+        }
+    }
+}
+
+/// Parse a version-5 uuid from its short form, decoded as by [`decode_uuid`] then validated as
+/// by [`crate::validate_uuid`].
+///
+/// # Errors
+/// If the short form is invalid, or the uuid is not of the variant 1 and the version 5.
+///
+/// # Examples
+/// ``` ignore
+/// let uuid = parse_short_uuid_v5("xw3EVBx9XFmP7ToyHmpKSQ", UuidEncoding::Base64Url)?;
+/// assert_eq!(uuid.to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+/// ```
+pub fn parse_short_uuid_v5(encoded: &str, encoding: UuidEncoding) -> Result<Uuid, ValidationError> {
+    let uuid = decode_uuid(encoded, encoding)?;
+    Ok(UuidValidator::builder().build().parse_bytes(uuid.as_bytes())?)
+}
+
+/// Validate a version-5 uuid in its short form, see [`parse_short_uuid_v5`].
+pub fn validate_short_uuid(encoded: &str, encoding: UuidEncoding) -> bool {
+    parse_short_uuid_v5(encoded, encoding).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::{decode_uuid, encode_uuid, parse_short_uuid_v5, validate_short_uuid, UuidEncoding, UuidError, ValidationError};
+
+    const UUID: &str = "c70dc454-1c7d-5c59-8fed-3a321e6a4a49";
+
+    #[test]
+    fn base64url_uuids() {
+        let uuid = Uuid::parse_str(UUID).unwrap();
+        assert_eq!(encode_uuid(&uuid, UuidEncoding::Base64Url), "xw3EVBx9XFmP7ToyHmpKSQ");
+        assert_eq!(decode_uuid("xw3EVBx9XFmP7ToyHmpKSQ", UuidEncoding::Base64Url), Ok(uuid));
+        assert_eq!(encode_uuid(&Uuid::from_bytes([0xFF; 16]), UuidEncoding::Base64Url), "_____________________w");
+        assert!(validate_short_uuid("xw3EVBx9XFmP7ToyHmpKSQ", UuidEncoding::Base64Url));
+
+        // standard alphabet, padding, non-canonical last char, wrong length
+        assert_eq!(decode_uuid("xw3EVBx9XFmP7ToyHmpK+Q", UuidEncoding::Base64Url), Err(UuidError::Malformed));
+        assert_eq!(decode_uuid("xw3EVBx9XFmP7ToyHmpKSQ==", UuidEncoding::Base64Url), Err(UuidError::Malformed));
+        assert_eq!(decode_uuid("xw3EVBx9XFmP7ToyHmpKSR", UuidEncoding::Base64Url), Err(UuidError::Malformed));
+        assert_eq!(decode_uuid("xw3EVBx9XFmP7ToyHmpKS", UuidEncoding::Base64Url), Err(UuidError::Malformed));
+    }
+
+    #[test]
+    fn base58_uuids() {
+        let uuid = Uuid::parse_str(UUID).unwrap();
+        assert_eq!(encode_uuid(&uuid, UuidEncoding::Base58), "Rae9iTk37mYLkR5T7mnZSQ");
+        assert_eq!(decode_uuid("Rae9iTk37mYLkR5T7mnZSQ", UuidEncoding::Base58), Ok(uuid));
+        assert_eq!(encode_uuid(&Uuid::nil(), UuidEncoding::Base58), "1111111111111111111111");
+        assert_eq!(encode_uuid(&Uuid::from_bytes([0xFF; 16]), UuidEncoding::Base58), "YcVfxkQb6JRzqk5kF2tNLv");
+        assert_eq!(decode_uuid("YcVfxkQb6JRzqk5kF2tNLv", UuidEncoding::Base58), Ok(Uuid::from_bytes([0xFF; 16])));
+
+        // over 128 bits, look-alike chars
+        assert_eq!(decode_uuid("YcVfxkQb6JRzqk5kF2tNLw", UuidEncoding::Base58), Err(UuidError::Malformed));
+        assert_eq!(decode_uuid("zzzzzzzzzzzzzzzzzzzzzz", UuidEncoding::Base58), Err(UuidError::Malformed));
+        assert_eq!(decode_uuid("0111111111111111111111", UuidEncoding::Base58), Err(UuidError::Malformed));
+    }
+
+    #[test]
+    fn short_uuid_versions() {
+        for name in 0..100u32 {
+            let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, &name.to_le_bytes());
+            for encoding in [UuidEncoding::Base64Url, UuidEncoding::Base58] {
+                assert_eq!(parse_short_uuid_v5(&encode_uuid(&uuid, encoding), encoding).unwrap(), uuid);
+            }
+        }
+        let v4 = Uuid::parse_str("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04").unwrap();
+        let error = parse_short_uuid_v5(&encode_uuid(&v4, UuidEncoding::Base58), UuidEncoding::Base58).unwrap_err();
+        assert!(matches!(error, ValidationError::Uuid(UuidError::Version { actual: 4, .. })));
+        assert!(matches!(parse_short_uuid_v5("AAAAAAAAAAAAAAAAAAAAAA", UuidEncoding::Base64Url), Err(ValidationError::Uuid(UuidError::Nil))));
+    }
+}
@@ -0,0 +1,113 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::punycode;
+use super::ValidationError;
+
+/// Top level domains of the [IANA root zone](https://data.iana.org/TLD/tlds-alpha-by-domain.txt),
+/// enabled with the `iana` feature.
+///
+/// The list is loaded at runtime from its official text format, so that new top level domains
+/// are accepted without a new release of the crate.
+///
+/// # Examples
+/// ``` ignore
+/// let list = TldList::from_file("tlds-alpha-by-domain.txt")?;
+/// assert!(list.contains("com"));
+/// assert!(!list.contains("zzz"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TldList {
+    tlds: HashSet<String>,
+}
+
+impl TldList {
+    /// Parse a list in the official format: one top level domain per line, in any case, with
+    /// `#` comments. Unicode domains are converted to punycode.
+    ///
+    /// # Errors
+    /// If a top level domain is invalid, an error telling the line number is returned.
+    pub fn parse(list: &str) -> Result<TldList, ValidationError> {
+        let mut tlds = HashSet::new();
+
+        for (index, line) in list.lines().enumerate() {
+            let tld = line.trim();
+            if tld.is_empty() || tld.starts_with('#') {
+                continue;
+            }
+
+            let error = || ValidationError::InvalidOption(format!("Invalid top level domain at line {}.", index + 1));
+            let tld = punycode::host_to_ascii(tld).ok_or_else(error)?.to_lowercase();
+            if !tld.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-') || tld.starts_with('-') || tld.ends_with('-') {
+                return Err(error());
+            }
+            tlds.insert(tld);
+        }
+
+        Ok(TldList { tlds })
+    }
+
+    /// Read and parse a list from a file, see [`TldList::parse`].
+    ///
+    /// # Errors
+    /// If the file could not be read or a top level domain is invalid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TldList, ValidationError> {
+        let list = std::fs::read_to_string(path)?;
+        TldList::parse(&list)
+    }
+
+    /// Check whether a top level domain, with or without its leading full stop, is in the list.
+    /// The case is ignored and unicode domains are compared in their punycode form.
+    pub fn contains(&self, tld: &str) -> bool {
+        let tld = tld.strip_prefix('.').unwrap_or(tld);
+        match punycode::host_to_ascii(tld) {
+            None => false,
+            Some(tld) => self.tlds.contains(&tld.to_lowercase()),
+        }
+    }
+
+    /// Number of top level domains in the list.
+    pub fn len(&self) -> usize {
+        self.tlds.len()
+    }
+
+    /// Whether the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.tlds.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::TldList;
+
+    const LIST: &str = "# Version 2024101500, Last Updated Tue Oct 15 07:07:01 2024 UTC
+AAA
+CH
+COM
+XN--P1AI
+";
+
+    #[test]
+    fn parse_lists() {
+        assert_eq!(TldList::parse(LIST).unwrap().len(), 4);
+        assert!(TldList::parse("").unwrap().is_empty());
+
+        assert_eq!(TldList::parse("COM\nC_M").unwrap_err().to_string(), "Invalid top level domain at line 2.");
+        assert!(TldList::parse("co.uk").is_err());
+        assert!(TldList::parse("-com").is_err());
+    }
+
+    #[test]
+    fn contained_tlds() {
+        let list = TldList::parse(LIST).unwrap();
+        assert!(list.contains("com"));
+        assert!(list.contains(".COM"));
+        assert!(list.contains("xn--p1ai"));
+        assert!(list.contains("рф"));
+
+        assert!(!list.contains("zzz"));
+        assert!(!list.contains(""));
+        assert!(!list.contains("co.ch"));
+    }
+}
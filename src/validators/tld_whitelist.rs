@@ -0,0 +1,280 @@
+use std::iter::Peekable;
+use std::path::{Path, PathBuf};
+use std::str::Chars;
+use std::sync::{Arc, RwLock};
+
+use super::{UrlValidator, UrlValidatorBuilder, ValidationError};
+
+/// Top level whitelist loaded from a file, compiled once into an [`UrlValidator`] and reloadable
+/// at runtime.
+///
+/// The whitelist is meant to be shared across threads, e.g. in an `Arc`: [`TldWhitelist::reload`]
+/// compiles the new list and swaps it atomically, the urls being validated meanwhile use the
+/// previous one. A list failing to load leaves the current one in place.
+///
+/// A list is either one top level domain per line, with `#` comments, or a JSON array of
+/// strings. The leading full stop of the domains is optional.
+///
+/// # Examples
+/// ``` ignore
+/// let whitelist = Arc::new(TldWhitelist::from_file("whitelist.txt")?);
+/// assert!(whitelist.validate("https://test.ch"));
+///
+/// // after the file changed
+/// whitelist.reload()?;
+/// ```
+#[derive(Debug)]
+pub struct TldWhitelist {
+    builder: UrlValidatorBuilder,
+    path: Option<PathBuf>,
+    compiled: RwLock<Compiled>,
+}
+
+#[derive(Debug, Clone)]
+struct Compiled {
+    tlds: Vec<String>,
+    validator: Arc<UrlValidator>,
+}
+
+impl TldWhitelist {
+    /// Parse a whitelist, see [`TldWhitelist`] for the formats.
+    ///
+    /// # Errors
+    /// If the list is empty or malformed, or a top level domain is invalid, an error telling the
+    /// line number is returned.
+    pub fn parse(list: &str) -> Result<TldWhitelist, ValidationError> {
+        TldWhitelist::parse_with(list, UrlValidator::builder())
+    }
+
+    /// Parse a whitelist applied on top of the options of a builder, e.g. to allow ports. Its
+    /// top level rules also apply to the whitelist.
+    ///
+    /// # Errors
+    /// See [`TldWhitelist::parse`], the options of the builder must also be valid.
+    pub fn parse_with(list: &str, builder: UrlValidatorBuilder) -> Result<TldWhitelist, ValidationError> {
+        let compiled = compile(list, &builder)?;
+        Ok(TldWhitelist { builder, path: None, compiled: RwLock::new(compiled) })
+    }
+
+    /// Read and parse a whitelist from a file, see [`TldWhitelist::parse`]. The file is read
+    /// again by [`TldWhitelist::reload`].
+    ///
+    /// # Errors
+    /// If the file could not be read or the list is invalid.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<TldWhitelist, ValidationError> {
+        TldWhitelist::from_file_with(path, UrlValidator::builder())
+    }
+
+    /// Read and parse a whitelist from a file, applied on top of the options of a builder, see
+    /// [`TldWhitelist::parse_with`].
+    ///
+    /// # Errors
+    /// If the file could not be read or the list is invalid.
+    pub fn from_file_with<P: AsRef<Path>>(path: P, builder: UrlValidatorBuilder) -> Result<TldWhitelist, ValidationError> {
+        let path = path.as_ref().to_path_buf();
+        let list = std::fs::read_to_string(&path)?;
+        let compiled = compile(&list, &builder)?;
+        Ok(TldWhitelist { builder, path: Some(path), compiled: RwLock::new(compiled) })
+    }
+
+    /// Read the file of the whitelist again and replace the list.
+    ///
+    /// # Errors
+    /// If the whitelist was not read from a file, the file could not be read or the new list is
+    /// invalid. The current list is kept.
+    pub fn reload(&self) -> Result<(), ValidationError> {
+        let path = self.path.as_ref().ok_or_else(|| ValidationError::option("The white list has no file."))?;
+        let list = std::fs::read_to_string(path)?;
+        self.update(&list)
+    }
+
+    /// Replace the list with a new one, see [`TldWhitelist::parse`].
+    ///
+    /// # Errors
+    /// If the new list is invalid. The current list is kept.
+    pub fn update(&self, list: &str) -> Result<(), ValidationError> {
+        let compiled = compile(list, &self.builder)?;
+        *self.compiled.write().unwrap() = compiled;
+        Ok(())
+    }
+
+    /// Validate an url against the current list.
+    pub fn validate(&self, url: &str) -> bool {
+        self.validator().validate(url)
+    }
+
+    /// The validator of the current list, kept valid across reloads.
+    pub fn validator(&self) -> Arc<UrlValidator> {
+        self.compiled.read().unwrap().validator.clone()
+    }
+
+    /// The top level domains of the current list, with their leading full stop.
+    pub fn tlds(&self) -> Vec<String> {
+        self.compiled.read().unwrap().tlds.clone()
+    }
+}
+
+/// Parse a list and build its validator.
+fn compile(list: &str, builder: &UrlValidatorBuilder) -> Result<Compiled, ValidationError> {
+    let entries = if list.trim_start().starts_with('[') { parse_json(list).map_err(ValidationError::InvalidOption)? } else { parse_lines(list) };
+    if entries.is_empty() {
+        return Err(ValidationError::EmptyWhitelist { list: "white list" });
+    }
+
+    let tlds: Vec<String> = entries
+        .iter()
+        .map(|(_, tld)| if tld.starts_with('.') { tld.clone() } else { format!(".{}", tld) })
+        .collect();
+
+    // The entries are only checked one by one to locate an error
+    match builder.clone().top_level_whitelist(&tlds).build() {
+        Ok(validator) => Ok(Compiled { tlds, validator: Arc::new(validator) }),
+        Err(e) => match entries.iter().zip(&tlds).find(|(_, tld)| builder.clone().top_level_whitelist([tld]).build().is_err()) {
+            Some(((line, _), _)) => Err(ValidationError::InvalidOption(format!("Invalid top level domain at line {}.", line))),
+            None => Err(e),
+        },
+    }
+}
+
+/// Entries of a list with one top level domain per line, with their line number.
+fn parse_lines(list: &str) -> Vec<(usize, String)> {
+    list.lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(|(line, tld)| (line, tld.to_string()))
+        .collect()
+}
+
+/// Entries of a JSON array of strings, with their line number.
+fn parse_json(list: &str) -> Result<Vec<(usize, String)>, String> {
+    let mut entries = Vec::new();
+    let mut line = 1;
+    let mut chars = list.chars().peekable();
+    let error = |line| format!("Invalid JSON at line {}.", line);
+
+    skip_whitespace(&mut chars, &mut line);
+    if chars.next() != Some('[') {
+        return Err(error(line));
+    }
+    skip_whitespace(&mut chars, &mut line);
+
+    if chars.peek() == Some(&']') {
+        chars.next();
+    } else {
+        loop {
+            if chars.next() != Some('"') {
+                return Err(error(line));
+            }
+
+            let mut entry = String::new();
+            loop {
+                match chars.next() {
+                    None | Some('\n') => return Err(error(line)),
+                    Some('"') => break,
+                    Some('\\') => match chars.next() {
+                        Some(c @ ('"' | '\\' | '/')) => entry.push(c),
+                        Some('u') => {
+                            let digits: String = chars.by_ref().take(4).collect();
+                            let c = u32::from_str_radix(&digits, 16).ok().and_then(char::from_u32).ok_or_else(|| error(line))?;
+                            entry.push(c);
+                        }
+                        _ => return Err(error(line)),
+                    },
+                    Some(c) => entry.push(c),
+                }
+            }
+            entries.push((line, entry));
+
+            skip_whitespace(&mut chars, &mut line);
+            match chars.next() {
+                Some(',') => skip_whitespace(&mut chars, &mut line),
+                Some(']') => break,
+                _ => return Err(error(line)),
+            }
+        }
+    }
+
+    skip_whitespace(&mut chars, &mut line);
+    match chars.next() {
+        None => Ok(entries),
+        Some(_) => Err(error(line)),
+    }
+}
+
+/// Skip the whitespace of a JSON list, counting the lines.
+fn skip_whitespace(chars: &mut Peekable<Chars>, line: &mut usize) {
+    while let Some(&c) = chars.peek().filter(|c| c.is_whitespace()) {
+        if c == '\n' {
+            *line += 1;
+        }
+        chars.next();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::thread;
+
+    use crate::{TldWhitelist, UrlValidator};
+
+    #[test]
+    fn parse_whitelists() {
+        let whitelist = TldWhitelist::parse("# allowed\n.ch\n\ncom\n").unwrap();
+        assert_eq!(whitelist.tlds(), [".ch", ".com"]);
+        assert!(whitelist.validate("https://test.ch"));
+        assert!(whitelist.validate("test.com"));
+        assert!(!whitelist.validate("test.org"));
+
+        let whitelist = TldWhitelist::parse("[\n  \".ch\",\n  \"co\\u002euk\"\n]").unwrap();
+        assert_eq!(whitelist.tlds(), [".ch", ".co.uk"]);
+        assert!(TldWhitelist::parse("  [\".ch\"]  ").is_ok());
+    }
+
+    #[test]
+    fn invalid_whitelists() {
+        assert_eq!(TldWhitelist::parse("").unwrap_err().to_string(), "The white list is empty.");
+        assert_eq!(TldWhitelist::parse("[]").unwrap_err().to_string(), "The white list is empty.");
+        assert_eq!(TldWhitelist::parse(".ch\n# comment\n.c_m").unwrap_err().to_string(), "Invalid top level domain at line 3.");
+        assert_eq!(TldWhitelist::parse("[\n\".ch\",\n\".1\"\n]").unwrap_err().to_string(), "Invalid top level domain at line 3.");
+        assert_eq!(TldWhitelist::parse("[\n\".ch\"\n\".com\"]").unwrap_err().to_string(), "Invalid JSON at line 3.");
+        assert_eq!(TldWhitelist::parse("[\".ch\",]").unwrap_err().to_string(), "Invalid JSON at line 1.");
+        assert_eq!(TldWhitelist::parse("[\".ch\"] x").unwrap_err().to_string(), "Invalid JSON at line 1.");
+        assert!(TldWhitelist::parse("[\".ch").is_err());
+
+        // the whitelist follows the top level rules of the builder
+        assert!(TldWhitelist::parse(".c0m").is_err());
+        let builder = UrlValidator::builder().top_level_charset(crate::TopLevelCharset::Alphanumeric).ports(true);
+        let whitelist = TldWhitelist::parse_with(".c0m", builder).unwrap();
+        assert!(whitelist.validate("test.c0m:8080"));
+    }
+
+    #[test]
+    fn reload_whitelists() {
+        let path = std::env::temp_dir().join(format!("tld_whitelist_{}.txt", std::process::id()));
+        std::fs::write(&path, ".ch\n").unwrap();
+
+        let whitelist = Arc::new(TldWhitelist::from_file(&path).unwrap());
+        let validator = whitelist.validator();
+        assert!(whitelist.validate("test.ch"));
+
+        std::fs::write(&path, ".com\n").unwrap();
+        let reloader = Arc::clone(&whitelist);
+        thread::spawn(move || reloader.reload().unwrap()).join().unwrap();
+        assert!(whitelist.validate("test.com"));
+        assert!(!whitelist.validate("test.ch"));
+
+        // validators obtained before stay valid
+        assert!(validator.validate("test.ch"));
+
+        // an invalid list keeps the current one
+        std::fs::write(&path, ".c_m\n").unwrap();
+        assert_eq!(whitelist.reload().unwrap_err().to_string(), "Invalid top level domain at line 1.");
+        assert!(whitelist.validate("test.com"));
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(whitelist.reload().is_err());
+        assert!(TldWhitelist::parse(".ch").unwrap().reload().is_err());
+    }
+}
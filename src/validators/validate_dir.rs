@@ -0,0 +1,185 @@
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+use super::{FileValidator, ValidatedFile, ValidationError};
+
+/// Options of [`validate_dir`].
+#[derive(Debug, Clone)]
+pub struct DirOptions {
+    /// Validator of the files, any image or video without extension check by default
+    pub validator: FileValidator,
+    /// Whether the files of the subdirectories are validated too, `false` by default
+    pub recursive: bool,
+}
+
+impl Default for DirOptions {
+    fn default() -> Self {
+        DirOptions { validator: FileValidator::builder().build().unwrap(), recursive: false }
+    }
+}
+
+/// File of a directory validated by [`validate_dir`].
+#[derive(Debug)]
+pub struct DirEntryReport {
+    /// Path of the file, inside the validated directory
+    pub path: PathBuf,
+    /// The validated file, or the error if it could not be read or its type is unknown
+    pub result: Result<ValidatedFile, ValidationError>,
+}
+
+/// Counts of the files validated by [`validate_dir`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirSummary {
+    /// Number of valid files
+    pub valid: usize,
+    /// Number of invalid files
+    pub invalid: usize,
+    /// Number of files which could not be read or whose type is unknown
+    pub errors: usize,
+    /// Number of bytes inspected in the validated files
+    pub bytes_inspected: u64,
+}
+
+impl DirSummary {
+    /// Number of validated files.
+    pub fn total(&self) -> usize {
+        self.valid + self.invalid + self.errors
+    }
+}
+
+/// Result of [`validate_dir`].
+#[derive(Debug)]
+pub struct DirReport {
+    /// The files, in the order of their paths
+    pub files: Vec<DirEntryReport>,
+    /// Counts of the files
+    pub summary: DirSummary,
+}
+
+/// Validate every file of a directory, e.g. to bulk-import an existing media library.
+///
+/// With [`DirOptions::recursive`], the subdirectories are walked too. The symbolic links to
+/// files are validated but the ones to directories are not followed, so that a link can't loop.
+/// The other special files are skipped. With the `parallel` feature, the files are split in one
+/// chunk per available cpu, each validated by its own thread.
+///
+/// # Errors
+/// If a directory could not be read.
+///
+/// # Examples
+/// ``` ignore
+/// let report = validate_dir("library", &DirOptions { recursive: true, ..Default::default() })?;
+/// for file in report.files.iter().filter(|file| !file.result.as_ref().is_ok_and(ValidatedFile::is_valid)) {
+///     println!("Rejected {}", file.path.display());
+/// }
+/// println!("{} valid files of {}", report.summary.valid, report.summary.total());
+/// ```
+pub fn validate_dir<P: AsRef<Path>>(path: P, options: &DirOptions) -> Result<DirReport, ValidationError> {
+    let mut paths = Vec::new();
+    list_files(path.as_ref(), options.recursive, &mut paths)?;
+    paths.sort();
+
+    let files = validate_paths(paths, &options.validator);
+
+    let mut summary = DirSummary::default();
+    for file in &files {
+        match &file.result {
+            Ok(validated) => {
+                if validated.is_valid() {
+                    summary.valid += 1;
+                } else {
+                    summary.invalid += 1;
+                }
+                summary.bytes_inspected += validated.bytes_inspected as u64;
+            }
+            Err(_) => summary.errors += 1,
+        }
+    }
+    Ok(DirReport { files, summary })
+}
+
+/// Collect the paths of the files of a directory.
+fn list_files(dir: &Path, recursive: bool, paths: &mut Vec<PathBuf>) -> Result<(), Error> {
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // The type of the entry itself, a link to a directory is not a directory
+        if entry.file_type()?.is_dir() {
+            if recursive {
+                list_files(&path, recursive, paths)?;
+            }
+        } else if path.is_file() {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn validate_path(path: PathBuf, validator: &FileValidator) -> DirEntryReport {
+    let result = validator.validate(&path);
+    DirEntryReport { path, result }
+}
+
+#[cfg(not(feature = "parallel"))]
+fn validate_paths(paths: Vec<PathBuf>, validator: &FileValidator) -> Vec<DirEntryReport> {
+    paths.into_iter().map(|path| validate_path(path, validator)).collect()
+}
+
+#[cfg(feature = "parallel")]
+fn validate_paths(paths: Vec<PathBuf>, validator: &FileValidator) -> Vec<DirEntryReport> {
+    let threads = std::thread::available_parallelism().map_or(1, |threads| threads.get());
+    let chunk_size = paths.len().div_ceil(threads).max(1);
+
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = paths
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(|path| validate_path(path.clone(), validator)).collect::<Vec<_>>()))
+            .collect();
+        handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::{validate_dir, DirOptions, DirSummary, FileKind, FileValidator};
+
+    const TEST_DIR: &str = "test_files";
+
+    #[test]
+    fn flat_directory() {
+        let report = validate_dir(TEST_DIR, &DirOptions::default()).unwrap();
+        assert_eq!(report.files.len(), 13);
+        assert_eq!((report.summary.valid, report.summary.invalid, report.summary.errors), (11, 2, 0));
+        assert_eq!(report.files[0].path, Path::new(TEST_DIR).join("invalid_ext_image.jpg.png"));
+        assert_eq!(report.files[4].path, Path::new(TEST_DIR).join("invalid_file.pdf"));
+        assert_eq!(report.files[4].result.as_ref().unwrap().kind, FileKind::Invalid);
+
+        // the extension is checked on the path
+        let options = DirOptions { validator: FileValidator::builder().check_extension(true).build().unwrap(), recursive: false };
+        let summary = validate_dir(TEST_DIR, &options).unwrap().summary;
+        assert_eq!((summary.valid, summary.invalid), (7, 6));
+    }
+
+    #[test]
+    fn recursive_directory() {
+        let options = DirOptions { recursive: true, ..Default::default() };
+        let report = validate_dir(TEST_DIR, &options).unwrap();
+        assert_eq!(report.files.len(), 18);
+        assert_eq!(report.summary.total(), 18);
+
+        // the corpus lists and manifest are text files of unknown type
+        let errors: Vec<_> = report.files.iter().filter(|file| file.result.is_err()).map(|file| &file.path).collect();
+        assert_eq!(errors.len(), 5);
+        assert!(errors.iter().all(|path| path.starts_with(Path::new(TEST_DIR).join("corpus"))));
+
+        assert!(validate_dir("missing", &options).is_err());
+        let empty = std::env::temp_dir().join(format!("validate_dir_{}", std::process::id()));
+        std::fs::create_dir_all(&empty).unwrap();
+        let report = validate_dir(&empty, &options).unwrap();
+        std::fs::remove_dir(&empty).unwrap();
+        assert!(report.files.is_empty());
+        assert_eq!(report.summary, DirSummary::default());
+    }
+}
@@ -1,116 +1,1830 @@
-use regex::Regex;
-use std::io::{Error, ErrorKind};
+use infer::MatcherType;
+use lazy_static::lazy_static;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File, FileType};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use super::quarantine::Quarantine;
+use super::scanner::{ContentScanner, ScanVerdict};
+use super::blake3::{self, Blake3};
+use super::sha256::{self, Sha256};
+use super::{find_denied_extension, is_svg, sanitize_svg, SvgMode, ValidationError, DANGEROUS_EXTENSIONS};
+#[cfg(all(feature = "mmap", unix))]
+use super::mmap::Mmap;
+#[cfg(feature = "deep")]
+use super::validate_upload_path;
+#[cfg(feature = "deep")]
+use super::archive::{self, ArchiveEntry, ArchiveError, ArchiveInfo, ArchiveLimit, ArchiveLimits};
+#[cfg(feature = "deep")]
+use super::image_header::{self, ImageInfo, ImageLimits};
+#[cfg(feature = "deep")]
+use super::polyglot;
+#[cfg(feature = "deep")]
+use super::video_container::{self, VideoInfo};
+
+/// Length of the prefix of a file read by default for the magic numbers detection, enough for
+/// every file type known by infer.
+pub const MAGIC_PREFIX_LENGTH: usize = 8192;
+
+/// Mime type of the SVG images, accepted with [`FileValidatorBuilder::allow_svg`].
+pub const SVG_MIME_TYPE: &str = "image/svg+xml";
+
+/// Mime type of the JPEG XL images, unknown to infer.
+pub const JXL_MIME_TYPE: &str = "image/jxl";
+
+/// Mime types of the modern image formats, accepted unless excluded with
+/// [`FileValidatorBuilder::allow_modern_images`]: the HEIF images of the cameras (HEIC), AVIF,
+/// JPEG XL and WebP images.
+pub const MODERN_IMAGE_MIME_TYPES: &[&str] = &["image/heif", "image/avif", JXL_MIME_TYPE, "image/webp"];
+
+/// Brands of the HEIF images coded with HEVC, infer only detecting the `heic` one.
+const HEIF_BRANDS: &[&[u8]] = &[b"heic", b"heix", b"hevc", b"hevx", b"heim", b"heis"];
+
+/// Other extensions commonly used for the extensions reported by infer.
+const EXTENSION_ALIASES: &[(&str, &[&str])] = &[
+    ("jpg", &["jpeg", "jpe"]),
+    ("tif", &["tiff"]),
+    ("heif", &["heic", "hif", "heics", "heifs"]),
+    ("avif", &["avifs"]),
+    ("mpg", &["mpeg"]),
+    ("mov", &["qt"]),
+    ("html", &["htm"]),
+    ("gz", &["gzip"]),
+];
+
+/// Kind of a validated file, following the matcher types of infer, to which kinds may be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FileKind {
+    /// The file is valid and it is an image
+    Image,
+    /// The file is valid and it is a video
+    Video,
+    /// The file is valid and it is an audio file
+    Audio,
+    /// The file is valid and it is an archive, PDF files included
+    Archive,
+    /// The file is valid and it is an office document
+    Document,
+    /// The file is valid and it is an electronic book
+    Book,
+    /// The file is valid and it is a font
+    Font,
+    /// The file is valid and it is an executable or a library
+    Application,
+    /// The file is valid and it is a text file with a known signature, e.g. html
+    Text,
+    /// The file type is not allowed, or its extension doesn't match its contents
+    Invalid,
+}
+
+impl FileKind {
+    /// Kind of the files detected with a matcher type, `None` for the custom matchers.
+    fn from_matcher_type(matcher_type: MatcherType) -> Option<FileKind> {
+        match matcher_type {
+            MatcherType::Image => Some(FileKind::Image),
+            MatcherType::Video => Some(FileKind::Video),
+            MatcherType::Audio => Some(FileKind::Audio),
+            MatcherType::Archive => Some(FileKind::Archive),
+            MatcherType::Doc => Some(FileKind::Document),
+            MatcherType::Book => Some(FileKind::Book),
+            MatcherType::Font => Some(FileKind::Font),
+            MatcherType::App => Some(FileKind::Application),
+            MatcherType::Text => Some(FileKind::Text),
+            MatcherType::Custom => None,
+        }
+    }
+}
+
+/// Magic numbers of a format unknown to infer, registered with
+/// [`FileValidatorBuilder::custom_matcher`].
+///
+/// # Examples
+/// ``` ignore
+/// // HEIF image sequences
+/// let matcher = CustomMatcher::new(b"ftypmsf1", "image/heif-sequence", "heifs", MatcherType::Image).offset(4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomMatcher {
+    offset: usize,
+    magic: Vec<u8>,
+    mime_type: &'static str,
+    extension: &'static str,
+    matcher_type: MatcherType,
+}
+
+impl CustomMatcher {
+    /// Match the files starting with the magic bytes, detected with a mime type, an extension
+    /// without full stop and the matcher type they belong to.
+    pub fn new(magic: &[u8], mime_type: &'static str, extension: &'static str, matcher_type: MatcherType) -> Self {
+        CustomMatcher { offset: 0, magic: magic.to_vec(), mime_type, extension, matcher_type }
+    }
+
+    /// Look for the magic bytes at an offset from the start of the file, 0 by default.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Whether the contents match the magic bytes.
+    fn matches(&self, contents: &[u8]) -> bool {
+        contents.get(self.offset..).is_some_and(|contents| contents.starts_with(&self.magic))
+    }
+}
+
+/// File validated by [`validate_file`], with the details of its detection, e.g. to log why an
+/// upload was rejected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedFile {
+    /// Kind of the file
+    pub kind: FileKind,
+    /// Mime type detected from the contents, e.g. `image/png`
+    pub mime_type: &'static str,
+    /// Extension matching the contents, without its full stop, e.g. `png`
+    pub extension: &'static str,
+    /// Category of the detected type
+    pub matcher_type: MatcherType,
+    /// Whether the filename ends with the extension, `None` if the extension was not checked
+    pub extension_matched: Option<bool>,
+    /// Denied extension found in the filename, e.g. `php` for `invoice.php.jpg`, `None` if the
+    /// filename was not checked or has none
+    pub denied_extension: Option<String>,
+    /// Number of bytes inspected to detect the type
+    pub bytes_inspected: usize,
+    /// Header of the image parsed by the deep validation, `None` if not requested or not an
+    /// image
+    #[cfg(feature = "deep")]
+    pub image: Option<ImageInfo>,
+    /// Container of the video parsed by the deep validation, `None` if not requested or not a
+    /// video
+    #[cfg(feature = "deep")]
+    pub video: Option<VideoInfo>,
+    /// Entries of the archive inspected by the deep validation, `None` if not requested or not a
+    /// ZIP archive
+    #[cfg(feature = "deep")]
+    pub archive: Option<ArchiveInfo>,
+    /// Verdict of the content scanners, the first one which is not clean, `None` if no scanner is
+    /// configured or the file was invalid before being scanned
+    pub scan: Option<ScanVerdict>,
+    /// Path of the file in the quarantine directory, `None` if no quarantine is configured or
+    /// the file is valid
+    pub quarantined: Option<PathBuf>,
+    /// SHA-256 digest of the whole contents, `None` if not requested
+    pub sha256: Option<[u8; 32]>,
+    /// BLAKE3 digest of the whole contents, `None` if not requested
+    pub blake3: Option<[u8; 32]>,
+}
+
+impl ValidatedFile {
+    /// Whether the file is valid, i.e. of an allowed type.
+    pub fn is_valid(&self) -> bool {
+        self.kind != FileKind::Invalid
+    }
+}
+
+/// Error of a file which is not a regular file, rejected by [`FileValidator::validate`] before
+/// being opened, e.g. a link to `/dev/zero` which would never end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileTypeError {
+    /// A symbolic link, not followed as configured by [`FileValidatorBuilder::follow_symlinks`]
+    Symlink,
+    /// A directory
+    Directory,
+    /// A named pipe, whose reads wait for a writer
+    Fifo,
+    /// A character or block device, e.g. `/dev/zero` or a disk
+    Device,
+    /// A unix domain socket
+    Socket,
+    /// Any other special file
+    Other,
+}
+
+impl FileTypeError {
+    /// Error of a file type, `None` for a regular file.
+    fn of(file_type: FileType) -> Option<FileTypeError> {
+        if file_type.is_file() {
+            None
+        } else if file_type.is_symlink() {
+            Some(FileTypeError::Symlink)
+        } else if file_type.is_dir() {
+            Some(FileTypeError::Directory)
+        } else {
+            Some(FileTypeError::special(file_type))
+        }
+    }
+
+    #[cfg(unix)]
+    fn special(file_type: FileType) -> FileTypeError {
+        use std::os::unix::fs::FileTypeExt;
+
+        if file_type.is_fifo() {
+            FileTypeError::Fifo
+        } else if file_type.is_char_device() || file_type.is_block_device() {
+            FileTypeError::Device
+        } else if file_type.is_socket() {
+            FileTypeError::Socket
+        } else {
+            FileTypeError::Other
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn special(_: FileType) -> FileTypeError {
+        FileTypeError::Other
+    }
+}
+
+impl Display for FileTypeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FileTypeError::Symlink => write!(f, "The file is a symbolic link, which is not followed."),
+            FileTypeError::Directory => write!(f, "The file is a directory."),
+            FileTypeError::Fifo => write!(f, "The file is a named pipe."),
+            FileTypeError::Device => write!(f, "The file is a device."),
+            FileTypeError::Socket => write!(f, "The file is a socket."),
+            FileTypeError::Other => write!(f, "The file is not a regular file."),
+        }
+    }
+}
+
+impl std::error::Error for FileTypeError {}
+
+/// File validator configured through a [`FileValidatorBuilder`].
+///
+/// # Examples
+/// ``` ignore
+/// let validator = FileValidator::builder()
+///     .allow_mime(["image/png", "image/jpeg", "video/mp4"])
+///     .check_extension(true)
+///     .build()?;
+///
+/// assert!(validator.validate("myDir/myImage.png")?.is_valid());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FileValidator {
+    allowed_matcher_types: Vec<MatcherType>,
+    allowed_mime_types: Option<Vec<String>>,
+    check_extension: bool,
+    extension_aliases: Vec<(String, String)>,
+    denied_extensions: Option<Vec<String>>,
+    custom_matchers: Vec<CustomMatcher>,
+    scanners: Vec<Arc<dyn ContentScanner>>,
+    prefix_length: usize,
+    allow_svg: bool,
+    allow_modern_images: bool,
+    sha256: bool,
+    blake3: bool,
+    follow_symlinks: bool,
+    quarantine: Option<Quarantine>,
+    #[cfg(feature = "deep")]
+    deep: bool,
+    #[cfg(feature = "deep")]
+    image_limits: ImageLimits,
+    #[cfg(feature = "deep")]
+    reject_polyglots: bool,
+    #[cfg(feature = "deep")]
+    inspect_archives: bool,
+    #[cfg(feature = "deep")]
+    archive_limits: ArchiveLimits,
+}
+
+/// Builder of a [`FileValidator`].
+#[derive(Debug, Clone)]
+pub struct FileValidatorBuilder {
+    allowed_matcher_types: Vec<MatcherType>,
+    allowed_mime_types: Option<Vec<String>>,
+    check_extension: bool,
+    extension_aliases: Vec<(String, String)>,
+    denied_extensions: Option<Vec<String>>,
+    custom_matchers: Vec<CustomMatcher>,
+    scanners: Vec<Arc<dyn ContentScanner>>,
+    prefix_length: usize,
+    allow_svg: bool,
+    allow_modern_images: bool,
+    sha256: bool,
+    blake3: bool,
+    follow_symlinks: bool,
+    quarantine: Option<Quarantine>,
+    #[cfg(feature = "deep")]
+    deep: bool,
+    #[cfg(feature = "deep")]
+    image_limits: ImageLimits,
+    #[cfg(feature = "deep")]
+    reject_polyglots: bool,
+    #[cfg(feature = "deep")]
+    inspect_archives: bool,
+    #[cfg(feature = "deep")]
+    archive_limits: ArchiveLimits,
+}
+
+impl Default for FileValidatorBuilder {
+    fn default() -> Self {
+        FileValidatorBuilder {
+            allowed_matcher_types: vec![MatcherType::Image, MatcherType::Video],
+            allowed_mime_types: None,
+            check_extension: false,
+            extension_aliases: Vec::new(),
+            denied_extensions: None,
+            custom_matchers: Vec::new(),
+            scanners: Vec::new(),
+            prefix_length: MAGIC_PREFIX_LENGTH,
+            allow_svg: false,
+            allow_modern_images: true,
+            sha256: false,
+            blake3: false,
+            follow_symlinks: true,
+            quarantine: None,
+            #[cfg(feature = "deep")]
+            deep: false,
+            #[cfg(feature = "deep")]
+            image_limits: ImageLimits::default(),
+            #[cfg(feature = "deep")]
+            reject_polyglots: false,
+            #[cfg(feature = "deep")]
+            inspect_archives: false,
+            #[cfg(feature = "deep")]
+            archive_limits: ArchiveLimits::default(),
+        }
+    }
+}
+
+impl FileValidatorBuilder {
+    /// Accept the files of the matcher types inside the whitelist instead of the images and
+    /// videos, e.g. `[MatcherType::Image, MatcherType::Audio]`. infer detects PDF files as
+    /// archives.
+    pub fn allowed_matcher_types<I>(mut self, matcher_types: I) -> Self
+    where
+        I: IntoIterator<Item = MatcherType>,
+    {
+        self.allowed_matcher_types = matcher_types.into_iter().collect();
+        self
+    }
+
+    /// Only accept the files of the allowed matcher types whose detected mime type is inside the
+    /// whitelist, e.g. `["image/png", "video/mp4"]`. The mime types are not case sensitive.
+    pub fn allow_mime<I, S>(mut self, mime_types: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_mime_types = Some(mime_types.into_iter().map(|mime| mime.as_ref().to_lowercase()).collect());
+        self
+    }
+
+    /// Check that the extension of the filename, after its last full stop, is the extension of
+    /// the detected type or one of its aliases, e.g. `jpeg` for `jpg`. A filename without
+    /// extension, such as `image`, `.png` or `image.`, doesn't match.
+    pub fn check_extension(mut self, check: bool) -> Self {
+        self.check_extension = check;
+        self
+    }
+
+    /// Accept an alias for an extension reported by infer, in addition to the built-in ones
+    /// (`jpeg` and `jpe` for `jpg`, `tiff` for `tif`, `mpeg` for `mpg`, `qt` for `mov`, `htm` for
+    /// `html`, `gzip` for `gz`). The extensions are not case sensitive and have no full stop.
+    pub fn extension_alias(mut self, extension: &str, alias: &str) -> Self {
+        self.extension_aliases.push((extension.to_lowercase(), alias.to_lowercase()));
+        self
+    }
+
+    /// Detect the files matching magic numbers unknown to infer, e.g. in-house or camera RAW
+    /// formats. The custom matchers are tried in their registration order before infer, so they
+    /// take precedence over the built-in detection, and their files are accepted like the others
+    /// if their matcher type and mime type are allowed. The magic bytes must fit in the prefix
+    /// read from a stream. The deep validation rejects the custom images and videos, their
+    /// structure being unknown.
+    pub fn custom_matcher(mut self, matcher: CustomMatcher) -> Self {
+        self.custom_matchers.push(matcher);
+        self
+    }
+
+    /// Reject the files whose filename has an extension of [`DANGEROUS_EXTENSIONS`] anywhere in
+    /// it, e.g. `invoice.php.jpg` or `run.jpg.exe`, see [`ValidatedFile::denied_extension`]. A
+    /// file without declared name is not rejected.
+    pub fn reject_dangerous_extensions(mut self, reject: bool) -> Self {
+        self.denied_extensions = reject.then(|| DANGEROUS_EXTENSIONS.iter().map(|extension| extension.to_string()).collect());
+        self
+    }
+
+    /// Reject the files whose filename has an extension of the deny-list anywhere in it, instead
+    /// of [`DANGEROUS_EXTENSIONS`], see [`find_denied_extension`](crate::find_denied_extension).
+    /// The extensions are not case sensitive and have no full stop.
+    pub fn deny_extensions<I, S>(mut self, extensions: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.denied_extensions = Some(extensions.into_iter().map(|extension| extension.as_ref().to_lowercase()).collect());
+        self
+    }
+
+    /// Length of the prefix read for the magic numbers detection, [`MAGIC_PREFIX_LENGTH`] by
+    /// default.
+    pub fn prefix_length(mut self, length: usize) -> Self {
+        self.prefix_length = length;
+        self
+    }
+
+    /// Detect the SVG images, unknown to infer, as images of the [`SVG_MIME_TYPE`] mime type with
+    /// the `svg` extension. The SVG images with scripts, event handlers or external references
+    /// are rejected, see [`sanitize_svg`](crate::sanitize_svg). The whole file is read.
+    pub fn allow_svg(mut self, allow: bool) -> Self {
+        self.allow_svg = allow;
+        self
+    }
+
+    /// Accept the modern image formats of [`MODERN_IMAGE_MIME_TYPES`], `true` by default. The
+    /// JPEG XL images and the HEIF images of every HEVC brand are detected as images, and the
+    /// `heic` extension matches the HEIF images. A modern format listed with
+    /// [`FileValidatorBuilder::allow_mime`] is accepted whatever this option.
+    pub fn allow_modern_images(mut self, allow: bool) -> Self {
+        self.allow_modern_images = allow;
+        self
+    }
+
+    /// Compute the SHA-256 digest of the whole contents in the same pass as the validation, see
+    /// [`ValidatedFile::sha256`]. A stream is read to its end, past the prefix inspected for the
+    /// magic numbers.
+    pub fn sha256(mut self, sha256: bool) -> Self {
+        self.sha256 = sha256;
+        self
+    }
+
+    /// Compute the BLAKE3 digest of the whole contents in the same pass as the validation, see
+    /// [`ValidatedFile::blake3`], as for [`FileValidatorBuilder::sha256`].
+    pub fn blake3(mut self, blake3: bool) -> Self {
+        self.blake3 = blake3;
+        self
+    }
+
+    /// Follow the symbolic links validated by path, `true` by default. Without, a link is
+    /// rejected with [`FileTypeError::Symlink`], e.g. an upload directory where a link could
+    /// expose a file of the server. The target of a followed link must still be a regular file.
+    pub fn follow_symlinks(mut self, follow: bool) -> Self {
+        self.follow_symlinks = follow;
+        self
+    }
+
+    /// Put the files rejected by [`FileValidator::validate`] in a quarantine directory, with a
+    /// sidecar telling why, see [`ValidatedFile::quarantined`]. The invalid files and the files
+    /// failing the validation of their contents, e.g. of an unknown type, are put in quarantine,
+    /// not the ones which could not be read or are not regular files.
+    pub fn quarantine(mut self, quarantine: Quarantine) -> Self {
+        self.quarantine = Some(quarantine);
+        self
+    }
+
+    /// Parse the headers of the allowed images (PNG, JPEG, GIF, BMP and WebP) and check their
+    /// structure, so that truncated or crafted files are rejected, see [`ValidatedFile::image`].
+    /// The containers of the allowed videos (MP4, MOV, AVI, WebM and Matroska) must have a video
+    /// track, see [`ValidatedFile::video`]. The whole file is read and the other image and video
+    /// formats are rejected.
+    #[cfg(feature = "deep")]
+    pub fn deep(mut self, deep: bool) -> Self {
+        self.deep = deep;
+        self
+    }
+
+    /// Scan the valid files with an external scanner, e.g. an antivirus, in the order the
+    /// scanners are added. A file with a threat or a suspicion is [`FileKind::Invalid`] with the
+    /// verdict in [`ValidatedFile::scan`]; a scanner failing rejects the file with an error. The
+    /// whole file is read.
+    pub fn content_scanner<S: ContentScanner + 'static>(mut self, scanner: S) -> Self {
+        self.scanners.push(Arc::new(scanner));
+        self
+    }
+
+    /// Reject the images wider than a number of pixels in deep mode, no limit by default.
+    #[cfg(feature = "deep")]
+    pub fn max_image_width(mut self, max: u32) -> Self {
+        self.image_limits.max_width = Some(max);
+        self
+    }
+
+    /// Reject the images higher than a number of pixels in deep mode, no limit by default.
+    #[cfg(feature = "deep")]
+    pub fn max_image_height(mut self, max: u32) -> Self {
+        self.image_limits.max_height = Some(max);
+        self
+    }
+
+    /// Reject the images with more pixels than a maximum in deep mode, no limit by default. The
+    /// size of an image is known from its header, so decompression bombs are rejected before
+    /// being decoded.
+    #[cfg(feature = "deep")]
+    pub fn max_image_pixels(mut self, max: u64) -> Self {
+        self.image_limits.max_pixels = Some(max);
+        self
+    }
+
+    /// Reject the files embedding content of other formats, or with data after the end of their
+    /// image, in deep mode, see [`scan_polyglot`](crate::scan_polyglot).
+    #[cfg(feature = "deep")]
+    pub fn reject_polyglots(mut self, reject: bool) -> Self {
+        self.reject_polyglots = reject;
+        self
+    }
+
+    /// Inspect the entries of the allowed ZIP archives in deep mode, see
+    /// [`ValidatedFile::archive`]. The central directory is checked against the archive limits
+    /// before any entry is decompressed, then each entry is validated like a file with its name,
+    /// nested archives included, and the archive is rejected if an entry is not allowed, e.g. an
+    /// image bundle holding an executable. The entries of the RAR and 7z archives can't be read,
+    /// so these archives are rejected.
+    #[cfg(feature = "deep")]
+    pub fn inspect_archives(mut self, inspect: bool) -> Self {
+        self.inspect_archives = inspect;
+        self
+    }
+
+    /// Reject the archives with more entries than a maximum, 10000 by default.
+    #[cfg(feature = "deep")]
+    pub fn max_archive_entries(mut self, max: usize) -> Self {
+        self.archive_limits.max_entries = max;
+        self
+    }
+
+    /// Reject the archives whose entries are larger than a number of bytes once decompressed,
+    /// 1 GiB by default. The limit applies to each archive, a nested one included.
+    #[cfg(feature = "deep")]
+    pub fn max_archive_size(mut self, max: u64) -> Self {
+        self.archive_limits.max_size = max;
+        self
+    }
+
+    /// Reject the archives with an entry whose decompressed size exceeds its compressed size by
+    /// more than a ratio, 100 by default.
+    #[cfg(feature = "deep")]
+    pub fn max_compression_ratio(mut self, max: u64) -> Self {
+        self.archive_limits.max_ratio = max;
+        self
+    }
+
+    /// Reject the archives nested deeper than a number of levels, 2 by default: an archive of
+    /// images has a single level, an archive holding archives of images has two.
+    #[cfg(feature = "deep")]
+    pub fn max_archive_depth(mut self, max: usize) -> Self {
+        self.archive_limits.max_depth = max;
+        self
+    }
+
+    /// Build the validator.
+    ///
+    /// # Errors
+    /// If a whitelist or the extension deny-list is empty, the matcher types include
+    /// [`MatcherType::Custom`], a mime type is unknown to infer and to the custom matchers, an
+    /// extension alias or a denied extension is not alphanumeric, a custom matcher is invalid,
+    /// or the prefix length, an image limit or an archive limit is 0.
+    pub fn build(self) -> Result<FileValidator, ValidationError> {
+        if self.allowed_matcher_types.is_empty() {
+            return Err(ValidationError::EmptyWhitelist { list: "matcher type white list" });
+        }
+
+        if self.allowed_matcher_types.contains(&MatcherType::Custom) {
+            return Err(ValidationError::option("Invalid matcher type in white list."));
+        }
+
+        let is_extension = |extension: &str| !extension.is_empty() && extension.chars().all(|c| c.is_ascii_alphanumeric());
+        for matcher in &self.custom_matchers {
+            if matcher.magic.is_empty()
+                || matcher.matcher_type == MatcherType::Custom
+                || !matcher.mime_type.contains('/')
+                || !is_extension(matcher.extension)
+            {
+                return Err(ValidationError::option("Invalid custom matcher."));
+            }
+        }
+
+        if let Some(mime_types) = &self.allowed_mime_types {
+            if mime_types.is_empty() {
+                return Err(ValidationError::EmptyWhitelist { list: "mime type white list" });
+            }
+
+            let is_custom = |mime: &str| self.custom_matchers.iter().any(|matcher| matcher.mime_type.eq_ignore_ascii_case(mime));
+            if mime_types.iter().any(|mime| mime != SVG_MIME_TYPE && mime != JXL_MIME_TYPE && !is_custom(mime) && !infer::is_mime_supported(mime)) {
+                return Err(ValidationError::option("Invalid mime type in white list."));
+            }
+        }
+
+        if self.extension_aliases.iter().any(|(extension, alias)| !is_extension(extension) || !is_extension(alias)) {
+            return Err(ValidationError::option("Invalid extension alias."));
+        }
+
+        if let Some(extensions) = &self.denied_extensions {
+            if extensions.is_empty() {
+                return Err(ValidationError::EmptyWhitelist { list: "extension deny list" });
+            }
+
+            if !extensions.iter().all(|extension| is_extension(extension)) {
+                return Err(ValidationError::option("Invalid denied extension."));
+            }
+        }
+
+        if self.prefix_length == 0 {
+            return Err(ValidationError::option("Invalid prefix length."));
+        }
+
+        #[cfg(feature = "deep")]
+        {
+            let limits = &self.image_limits;
+            if limits.max_width == Some(0) || limits.max_height == Some(0) || limits.max_pixels == Some(0) {
+                return Err(ValidationError::option("Invalid image limit."));
+            }
+
+            let limits = &self.archive_limits;
+            if limits.max_entries == 0 || limits.max_size == 0 || limits.max_ratio == 0 || limits.max_depth == 0 {
+                return Err(ValidationError::option("Invalid archive limit."));
+            }
+        }
+
+        Ok(FileValidator {
+            allowed_matcher_types: self.allowed_matcher_types,
+            allowed_mime_types: self.allowed_mime_types,
+            check_extension: self.check_extension,
+            extension_aliases: self.extension_aliases,
+            denied_extensions: self.denied_extensions,
+            custom_matchers: self.custom_matchers,
+            scanners: self.scanners,
+            prefix_length: self.prefix_length,
+            allow_svg: self.allow_svg,
+            allow_modern_images: self.allow_modern_images,
+            sha256: self.sha256,
+            blake3: self.blake3,
+            follow_symlinks: self.follow_symlinks,
+            quarantine: self.quarantine,
+            #[cfg(feature = "deep")]
+            deep: self.deep,
+            #[cfg(feature = "deep")]
+            image_limits: self.image_limits,
+            #[cfg(feature = "deep")]
+            reject_polyglots: self.reject_polyglots,
+            #[cfg(feature = "deep")]
+            inspect_archives: self.inspect_archives,
+            #[cfg(feature = "deep")]
+            archive_limits: self.archive_limits,
+        })
+    }
+}
+
+impl FileValidator {
+    /// Create a builder accepting any image or video, without extension check.
+    pub fn builder() -> FileValidatorBuilder {
+        FileValidatorBuilder::default()
+    }
+
+    /// Validate a file from its path, see [`validate_file`].
+    ///
+    /// Only the regular files are opened: the directories, named pipes, devices and sockets are
+    /// rejected with a [`FileTypeError`], and the symbolic links too unless followed, see
+    /// [`FileValidatorBuilder::follow_symlinks`].
+    ///
+    /// With a [`Quarantine`], a rejected file is put in quarantine; the error of a file rejected
+    /// with an error is returned once the file is put in quarantine.
+    ///
+    /// # Errors
+    /// If the file could not be found or opened, is not a regular file (a
+    /// [`ValidationError::FileType`]), its type is unknown, its contents are invalid, or it could
+    /// not be put in quarantine.
+    pub fn validate<P: AsRef<Path>>(&self, filename: P) -> Result<ValidatedFile, ValidationError> {
+        let filename = filename.as_ref();
+        let result = self.open(filename).and_then(|file| self.validate_stream(file, Some(filename)));
+        self.quarantine_rejected(filename, result)
+    }
+
+    /// Validate a file from its path as [`FileValidator::validate`], the file being mapped in
+    /// memory instead of being copied in a buffer: only the pages inspected are read, e.g. the
+    /// boxes of a multi-gigabyte video with the deep validation.
+    ///
+    /// # Safety
+    /// The file must be neither truncated nor written until the validation returns, by this
+    /// process or another one of any user allowed to write it. Reading a page past the new end
+    /// of a truncated file raises `SIGBUS`, which kills the process, and a written page changes
+    /// the contents while they are validated. Only map the files no one else can change, e.g.
+    /// the uploads moved to a directory only the process can write.
+    ///
+    /// # Errors
+    /// Same as [`FileValidator::validate`], or if the file could not be mapped.
+    #[cfg(all(feature = "mmap", unix))]
+    pub unsafe fn validate_mapped<P: AsRef<Path>>(&self, filename: P) -> Result<ValidatedFile, ValidationError> {
+        let filename = filename.as_ref();
+        let result = self.open(filename).and_then(|file| {
+            // SAFETY: the file is unchanged while mapped per the contract of this function
+            let map = unsafe { Mmap::map(&file) }?;
+            self.validate_contents(&map, Some(filename))
+        });
+        self.quarantine_rejected(filename, result)
+    }
+
+    /// Put a file in quarantine if it is rejected, see [`FileValidator::validate`].
+    fn quarantine_rejected(&self, filename: &Path, result: Result<ValidatedFile, ValidationError>) -> Result<ValidatedFile, ValidationError> {
+        let Some(quarantine) = &self.quarantine else {
+            return result;
+        };
+
+        match result {
+            Ok(mut file) if !file.is_valid() => {
+                let quarantined = quarantine.quarantine(filename, &rejection_reason(&file), Some(file.mime_type))?;
+                file.quarantined = Some(quarantined.path);
+                Ok(file)
+            }
+            // The contents are rejected, not the reading of the file
+            Err(error @ (ValidationError::UnknownFileType | ValidationError::Contents(_) | ValidationError::Scanner(_))) => {
+                quarantine.quarantine(filename, &error.to_string(), None)?;
+                Err(error)
+            }
+            result => result,
+        }
+    }
+
+    /// Open a regular file to validate, rejecting the other types of files. The type is checked
+    /// on the opened file, which can't be replaced by a link or a special file after the check.
+    fn open(&self, filename: &Path) -> Result<File, ValidationError> {
+        let file = self.open_file(filename)?;
+        if let Some(error) = FileTypeError::of(file.metadata()?.file_type()) {
+            return Err(error.into());
+        }
+        Ok(file)
+    }
+
+    /// Open a file without waiting for the writer of a named pipe, nor following a symbolic link
+    /// unless configured.
+    #[cfg(unix)]
+    fn open_file(&self, filename: &Path) -> Result<File, ValidationError> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let flags = if self.follow_symlinks { libc::O_NONBLOCK } else { libc::O_NONBLOCK | libc::O_NOFOLLOW };
+        fs::OpenOptions::new().read(true).custom_flags(flags).open(filename).map_err(|e| {
+            // The links not followed and the sockets can't be opened, the file is rejected anyway
+            let metadata = if self.follow_symlinks { fs::metadata(filename) } else { fs::symlink_metadata(filename) };
+            match metadata.ok().and_then(|metadata| FileTypeError::of(metadata.file_type())) {
+                Some(error) if matches!(e.raw_os_error(), Some(libc::ELOOP | libc::ENXIO)) => error.into(),
+                _ => e.into(),
+            }
+        })
+    }
+
+    /// Open a file, the symbolic links being checked before as there is no flag to refuse them.
+    #[cfg(not(unix))]
+    fn open_file(&self, filename: &Path) -> Result<File, ValidationError> {
+        if !self.follow_symlinks && fs::symlink_metadata(filename)?.file_type().is_symlink() {
+            return Err(FileTypeError::Symlink.into());
+        }
+        Ok(File::open(filename)?)
+    }
+
+    /// Validate a file from its contents, see [`validate_file_bytes`].
+    ///
+    /// # Errors
+    /// If the file type is unknown, an SVG image is dangerous, the deep validation of an image, a
+    /// video or an archive failed, a polyglot file is rejected, or a content scanner
+    /// failed.
+    pub fn validate_bytes(&self, contents: &[u8], declared_name: Option<&str>) -> Result<ValidatedFile, ValidationError> {
+        self.validate_contents(contents, declared_name.map(Path::new))
+    }
+
+    /// Validate a file from its contents, with the path of its declared name.
+    fn validate_contents(&self, contents: &[u8], declared_name: Option<&Path>) -> Result<ValidatedFile, ValidationError> {
+        let mut file = self.check_contents(contents, declared_name, 0)?;
+        if self.sha256 {
+            file.sha256 = Some(sha256::sha256(contents));
+        }
+        if self.blake3 {
+            file.blake3 = Some(blake3::blake3(contents));
+        }
+        Ok(file)
+    }
+
+    /// Whether the whole file is needed for the validation, not only its prefix.
+    fn reads_whole_file(&self) -> bool {
+        #[cfg(feature = "deep")]
+        return self.allow_svg || self.deep || !self.scanners.is_empty();
+        #[cfg(not(feature = "deep"))]
+        return self.allow_svg || !self.scanners.is_empty();
+    }
+
+    /// Detect the type of a file and check it against the configuration, without its digest.
+    /// The depth is the number of archives holding the file.
+    fn check_contents(&self, contents: &[u8], declared_name: Option<&Path>, depth: usize) -> Result<ValidatedFile, ValidationError> {
+        // infer doesn't detect the SVG images
+        let custom = self.custom_matchers.iter().find(|matcher| matcher.matches(contents));
+        let (mime_type, extension, matcher_type) = if let Some(matcher) = custom {
+            (matcher.mime_type, matcher.extension, matcher.matcher_type)
+        } else if self.allow_svg && is_svg(contents) {
+            (SVG_MIME_TYPE, "svg", MatcherType::Image)
+        } else if let Some(kind) = infer::get(contents) {
+            (kind.mime_type(), kind.extension(), kind.matcher_type())
+        } else {
+            detect_modern_image(contents).ok_or(ValidationError::UnknownFileType)?
+        };
+
+        // An explicitly allowed mime type overrides the modern image option
+        let allowed_mime = match &self.allowed_mime_types {
+            Some(mime_types) => mime_types.iter().any(|mime| mime.eq_ignore_ascii_case(mime_type)),
+            None => self.allow_modern_images || !MODERN_IMAGE_MIME_TYPES.contains(&mime_type),
+        };
+        let allowed = self.allowed_matcher_types.contains(&matcher_type) && allowed_mime;
+        let file_kind = match FileKind::from_matcher_type(matcher_type) {
+            Some(file_kind) if allowed => file_kind,
+            _ => FileKind::Invalid,
+        };
+        let mut file = ValidatedFile {
+            kind: file_kind,
+            mime_type,
+            extension,
+            matcher_type,
+            extension_matched: None,
+            denied_extension: None,
+            bytes_inspected: contents.len(),
+            #[cfg(feature = "deep")]
+            image: None,
+            #[cfg(feature = "deep")]
+            video: None,
+            #[cfg(feature = "deep")]
+            archive: None,
+            scan: None,
+            quarantined: None,
+            sha256: None,
+            blake3: None,
+        };
+
+        // Check the extension if requested
+        if self.check_extension {
+            // Case is irrelevant for the extension
+            let matched = declared_name
+                .and_then(Path::extension)
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|name_extension| {
+                    self.extensions(extension).any(|extension| extension.eq_ignore_ascii_case(name_extension))
+                });
+            file.extension_matched = Some(matched);
+            if !matched {
+                file.kind = FileKind::Invalid;
+            }
+        }
+
+        // Look for dangerous extensions anywhere in the filename if requested
+        if let (Some(denied), Some(name)) = (&self.denied_extensions, declared_name.and_then(Path::file_name)) {
+            if let Some(extension) = find_denied_extension(&name.to_string_lossy(), denied) {
+                file.denied_extension = Some(extension.to_lowercase());
+                file.kind = FileKind::Invalid;
+            }
+        }
+
+        // Reject the SVG images with scripts
+        if mime_type == SVG_MIME_TYPE && file.is_valid() {
+            sanitize_svg(contents, SvgMode::Reject).map_err(ValidationError::contents)?;
+        }
+
+        // Check the structure of the image if requested
+        #[cfg(feature = "deep")]
+        if self.deep && file.kind == FileKind::Image && mime_type != SVG_MIME_TYPE {
+            let image = image_header::parse_image(contents, mime_type, &self.image_limits).map_err(ValidationError::contents)?;
+            file.image = Some(image);
+        }
+
+        // Check the container of the video if requested
+        #[cfg(feature = "deep")]
+        if self.deep && file.kind == FileKind::Video {
+            let video = video_container::parse_video(contents, mime_type).map_err(ValidationError::contents)?;
+            file.video = Some(video);
+        }
+
+        // Check the entries of the archive if requested
+        #[cfg(feature = "deep")]
+        if self.deep && self.inspect_archives && file.kind == FileKind::Archive && archive::INSPECTED_MIME_TYPES.contains(&mime_type) {
+            let archive = self.inspect_archive(contents, mime_type, depth).map_err(ValidationError::contents)?;
+            file.archive = Some(archive);
+        }
+
+        // Look for embedded content if requested
+        #[cfg(feature = "deep")]
+        if self.deep && self.reject_polyglots && file.is_valid() {
+            let report = polyglot::scan_polyglot(contents);
+            if report.is_polyglot() {
+                return Err(ValidationError::contents(report.to_string()));
+            }
+        }
+
+        // Scan the file itself, not the entries of an archive
+        if depth == 0 && file.is_valid() && !self.scanners.is_empty() {
+            let verdict = self
+                .scanners
+                .iter()
+                .map(|scanner| scanner.scan(contents))
+                .find(|verdict| !verdict.is_clean())
+                .unwrap_or(ScanVerdict::Clean);
+            match verdict {
+                ScanVerdict::Error(_) => return Err(ValidationError::Scanner(verdict.to_string())),
+                ScanVerdict::Clean => {}
+                _ => file.kind = FileKind::Invalid,
+            }
+            file.scan = Some(verdict);
+        }
+
+        Ok(file)
+    }
+
+    /// Validate the entries of an archive holding files at a depth.
+    #[cfg(feature = "deep")]
+    fn inspect_archive(&self, contents: &[u8], mime_type: &str, depth: usize) -> Result<ArchiveInfo, ArchiveError> {
+        if mime_type != archive::ZIP_MIME_TYPE {
+            return Err(ArchiveError::Unsupported(format!("{} format", mime_type)));
+        }
+        self.archive_limits.check(ArchiveLimit::Depth, depth as u64 + 1)?;
+
+        let mut entries = Vec::new();
+        for entry in archive::read_zip(contents, &self.archive_limits)? {
+            let entry_error = |reason: String| ArchiveError::Entry { name: entry.name.clone(), reason };
+            // An entry is extracted under its name, e.g. `../../etc/passwd`
+            validate_upload_path(entry.name.trim_end_matches('/')).map_err(|e| entry_error(e.to_string()))?;
+
+            let mime_type = if entry.is_dir() {
+                None
+            } else {
+                let data = entry.decompress()?;
+                let file = self.check_contents(&data, Some(Path::new(&entry.name)), depth + 1).map_err(|e| match e {
+                    // Keep the errors of the nested archives
+                    ValidationError::Contents(inner) => match inner.downcast::<ArchiveError>() {
+                        Ok(nested) => *nested,
+                        Err(inner) => entry_error(inner.to_string()),
+                    },
+                    e => entry_error(e.to_string()),
+                })?;
+                if !file.is_valid() {
+                    return Err(entry_error(format!("{} not allowed.", file.mime_type)));
+                }
+                Some(file.mime_type)
+            };
+            entries.push(ArchiveEntry { name: entry.name, compressed_size: entry.compressed_size, size: entry.size, mime_type });
+        }
+        Ok(ArchiveInfo { entries })
+    }
+
+    /// The extension reported by infer and its aliases.
+    fn extensions<'a>(&'a self, extension: &'a str) -> impl Iterator<Item = &'a str> {
+        let builtin = EXTENSION_ALIASES
+            .iter()
+            .filter(move |(canonical, _)| *canonical == extension)
+            .flat_map(|(_, aliases)| aliases.iter().copied());
+        let custom = self
+            .extension_aliases
+            .iter()
+            .filter(move |(canonical, _)| canonical == extension)
+            .map(|(_, alias)| alias.as_str());
+        std::iter::once(extension).chain(builtin).chain(custom)
+    }
+
+    /// Validate a file read from a stream, only reading the configured prefix unless the SVG
+    /// images, the deep validation, the digests or the scanners are enabled, see [`validate_file_reader`].
+    ///
+    /// # Errors
+    /// If the stream could not be read or the file type is unknown.
+    pub fn validate_reader<R: Read>(&self, reader: R, declared_name: Option<&str>) -> Result<ValidatedFile, ValidationError> {
+        self.validate_stream(reader, declared_name.map(Path::new))
+    }
+
+    /// Validate a file read from a stream, with the path of its declared name.
+    fn validate_stream<R: Read>(&self, mut reader: R, declared_name: Option<&Path>) -> Result<ValidatedFile, ValidationError> {
+        let prefix_length = if self.reads_whole_file() { usize::MAX } else { self.prefix_length };
+
+        let mut prefix = Vec::with_capacity(prefix_length.min(MAGIC_PREFIX_LENGTH));
+        reader.by_ref().take(prefix_length as u64).read_to_end(&mut prefix)?;
+        let mut file = self.check_contents(&prefix, declared_name, 0)?;
+
+        // Hash the rest of the stream without keeping it
+        if self.sha256 || self.blake3 {
+            let mut digests = Digests { sha256: self.sha256.then(Sha256::new), blake3: self.blake3.then(Blake3::new) };
+            digests.write_all(&prefix)?;
+            io::copy(&mut reader, &mut digests)?;
+            file.sha256 = digests.sha256.map(Sha256::finalize);
+            file.blake3 = digests.blake3.map(Blake3::finalize);
+        }
+        Ok(file)
+    }
+}
+
+/// The requested digests of a stream, computed in the same pass.
+struct Digests {
+    sha256: Option<Sha256>,
+    blake3: Option<Blake3>,
+}
+
+impl Write for Digests {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        if let Some(hasher) = &mut self.sha256 {
+            hasher.update(bytes);
+        }
+        if let Some(hasher) = &mut self.blake3 {
+            hasher.update(bytes);
+        }
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Reason of the rejection of an invalid file.
+pub(crate) fn rejection_reason(file: &ValidatedFile) -> String {
+    if let Some(verdict) = file.scan.as_ref().filter(|verdict| !verdict.is_clean()) {
+        verdict.to_string()
+    } else if let Some(extension) = &file.denied_extension {
+        format!("Dangerous extension {} in filename.", extension)
+    } else if file.extension_matched == Some(false) {
+        format!("The extension doesn't match the type {}.", file.mime_type)
+    } else {
+        format!("The type {} is not allowed.", file.mime_type)
+    }
+}
+
+/// Detect the modern images unknown to infer: the JPEG XL images, bare codestreams or in their
+/// container, and the HEIF images of an HEVC brand other than `heic`.
+fn detect_modern_image(contents: &[u8]) -> Option<(&'static str, &'static str, MatcherType)> {
+    if contents.starts_with(b"\xff\x0a") || contents.starts_with(b"\x00\x00\x00\x0cJXL \r\n\x87\n") {
+        return Some((JXL_MIME_TYPE, "jxl", MatcherType::Image));
+    }
+
+    // File type box: major brand, minor version and compatible brands
+    let box_size = u32::from_be_bytes(contents.get(..4)?.try_into().ok()?) as usize;
+    if contents.get(4..8)? != b"ftyp" {
+        return None;
+    }
+    let brands = contents.get(8..box_size.min(contents.len()))?;
+    let is_heif = brands.chunks_exact(4).enumerate().any(|(index, brand)| index != 1 && HEIF_BRANDS.contains(&brand));
+    is_heif.then_some(("image/heif", "heif", MatcherType::Image))
+}
+
+/// Default validator, with or without extension check.
+fn default_validator(check_extension: bool) -> &'static FileValidator {
+    lazy_static! {
+        static ref VALIDATOR: FileValidator = FileValidator::builder().build().unwrap();
+        static ref EXTENSION_VALIDATOR: FileValidator = FileValidator::builder().check_extension(true).build().unwrap();
+    }
+
+    if check_extension {
+        &EXTENSION_VALIDATOR
+    } else {
+        &VALIDATOR
+    }
+}
 
 /// Validate a file by checking that it is an image or a video. And check his filename extension
 /// if requested.
 ///
-/// The result is an unsigned integer telling:
-///     0 - The file is invalid (not a video or image or the extension is invalid)
-///     1 - The file is valid and it is an image
-///     2 - The file is valid and it a video
+/// The file is [`FileKind::Invalid`] if it is not a video or image or the extension is invalid.
+/// See [`compat::validate_file`](crate::compat::validate_file) for the former numeric result and
+/// [`FileValidator::builder`] for more options.
 ///
 /// # Errors
 /// If the filename could not be found or opened. Also return an error if the file type is unknown
 /// (cf. crate infer).
 ///
+/// The filename is any path, e.g. a `PathBuf` or a non-UTF-8 name, its extension being checked
+/// on the path components.
+///
 /// # Examples
 /// ``` ignore
 /// match validate_file("myDir/myImage.png", true) {
-///     Ok(result) => match result {
-///         0 => println!("Invalid file contents !"),
-///         1 => println!("The file is valid and it is an image !"),
-///         2 => println!("The file is valid and it is a video !"),
-///         _ => {},
+///     Ok(file) => match file.kind {
+///         FileKind::Invalid => println!("Invalid file contents !"),
+///         FileKind::Image => println!("The file is valid and it is an image !"),
+///         FileKind::Video => println!("The file is valid and it is a video !"),
+///         _ => println!("The file is valid and it is of another allowed kind !"),
 ///     },
 ///     Err(e) => println!("An error occurred: {}", e.to_string()),
 /// }
 /// ```
-pub fn validate_file(filename: &str, check_extension: bool) -> Result<u8, Error> {
-    // Read the file to check the magic numbers
-    match infer::get_from_path(filename)? {
-        None => Err(Error::new(ErrorKind::Other, "File type is unknown.")),
-
-        Some(kind) => {
-            // Check the extension if requested
-            if check_extension {
-                // Case is irrelevant for the extension
-                let file_extension = kind.extension().to_lowercase();
-                let regex = Regex::new(&format!(r"{}$", file_extension)).unwrap();
-                if !regex.is_match(&filename.to_lowercase()) {
-                    return Ok(0);
-                }
-            }
+pub fn validate_file<P: AsRef<Path>>(filename: P, check_extension: bool) -> Result<ValidatedFile, ValidationError> {
+    default_validator(check_extension).validate(filename)
+}
 
-            // Check if the file is an image (1), a video (2) or other (0)
-            match kind.matcher_type() {
-                infer::MatcherType::Image => Ok(1),
-                infer::MatcherType::Video => Ok(2),
-                _ => Ok(0),
-            }
-        }
-    }
+/// Validate a file from its contents, e.g. an upload before writing it to disk, see
+/// [`validate_file`]. The extension is checked on the declared name of the file; without one,
+/// the file is [`FileKind::Invalid`] if the extension check is requested.
+///
+/// # Errors
+/// If the file type is unknown (cf. crate infer).
+///
+/// # Examples
+/// ``` ignore
+/// let file = validate_file_bytes(&upload.bytes, Some(&upload.filename), true)?;
+/// if !file.is_valid() {
+///     println!("Invalid file contents !");
+/// }
+/// ```
+pub fn validate_file_bytes(contents: &[u8], declared_name: Option<&str>, check_extension: bool) -> Result<ValidatedFile, ValidationError> {
+    default_validator(check_extension).validate_bytes(contents, declared_name)
 }
 
+/// Validate a file read from a stream, e.g. a network upload or a very large file, see
+/// [`validate_file_bytes`]. Only the first `prefix_length` bytes are read for the magic numbers
+/// detection, [`MAGIC_PREFIX_LENGTH`] being enough for every known file type; the rest of the
+/// stream is left unread.
+///
+/// # Errors
+/// If the stream could not be read. Also return an error if the file type is unknown
+/// (cf. crate infer).
+///
+/// # Examples
+/// ``` ignore
+/// let file = validate_file_reader(&mut stream, Some("upload.png"), true, MAGIC_PREFIX_LENGTH)?;
+/// ```
+pub fn validate_file_reader<R: Read>(
+    reader: R,
+    declared_name: Option<&str>,
+    check_extension: bool,
+    prefix_length: usize,
+) -> Result<ValidatedFile, ValidationError> {
+    let mut prefix = Vec::with_capacity(prefix_length.min(MAGIC_PREFIX_LENGTH));
+    reader.take(prefix_length as u64).read_to_end(&mut prefix)?;
+    default_validator(check_extension).validate_bytes(&prefix, declared_name)
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::validate_file;
+    use std::io::{Cursor, Read};
+
+    use infer::MatcherType;
+
+    // Start of an mp3 file with an ID3v2 tag
+    const MP3_PREFIX: &[u8] = b"ID3\x03\x00\x00\x00\x00\x00\x00";
+
+    use crate::{blake3, sha256, validate_file, ContentScanner, Quarantine, QuarantineMode, ScanVerdict, validate_file_bytes, validate_file_reader, CustomMatcher, FileKind, FileTypeError, FileValidator, ValidatedFile, ValidationError, JXL_MIME_TYPE, MAGIC_PREFIX_LENGTH, SVG_MIME_TYPE};
 
     const TEST_DIR: &str = "test_files";
 
     #[test]
     fn valid_files_image() {
-        assert_eq!(validate_file(&format!("{}/valid_image.jpg", TEST_DIR), false).unwrap(), 1);
-        assert_eq!(validate_file(&format!("{}/valid_image.png", TEST_DIR), false).unwrap(), 1);
+        assert_eq!(validate_file(format!("{}/valid_image.jpg", TEST_DIR), false).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file(format!("{}/valid_image.png", TEST_DIR), false).unwrap().kind, FileKind::Image);
 
         // valid image even if the extension doesn't correspond
-        assert_eq!(validate_file(&format!("{}/invalid_ext_image_jpg.png", TEST_DIR), false).unwrap(), 1);
+        assert_eq!(validate_file(format!("{}/invalid_ext_image_jpg.png", TEST_DIR), false).unwrap().kind, FileKind::Image);
     }
 
     #[test]
     fn valid_files_video() {
-        assert_eq!(validate_file(&format!("{}/valid_video.avi", TEST_DIR), false).unwrap(), 2);
-        assert_eq!(validate_file(&format!("{}/valid_video.mov", TEST_DIR), false).unwrap(), 2);
+        assert_eq!(validate_file(format!("{}/valid_video.avi", TEST_DIR), false).unwrap().kind, FileKind::Video);
+        assert_eq!(validate_file(format!("{}/valid_video.mov", TEST_DIR), false).unwrap().kind, FileKind::Video);
 
         // valid video even if the extension doesn't correspond
-        assert_eq!(validate_file(&format!("{}/invalid_ext_video_avi.mp4", TEST_DIR), false).unwrap(), 2);
+        assert_eq!(validate_file(format!("{}/invalid_ext_video_avi.mp4", TEST_DIR), false).unwrap().kind, FileKind::Video);
     }
 
     #[test]
     fn invalid_files() {
-        assert_eq!(validate_file(&format!("{}/invalid_file.pdf", TEST_DIR), false).unwrap(), 0);
-        assert_eq!(validate_file(&format!("{}/invalid_file.ppt", TEST_DIR), false).unwrap(), 0);
+        assert_eq!(validate_file(format!("{}/invalid_file.pdf", TEST_DIR), false).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validate_file(format!("{}/invalid_file.ppt", TEST_DIR), false).unwrap().kind, FileKind::Invalid);
     }
 
     #[test]
     fn valid_extensions() {
-        assert_eq!(validate_file(&format!("{}/valid_image.jpg", TEST_DIR), true).unwrap(), 1);
-        assert_eq!(validate_file(&format!("{}/valid_video.avi", TEST_DIR), true).unwrap(), 2);
+        assert_eq!(validate_file(format!("{}/valid_image.jpg", TEST_DIR), true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file(format!("{}/valid_video.avi", TEST_DIR), true).unwrap().kind, FileKind::Video);
 
         // extensions should not be case sensitive
-        assert_eq!(validate_file(&format!("{}/valid_ext_image.JpG", TEST_DIR), true).unwrap(), 1);
-        assert_eq!(validate_file(&format!("{}/valid_ext_video.AVI", TEST_DIR), true).unwrap(), 2);
+        assert_eq!(validate_file(format!("{}/valid_ext_image.JpG", TEST_DIR), true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file(format!("{}/valid_ext_video.AVI", TEST_DIR), true).unwrap().kind, FileKind::Video);
+    }
+
+    #[test]
+    fn path_filenames() {
+        let path = std::path::Path::new(TEST_DIR).join("valid_image.png");
+        assert_eq!(validate_file(&path, true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file(path.clone(), true).unwrap().kind, FileKind::Image);
+        assert_eq!(FileValidator::builder().check_extension(true).build().unwrap().validate(path.as_path()).unwrap().kind, FileKind::Image);
+
+        // the extension of a non-UTF-8 name is still checked
+        #[cfg(unix)]
+        {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+
+            let name = [format!("image_{}_", std::process::id()).as_bytes(), b"\xff.png"].concat();
+            let copy = std::env::temp_dir().join(OsStr::from_bytes(&name));
+            std::fs::copy(&path, &copy).unwrap();
+            let kind = validate_file(&copy, true).unwrap().kind;
+            std::fs::remove_file(&copy).unwrap();
+            assert_eq!(kind, FileKind::Image);
+        }
+    }
+
+    #[test]
+    fn special_files() {
+        let file_type_error = |error| match error {
+            ValidationError::FileType(error) => error,
+            error => panic!("{:?}", error),
+        };
+        assert_eq!(file_type_error(validate_file(TEST_DIR, false).unwrap_err()), FileTypeError::Directory);
+
+        #[cfg(unix)]
+        {
+            assert_eq!(file_type_error(validate_file("/dev/null", false).unwrap_err()), FileTypeError::Device);
+
+            // a link to a device is rejected instead of read forever
+            let target = std::fs::canonicalize(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+            let image_link = std::env::temp_dir().join(format!("image_link_{}.png", std::process::id()));
+            let device_link = std::env::temp_dir().join(format!("device_link_{}.png", std::process::id()));
+            std::os::unix::fs::symlink(&target, &image_link).unwrap();
+            std::os::unix::fs::symlink("/dev/zero", &device_link).unwrap();
+
+            let validator = FileValidator::builder().sha256(true).build().unwrap();
+            let no_follow = FileValidator::builder().follow_symlinks(false).build().unwrap();
+            let results = (validator.validate(&image_link), validator.validate(&device_link), no_follow.validate(&image_link));
+            std::fs::remove_file(&image_link).unwrap();
+            std::fs::remove_file(&device_link).unwrap();
+
+            assert_eq!(results.0.unwrap().kind, FileKind::Image);
+            assert_eq!(file_type_error(results.1.unwrap_err()), FileTypeError::Device);
+            assert_eq!(file_type_error(results.2.unwrap_err()), FileTypeError::Symlink);
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_not_followed() {
+        let file_type_error = |result: Result<ValidatedFile, ValidationError>| match result {
+            Err(ValidationError::FileType(error)) => error,
+            result => panic!("{:?}", result),
+        };
+        let no_follow = FileValidator::builder().follow_symlinks(false).build().unwrap();
+        let prefix = std::env::temp_dir().join(format!("no_follow_{}", std::process::id()));
+        let (fifo, fifo_link, dangling_link) = (prefix.with_extension("fifo"), prefix.with_extension("link"), prefix.with_extension("dangling"));
+
+        // a named pipe is rejected without waiting for a writer, followed or not
+        let path = std::ffi::CString::new(fifo.as_os_str().as_encoded_bytes()).unwrap();
+        // SAFETY: the path is a NUL-terminated string
+        assert_eq!(unsafe { libc::mkfifo(path.as_ptr(), 0o600) }, 0);
+        std::os::unix::fs::symlink(&fifo, &fifo_link).unwrap();
+        std::os::unix::fs::symlink(prefix.with_extension("missing"), &dangling_link).unwrap();
+
+        let results = [
+            validate_file(&fifo, false),
+            validate_file(&fifo_link, false),
+            no_follow.validate(&fifo),
+            no_follow.validate(&fifo_link),
+            no_follow.validate(&dangling_link),
+        ];
+        for path in [&fifo, &fifo_link, &dangling_link] {
+            std::fs::remove_file(path).unwrap();
+        }
+
+        let [fifo, followed_link, no_follow_fifo, link, dangling] = results;
+        assert_eq!(file_type_error(fifo), FileTypeError::Fifo);
+        assert_eq!(file_type_error(followed_link), FileTypeError::Fifo);
+        assert_eq!(file_type_error(no_follow_fifo), FileTypeError::Fifo);
+        assert_eq!(file_type_error(link), FileTypeError::Symlink);
+        assert_eq!(file_type_error(dangling), FileTypeError::Symlink);
     }
 
     #[test]
     fn invalid_extensions() {
         // content doesn't match extension
-        assert_eq!(validate_file(&format!("{}/invalid_ext_image_jpg.png", TEST_DIR), true).unwrap(), 0);
-        assert_eq!(validate_file(&format!("{}/invalid_ext_video_avi.mp4", TEST_DIR), true).unwrap(), 0);
+        assert_eq!(validate_file(format!("{}/invalid_ext_image_jpg.png", TEST_DIR), true).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validate_file(format!("{}/invalid_ext_video_avi.mp4", TEST_DIR), true).unwrap().kind, FileKind::Invalid);
 
         // adding extension doesn't pass
-        assert_eq!(validate_file(&format!("{}/invalid_ext_image.jpg.png", TEST_DIR), true).unwrap(), 0);
-        assert_eq!(validate_file(&format!("{}/invalid_ext_video.avi.mov", TEST_DIR), true).unwrap(), 0);
+        assert_eq!(validate_file(format!("{}/invalid_ext_image.jpg.png", TEST_DIR), true).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validate_file(format!("{}/invalid_ext_video.avi.mov", TEST_DIR), true).unwrap().kind, FileKind::Invalid);
     }
 
     #[test]
     fn invalid_filepath() {
         assert_eq!(validate_file("", false).unwrap_err().to_string(), "No such file or directory (os error 2)");
-        assert_eq!(validate_file(&format!("{}/oe.png", TEST_DIR), false).unwrap_err().to_string(), "No such file or directory (os error 2)");
+        assert_eq!(validate_file(format!("{}/oe.png", TEST_DIR), false).unwrap_err().to_string(), "No such file or directory (os error 2)");
     }
 
     #[test]
     fn invalid_file_type() {
         assert_eq!(validate_file("Cargo.toml", false).unwrap_err().to_string(), "File type is unknown.");
     }
+
+    #[test]
+    fn detected_types() {
+        let path = format!("{}/valid_image.png", TEST_DIR);
+        let file = validate_file(&path, true).unwrap();
+        let expected = ValidatedFile {
+            kind: FileKind::Image,
+            mime_type: "image/png",
+            extension: "png",
+            matcher_type: MatcherType::Image,
+            extension_matched: Some(true),
+            denied_extension: None,
+            bytes_inspected: MAGIC_PREFIX_LENGTH.min(std::fs::metadata(&path).unwrap().len() as usize),
+            #[cfg(feature = "deep")]
+            image: None,
+            #[cfg(feature = "deep")]
+            video: None,
+            #[cfg(feature = "deep")]
+            archive: None,
+            scan: None,
+            quarantined: None,
+            sha256: None,
+            blake3: None,
+        };
+        assert_eq!(file, expected);
+        assert!(file.is_valid());
+        assert_eq!(validate_file(&path, false).unwrap().extension_matched, None);
+
+        // the detected type is kept when the extension doesn't match
+        let file = validate_file(format!("{}/invalid_ext_image_jpg.png", TEST_DIR), true).unwrap();
+        assert_eq!((file.kind, file.mime_type, file.extension), (FileKind::Invalid, "image/jpeg", "jpg"));
+        assert_eq!(file.extension_matched, Some(false));
+        assert!(!file.is_valid());
+
+        let file = validate_file(format!("{}/invalid_file.pdf", TEST_DIR), true).unwrap();
+        assert_eq!((file.kind, file.matcher_type, file.extension_matched), (FileKind::Invalid, MatcherType::Archive, Some(true)));
+    }
+
+    #[test]
+    fn validate_buffers() {
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&image, None, false).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file_bytes(&image, Some("upload.PNG"), true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file_bytes(&image, Some("upload.jpg"), true).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validate_file_bytes(&image, None, true).unwrap().kind, FileKind::Invalid);
+
+        let video = std::fs::read(format!("{}/valid_video.mov", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&video, Some("upload.mov"), true).unwrap().kind, FileKind::Video);
+
+        let pdf = std::fs::read(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&pdf, Some("upload.pdf"), true).unwrap().kind, FileKind::Invalid);
+
+        assert_eq!(validate_file_bytes(b"", None, false).unwrap_err().to_string(), "File type is unknown.");
+        assert_eq!(validate_file_bytes(b"[package]", None, false).unwrap_err().to_string(), "File type is unknown.");
+    }
+
+    #[test]
+    fn validate_streams() {
+        let video = std::fs::read(format!("{}/valid_video.avi", TEST_DIR)).unwrap();
+        let mut stream = Cursor::new(&video);
+        let file = validate_file_reader(&mut stream, Some("upload.avi"), true, MAGIC_PREFIX_LENGTH).unwrap();
+        assert_eq!(file.kind, FileKind::Video);
+
+        // only the prefix is read
+        assert_eq!(stream.position() as usize, MAGIC_PREFIX_LENGTH.min(video.len()));
+        let mut stream = Cursor::new(&video);
+        validate_file_reader(&mut stream, None, false, 16).unwrap();
+        assert_eq!(stream.position(), 16);
+        let mut rest = Vec::new();
+        stream.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest.len(), video.len() - 16);
+
+        // a too short prefix can't be detected
+        assert!(validate_file_reader(Cursor::new(&video), None, false, 2).is_err());
+        assert!(validate_file_reader(std::fs::File::open("Cargo.toml").unwrap(), None, false, MAGIC_PREFIX_LENGTH).is_err());
+    }
+
+    #[cfg(all(feature = "mmap", unix))]
+    #[test]
+    fn mapped_files() {
+        let validator = FileValidator::builder().sha256(true).build().unwrap();
+        for name in ["valid_image.png", "valid_video.mov", "invalid_file.pdf"] {
+            let path = format!("{}/{}", TEST_DIR, name);
+            // SAFETY: the test files are not modified
+            let mapped = unsafe { validator.validate_mapped(&path) }.unwrap();
+            let read = validator.validate(&path).unwrap();
+            assert_eq!((mapped.kind, mapped.sha256), (read.kind, read.sha256), "{}", name);
+        }
+        // SAFETY: the directory is not mapped
+        assert!(matches!(unsafe { validator.validate_mapped(TEST_DIR) }, Err(ValidationError::FileType(_))));
+    }
+
+    #[test]
+    fn content_digests() {
+        let video = std::fs::read(format!("{}/valid_video.avi", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&video, None, false).unwrap().sha256, None);
+
+        let validator = FileValidator::builder().sha256(true).prefix_length(16).build().unwrap();
+        assert_eq!(validator.validate_bytes(&video, None).unwrap().sha256, Some(sha256(&video)));
+
+        // the whole stream is hashed, only the prefix is inspected
+        let mut stream = Cursor::new(&video);
+        let file = validator.validate_reader(&mut stream, None).unwrap();
+        assert_eq!(file.sha256, Some(sha256(&video)));
+        assert_eq!(file.bytes_inspected, 16);
+        assert_eq!(stream.position() as usize, video.len());
+        assert_eq!(validator.validate(format!("{}/valid_video.avi", TEST_DIR)).unwrap().sha256, Some(sha256(&video)));
+
+        // both digests in the same pass
+        let validator = FileValidator::builder().sha256(true).blake3(true).prefix_length(16).build().unwrap();
+        let file = validator.validate_reader(Cursor::new(&video), None).unwrap();
+        assert_eq!((file.sha256, file.blake3), (Some(sha256(&video)), Some(blake3(&video))));
+        let file = validator.validate_bytes(&video, None).unwrap();
+        assert_eq!((file.sha256, file.blake3), (Some(sha256(&video)), Some(blake3(&video))));
+        let file = FileValidator::builder().blake3(true).build().unwrap().validate(format!("{}/valid_video.avi", TEST_DIR)).unwrap();
+        assert_eq!((file.sha256, file.blake3), (None, Some(blake3(&video))));
+    }
+
+    #[test]
+    fn mime_whitelist() {
+        let validator = FileValidator::builder().allow_mime(["image/png", "IMAGE/JPEG", "video/mp4"]).build().unwrap();
+        assert_eq!(validator.validate(format!("{}/valid_image.png", TEST_DIR)).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate(format!("{}/valid_image.jpg", TEST_DIR)).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate(format!("{}/valid_video.avi", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate(format!("{}/valid_video.mov", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+
+        // only images and videos are accepted by default
+        let validator = FileValidator::builder().allow_mime(["application/pdf"]).build().unwrap();
+        assert_eq!(validator.validate(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+
+        let validator = FileValidator::builder().allow_mime(["video/x-msvideo"]).check_extension(true).build().unwrap();
+        assert_eq!(validator.validate(format!("{}/valid_video.avi", TEST_DIR)).unwrap().kind, FileKind::Video);
+        assert_eq!(validator.validate(format!("{}/invalid_ext_video_avi.mp4", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+    }
+
+    #[test]
+    fn invalid_file_validators() {
+        assert_eq!(FileValidator::builder().allow_mime([] as [&str; 0]).build().unwrap_err().to_string(), "The mime type white list is empty.");
+        assert_eq!(FileValidator::builder().allow_mime(["image/png", "image/unknown"]).build().unwrap_err().to_string(), "Invalid mime type in white list.");
+        assert_eq!(FileValidator::builder().prefix_length(0).build().unwrap_err().to_string(), "Invalid prefix length.");
+    }
+
+    #[test]
+    fn matcher_types() {
+        let validator = FileValidator::builder()
+            .allowed_matcher_types([MatcherType::Archive, MatcherType::Audio])
+            .check_extension(true)
+            .build()
+            .unwrap();
+        assert_eq!(validator.validate(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap().kind, FileKind::Archive);
+        assert_eq!(validator.validate_bytes(MP3_PREFIX, Some("song.mp3")).unwrap().kind, FileKind::Audio);
+        assert_eq!(validator.validate(format!("{}/valid_image.png", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+
+        // infer detects the ppt file as an ole storage (msi) archive
+        assert_eq!(validator.validate(format!("{}/invalid_file.ppt", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+
+        // the mime types restrict the matcher types
+        let validator = FileValidator::builder()
+            .allowed_matcher_types([MatcherType::Image, MatcherType::Archive])
+            .allow_mime(["application/pdf", "image/png"])
+            .build()
+            .unwrap();
+        assert_eq!(validator.validate(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap().kind, FileKind::Archive);
+        assert_eq!(validator.validate(format!("{}/valid_image.jpg", TEST_DIR)).unwrap().kind, FileKind::Invalid);
+
+        assert_eq!(validate_file_bytes(MP3_PREFIX, None, false).unwrap().kind, FileKind::Invalid);
+        assert_eq!(FileValidator::builder().allowed_matcher_types([]).build().unwrap_err().to_string(), "The matcher type white list is empty.");
+        assert_eq!(FileValidator::builder().allowed_matcher_types([MatcherType::Custom]).build().unwrap_err().to_string(), "Invalid matcher type in white list.");
+    }
+
+    #[test]
+    fn extension_aliases() {
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&image, Some("photo.jpeg"), true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file_bytes(&image, Some("PHOTO.JPE"), true).unwrap().kind, FileKind::Image);
+        assert_eq!(validate_file_bytes(&image, Some("photo.jfif"), true).unwrap().kind, FileKind::Invalid);
+
+        let validator = FileValidator::builder().check_extension(true).extension_alias("JPG", "JFIF").build().unwrap();
+        assert_eq!(validator.validate_bytes(&image, Some("photo.jfif")).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate_bytes(&image, Some("photo.jpeg")).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate_bytes(&image, Some("photo.png")).unwrap().kind, FileKind::Invalid);
+
+        // aliases only apply to their extension
+        let png = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(validator.validate_bytes(&png, Some("photo.jfif")).unwrap().kind, FileKind::Invalid);
+
+        assert_eq!(FileValidator::builder().extension_alias("jpg", ".jpeg").build().unwrap_err().to_string(), "Invalid extension alias.");
+        assert_eq!(FileValidator::builder().extension_alias("", "jpeg").build().unwrap_err().to_string(), "Invalid extension alias.");
+    }
+
+    #[test]
+    fn denied_extensions() {
+        let image = std::fs::read(format!("{}/valid_image.jpg", TEST_DIR)).unwrap();
+        assert_eq!(validate_file_bytes(&image, Some("invoice.php.jpg"), true).unwrap().kind, FileKind::Image);
+
+        let validator = FileValidator::builder().reject_dangerous_extensions(true).build().unwrap();
+        let file = validator.validate_bytes(&image, Some("invoice.PHP.jpg")).unwrap();
+        assert_eq!((file.kind, file.denied_extension.as_deref()), (FileKind::Invalid, Some("php")));
+        assert_eq!(validator.validate_bytes(&image, Some("run.jpg.exe")).unwrap().denied_extension.as_deref(), Some("exe"));
+        assert_eq!(validator.validate_bytes(&image, Some("photo.jpg")).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate_bytes(&image, None).unwrap().kind, FileKind::Image);
+
+        let validator = FileValidator::builder().deny_extensions(["SVG"]).build().unwrap();
+        assert_eq!(validator.validate_bytes(&image, Some("image.svg.jpg")).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate_bytes(&image, Some("invoice.php.jpg")).unwrap().kind, FileKind::Image);
+
+        assert_eq!(FileValidator::builder().deny_extensions([] as [&str; 0]).build().unwrap_err().to_string(), "The extension deny list is empty.");
+        assert_eq!(FileValidator::builder().deny_extensions([".php"]).build().unwrap_err().to_string(), "Invalid denied extension.");
+    }
+
+    #[test]
+    fn modern_images() {
+        let heic = b"\x00\x00\x00\x18ftypheic\x00\x00\x00\x00mif1heic";
+        let heix = b"\x00\x00\x00\x18ftypmif1\x00\x00\x00\x00mif1heix";
+        let avif = b"\x00\x00\x00\x1cftypavif\x00\x00\x00\x00avifmif1miaf";
+        let jxl = b"\xff\x0a\xfa\x7f\x01\x90\x08";
+        let jxl_container = b"\x00\x00\x00\x0cJXL \r\n\x87\n\x00\x00\x00\x14ftypjxl ";
+        let webp = b"RIFF\x24\x00\x00\x00WEBPVP8 ";
+
+        for (contents, mime_type, name) in [
+            (&heic[..], "image/heif", "photo.HEIC"),
+            (heix, "image/heif", "photo.heic"),
+            (avif, "image/avif", "photo.avif"),
+            (jxl, JXL_MIME_TYPE, "photo.jxl"),
+            (jxl_container, JXL_MIME_TYPE, "photo.jxl"),
+            (webp, "image/webp", "photo.webp"),
+        ] {
+            let file = validate_file_bytes(contents, Some(name), true).unwrap();
+            assert_eq!((file.kind, file.mime_type), (FileKind::Image, mime_type));
+        }
+        assert_eq!(validate_file(format!("{}/valid_image.webp", TEST_DIR), true).unwrap().kind, FileKind::Image);
+
+        let validator = FileValidator::builder().allow_modern_images(false).build().unwrap();
+        assert_eq!(validator.validate_bytes(heix, None).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate_bytes(webp, None).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate(format!("{}/valid_image.png", TEST_DIR)).unwrap().kind, FileKind::Image);
+
+        let validator = FileValidator::builder().allow_mime([JXL_MIME_TYPE]).build().unwrap();
+        assert_eq!(validator.validate_bytes(jxl, None).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate_bytes(heic, None).unwrap().kind, FileKind::Invalid);
+
+        // an explicitly allowed mime type overrides the option
+        let validator = FileValidator::builder().allow_modern_images(false).allow_mime(["image/webp"]).build().unwrap();
+        assert_eq!(validator.validate_bytes(webp, None).unwrap().kind, FileKind::Image);
+        assert_eq!(validator.validate_bytes(heix, None).unwrap().kind, FileKind::Invalid);
+    }
+
+    #[test]
+    fn content_scanners() {
+        #[derive(Debug)]
+        struct Signature(&'static [u8], ScanVerdict);
+
+        impl ContentScanner for Signature {
+            fn scan(&self, bytes: &[u8]) -> ScanVerdict {
+                if bytes.windows(self.0.len()).any(|window| window == self.0) {
+                    self.1.clone()
+                } else {
+                    ScanVerdict::Clean
+                }
+            }
+        }
+
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        let infected = [&image[..], b"EICAR"].concat();
+        let validator = FileValidator::builder()
+            .content_scanner(Signature(b"EICAR", ScanVerdict::Infected(String::from("Eicar-Signature"))))
+            .content_scanner(Signature(b"EICAR", ScanVerdict::Suspicious(String::from("test signature"))))
+            .build()
+            .unwrap();
+
+        let file = validator.validate_bytes(&image, None).unwrap();
+        assert_eq!((file.kind, file.scan), (FileKind::Image, Some(ScanVerdict::Clean)));
+        // the first verdict which is not clean is kept, the whole stream being read
+        let file = validator.validate_reader(Cursor::new(&infected), None).unwrap();
+        assert_eq!((file.kind, file.scan), (FileKind::Invalid, Some(ScanVerdict::Infected(String::from("Eicar-Signature")))));
+        // an invalid file is not scanned
+        assert_eq!(validator.validate(format!("{}/invalid_file.pdf", TEST_DIR)).unwrap().scan, None);
+        assert_eq!(validate_file_bytes(&infected, None, false).unwrap().scan, None);
+
+        let failing = FileValidator::builder().content_scanner(Signature(b"PNG", ScanVerdict::Error(String::from("connection refused")))).build().unwrap();
+        assert_eq!(failing.validate_bytes(&image, None).unwrap_err().to_string(), "The file could not be scanned: connection refused.");
+    }
+
+    #[test]
+    fn quarantined_files() {
+        let dir = std::env::temp_dir().join(format!("validate_quarantine_{}", std::process::id()));
+        let validator = FileValidator::builder().check_extension(true).quarantine(Quarantine::new(&dir, QuarantineMode::Copy)).build().unwrap();
+
+        assert_eq!(validator.validate(format!("{}/valid_image.png", TEST_DIR)).unwrap().quarantined, None);
+        let file = validator.validate(format!("{}/invalid_ext_image_jpg.png", TEST_DIR)).unwrap();
+        let quarantined = file.quarantined.unwrap();
+        assert_eq!(std::fs::read(&quarantined).unwrap(), std::fs::read(format!("{}/invalid_ext_image_jpg.png", TEST_DIR)).unwrap());
+        let sidecar = std::fs::read_to_string(quarantined.with_extension("json")).unwrap();
+        assert!(sidecar.contains("\"reason\": \"The extension doesn't match the type image/jpeg.\", \"mime_type\": \"image/jpeg\""));
+
+        // the unknown types are put in quarantine, not the missing files
+        let error = validator.validate(format!("{}/corpus/urls/accept.txt", TEST_DIR)).unwrap_err();
+        assert_eq!(error.to_string(), "File type is unknown.");
+        assert!(validator.validate(format!("{}/missing.png", TEST_DIR)).is_err());
+        let sidecars = std::fs::read_dir(&dir).unwrap().filter(|entry| entry.as_ref().unwrap().path().extension().is_some_and(|extension| extension == "json")).count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sidecars, 2);
+    }
+
+    #[test]
+    fn custom_matchers() {
+        let raw = b"IIRO\x08\x00\x00\x00 raw sensor data";
+        let heifs = b"\x00\x00\x00\x18ftypmsf1\x00\x00\x00\x00";
+        assert!(validate_file_bytes(raw, Some("photo.orf"), true).is_err());
+
+        let validator = FileValidator::builder()
+            .custom_matcher(CustomMatcher::new(b"IIRO", "image/x-olympus-orf", "orf", MatcherType::Image))
+            .custom_matcher(CustomMatcher::new(b"ftypmsf1", "image/heif-sequence", "heifs", MatcherType::Image).offset(4))
+            .check_extension(true)
+            .build()
+            .unwrap();
+        let file = validator.validate_bytes(raw, Some("photo.ORF")).unwrap();
+        assert_eq!((file.kind, file.mime_type, file.extension), (FileKind::Image, "image/x-olympus-orf", "orf"));
+        assert_eq!(validator.validate_bytes(raw, Some("photo.jpg")).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate_bytes(heifs, Some("burst.heifs")).unwrap().kind, FileKind::Image);
+        assert!(validator.validate_bytes(&heifs[4..], Some("burst.heifs")).is_err());
+
+        // the custom mime types can be whitelisted, the matcher types must be allowed
+        let matcher = CustomMatcher::new(b"IIRO", "image/x-olympus-orf", "orf", MatcherType::Image);
+        let validator = FileValidator::builder().custom_matcher(matcher.clone()).allow_mime(["IMAGE/X-OLYMPUS-ORF"]).build().unwrap();
+        assert_eq!(validator.validate_bytes(raw, None).unwrap().kind, FileKind::Image);
+        let validator = FileValidator::builder().custom_matcher(matcher).allowed_matcher_types([MatcherType::Video]).build().unwrap();
+        assert_eq!(validator.validate_bytes(raw, None).unwrap().kind, FileKind::Invalid);
+        assert_eq!(FileValidator::builder().allow_mime(["image/x-olympus-orf"]).build().unwrap_err().to_string(), "Invalid mime type in white list.");
+
+        let invalid = [
+            CustomMatcher::new(b"", "image/x-raw", "raw", MatcherType::Image),
+            CustomMatcher::new(b"RAW", "image/x-raw", "raw", MatcherType::Custom),
+            CustomMatcher::new(b"RAW", "raw", "raw", MatcherType::Image),
+            CustomMatcher::new(b"RAW", "image/x-raw", ".raw", MatcherType::Image),
+        ];
+        for matcher in invalid {
+            assert_eq!(FileValidator::builder().custom_matcher(matcher).build().unwrap_err().to_string(), "Invalid custom matcher.");
+        }
+    }
+
+    #[test]
+    fn svg_images() {
+        let svg = br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"><circle r="5"/></svg>"#;
+        // infer only detects an xml file
+        assert_eq!(validate_file_bytes(svg, Some("image.svg"), true).unwrap().mime_type, "text/xml");
+        assert_eq!(validate_file_bytes(b"<svg/>", Some("image.svg"), true).unwrap_err().to_string(), "File type is unknown.");
+
+        let validator = FileValidator::builder().allow_svg(true).check_extension(true).build().unwrap();
+        let file = validator.validate_bytes(svg, Some("image.SVG")).unwrap();
+        assert_eq!((file.kind, file.mime_type, file.extension), (FileKind::Image, SVG_MIME_TYPE, "svg"));
+        assert_eq!(validator.validate_bytes(svg, Some("image.png")).unwrap().kind, FileKind::Invalid);
+        assert_eq!(validator.validate_reader(Cursor::new(svg), Some("image.svg")).unwrap().kind, FileKind::Image);
+
+        let error = validator.validate_bytes(br#"<svg onload="alert(1)"/>"#, Some("image.svg")).unwrap_err();
+        assert!(matches!(error, ValidationError::Contents(_)));
+        assert_eq!(error.to_string(), "Dangerous SVG content: event handler onload.");
+
+        // the mime types restrict the svg images
+        let validator = FileValidator::builder().allow_svg(true).allow_mime(["image/png"]).build().unwrap();
+        assert_eq!(validator.validate_bytes(svg, None).unwrap().kind, FileKind::Invalid);
+        let validator = FileValidator::builder().allow_svg(true).allow_mime([SVG_MIME_TYPE]).build().unwrap();
+        assert_eq!(validator.validate_bytes(svg, None).unwrap().kind, FileKind::Image);
+    }
+
+    #[test]
+    fn parsed_extensions() {
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        let kind = |name| validate_file_bytes(&image, Some(name), true).unwrap().kind;
+        assert_eq!(kind("dir.jpg/image.png"), FileKind::Image);
+        assert_eq!(kind("image.jpg.png"), FileKind::Image);
+
+        // the extension must follow a full stop
+        assert_eq!(kind("imagepng"), FileKind::Invalid);
+        assert_eq!(kind("image.apng"), FileKind::Invalid);
+        assert_eq!(kind("image.png.jpg"), FileKind::Invalid);
+
+        // missing extensions
+        assert_eq!(kind("image"), FileKind::Invalid);
+        assert_eq!(kind(".png"), FileKind::Invalid);
+        assert_eq!(kind("image.png."), FileKind::Invalid);
+        assert_eq!(kind(""), FileKind::Invalid);
+    }
+
+    #[cfg(feature = "deep")]
+    #[test]
+    fn deep_validation() {
+        use crate::ImageFormat;
+
+        let path = format!("{}/valid_image.png", TEST_DIR);
+        let validator = FileValidator::builder().deep(true).build().unwrap();
+        let file = validator.validate(&path).unwrap();
+        let image = file.image.unwrap();
+        assert_eq!(image.format, ImageFormat::Png);
+        assert!(image.width > 0 && image.height > 0);
+        assert_eq!(file.bytes_inspected, std::fs::metadata(&path).unwrap().len() as usize);
+
+        // truncated images are only rejected in deep mode
+        let contents = std::fs::read(&path).unwrap();
+        let truncated = &contents[..contents.len() / 2];
+        assert_eq!(validate_file_bytes(truncated, None, false).unwrap().kind, FileKind::Image);
+        let error = validator.validate_bytes(truncated, None).unwrap_err();
+        assert!(matches!(error, ValidationError::Contents(_)));
+        assert_eq!(error.to_string(), "Invalid image: truncated file.");
+
+        // the headers of the other kinds are not parsed
+        let video = std::fs::read(format!("{}/valid_video.mov", TEST_DIR)).unwrap();
+        assert_eq!(validator.validate_bytes(&video, None).unwrap().image, None);
+    }
+
+    #[cfg(feature = "deep")]
+    #[test]
+    fn deep_video_validation() {
+        use crate::VideoFormat;
+
+        let validator = FileValidator::builder().deep(true).build().unwrap();
+        let video = validator.validate(format!("{}/valid_video.mov", TEST_DIR)).unwrap().video.unwrap();
+        assert_eq!((video.format, video.codec.as_str()), (VideoFormat::QuickTime, "avc1"));
+        assert!(video.duration.is_some());
+        let video = validator.validate(format!("{}/valid_video.avi", TEST_DIR)).unwrap().video.unwrap();
+        assert_eq!((video.format, video.codec.as_str()), (VideoFormat::Avi, "H264"));
+
+        // a renamed garbage file is only rejected in deep mode
+        let garbage = b"\x00\x00\x00\x18ftypmp42\x00\x00\x00\x00mp42isomgarbage garbage garbage";
+        assert_eq!(validate_file_bytes(garbage, Some("video.mp4"), true).unwrap().kind, FileKind::Video);
+        let error = validator.validate_bytes(garbage, Some("video.mp4")).unwrap_err();
+        assert!(matches!(error, ValidationError::Contents(_)));
+        assert!(error.to_string().starts_with("Invalid video"));
+
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        assert_eq!(validator.validate_bytes(&image, None).unwrap().video, None);
+    }
+
+    #[cfg(feature = "deep")]
+    #[test]
+    fn polyglot_files() {
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        let polyglot = [&image[..], b"<?php echo 1; ?>"].concat();
+        let validator = FileValidator::builder().deep(true).reject_polyglots(true).build().unwrap();
+        assert!(validator.validate_bytes(&image, None).unwrap().is_valid());
+        let error = validator.validate_bytes(&polyglot, None).unwrap_err();
+        assert!(matches!(error, ValidationError::Contents(_)));
+        assert!(error.to_string().starts_with("Embedded content: PHP code at"));
+
+        // polyglot files are accepted by default
+        let validator = FileValidator::builder().deep(true).build().unwrap();
+        assert!(validator.validate_bytes(&polyglot, None).unwrap().is_valid());
+    }
+
+    #[cfg(feature = "deep")]
+    #[test]
+    fn archive_inspection() {
+        use crate::validators::archive::tests::zip;
+
+        let image = std::fs::read(format!("{}/valid_image.png", TEST_DIR)).unwrap();
+        let size = image.len() as u32;
+        let validator = FileValidator::builder()
+            .allowed_matcher_types([MatcherType::Image, MatcherType::Archive])
+            .check_extension(true)
+            .deep(true)
+            .inspect_archives(true)
+            .build()
+            .unwrap();
+        let error = |archive: &[u8]| validator.validate_bytes(archive, Some("bundle.zip")).unwrap_err().to_string();
+
+        let bundle = zip(&[("photos/", 0, 0, b""), ("photos/a.png", 0, size, &image)]);
+        let file = validator.validate_bytes(&bundle, Some("bundle.zip")).unwrap();
+        assert_eq!(file.kind, FileKind::Archive);
+        let archive = file.archive.unwrap();
+        let mime_types: Vec<_> = archive.entries.iter().map(|entry| entry.mime_type).collect();
+        assert_eq!(mime_types, [None, Some("image/png")]);
+        assert_eq!(archive.size(), image.len() as u64);
+
+        // the entries are validated with their names
+        assert_eq!(error(&zip(&[("notes.txt", 0, 5, b"hello")])), "Invalid archive entry notes.txt: File type is unknown.");
+        assert_eq!(error(&zip(&[("a.jpg", 0, size, &image)])), "Invalid archive entry a.jpg: image/png not allowed.");
+        assert_eq!(error(&zip(&[("../a.png", 0, size, &image)])), "Invalid archive entry ../a.png: Parent directory in path.");
+        assert_eq!(error(&zip(&[("a.png", 0, 8, &image[..8])])), "Invalid archive entry a.png: Invalid image: truncated file.");
+
+        // nested archives
+        let nested = zip(&[("inner.zip", 0, bundle.len() as u32, &bundle)]);
+        assert!(validator.validate_bytes(&nested, Some("bundle.zip")).unwrap().is_valid());
+        let twice = zip(&[("nested.zip", 0, nested.len() as u32, &nested)]);
+        assert_eq!(error(&twice), "The archives are nested too deep (3 levels, at most 2).");
+
+        // zip bomb
+        assert_eq!(error(&zip(&[("a.png", 8, 1 << 20, b"\x03\x00")])), "An archive entry is too compressed (ratio of 524288, at most 100).");
+        let validator = FileValidator::builder().allowed_matcher_types([MatcherType::Archive]).deep(true).inspect_archives(true).max_archive_size(100).build().unwrap();
+        assert_eq!(
+            validator.validate_bytes(&bundle, None).unwrap_err().to_string(),
+            format!("The archive is too large once decompressed ({} bytes, at most 100).", image.len())
+        );
+
+        let rar = validator.validate_bytes(b"Rar!\x1a\x07\x01\x00", Some("bundle.rar")).unwrap_err();
+        assert_eq!(rar.to_string(), "The archive can't be inspected: application/vnd.rar format.");
+
+        // the entries are not inspected by default
+        let validator = FileValidator::builder().allowed_matcher_types([MatcherType::Archive]).deep(true).build().unwrap();
+        assert_eq!(validator.validate_bytes(&zip(&[("notes.txt", 0, 5, b"hello")]), None).unwrap().archive, None);
+        assert_eq!(FileValidator::builder().max_archive_depth(0).build().unwrap_err().to_string(), "Invalid archive limit.");
+    }
+
+    #[cfg(feature = "deep")]
+    #[test]
+    fn image_limits() {
+        use crate::{ImageError, ImageLimit};
+
+        let path = format!("{}/valid_image.png", TEST_DIR);
+        let image = FileValidator::builder().deep(true).build().unwrap().validate(&path).unwrap().image.unwrap();
+
+        let validator = FileValidator::builder().deep(true).max_image_pixels(image.width as u64 * image.height as u64 - 1).build().unwrap();
+        let error = validator.validate(&path).unwrap_err();
+        assert!(matches!(error, ValidationError::Contents(_)));
+        let ValidationError::Contents(error) = error else { panic!("{:?}", error) };
+        let error = error.downcast_ref::<ImageError>().unwrap();
+        assert!(matches!(error, ImageError::TooLarge { limit: ImageLimit::Pixels, .. }));
+
+        let validator = FileValidator::builder().deep(true).max_image_width(image.width).max_image_height(image.height).build().unwrap();
+        assert!(validator.validate(&path).unwrap().is_valid());
+        let validator = FileValidator::builder().deep(true).max_image_height(image.height - 1).build().unwrap();
+        assert!(validator.validate(&path).unwrap_err().to_string().starts_with("The image is too high"));
+
+        assert_eq!(FileValidator::builder().max_image_width(0).build().unwrap_err().to_string(), "Invalid image limit.");
+        assert_eq!(FileValidator::builder().max_image_pixels(0).build().unwrap_err().to_string(), "Invalid image limit.");
+    }
 }
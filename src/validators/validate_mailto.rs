@@ -0,0 +1,170 @@
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::validate_url::{is_domain_name, percent_decode_bytes};
+
+const SCHEME: &str = "mailto:";
+const MAX_ADDRESS_LENGTH: usize = 254;
+const MAX_LOCAL_PART_LENGTH: usize = 64;
+
+// Special characters allowed in an unquoted local part
+const ATEXT_SPECIALS: &str = "!#$%&'*+-/=?^_`{|}~";
+
+/// Validate a `mailto:` uri ([RFC 6068](https://www.rfc-editor.org/rfc/rfc6068)).
+///
+/// The recipients before the `?` are a comma separated list of addresses, followed by optional
+/// `name=value` header fields separated by `&`. The addresses of the `to`, `cc` and `bcc`
+/// fields are validated too. Addresses and fields are percent-decoded first and must be valid
+/// utf-8. The domain of an address follows the host rules of [`validate_url`](crate::validate_url),
+/// or is an ip literal such as `[1.2.3.4]` or `[IPv6:::1]`. The scheme is case insensitive and
+/// the uri must not be empty after it.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_mailto("mailto:jane.doe@test.com,john@test.ch?subject=Hello%20world"));
+/// assert!(!validate_mailto("mailto:jane.doe@test"));
+/// ```
+pub fn validate_mailto(uri: &str) -> bool {
+    let rest = match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => return false,
+    };
+    if rest.is_empty() || rest.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+
+    let (recipients, fields) = match rest.split_once('?') {
+        None => (rest, None),
+        Some((recipients, fields)) => (recipients, Some(fields)),
+    };
+
+    if !recipients.is_empty() && !recipients.split(',').all(|address| decode(address).is_some_and(|address| is_address(&address))) {
+        return false;
+    }
+
+    fields.is_none_or(|fields| fields.split('&').all(is_field))
+}
+
+/// Check a `name=value` header field, the value of the recipient fields being addresses.
+fn is_field(field: &str) -> bool {
+    let (name, value) = match field.split_once('=') {
+        Some((name, value)) => match (decode(name), decode(value)) {
+            (Some(name), Some(value)) if !name.is_empty() => (name, value),
+            _ => return false,
+        },
+        None => return false,
+    };
+
+    match name.to_ascii_lowercase().as_str() {
+        "to" | "cc" | "bcc" => value.split(',').all(|address| is_address(address.trim())),
+        _ => true,
+    }
+}
+
+/// Decode the escapes of an uri component. Returns `None` if an escape is malformed or the
+/// result is not valid utf-8.
+fn decode(component: &str) -> Option<String> {
+    let bytes = component.as_bytes();
+    let malformed = bytes.iter().enumerate().any(|(i, &c)| {
+        c == b'%' && !bytes.get(i + 1..i + 3).is_some_and(|digits| digits.iter().all(u8::is_ascii_hexdigit))
+    });
+    if malformed {
+        return None;
+    }
+    String::from_utf8(percent_decode_bytes(component)).ok()
+}
+
+/// Check an `addr-spec` address: a local part and a domain separated by `@`.
+fn is_address(address: &str) -> bool {
+    match address.rsplit_once('@') {
+        Some((local, domain)) => address.len() <= MAX_ADDRESS_LENGTH && is_local_part(local) && is_mail_domain(domain),
+        None => false,
+    }
+}
+
+/// Check a local part, either dot separated atoms or a quoted string.
+fn is_local_part(local: &str) -> bool {
+    if local.len() > MAX_LOCAL_PART_LENGTH {
+        return false;
+    }
+
+    match local.strip_prefix('"').and_then(|quoted| quoted.strip_suffix('"')) {
+        Some(quoted) => {
+            let mut chars = quoted.chars();
+            while let Some(c) = chars.next() {
+                let valid = match c {
+                    // A quoted pair escapes any printable char
+                    '\\' => chars.next().is_some_and(|escaped| escaped.is_ascii_graphic() || escaped == ' '),
+                    '"' => false,
+                    c => c.is_ascii_graphic() || c == ' ',
+                };
+                if !valid {
+                    return false;
+                }
+            }
+            true
+        }
+        None => local.split('.').all(|atom| {
+            !atom.is_empty() && atom.chars().all(|c| c.is_ascii_alphanumeric() || ATEXT_SPECIALS.contains(c) || !c.is_ascii())
+        }),
+    }
+}
+
+/// Check the domain of an address, either a domain name or an ip literal.
+fn is_mail_domain(domain: &str) -> bool {
+    match domain.strip_prefix('[').and_then(|literal| literal.strip_suffix(']')) {
+        Some(literal) => match literal.strip_prefix("IPv6:") {
+            Some(ipv6) => ipv6.parse::<Ipv6Addr>().is_ok(),
+            None => literal.parse::<Ipv4Addr>().is_ok(),
+        },
+        None => is_domain_name(domain),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate_mailto;
+
+    #[test]
+    fn valid_mailtos() {
+        assert!(validate_mailto("mailto:jane.doe@test.com"));
+        assert!(validate_mailto("MAILTO:jane.doe@test.com"));
+        assert!(validate_mailto("mailto:jane.doe@test.com,john@sub.test.ch"));
+        assert!(validate_mailto("mailto:jane+tag@test.com?subject=Hello%20world&body=Hi"));
+        assert!(validate_mailto("mailto:?to=jane@test.com&cc=john@test.ch,%20bob@test.org"));
+        assert!(validate_mailto("mailto:?subject=Hello"));
+        assert!(validate_mailto("mailto:%22jane%20doe%22@test.com"));
+        assert!(validate_mailto("mailto:jane@[1.2.3.4]"));
+        assert!(validate_mailto("mailto:jane@[IPv6:::1]"));
+        #[cfg(feature = "idna")]
+        assert!(validate_mailto("mailto:j%C3%BCrgen@m%C3%BCnchen.de"));
+        assert!(validate_mailto("mailto:jane@test.com?In-Reply-To=%3C3469A91.D10AF4C@test.com%3E"));
+    }
+
+    #[test]
+    fn invalid_mailtos() {
+        assert!(!validate_mailto(""));
+        assert!(!validate_mailto("mailto:"));
+        assert!(!validate_mailto("jane.doe@test.com"));
+        assert!(!validate_mailto("https://test.com"));
+
+        // invalid addresses
+        assert!(!validate_mailto("mailto:jane.doe@test"));
+        assert!(!validate_mailto("mailto:jane.doe"));
+        assert!(!validate_mailto("mailto:@test.com"));
+        assert!(!validate_mailto("mailto:jane..doe@test.com"));
+        assert!(!validate_mailto("mailto:.jane@test.com"));
+        assert!(!validate_mailto("mailto:jane@test.com,"));
+        assert!(!validate_mailto("mailto:jane doe@test.com"));
+        assert!(!validate_mailto("mailto:jane%20doe@test.com"));
+        assert!(!validate_mailto("mailto:jane@[1.2.3]"));
+        assert!(!validate_mailto("mailto:jane@test.com/path"));
+        assert!(!validate_mailto(&format!("mailto:{}@test.com", "a".repeat(65))));
+
+        // invalid fields
+        assert!(!validate_mailto("mailto:jane@test.com?subject"));
+        assert!(!validate_mailto("mailto:jane@test.com?=Hello"));
+        assert!(!validate_mailto("mailto:?cc=john"));
+        assert!(!validate_mailto("mailto:jane@test.com?subject=100%"));
+        assert!(!validate_mailto("mailto:jane@test.com?body=%FF"));
+    }
+}
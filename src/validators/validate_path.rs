@@ -0,0 +1,325 @@
+use std::fmt::{self, Display, Formatter};
+
+use icu_normalizer::ComposingNormalizerBorrowed;
+
+use super::ValidationError;
+
+/// Maximal length in bytes of a path component on common filesystems.
+const MAX_COMPONENT_LENGTH: usize = 255;
+
+/// Characters Windows doesn't allow in filenames, the separators excepted.
+const WINDOWS_INVALID_CHARS: [char; 7] = ['<', '>', ':', '"', '|', '?', '*'];
+
+/// Names of devices on Windows, with or without extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8", "com9", "lpt1", "lpt2",
+    "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Extensions of executables, scripts and server-side pages, flagged anywhere in a filename by
+/// [`find_denied_extension`].
+pub const DANGEROUS_EXTENSIONS: &[&str] = &[
+    "exe", "dll", "scr", "pif", "cpl", "msi", "bat", "cmd", "vbs", "vbe", "js", "jse", "wsf", "wsh", "hta", "ps1", "psm1",
+    "jar", "sh", "bash", "php", "php3", "php4", "php5", "php7", "phtml", "pht", "phar", "asp", "aspx", "ascx", "ashx",
+    "asmx", "jsp", "jspx", "cgi", "pl", "py", "rb", "shtml", "htaccess",
+];
+
+/// Reason why a path is rejected by [`validate_upload_path`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathError {
+    /// The path is empty
+    Empty,
+    /// The path has a NUL byte
+    NulByte,
+    /// The path has another control character
+    ControlCharacter,
+    /// The path is absolute, e.g. `/a`, `C:a` or `\\server\a`
+    Absolute,
+    /// The path has a character not allowed on Windows
+    InvalidCharacter,
+    /// A component of the path is `..`
+    ParentDirectory,
+    /// A component of the path is longer than the maximum in bytes
+    TooLong {
+        /// The maximal length
+        max: usize,
+    },
+    /// A component of the path is reserved on Windows, e.g. `CON`
+    ReservedName,
+    /// A component of the path ends with a full stop or a space
+    TrailingDot,
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::Empty => write!(f, "The path is empty."),
+            PathError::NulByte => write!(f, "NUL byte in path."),
+            PathError::ControlCharacter => write!(f, "Control character in path."),
+            PathError::Absolute => write!(f, "Absolute paths are not allowed."),
+            PathError::InvalidCharacter => write!(f, "Invalid character in path."),
+            PathError::ParentDirectory => write!(f, "Parent directory in path."),
+            PathError::TooLong { max } => write!(f, "Path component longer than {} bytes.", max),
+            PathError::ReservedName => write!(f, "Reserved name in path."),
+            PathError::TrailingDot => write!(f, "Path component ending with a full stop or a space."),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Validate a user supplied relative path before storing a file under it, e.g. the name of an
+/// upload.
+///
+/// Both `/` and `\` are separators. The path is rejected if it is absolute (`/a`, `\a`, `C:a`,
+/// `\\server\a`), has a `..` component, NUL bytes or other control characters, characters not
+/// allowed on Windows (`<>:"|?*`), a component reserved on Windows (`CON`, `nul.txt`, ...) or
+/// ending with a full stop or a space, or a component longer than 255 bytes.
+///
+/// # Errors
+/// If the path is unsafe, a [`ValidationError::Path`] telling why is returned.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_upload_path("images/cat.png").is_ok());
+/// assert!(validate_upload_path("../../etc/passwd").is_err());
+/// ```
+pub fn validate_upload_path(path: &str) -> Result<(), ValidationError> {
+    if path.is_empty() {
+        return Err(PathError::Empty.into());
+    }
+
+    if path.contains('\0') {
+        return Err(PathError::NulByte.into());
+    }
+
+    if path.chars().any(char::is_control) {
+        return Err(PathError::ControlCharacter.into());
+    }
+
+    let drive = path.len() >= 2 && path.as_bytes()[0].is_ascii_alphabetic() && path.as_bytes()[1] == b':';
+    if path.starts_with(['/', '\\']) || drive {
+        return Err(PathError::Absolute.into());
+    }
+
+    if path.contains(WINDOWS_INVALID_CHARS) {
+        return Err(PathError::InvalidCharacter.into());
+    }
+
+    for component in path.split(['/', '\\']) {
+        if component == ".." {
+            return Err(PathError::ParentDirectory.into());
+        }
+
+        if component.len() > MAX_COMPONENT_LENGTH {
+            return Err(PathError::TooLong { max: MAX_COMPONENT_LENGTH }.into());
+        }
+
+        if is_windows_reserved(component) {
+            return Err(PathError::ReservedName.into());
+        }
+
+        if component != "." && component.ends_with(['.', ' ']) {
+            return Err(PathError::TrailingDot.into());
+        }
+    }
+
+    Ok(())
+}
+
+/// Sanitize a user supplied filename into a name safe to store on common filesystems, see
+/// [`sanitize_filename_with_length`]. The name is at most 255 bytes long.
+///
+/// # Examples
+/// ``` ignore
+/// assert_eq!(sanitize_filename("../../etc/pass?wd"), "pass_wd");
+/// assert_eq!(sanitize_filename("CON.txt"), "_CON.txt");
+/// ```
+pub fn sanitize_filename(name: &str) -> String {
+    sanitize_filename_with_length(name, MAX_COMPONENT_LENGTH)
+}
+
+/// Sanitize a user supplied filename into a name safe to store on common filesystems, at most
+/// `max_length` bytes long. The result is always accepted by [`validate_upload_path`] when the
+/// length is at most 255.
+///
+/// The directories are stripped, the name is normalized to its composed form (NFC), control and
+/// invisible formatting characters (e.g. the right to left override of `cat\u{202E}gnp.exe`) are
+/// removed, the separators and the characters not
+/// allowed on Windows are replaced with `_`, and the leading and trailing full stops and spaces
+/// are trimmed. Windows reserved names are prefixed with `_`. A too long name is truncated,
+/// keeping its extension if it is short enough. An empty result is replaced with `_`.
+///
+/// The composed and decomposed forms of a name, e.g. an `é` typed on macOS or on Windows, are
+/// sanitized to the same name.
+pub fn sanitize_filename_with_length(name: &str, max_length: usize) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let name = ComposingNormalizerBorrowed::new_nfc().normalize(name);
+    let name: String = name
+        .chars()
+        .filter(|&c| !c.is_control() && !is_invisible(c))
+        .map(|c| if WINDOWS_INVALID_CHARS.contains(&c) { '_' } else { c })
+        .collect();
+    let name = name.trim_matches(['.', ' ']);
+
+    let mut sanitized = truncate_filename(name, max_length);
+    if is_windows_reserved(&sanitized) {
+        sanitized = truncate_filename(&format!("_{}", sanitized), max_length);
+    }
+    if sanitized.is_empty() {
+        sanitized = String::from("_");
+    }
+    sanitized
+}
+
+/// Find the first extension of a filename inside a deny-list, e.g. [`DANGEROUS_EXTENSIONS`],
+/// wherever it is in the name: `invoice.php.jpg` is flagged like `run.jpg.exe`. Both `.` and `;`
+/// separate the extensions, so that `shell.php;.jpg` is flagged too, and the directories of the
+/// path are ignored. The extensions are not case sensitive and the spaces around them are
+/// ignored.
+///
+/// # Examples
+/// ``` ignore
+/// assert_eq!(find_denied_extension("invoice.PHP.jpg", DANGEROUS_EXTENSIONS), Some("PHP"));
+/// assert_eq!(find_denied_extension("cat.png", DANGEROUS_EXTENSIONS), None);
+/// ```
+pub fn find_denied_extension<'a, S: AsRef<str>>(filename: &'a str, deny_list: &[S]) -> Option<&'a str> {
+    let name = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+    name.split(['.', ';'])
+        .skip(1)
+        .map(str::trim)
+        .find(|extension| deny_list.iter().any(|denied| denied.as_ref().eq_ignore_ascii_case(extension)))
+}
+
+/// Check whether a char is an invisible formatting char, such as a zero width space or a
+/// bidirectional override.
+fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{AD}' | '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2064}' | '\u{2066}'..='\u{2069}' | '\u{FEFF}')
+}
+
+/// Truncate a filename to a length in bytes, keeping its extension if it takes at most half of
+/// the length.
+fn truncate_filename(name: &str, max_length: usize) -> String {
+    if name.len() <= max_length {
+        return name.to_string();
+    }
+
+    let (stem, extension) = match name.rfind('.') {
+        Some(index) if index > 0 && name.len() - index <= max_length / 2 => name.split_at(index),
+        _ => (name, ""),
+    };
+
+    let mut end = max_length - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", stem[..end].trim_end_matches(['.', ' ']), extension)
+}
+
+/// Check whether a path component names a Windows device, ignoring its extension.
+fn is_windows_reserved(component: &str) -> bool {
+    let stem = component.split('.').next().unwrap_or_default().trim_end();
+    WINDOWS_RESERVED_NAMES.iter().any(|name| stem.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{find_denied_extension, sanitize_filename, sanitize_filename_with_length, validate_upload_path, DANGEROUS_EXTENSIONS};
+
+    #[test]
+    fn valid_paths() {
+        assert!(validate_upload_path("cat.png").is_ok());
+        assert!(validate_upload_path("images/cat.png").is_ok());
+        assert!(validate_upload_path("images\\2024\\cat.tar.gz").is_ok());
+        assert!(validate_upload_path("./images/..cat.png").is_ok());
+        assert!(validate_upload_path("console.png").is_ok());
+        assert!(validate_upload_path("chat noir.png").is_ok());
+        assert!(validate_upload_path(&"a".repeat(255)).is_ok());
+    }
+
+    #[test]
+    fn invalid_paths() {
+        assert_eq!(validate_upload_path("").unwrap_err().to_string(), "The path is empty.");
+        assert_eq!(validate_upload_path("/etc/passwd").unwrap_err().to_string(), "Absolute paths are not allowed.");
+        assert_eq!(validate_upload_path("\\\\server\\share").unwrap_err().to_string(), "Absolute paths are not allowed.");
+        assert_eq!(validate_upload_path("C:\\Windows").unwrap_err().to_string(), "Absolute paths are not allowed.");
+        assert_eq!(validate_upload_path("c:cat.png").unwrap_err().to_string(), "Absolute paths are not allowed.");
+        assert_eq!(validate_upload_path("../../etc/passwd").unwrap_err().to_string(), "Parent directory in path.");
+        assert_eq!(validate_upload_path("images\\..\\..\\cat.png").unwrap_err().to_string(), "Parent directory in path.");
+        assert_eq!(validate_upload_path("cat.png\0.jpg").unwrap_err().to_string(), "NUL byte in path.");
+        assert_eq!(validate_upload_path("cat\n.png").unwrap_err().to_string(), "Control character in path.");
+        assert_eq!(validate_upload_path("cat.png:stream").unwrap_err().to_string(), "Invalid character in path.");
+        assert_eq!(validate_upload_path("cat?.png").unwrap_err().to_string(), "Invalid character in path.");
+        assert_eq!(validate_upload_path("CON").unwrap_err().to_string(), "Reserved name in path.");
+        assert_eq!(validate_upload_path("images/nul.txt").unwrap_err().to_string(), "Reserved name in path.");
+        assert_eq!(validate_upload_path("Com1 .png").unwrap_err().to_string(), "Reserved name in path.");
+        assert_eq!(validate_upload_path("cat.png.").unwrap_err().to_string(), "Path component ending with a full stop or a space.");
+        assert_eq!(validate_upload_path("images /cat.png").unwrap_err().to_string(), "Path component ending with a full stop or a space.");
+        assert_eq!(validate_upload_path(&"a".repeat(256)).unwrap_err().to_string(), "Path component longer than 255 bytes.");
+    }
+
+    #[test]
+    fn denied_extensions() {
+        assert_eq!(find_denied_extension("invoice.php.jpg", DANGEROUS_EXTENSIONS), Some("php"));
+        assert_eq!(find_denied_extension("run.jpg.exe", DANGEROUS_EXTENSIONS), Some("exe"));
+        assert_eq!(find_denied_extension("uploads/Shell.PhTmL", DANGEROUS_EXTENSIONS), Some("PhTmL"));
+        assert_eq!(find_denied_extension("shell.php;.jpg", DANGEROUS_EXTENSIONS), Some("php"));
+        assert_eq!(find_denied_extension("shell.asp .png", DANGEROUS_EXTENSIONS), Some("asp"));
+        assert_eq!(find_denied_extension(".htaccess", DANGEROUS_EXTENSIONS), Some("htaccess"));
+
+        // the stem and the directories are not extensions
+        assert_eq!(find_denied_extension("php.png", DANGEROUS_EXTENSIONS), None);
+        assert_eq!(find_denied_extension("a.exe/cat.png", DANGEROUS_EXTENSIONS), None);
+        assert_eq!(find_denied_extension("cat.tar.gz", DANGEROUS_EXTENSIONS), None);
+        assert_eq!(find_denied_extension("php5", DANGEROUS_EXTENSIONS), None);
+
+        assert_eq!(find_denied_extension("cat.svg.png", &["svg"]), Some("svg"));
+        assert_eq!(find_denied_extension("invoice.php.jpg", &[] as &[&str]), None);
+    }
+
+    #[test]
+    fn sanitize_filenames() {
+        assert_eq!(sanitize_filename("cat.png"), "cat.png");
+        assert_eq!(sanitize_filename("../../etc/pass?wd"), "pass_wd");
+        assert_eq!(sanitize_filename("C:\\Users\\me\\cat.png"), "cat.png");
+        assert_eq!(sanitize_filename("c:cat<1>.png"), "c_cat_1_.png");
+        assert_eq!(sanitize_filename("cat\u{202E}gnp.exe"), "catgnp.exe");
+        assert_eq!(sanitize_filename("c\0a\nt\u{200B}.png"), "cat.png");
+        assert_eq!(sanitize_filename(" ..hidden.png. "), "hidden.png");
+        assert_eq!(sanitize_filename("CON.txt"), "_CON.txt");
+        assert_eq!(sanitize_filename("nul"), "_nul");
+        assert_eq!(sanitize_filename("chat noir.png"), "chat noir.png");
+        assert_eq!(sanitize_filename(".."), "_");
+        assert_eq!(sanitize_filename(""), "_");
+        assert_eq!(sanitize_filename("images/"), "_");
+
+        // decomposed forms are composed
+        assert_eq!(sanitize_filename("caf\u{65}\u{301}.png"), "caf\u{E9}.png");
+        assert_eq!(sanitize_filename("caf\u{65}\u{301}.png"), sanitize_filename("caf\u{E9}.png"));
+    }
+
+    #[test]
+    fn truncate_filenames() {
+        assert_eq!(sanitize_filename_with_length("abcdefgh.png", 8), "abcd.png");
+        assert_eq!(sanitize_filename_with_length("abcdefgh.tar.gz", 10), "abcdefg.gz");
+        assert_eq!(sanitize_filename_with_length("abc.d.png", 8), "abc.png");
+        assert_eq!(sanitize_filename_with_length("abcdefgh.png", 6), "abcdef");
+        assert_eq!(sanitize_filename_with_length("ééé.png", 8), "éé.png");
+        assert_eq!(sanitize_filename_with_length("console", 3), "_co");
+        assert_eq!(sanitize_filename_with_length("é", 1), "_");
+        assert_eq!(sanitize_filename(&"a".repeat(300)).len(), 255);
+        assert_eq!(sanitize_filename(&format!("{}.png", "é".repeat(200))), format!("{}.png", "é".repeat(125)));
+    }
+
+    #[test]
+    fn sanitized_filenames_are_valid() {
+        let names = ["../..", "a/b/../..", "\\\\server\\share", "AUX.tar.gz", "a\u{0}b", "  . ", "x:y", "COM1 ", "lpt9..", "é. .é"];
+        for name in names {
+            for max_length in [1, 3, 5, 255] {
+                let sanitized = sanitize_filename_with_length(name, max_length);
+                assert!(sanitized.len() <= max_length, "{:?}", sanitized);
+                assert!(validate_upload_path(&sanitized).is_ok(), "{:?} {:?}", name, sanitized);
+            }
+        }
+    }
+}
@@ -0,0 +1,595 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Elements removed with their contents: scripts and embedded documents.
+const DANGEROUS_ELEMENTS: [&str; 7] = ["script", "foreignobject", "iframe", "embed", "object", "handler", "listener"];
+
+/// Animation elements, able to set an attribute such as a link to a script.
+const ANIMATION_ELEMENTS: [&str; 4] = ["set", "animate", "animatemotion", "animatetransform"];
+
+/// Raster images accepted inline in the references.
+const DATA_IMAGE_TYPES: [&str; 4] = ["data:image/png", "data:image/jpeg", "data:image/gif", "data:image/webp"];
+
+/// Handling of the dangerous content of an SVG image by [`sanitize_svg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SvgMode {
+    /// Remove the dangerous content and return the cleaned image
+    Sanitize,
+    /// Reject the image at the first dangerous content
+    Reject,
+}
+
+/// Error of [`sanitize_svg`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SvgError {
+    /// The file is not a well-formed SVG image, for the given reason
+    Invalid(String),
+    /// The image has dangerous content, given by the reason, in [`SvgMode::Reject`]
+    Dangerous(String),
+}
+
+impl Display for SvgError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SvgError::Invalid(reason) => write!(f, "Invalid SVG image: {}.", reason),
+            SvgError::Dangerous(reason) => write!(f, "Dangerous SVG content: {}.", reason),
+        }
+    }
+}
+
+impl std::error::Error for SvgError {}
+
+fn invalid(reason: &str) -> SvgError {
+    SvgError::Invalid(reason.to_string())
+}
+
+/// Check whether a file is an SVG image: an XML document whose root element is `svg`, after an
+/// optional XML declaration, comments, processing instructions and document type declaration.
+/// infer doesn't detect SVG images.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(is_svg(br#"<?xml version="1.0"?><svg xmlns="http://www.w3.org/2000/svg"/>"#));
+/// assert!(!is_svg(b"<html><svg/></html>"));
+/// ```
+pub fn is_svg(contents: &[u8]) -> bool {
+    let Ok(text) = std::str::from_utf8(contents) else { return false };
+    let mut parser = Parser::new(text.strip_prefix('\u{FEFF}').unwrap_or(text));
+    loop {
+        match parser.next_token() {
+            Ok(Some(Token::Text(text))) if text.trim().is_empty() => {}
+            Ok(Some(Token::Comment | Token::Declaration | Token::Doctype { .. } | Token::Instruction)) => {}
+            Ok(Some(Token::Start { name, .. })) => return name == "svg",
+            _ => return false,
+        }
+    }
+}
+
+/// Sanitize an SVG image before serving it, as its scripts run when it is opened by a browser.
+///
+/// The dangerous content is: the scripts, foreign objects and embedded documents (removed with
+/// their contents), the event handler attributes (`onload`...), the references to anything but
+/// an element of the image or an inline PNG, JPEG, GIF or WebP image (`href`, `xlink:href`,
+/// `src` and `url()` in the styles, CSS imports and escapes), the animations of these
+/// attributes, the processing instructions such as `xml-stylesheet` and the document type
+/// declarations with entities. The rest of the image is kept as is.
+///
+/// # Errors
+/// If the file is not a well-formed SVG image, or has dangerous content in [`SvgMode::Reject`].
+///
+/// # Examples
+/// ``` ignore
+/// let svg = sanitize_svg(br#"<svg onload="alert(1)"><circle r="5"/></svg>"#, SvgMode::Sanitize)?;
+/// assert_eq!(svg, r#"<svg><circle r="5"/></svg>"#);
+/// ```
+pub fn sanitize_svg(contents: &[u8], mode: SvgMode) -> Result<String, SvgError> {
+    if !is_svg(contents) {
+        return Err(invalid("not an SVG image"));
+    }
+    let text = std::str::from_utf8(contents).unwrap();
+    let text = text.strip_prefix('\u{FEFF}').unwrap_or(text);
+
+    let mut sanitizer = Sanitizer { mode, output: String::with_capacity(text.len()) };
+    let mut parser = Parser::new(text);
+    // Open elements, and depth of the removed element whose contents are skipped
+    let mut stack: Vec<&str> = Vec::new();
+    let mut skipped: Option<usize> = None;
+    let mut root_closed = false;
+
+    while let Some(token) = parser.next_token()? {
+        match token {
+            Token::Start { name, attributes, empty } => {
+                if root_closed {
+                    return Err(invalid("content after the root element"));
+                }
+                if skipped.is_none() {
+                    if let Some(reason) = dangerous_element(name, &attributes) {
+                        sanitizer.found(reason)?;
+                        skipped = Some(stack.len());
+                    } else if name.eq_ignore_ascii_case("style") && !empty {
+                        // The style sheet is checked as a whole
+                        let css = parser.raw_until("</")?;
+                        match css_reference(css) {
+                            Some(reason) => {
+                                sanitizer.found(reason)?;
+                                skipped = Some(stack.len());
+                            }
+                            None => {
+                                sanitizer.start_tag(name, &attributes, empty)?;
+                                sanitizer.output.push_str(css);
+                            }
+                        }
+                    } else {
+                        sanitizer.start_tag(name, &attributes, empty)?;
+                    }
+                }
+                if empty {
+                    if skipped == Some(stack.len()) {
+                        skipped = None;
+                    }
+                    root_closed = stack.is_empty();
+                } else {
+                    stack.push(name);
+                }
+            }
+            Token::End { name, raw } => {
+                if stack.pop() != Some(name) {
+                    return Err(invalid("mismatched end tag"));
+                }
+                if skipped == Some(stack.len()) {
+                    skipped = None;
+                } else if skipped.is_none() {
+                    sanitizer.output.push_str(raw);
+                }
+                root_closed = stack.is_empty();
+            }
+            Token::Text(text) => {
+                if root_closed && !text.trim().is_empty() {
+                    return Err(invalid("content after the root element"));
+                }
+                if skipped.is_none() {
+                    sanitizer.output.push_str(text);
+                }
+            }
+            Token::Comment | Token::Declaration | Token::Doctype { .. } if skipped.is_some() => {}
+            Token::Comment | Token::Declaration => sanitizer.output.push_str(parser.last_raw()),
+            Token::Doctype { entities: true } => sanitizer.found(String::from("entity declarations"))?,
+            Token::Doctype { entities: false } => sanitizer.output.push_str(parser.last_raw()),
+            Token::Instruction if skipped.is_some() => {}
+            Token::Instruction => sanitizer.found(String::from("processing instruction"))?,
+        }
+    }
+
+    if !root_closed {
+        return Err(invalid("unclosed element"));
+    }
+    Ok(sanitizer.output)
+}
+
+/// Reason why an element is removed with its contents, if dangerous.
+fn dangerous_element(name: &str, attributes: &[Attribute]) -> Option<String> {
+    let local_name = local_name(name).to_lowercase();
+    if DANGEROUS_ELEMENTS.contains(&local_name.as_str()) {
+        return Some(format!("{} element", name));
+    }
+
+    // An animation of a link or an event handler can inject a script
+    if ANIMATION_ELEMENTS.contains(&local_name.as_str()) {
+        let target = attributes.iter().find(|attribute| attribute.name == "attributeName").map(|attribute| decode_entities(attribute.value));
+        if let Some(target) = target.filter(|target| is_link(target) || is_event_handler(target)) {
+            return Some(format!("animation of the {} attribute", target.trim()));
+        }
+    }
+    None
+}
+
+/// Name without its namespace prefix.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn is_event_handler(name: &str) -> bool {
+    local_name(name.trim()).get(..2).is_some_and(|prefix| prefix.eq_ignore_ascii_case("on"))
+}
+
+fn is_link(name: &str) -> bool {
+    let name = local_name(name.trim());
+    name.eq_ignore_ascii_case("href") || name.eq_ignore_ascii_case("src")
+}
+
+/// Whether a reference stays inside the image: an element of the image, or an inline raster
+/// image.
+fn is_local_reference(reference: &str) -> bool {
+    let reference: String = reference.chars().filter(|c| !c.is_whitespace() && !c.is_control()).collect();
+    reference.starts_with('#')
+        || DATA_IMAGE_TYPES.iter().any(|data| {
+            reference.get(..data.len()).is_some_and(|prefix| prefix.eq_ignore_ascii_case(data))
+                && reference[data.len()..].starts_with([';', ','])
+        })
+}
+
+/// Reason why a style sheet or a style attribute is dangerous, if it references anything
+/// outside the image.
+fn css_reference(css: &str) -> Option<String> {
+    let lowercase = css.to_lowercase();
+    // Escapes could hide the other constructs
+    if lowercase.contains('\\') {
+        return Some(String::from("escaped style"));
+    }
+    if lowercase.contains("@import") {
+        return Some(String::from("style sheet import"));
+    }
+    if lowercase.contains("expression(") || lowercase.contains("javascript:") {
+        return Some(String::from("script in style"));
+    }
+
+    let mut rest = lowercase.as_str();
+    while let Some(start) = rest.find("url(") {
+        rest = &rest[start + 4..];
+        let end = rest.find(')').unwrap_or(rest.len());
+        let reference = rest[..end].trim().trim_matches(['"', '\'']);
+        if !is_local_reference(reference) {
+            return Some(format!("external reference {}", reference));
+        }
+    }
+    None
+}
+
+/// Decode the predefined and character entities of an attribute value. Other entities are kept
+/// as is.
+fn decode_entities(value: &str) -> String {
+    let mut decoded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let character = match entity {
+            Some("lt") => Some('<'),
+            Some("gt") => Some('>'),
+            Some("amp") => Some('&'),
+            Some("quot") => Some('"'),
+            Some("apos") => Some('\''),
+            Some(entity) => entity
+                .strip_prefix("#x")
+                .or_else(|| entity.strip_prefix("#X"))
+                .map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|decimal| decimal.parse()))
+                .and_then(Result::ok)
+                .and_then(char::from_u32),
+            None => None,
+        };
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Output of [`sanitize_svg`], failing at the first dangerous content in reject mode.
+struct Sanitizer {
+    mode: SvgMode,
+    output: String,
+}
+
+impl Sanitizer {
+    fn found(&self, reason: String) -> Result<(), SvgError> {
+        match self.mode {
+            SvgMode::Sanitize => Ok(()),
+            SvgMode::Reject => Err(SvgError::Dangerous(reason)),
+        }
+    }
+
+    /// Write a start tag without its dangerous attributes.
+    fn start_tag(&mut self, name: &str, attributes: &[Attribute], empty: bool) -> Result<(), SvgError> {
+        self.output.push('<');
+        self.output.push_str(name);
+        for attribute in attributes {
+            let value = decode_entities(attribute.value);
+            let reason = if is_event_handler(attribute.name) {
+                Some(format!("event handler {}", attribute.name))
+            } else if is_link(attribute.name) && !is_local_reference(&value) {
+                Some(format!("external reference {}", value.trim()))
+            } else if attribute.name.eq_ignore_ascii_case("style") {
+                css_reference(&value)
+            } else {
+                None
+            };
+            match reason {
+                Some(reason) => self.found(reason)?,
+                None => {
+                    self.output.push(' ');
+                    self.output.push_str(attribute.raw);
+                }
+            }
+        }
+        self.output.push_str(if empty { "/>" } else { ">" });
+        Ok(())
+    }
+}
+
+/// Attribute of a start tag.
+struct Attribute<'a> {
+    name: &'a str,
+    /// Value between the quotes, with its entities
+    value: &'a str,
+    /// Whole attribute, from its name to its closing quote
+    raw: &'a str,
+}
+
+/// XML construct of an SVG image.
+enum Token<'a> {
+    Start { name: &'a str, attributes: Vec<Attribute<'a>>, empty: bool },
+    End { name: &'a str, raw: &'a str },
+    /// Text, character data sections included
+    Text(&'a str),
+    Comment,
+    /// XML declaration
+    Declaration,
+    /// Document type declaration, with or without entity declarations
+    Doctype { entities: bool },
+    /// Processing instruction other than the XML declaration
+    Instruction,
+}
+
+/// Minimal XML tokenizer, checking the syntax needed to sanitize an image.
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+    token_start: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(text: &'a str) -> Self {
+        Parser { text, position: 0, token_start: 0 }
+    }
+
+    /// Raw text of the last token.
+    fn last_raw(&self) -> &'a str {
+        &self.text[self.token_start..self.position]
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    /// Raw text up to a delimiter, excluded.
+    fn raw_until(&mut self, delimiter: &str) -> Result<&'a str, SvgError> {
+        let length = self.rest().find(delimiter).ok_or_else(|| invalid("unclosed element"))?;
+        let raw = &self.rest()[..length];
+        self.position += length;
+        Ok(raw)
+    }
+
+    /// Raw text up to a delimiter, included.
+    fn raw_through(&mut self, delimiter: &str, construct: &str) -> Result<&'a str, SvgError> {
+        let length = self.rest().find(delimiter).ok_or_else(|| SvgError::Invalid(format!("unclosed {}", construct)))?;
+        let raw = &self.rest()[..length];
+        self.position += length + delimiter.len();
+        Ok(raw)
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    fn name(&mut self) -> Result<&'a str, SvgError> {
+        let rest = self.rest();
+        let length = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '/' | '>' | '<' | '"' | '\''))
+            .unwrap_or(rest.len());
+        if length == 0 {
+            return Err(invalid("missing name"));
+        }
+        self.position += length;
+        Ok(&rest[..length])
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, SvgError> {
+        self.token_start = self.position;
+        let rest = self.rest();
+        if rest.is_empty() {
+            return Ok(None);
+        }
+
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            self.position += 4;
+            if self.raw_through("-->", "comment")?.contains("--") || comment.starts_with('-') {
+                return Err(invalid("bad comment"));
+            }
+            Ok(Some(Token::Comment))
+        } else if rest.starts_with("<![CDATA[") {
+            self.position += 9;
+            self.raw_through("]]>", "character data")?;
+            Ok(Some(Token::Text(self.last_raw())))
+        } else if rest.starts_with("<!DOCTYPE") {
+            // The internal subset, between brackets, declares the entities
+            let end = rest.find(['[', '>']).ok_or_else(|| invalid("unclosed document type"))?;
+            let entities = rest[end..].starts_with('[');
+            self.position += end;
+            if entities {
+                self.raw_through("]", "document type")?;
+                self.skip_whitespace();
+                if !self.rest().starts_with('>') {
+                    return Err(invalid("unclosed document type"));
+                }
+            }
+            self.position += 1;
+            Ok(Some(Token::Doctype { entities }))
+        } else if rest.starts_with("<!") {
+            Err(invalid("bad declaration"))
+        } else if rest.starts_with("<?") {
+            self.position += 2;
+            let target = self.name()?;
+            self.raw_through("?>", "processing instruction")?;
+            if target == "xml" {
+                if self.token_start != 0 {
+                    return Err(invalid("misplaced XML declaration"));
+                }
+                return Ok(Some(Token::Declaration));
+            }
+            Ok(Some(Token::Instruction))
+        } else if rest.starts_with("</") {
+            self.position += 2;
+            let name = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('>') {
+                return Err(invalid("bad end tag"));
+            }
+            self.position += 1;
+            Ok(Some(Token::End { name, raw: self.last_raw() }))
+        } else if rest.starts_with('<') {
+            self.position += 1;
+            self.start_tag().map(Some)
+        } else {
+            let length = rest.find('<').unwrap_or(rest.len());
+            self.position += length;
+            Ok(Some(Token::Text(&rest[..length])))
+        }
+    }
+
+    /// Parse a start tag after its opening bracket.
+    fn start_tag(&mut self) -> Result<Token<'a>, SvgError> {
+        let name = self.name()?;
+        let mut attributes: Vec<Attribute> = Vec::new();
+        loop {
+            let before = self.position;
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("/>") {
+                self.position += 2;
+                return Ok(Token::Start { name, attributes, empty: true });
+            }
+            if rest.starts_with('>') {
+                self.position += 1;
+                return Ok(Token::Start { name, attributes, empty: false });
+            }
+            if self.position == before {
+                return Err(invalid("bad start tag"));
+            }
+
+            let start = self.position;
+            let attribute_name = self.name()?;
+            self.skip_whitespace();
+            if !self.rest().starts_with('=') {
+                return Err(invalid("attribute without value"));
+            }
+            self.position += 1;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(invalid("unquoted attribute value")),
+            };
+            self.position += 1;
+            let value = self.raw_through(if quote == '"' { "\"" } else { "'" }, "attribute value")?;
+            if value.contains('<') {
+                return Err(invalid("bad attribute value"));
+            }
+            if attributes.iter().any(|attribute| attribute.name == attribute_name) {
+                return Err(invalid("duplicate attribute"));
+            }
+            attributes.push(Attribute { name: attribute_name, value, raw: &self.text[start..self.position] });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_svg, sanitize_svg, SvgError, SvgMode};
+
+    const CIRCLE: &str = r##"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "http://www.w3.org/Graphics/SVG/1.1/DTD/svg11.dtd">
+<!-- a circle -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="10" height="10">
+  <defs><circle id="c" r="5" style="fill: url(#gradient)"/></defs>
+  <use xlink:href="#c" x="5" y="5"/>
+  <style><![CDATA[ circle { stroke: red; } ]]></style>
+  <text>1 &lt; 2</text>
+</svg>
+"##;
+
+    fn sanitize(svg: &str) -> String {
+        sanitize_svg(svg.as_bytes(), SvgMode::Sanitize).unwrap()
+    }
+
+    fn reject(svg: &str) -> String {
+        sanitize_svg(svg.as_bytes(), SvgMode::Reject).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn svg_detection() {
+        assert!(is_svg(CIRCLE.as_bytes()));
+        assert!(is_svg(b"\xef\xbb\xbf <svg/>"));
+        assert!(is_svg(b"<svg\nwidth='1'></svg>"));
+
+        assert!(!is_svg(b"<html><svg/></html>"));
+        assert!(!is_svg(b"<svgx/>"));
+        assert!(!is_svg(b"text <svg/>"));
+        assert!(!is_svg(b"\x89PNG\r\n\x1a\n"));
+        assert!(!is_svg(b""));
+    }
+
+    #[test]
+    fn clean_images() {
+        assert_eq!(sanitize(CIRCLE), CIRCLE);
+        assert_eq!(sanitize_svg(CIRCLE.as_bytes(), SvgMode::Reject).unwrap(), CIRCLE);
+        let image = r#"<svg><image href="data:image/png;base64,iVBORw0KGgo=" width="1" height="1"/></svg>"#;
+        assert_eq!(sanitize(image), image);
+    }
+
+    #[test]
+    fn scripts() {
+        assert_eq!(sanitize("<svg><script>alert(1)</script><circle r='5'/></svg>"), "<svg><circle r='5'/></svg>");
+        assert_eq!(sanitize("<svg><script><![CDATA[ if (a</b) {} ]]></script></svg>"), "<svg></svg>");
+        assert_eq!(sanitize("<svg><SCRIPT/><foreignObject><p><b>html</b></p></foreignObject></svg>"), "<svg></svg>");
+        assert_eq!(reject("<svg><script>alert(1)</script></svg>"), "Dangerous SVG content: script element.");
+
+        assert_eq!(sanitize(r#"<svg onload="alert(1)" width="1"><g ONclick='x()'/></svg>"#), r#"<svg width="1"><g/></svg>"#);
+        assert_eq!(reject(r#"<svg onload="alert(1)"/>"#), "Dangerous SVG content: event handler onload.");
+
+        let animation = r#"<svg><a><set attributeName="href" to="javascript:alert(1)"/><text>x</text></a></svg>"#;
+        assert_eq!(sanitize(animation), "<svg><a><text>x</text></a></svg>");
+        assert_eq!(reject(animation), "Dangerous SVG content: animation of the href attribute.");
+        let animation = r#"<svg><animate attributeName="opacity" to="0"/></svg>"#;
+        assert_eq!(sanitize(animation), animation);
+    }
+
+    #[test]
+    fn external_references() {
+        assert_eq!(sanitize(r#"<svg><a href="javascript:alert(1)"><text>x</text></a></svg>"#), "<svg><a><text>x</text></a></svg>");
+        assert_eq!(sanitize(r#"<svg><a xlink:href="java&#115;cript:x()"/></svg>"#), "<svg><a/></svg>");
+        assert_eq!(sanitize(r#"<svg><use href="https://evil.com/a.svg#x"/></svg>"#), "<svg><use/></svg>");
+        assert_eq!(reject(r#"<svg><image href="http://track.er/p.png"/></svg>"#), "Dangerous SVG content: external reference http://track.er/p.png.");
+        assert_eq!(sanitize(r#"<svg><image href="data:image/svg+xml;base64,PHN2Zy8+"/></svg>"#), "<svg><image/></svg>");
+
+        assert_eq!(sanitize(r#"<svg><rect style="fill: url(http://evil.com/x)"/></svg>"#), "<svg><rect/></svg>");
+        assert_eq!(sanitize("<svg><style>@import 'http://evil.com/a.css';</style><rect/></svg>"), "<svg><rect/></svg>");
+        assert_eq!(reject("<svg><style>rect { fill: url('http://evil.com/x') }</style></svg>"), "Dangerous SVG content: external reference http://evil.com/x.");
+
+        let stylesheet = "<?xml-stylesheet href=\"http://evil.com/a.css\"?>\n<svg/>";
+        assert_eq!(sanitize(stylesheet), "\n<svg/>");
+        assert_eq!(reject(stylesheet), "Dangerous SVG content: processing instruction.");
+        let entities = "<!DOCTYPE svg [ <!ENTITY xxe SYSTEM \"file:///etc/passwd\"> ]><svg><text>&xxe;</text></svg>";
+        assert_eq!(sanitize(entities), "<svg><text>&xxe;</text></svg>");
+        assert_eq!(reject(entities), "Dangerous SVG content: entity declarations.");
+    }
+
+    #[test]
+    fn malformed_images() {
+        assert_eq!(sanitize_svg(b"<html/>", SvgMode::Sanitize).unwrap_err(), SvgError::Invalid(String::from("not an SVG image")));
+        assert_eq!(reject("<svg><g></svg>"), "Invalid SVG image: mismatched end tag.");
+        assert_eq!(reject("<svg><g>"), "Invalid SVG image: unclosed element.");
+        assert_eq!(reject("<svg><g width=1/></svg>"), "Invalid SVG image: unquoted attribute value.");
+        assert_eq!(reject("<svg><g a='1' a='2'/></svg>"), "Invalid SVG image: duplicate attribute.");
+        assert_eq!(reject("<svg/><script>alert(1)</script>"), "Invalid SVG image: content after the root element.");
+        assert_eq!(reject("<svg><style>x"), "Invalid SVG image: unclosed element.");
+    }
+}
@@ -0,0 +1,155 @@
+use super::validate_url::is_domain_name;
+
+const SCHEME: &str = "tel:";
+
+// Maximal number of digits of an E.164 number, country code included
+const MAX_E164_DIGITS: usize = 15;
+
+// Visual separators allowed between the digits of a number
+const VISUAL_SEPARATORS: &str = "-.()";
+
+/// Validate a `tel:` uri ([RFC 3966](https://www.rfc-editor.org/rfc/rfc3966)).
+///
+/// A global number starts with `+` followed by a valid [E.164](https://en.wikipedia.org/wiki/E.164)
+/// number: at most 15 digits, the first one not being 0. A local number must be followed by a
+/// `phone-context` parameter, either a global number prefix or a domain name. Visual
+/// separators (`-`, `.`, `(`, `)`) are allowed between the digits.
+///
+/// The number can be followed by `;name=value` parameters, each at most once: an `ext`
+/// extension of digits, an `isub` sub-address or any other parameter. The scheme and the
+/// parameter names are case insensitive.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_tel("tel:+41-21-123-45-67;ext=12"));
+/// assert!(validate_tel("tel:1234;phone-context=+41-21"));
+/// assert!(!validate_tel("tel:021-123-45-67"));
+/// ```
+pub fn validate_tel(uri: &str) -> bool {
+    let rest = match uri.get(..SCHEME.len()) {
+        Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &uri[SCHEME.len()..],
+        _ => return false,
+    };
+
+    let mut parameters = rest.split(';');
+    let number = parameters.next().unwrap_or_default();
+
+    let mut names = Vec::new();
+    let mut context = None;
+    for parameter in parameters {
+        let (name, value) = match parameter.split_once('=') {
+            None => (parameter.to_ascii_lowercase(), None),
+            Some((name, value)) => (name.to_ascii_lowercase(), Some(value)),
+        };
+        if name.is_empty() || !name.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-') || names.contains(&name) {
+            return false;
+        }
+
+        let valid = match (name.as_str(), value) {
+            ("ext", Some(extension)) => digit_count(extension, false).is_some(),
+            ("isub", Some(address)) => !address.is_empty() && address.bytes().all(is_uric),
+            ("phone-context", Some(prefix)) => {
+                context = Some(prefix);
+                is_phone_context(prefix)
+            }
+            ("ext" | "isub" | "phone-context", None) => false,
+            (_, Some(value)) => !value.is_empty() && value.bytes().all(is_uric),
+            (_, None) => true,
+        };
+        if !valid {
+            return false;
+        }
+        names.push(name);
+    }
+
+    match number.strip_prefix('+') {
+        Some(global) => context.is_none() && is_e164(global),
+        None => context.is_some() && digit_count(number, true).is_some(),
+    }
+}
+
+/// Check the digits of a global number, after its `+`.
+fn is_e164(digits: &str) -> bool {
+    let first = digits.bytes().find(u8::is_ascii_digit);
+    first != Some(b'0') && digit_count(digits, false).is_some_and(|count| count <= MAX_E164_DIGITS)
+}
+
+/// Count the digits of a number with visual separators. The digits of a local number also
+/// include the hexadecimal digits, `*` and `#` (escaped as `%23`). Returns `None` if the
+/// number has no digit or an invalid char.
+fn digit_count(number: &str, local: bool) -> Option<usize> {
+    let number = if local { number.replace("%23", "#") } else { number.to_string() };
+
+    let mut count = 0;
+    for c in number.chars() {
+        if c.is_ascii_digit() || (local && (c.is_ascii_hexdigit() || c == '*' || c == '#')) {
+            count += 1;
+        } else if !VISUAL_SEPARATORS.contains(c) {
+            return None;
+        }
+    }
+    Some(count).filter(|&count| count > 0)
+}
+
+/// Check the context of a local number: a global number prefix or a domain name.
+fn is_phone_context(context: &str) -> bool {
+    match context.strip_prefix('+') {
+        Some(prefix) => digit_count(prefix, false).is_some(),
+        None => is_domain_name(context),
+    }
+}
+
+/// Check whether a byte can appear as is in a parameter value: unreserved, reserved or part of
+/// an escape.
+fn is_uric(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || b"-_.!~*'()%/:@&=+$,?[]".contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validate_tel;
+
+    #[test]
+    fn valid_tels() {
+        assert!(validate_tel("tel:+41211234567"));
+        assert!(validate_tel("TEL:+41-21-123-45-67"));
+        assert!(validate_tel("tel:+1-(201)-555.0123"));
+        assert!(validate_tel("tel:+41211234567;ext=12"));
+        assert!(validate_tel("tel:+41211234567;isub=12ab;EXT=1-2"));
+        assert!(validate_tel("tel:+123456789012345"));
+
+        // local numbers
+        assert!(validate_tel("tel:1234;phone-context=+41-21"));
+        assert!(validate_tel("tel:*1a%23;phone-context=test.com"));
+        assert!(validate_tel("tel:7042;phone-context=test.com;custom"));
+    }
+
+    #[test]
+    fn invalid_tels() {
+        assert!(!validate_tel(""));
+        assert!(!validate_tel("tel:"));
+        assert!(!validate_tel("tel:+"));
+        assert!(!validate_tel("+41211234567"));
+        assert!(!validate_tel("tel:+41 21 123 45 67"));
+        assert!(!validate_tel("tel:+41211234567abc"));
+
+        // not E.164
+        assert!(!validate_tel("tel:+1234567890123456"));
+        assert!(!validate_tel("tel:+0211234567"));
+        assert!(!validate_tel("tel:+-0211234567"));
+
+        // local numbers need a context and global numbers can't have one
+        assert!(!validate_tel("tel:0211234567"));
+        assert!(!validate_tel("tel:+41211234567;phone-context=+41"));
+        assert!(!validate_tel("tel:1234;phone-context=test"));
+        assert!(!validate_tel("tel:1234;phone-context=+"));
+
+        // invalid parameters
+        assert!(!validate_tel("tel:+41211234567;ext"));
+        assert!(!validate_tel("tel:+41211234567;ext=a"));
+        assert!(!validate_tel("tel:+41211234567;ext=1;ext=2"));
+        assert!(!validate_tel("tel:+41211234567;"));
+        assert!(!validate_tel("tel:+41211234567;na_me=1"));
+        assert!(!validate_tel("tel:+41211234567;isub=a b"));
+    }
+}
@@ -0,0 +1,313 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::ops::Range;
+
+/// Template engines recognized by [`detect_template_injection`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemplateEngine {
+    /// Jinja2, Django and Nunjucks: `{{ ... }}` and `{% ... %}`
+    Jinja,
+    /// Twig: `{{ ... }}` and `{% ... %}`
+    Twig,
+    /// Handlebars and Mustache: `{{ ... }}`
+    Handlebars,
+    /// FreeMarker: `${ ... }` and `<#...>`
+    FreeMarker,
+    /// Apache Velocity: `${ ... }`, `$variable` and `#directive(...)`
+    Velocity,
+    /// Spring expression language and Thymeleaf: `${ ... }`, `#{ ... }` and `*{ ... }`
+    SpringEl,
+    /// Ruby ERB, JSP and ASP: `<% ... %>`
+    Erb,
+}
+
+impl TemplateEngine {
+    /// All the recognized template engines.
+    pub const ALL: [TemplateEngine; 7] = [
+        TemplateEngine::Jinja,
+        TemplateEngine::Twig,
+        TemplateEngine::Handlebars,
+        TemplateEngine::FreeMarker,
+        TemplateEngine::Velocity,
+        TemplateEngine::SpringEl,
+        TemplateEngine::Erb,
+    ];
+}
+
+/// Kind of template construct found in an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateConstruct {
+    /// An expression whose value is rendered, e.g. `{{ user.name }}`
+    Expression,
+    /// A statement or directive executed by the engine, e.g. `{% for x in y %}`
+    Statement,
+}
+
+/// Template construct found by [`detect_template_injection`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateInjection {
+    /// The engine interpreting the construct
+    pub engine: TemplateEngine,
+    /// The kind of construct
+    pub construct: TemplateConstruct,
+    /// Byte span of the construct in the input
+    pub span: Range<usize>,
+    /// True if the construct contains an engine specific dangerous construct (object
+    /// introspection, class loading, command execution...)
+    pub dangerous: bool,
+}
+
+/// Syntax of a template construct.
+enum Syntax {
+    /// Construct between an opening and a closing delimiter, found by [`find_delimited`]
+    Delimited(&'static str, &'static str),
+    /// Construct matched by a regex
+    Pattern(&'static Regex),
+}
+
+const DOUBLE_BRACES: Syntax = Syntax::Delimited("{{", "}}");
+const BRACE_PERCENT: Syntax = Syntax::Delimited("{%", "%}");
+const DOLLAR_BRACE: Syntax = Syntax::Delimited("${", "}");
+const HASH_BRACE: Syntax = Syntax::Delimited("#{", "}");
+const STAR_BRACE: Syntax = Syntax::Delimited("*{", "}");
+const ANGLE_PERCENT: Syntax = Syntax::Delimited("<%", "%>");
+
+lazy_static! {
+    static ref FREEMARKER_DIRECTIVE: Regex = Regex::new(r"(?s)</?[#@].*?>").unwrap();
+    static ref VELOCITY_VARIABLE: Regex = Regex::new(r"\$!?[[:alpha:]_][\w.]*(\([^)]*\)[\w.]*)*").unwrap();
+    static ref VELOCITY_DIRECTIVE: Regex =
+        Regex::new(r"#(set|if|elseif|foreach|macro|include|parse|evaluate|define)\s*\([^)]*\)").unwrap();
+
+    static ref JINJA_DANGEROUS: Regex = Regex::new(
+        r"__(class|mro|subclasses|globals|builtins|import|init|base|bases|dict)__|\b(config|lipsum|cycler|joiner|namespace|self)\b\s*\.|\brequest\s*\.\s*application"
+    ).unwrap();
+    static ref TWIG_DANGEROUS: Regex = Regex::new(
+        r"_self\s*\.\s*env|registerUndefinedFilterCallback|getFilter|\b(filter|map|reduce|sort)\s*\(\s*['\x22](system|exec|passthru|shell_exec|popen)"
+    ).unwrap();
+    static ref HANDLEBARS_DANGEROUS: Regex = Regex::new(
+        r"constructor|__proto__|__defineGetter__|\blookup\b"
+    ).unwrap();
+    static ref FREEMARKER_DANGEROUS: Regex = Regex::new(
+        r"freemarker\.template\.utility\.(Execute|ObjectConstructor|JythonRuntime)|\?\s*new\s*\(|\?\s*api\b"
+    ).unwrap();
+    static ref JAVA_DANGEROUS: Regex = Regex::new(
+        r"\bT\s*\(\s*java\.lang\.|java\.lang\.(Runtime|ProcessBuilder|System|Class)|getClass\s*\(|forName\s*\(|getRuntime\s*\(|new\s+java\."
+    ).unwrap();
+    static ref ERB_DANGEROUS: Regex = Regex::new(
+        r"`|%x|\b(system|exec|spawn|eval|open|syscall|fork|instance_eval|class_eval|send)\s*[\(\x22'\s]|IO\s*\.\s*popen|Kernel\s*\.|Runtime\s*\.\s*getRuntime"
+    ).unwrap();
+}
+
+/// Detect template engine constructs inside a user supplied input.
+///
+/// Every `{{...}}`, `{%...%}`, `${...}`, `<%...%>` or engine specific construct understood by
+/// one of the given engines is reported with its byte span. A construct is flagged as
+/// dangerous if it contains an engine specific construct used to escape the template sandbox,
+/// such as `__class__` for Jinja or `T(java.lang.Runtime)` for the Spring expression language.
+///
+/// An input without any finding can be rendered as plain text by the given engines.
+///
+/// # Examples
+/// ``` ignore
+/// let findings = detect_template_injection("Hello {{ ''.__class__ }}", &TemplateEngine::ALL);
+/// assert!(findings.iter().any(|finding| finding.dangerous));
+///
+/// assert!(detect_template_injection("Hello world", &[TemplateEngine::Jinja]).is_empty());
+/// ```
+pub fn detect_template_injection(input: &str, engines: &[TemplateEngine]) -> Vec<TemplateInjection> {
+    let mut findings = Vec::new();
+
+    for &engine in engines {
+        let (syntaxes, dangerous): (&[(Syntax, TemplateConstruct)], &Regex) = match engine {
+            TemplateEngine::Jinja => (
+                &[(DOUBLE_BRACES, TemplateConstruct::Expression), (BRACE_PERCENT, TemplateConstruct::Statement)],
+                &JINJA_DANGEROUS,
+            ),
+            TemplateEngine::Twig => (
+                &[(DOUBLE_BRACES, TemplateConstruct::Expression), (BRACE_PERCENT, TemplateConstruct::Statement)],
+                &TWIG_DANGEROUS,
+            ),
+            TemplateEngine::Handlebars => (
+                &[(DOUBLE_BRACES, TemplateConstruct::Expression)],
+                &HANDLEBARS_DANGEROUS,
+            ),
+            TemplateEngine::FreeMarker => (
+                &[
+                    (DOLLAR_BRACE, TemplateConstruct::Expression),
+                    (Syntax::Pattern(&FREEMARKER_DIRECTIVE), TemplateConstruct::Statement),
+                ],
+                &FREEMARKER_DANGEROUS,
+            ),
+            TemplateEngine::Velocity => (
+                &[
+                    (Syntax::Pattern(&VELOCITY_VARIABLE), TemplateConstruct::Expression),
+                    (Syntax::Pattern(&VELOCITY_DIRECTIVE), TemplateConstruct::Statement),
+                ],
+                &JAVA_DANGEROUS,
+            ),
+            TemplateEngine::SpringEl => (
+                &[
+                    (DOLLAR_BRACE, TemplateConstruct::Expression),
+                    (HASH_BRACE, TemplateConstruct::Expression),
+                    (STAR_BRACE, TemplateConstruct::Expression),
+                ],
+                &JAVA_DANGEROUS,
+            ),
+            TemplateEngine::Erb => (
+                &[(ANGLE_PERCENT, TemplateConstruct::Statement)],
+                &ERB_DANGEROUS,
+            ),
+        };
+
+        for (syntax, construct) in syntaxes {
+            let spans: Vec<Range<usize>> = match syntax {
+                Syntax::Delimited(open, close) => find_delimited(input, open, close),
+                Syntax::Pattern(regex) => regex.find_iter(input).map(|found| found.range()).collect(),
+            };
+            for span in spans {
+                findings.push(TemplateInjection {
+                    engine,
+                    construct: *construct,
+                    dangerous: dangerous.is_match(&input[span.clone()]),
+                    span,
+                });
+            }
+        }
+    }
+
+    findings.sort_by_key(|finding| finding.span.start);
+    findings
+}
+
+/// Find the spans of the constructs between the `open` and `close` delimiters.
+///
+/// The delimiters inside the string literals of a construct don't close it, as for the engines:
+/// `{{ "}}" ~ x }}` is a single construct. If a literal is never terminated, the construct runs
+/// up to the last closing delimiter of the input.
+fn find_delimited(input: &str, open: &str, close: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut from = 0;
+    while let Some(offset) = input[from..].find(open) {
+        let start = from + offset;
+        let body = start + open.len();
+        let end = match closing_delimiter(&input[body..], close).or_else(|| input[body..].rfind(close)) {
+            Some(end) => body + end + close.len(),
+            None => break,
+        };
+        spans.push(start..end);
+        from = end;
+    }
+    spans
+}
+
+/// Position of the first closing delimiter outside of the quoted string literals of a construct.
+fn closing_delimiter(body: &str, close: &str) -> Option<usize> {
+    let bytes = body.as_bytes();
+    let mut quote = None;
+    let mut i = 0;
+    while i < bytes.len() {
+        match quote {
+            Some(_) if bytes[i] == b'\\' => i += 1,
+            Some(q) if bytes[i] == q => quote = None,
+            Some(_) => {}
+            None if bytes[i] == b'\'' || bytes[i] == b'"' => quote = Some(bytes[i]),
+            None if bytes[i..].starts_with(close.as_bytes()) => return Some(i),
+            None => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{detect_template_injection, TemplateConstruct, TemplateEngine};
+
+    fn is_dangerous(input: &str, engine: TemplateEngine) -> bool {
+        detect_template_injection(input, &[engine]).iter().any(|finding| finding.dangerous)
+    }
+
+    #[test]
+    fn plain_inputs() {
+        assert!(detect_template_injection("", &TemplateEngine::ALL).is_empty());
+        assert!(detect_template_injection("Hello world !", &TemplateEngine::ALL).is_empty());
+        assert!(detect_template_injection("costs 5$ { not a template }", &TemplateEngine::ALL).is_empty());
+
+        // engines not requested are not considered
+        assert!(detect_template_injection("<%= 7*7 %>", &[TemplateEngine::Jinja]).is_empty());
+    }
+
+    #[test]
+    fn expressions_and_statements() {
+        let findings = detect_template_injection("Hi {{ name }} {% if x %}", &[TemplateEngine::Jinja]);
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].construct, TemplateConstruct::Expression);
+        assert_eq!(findings[0].span, 3..13);
+        assert!(!findings[0].dangerous);
+        assert_eq!(findings[1].construct, TemplateConstruct::Statement);
+        assert_eq!(findings[1].span, 14..24);
+
+        let findings = detect_template_injection("${7*7}", &[TemplateEngine::FreeMarker]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].span, 0..6);
+
+        let findings = detect_template_injection("a <%= 7*7 %> b", &[TemplateEngine::Erb]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].span, 2..12);
+
+        // multi-line constructs
+        assert_eq!(detect_template_injection("{{\nname\n}}", &[TemplateEngine::Handlebars]).len(), 1);
+
+        // every engine understanding the construct reports it
+        assert_eq!(detect_template_injection("{{ x }}", &TemplateEngine::ALL).len(), 3);
+    }
+
+    #[test]
+    fn dangerous_constructs() {
+        assert!(is_dangerous("{{ ''.__class__.__mro__[1].__subclasses__() }}", TemplateEngine::Jinja));
+        assert!(is_dangerous("{{ config.items() }}", TemplateEngine::Jinja));
+        assert!(is_dangerous("{{_self.env.registerUndefinedFilterCallback('exec')}}", TemplateEngine::Twig));
+        assert!(is_dangerous("{{#with \"s\" as |string|}}{{string.constructor}}", TemplateEngine::Handlebars));
+        assert!(is_dangerous("<#assign ex=\"freemarker.template.utility.Execute\"?new()>", TemplateEngine::FreeMarker));
+        assert!(is_dangerous("$class.forName('java.lang.Runtime')", TemplateEngine::Velocity));
+        assert!(is_dangerous("${T(java.lang.Runtime).getRuntime().exec('id')}", TemplateEngine::SpringEl));
+        assert!(is_dangerous("#{T (java.lang.System).exit(0)}", TemplateEngine::SpringEl));
+        assert!(is_dangerous("<%= `id` %>", TemplateEngine::Erb));
+        assert!(is_dangerous("<%= system('id') %>", TemplateEngine::Erb));
+    }
+
+    #[test]
+    fn delimiters_in_literals() {
+        // the closing delimiter inside a string literal doesn't split the construct
+        let input = "{{ \"}}\" ~ x.__class__ }}";
+        let findings = detect_template_injection(input, &[TemplateEngine::Jinja]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].span, 0..input.len());
+        assert!(findings[0].dangerous);
+
+        assert!(is_dangerous("{{ '}}' ~ config.items() }}", TemplateEngine::Jinja));
+        assert!(is_dangerous("${'}'.getClass().forName('java.lang.Runtime')}", TemplateEngine::SpringEl));
+        assert!(is_dangerous("<%= \"%>\" + `id` %>", TemplateEngine::Erb));
+
+        // escaped quotes don't terminate the literal
+        let findings = detect_template_injection(r#"{{ "\"}}" ~ x }}"#, &[TemplateEngine::Jinja]);
+        assert_eq!(findings.len(), 1);
+
+        // an unterminated literal runs up to the last closing delimiter
+        let input = "{{ don't }} and {{ x }}";
+        let findings = detect_template_injection(input, &[TemplateEngine::Jinja]);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].span, 0..input.len());
+    }
+
+    #[test]
+    fn harmless_constructs() {
+        assert!(!is_dangerous("{{ user.name | upper }}", TemplateEngine::Jinja));
+        assert!(!is_dangerous("{{ title }}", TemplateEngine::Twig));
+        assert!(!is_dangerous("{{#each items}}{{this}}{{/each}}", TemplateEngine::Handlebars));
+        assert!(!is_dangerous("${user.name}", TemplateEngine::FreeMarker));
+        assert!(!is_dangerous("#set($a = 1) $a", TemplateEngine::Velocity));
+        assert!(!is_dangerous("${user.name}", TemplateEngine::SpringEl));
+        assert!(!is_dangerous("<%= @user.name %>", TemplateEngine::Erb));
+    }
+}
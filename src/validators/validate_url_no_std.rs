@@ -0,0 +1,280 @@
+//! Url validation of the `no_std` builds, e.g. on embedded gateways, matching the urls with the
+//! hand-written grammar as [`crate::UrlValidator`] does. The validator only has the options of
+//! the host grammar, the schemes and the length, and there is no cache of the validators: a
+//! whitelist is checked on each call of [`validate_url_with_whitelist`].
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use super::grammar::{self, HostGrammar};
+use super::{TopLevelCharset, ValidationError};
+
+const DEFAULT_TOP_LEVEL_MIN_LENGTH: usize = 2;
+
+// Chars ending the authority part of an url, the rest of the url is accepted as is
+const AUTHORITY_END: [char; 2] = ['/', '#'];
+
+/// Url validator configured through an [`UrlValidatorBuilder`], accepting the same urls as the
+/// `UrlValidator` of the `std` feature with the same options.
+///
+/// # Examples
+/// ``` ignore
+/// let validator = UrlValidator::builder()
+///     .allowed_schemes(["https"])
+///     .top_level_whitelist([".ch"])
+///     .build()?;
+///
+/// assert!(validator.validate("https://heig-vd.ch"));
+/// assert!(!validator.validate("heig-vd.ch"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct UrlValidator {
+    host_grammar: HostGrammar,
+    allowed_schemes: Option<Vec<String>>,
+    ignore_case: bool,
+    max_length: Option<usize>,
+}
+
+/// Builder of an [`UrlValidator`].
+#[derive(Debug, Clone, Default)]
+pub struct UrlValidatorBuilder {
+    top_level_whitelist: Option<Vec<String>>,
+    allowed_schemes: Option<Vec<String>>,
+    ignore_case: bool,
+    top_level_charset: TopLevelCharset,
+    top_level_min_length: Option<usize>,
+    max_length: Option<usize>,
+}
+
+impl UrlValidator {
+    /// Create a builder for an url validator accepting every url matching the lab rules.
+    pub fn builder() -> UrlValidatorBuilder {
+        UrlValidatorBuilder::default()
+    }
+
+    /// Validate an url against the configured rules.
+    pub fn validate(&self, url: &str) -> bool {
+        if self.max_length.is_some_and(|max| url.len() > max) {
+            return false;
+        }
+
+        let scheme_end = grammar::scheme_end(url);
+
+        // The scheme is required when a scheme whitelist is set
+        if let Some(schemes) = &self.allowed_schemes {
+            match scheme_end {
+                Some(end) if schemes.contains(&url[..end - "://".len()].to_ascii_lowercase()) => {}
+                _ => return false,
+            }
+        }
+
+        let rest = scheme_end.map_or(url, |end| &url[end..]);
+        let host = &rest[..rest.find(AUTHORITY_END).unwrap_or(rest.len())];
+
+        // Lowercasing the whole host is harmless as the sub level domain is not case sensitive
+        if self.ignore_case {
+            self.host_grammar.matches(&host.to_ascii_lowercase())
+        } else {
+            self.host_grammar.matches(host)
+        }
+    }
+}
+
+impl UrlValidatorBuilder {
+    /// Only accept the top level domains inside the whitelist. They are case sensitive unless
+    /// [`UrlValidatorBuilder::ignore_case`] is set.
+    pub fn top_level_whitelist<I, S>(mut self, whitelist: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.top_level_whitelist = Some(whitelist.into_iter().map(|tld| tld.as_ref().to_string()).collect());
+        self
+    }
+
+    /// Only accept the schemes (protocols) inside the whitelist, e.g. `["https"]`. The schemes
+    /// are not case sensitive and a url without scheme is rejected.
+    pub fn allowed_schemes<I, S>(mut self, schemes: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.allowed_schemes = Some(schemes.into_iter().map(|scheme| scheme.as_ref().to_ascii_lowercase()).collect());
+        self
+    }
+
+    /// Match the top level domains of the whitelist without considering the case, e.g. `.COM`
+    /// accepts `test.com`.
+    pub fn ignore_case(mut self, ignore: bool) -> Self {
+        self.ignore_case = ignore;
+        self
+    }
+
+    /// Set the characters allowed in the top level domains, letters only by default. The entries
+    /// of the top level whitelist must follow the same rules.
+    pub fn top_level_charset(mut self, charset: TopLevelCharset) -> Self {
+        self.top_level_charset = charset;
+        self
+    }
+
+    /// Set the minimal number of characters of the top level domains, without the leading full
+    /// stop. The default is 2, so that the top level domains have at least 3 characters
+    /// including the full stop as in the lab.
+    pub fn top_level_min_length(mut self, min_length: usize) -> Self {
+        self.top_level_min_length = Some(min_length);
+        self
+    }
+
+    /// Reject the urls longer than `max` bytes before any matching.
+    pub fn max_length(mut self, max: usize) -> Self {
+        self.max_length = Some(max);
+        self
+    }
+
+    /// Build the url validator.
+    ///
+    /// # Errors
+    /// If a whitelist is empty or at least one of its entries is invalid, an error will
+    /// be returned.
+    pub fn build(mut self) -> Result<UrlValidator, ValidationError> {
+        if self.ignore_case {
+            if let Some(whitelist) = &mut self.top_level_whitelist {
+                whitelist.iter_mut().for_each(|tld| *tld = tld.to_lowercase());
+            }
+        }
+
+        let top_level_min_length = self.top_level_min_length.unwrap_or(DEFAULT_TOP_LEVEL_MIN_LENGTH);
+        if top_level_min_length == 0 {
+            return Err(ValidationError::InvalidOption(String::from("Invalid top level domain minimal length.")));
+        }
+
+        let host_grammar = match self.top_level_whitelist {
+            None => HostGrammar::Rules { charset: self.top_level_charset, min_length: top_level_min_length },
+            Some(whitelist) if whitelist.is_empty() => return Err(ValidationError::EmptyWhitelist { list: "white list" }),
+            Some(whitelist) => {
+                if let Some(tld) = whitelist.iter().find(|tld| !grammar::is_top_level(tld, self.top_level_charset, top_level_min_length)) {
+                    return Err(ValidationError::InvalidTld { value: tld.clone(), list: "white list" });
+                }
+                HostGrammar::Whitelist(whitelist)
+            }
+        };
+
+        if let Some(schemes) = &self.allowed_schemes {
+            if schemes.is_empty() {
+                return Err(ValidationError::EmptyWhitelist { list: "scheme white list" });
+            }
+
+            if schemes.iter().any(|scheme| scheme.is_empty() || !scheme.bytes().all(|c| c.is_ascii_alphanumeric())) {
+                return Err(ValidationError::InvalidOption(String::from("Invalid scheme in white list.")));
+            }
+        }
+
+        Ok(UrlValidator { host_grammar, allowed_schemes: self.allowed_schemes, ignore_case: self.ignore_case, max_length: self.max_length })
+    }
+}
+
+/// Validate an url providing an optional top level whitelist, as `validate_url` with the `std`
+/// feature.
+///
+/// # Errors
+/// If the whitelist is empty or at least one top level domain inside is invalid, an error will
+/// be returned.
+pub fn validate_url(url: &str, top_level_whitelist: Option<&Vec<&str>>) -> Result<bool, ValidationError> {
+    match top_level_whitelist {
+        None => Ok(UrlValidator::builder().build()?.validate(url)),
+        Some(whitelist) => validate_url_with_whitelist(url, whitelist),
+    }
+}
+
+/// Validate an url accepting only the top level domains inside the whitelist, e.g.
+/// `[".ch", ".com"]`, as `validate_url_with_whitelist` with the `std` feature.
+///
+/// # Errors
+/// If the whitelist is empty or at least one top level domain inside is invalid, an error will
+/// be returned.
+pub fn validate_url_with_whitelist<I, S>(url: &str, top_level_whitelist: I) -> Result<bool, ValidationError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    Ok(UrlValidator::builder().top_level_whitelist(top_level_whitelist).build()?.validate(url))
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::{validate_url, validate_url_with_whitelist, UrlValidator};
+    use crate::TopLevelCharset;
+
+    const URLS: [&str; 12] = [
+        "https://docs.rs/lazy_static",
+        "docs.rs",
+        "heig-vd.ch#top",
+        "https://test.co.uk/path?query",
+        "ftp://a.b.c.ch",
+        "https://docs.rs:443",
+        "https://docs .rs",
+        "https://127.0.0.1",
+        "https://test.c",
+        "://test.com",
+        "https://é.ch",
+        "",
+    ];
+
+    #[test]
+    fn same_urls_as_the_std_validator() {
+        for url in URLS {
+            assert_eq!(validate_url(url, None).unwrap(), crate::validate_url(url, None).unwrap(), "{}", url);
+            for whitelist in [vec![".ch", ".com"], vec![".co.uk"], vec![".RS"]] {
+                assert_eq!(validate_url(url, Some(&whitelist)).unwrap(), crate::validate_url(url, Some(&whitelist)).unwrap(), "{} {:?}", url, whitelist);
+            }
+        }
+    }
+
+    #[test]
+    fn invalid_whitelists() {
+        for whitelist in [vec![], vec!["ch"], vec![".c"], vec![".ch", "com."], vec![".c1"]] {
+            let error = validate_url_with_whitelist("test.ch", &whitelist).unwrap_err();
+            assert_eq!(error.code(), crate::validate_url("test.ch", Some(&whitelist)).unwrap_err().code(), "{:?}", whitelist);
+        }
+    }
+
+    #[test]
+    fn same_urls_as_the_std_builder() {
+        let urls = URLS.iter().chain(&["HTTPS://TEST.CH", "test.CH", "https://test.xn--p1ai", "https://test.c0m", "http://docs.rs"]);
+        for url in urls {
+            assert_eq!(
+                UrlValidator::builder().allowed_schemes(["HTTPS"]).build().unwrap().validate(url),
+                crate::UrlValidator::builder().allowed_schemes(["HTTPS"]).build().unwrap().validate(url),
+                "{}",
+                url
+            );
+            assert_eq!(
+                UrlValidator::builder().top_level_whitelist([".CH", ".rs"]).ignore_case(true).build().unwrap().validate(url),
+                crate::UrlValidator::builder().top_level_whitelist([".CH", ".rs"]).ignore_case(true).build().unwrap().validate(url),
+                "{}",
+                url
+            );
+            assert_eq!(
+                UrlValidator::builder().top_level_charset(TopLevelCharset::Punycode).top_level_min_length(3).build().unwrap().validate(url),
+                crate::UrlValidator::builder().top_level_charset(TopLevelCharset::Punycode).top_level_min_length(3).build().unwrap().validate(url),
+                "{}",
+                url
+            );
+            assert_eq!(
+                UrlValidator::builder().max_length(20).build().unwrap().validate(url),
+                crate::UrlValidator::builder().max_length(20).build().unwrap().validate(url),
+                "{}",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_builders() {
+        assert_eq!(UrlValidator::builder().allowed_schemes([] as [&str; 0]).build().unwrap_err().to_string(), "The scheme white list is empty.");
+        assert_eq!(UrlValidator::builder().allowed_schemes(["ht-tp"]).build().unwrap_err().to_string(), "Invalid scheme in white list.");
+        assert_eq!(UrlValidator::builder().top_level_min_length(0).build().unwrap_err().to_string(), "Invalid top level domain minimal length.");
+        assert!(UrlValidator::builder().top_level_whitelist([".c0m"]).build().is_err());
+        assert!(UrlValidator::builder().top_level_whitelist([".c0m"]).top_level_charset(TopLevelCharset::Alphanumeric).build().is_ok());
+    }
+}
@@ -1,75 +1,823 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Formatter};
+#[cfg(feature = "std")]
+use std::fs::File;
+#[cfg(feature = "async")]
+use std::future::poll_fn;
+#[cfg(feature = "std")]
+use std::io::{self, ErrorKind, Read};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "std")]
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "async")]
+use futures_io::AsyncRead;
+#[cfg(any(feature = "uuid_regex", feature = "bench"))]
 use lazy_static::lazy_static;
+#[cfg(any(feature = "uuid_regex", feature = "bench"))]
 use regex::Regex;
+#[cfg(feature = "std")]
+use sha1::Sha1;
 use uuid::Uuid;
 
-/// Validate a version-5 uuid [variant-1](https://en.wikipedia.org/wiki/Universally_unique_identifier#Variants)
+use super::ValidationError;
+
+/// Length of the chunks read by the streaming uuid validators.
+#[cfg(feature = "std")]
+const CHUNK_LENGTH: usize = 64 * 1024;
+
+/// Tolerance of [`validate_uuid_v7`] for the timestamps in the future, from clocks out of sync.
+#[cfg(feature = "std")]
+pub const UUID_V7_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// Version of a uuid, given by its 13th hexadecimal digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UuidVersion {
+    /// Time-based, with the MAC address of the host
+    V1,
+    /// DCE security
+    V2,
+    /// Name-based, hashed with MD5
+    V3,
+    /// Random, e.g. a session id
+    V4,
+    /// Name-based, hashed with SHA-1
+    V5,
+    /// Time-based, ordered by time
+    V6,
+    /// Time-based from the Unix epoch in milliseconds, e.g. a database key
+    V7,
+    /// Custom
+    V8,
+}
+
+impl UuidVersion {
+    /// Number of the version, its digit in a uuid.
+    pub fn number(&self) -> u8 {
+        *self as u8 + 1
+    }
+}
+
+/// Error of the parsing of a uuid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UuidError {
+    /// The uuid is not 32 hexadecimal digits in hyphenated groups of 8, 4, 4, 4 and 12 digits
+    Malformed,
+    /// The uuid is not of the variant 1
+    Variant,
+    /// The uuid is the nil uuid, all zeros, a sentinel value
+    Nil,
+    /// The uuid is the max uuid, all ones, a sentinel value
+    Max,
+    /// The uuid is of another version, given by its digit
+    Version {
+        /// The expected version
+        expected: UuidVersion,
+        /// The version digit of the uuid
+        actual: u8,
+    },
+    /// The timestamp of a version-7 uuid is older than the maximum age
+    Expired,
+    /// The timestamp of a version-7 uuid is in the future, beyond the tolerated clock skew
+    Future,
+}
+
+impl Display for UuidError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UuidError::Malformed => write!(f, "Invalid uuid."),
+            UuidError::Variant => write!(f, "Invalid uuid variant."),
+            UuidError::Nil => write!(f, "The nil uuid is not allowed."),
+            UuidError::Max => write!(f, "The max uuid is not allowed."),
+            UuidError::Version { expected, actual } => write!(f, "Invalid uuid version {}, expected {}.", actual, expected.number()),
+            UuidError::Expired => write!(f, "The uuid has expired."),
+            UuidError::Future => write!(f, "The uuid is from the future."),
+        }
+    }
+}
+
+impl core::error::Error for UuidError {}
+
+/// Version and variant digits of a uuid in the hyphenated form, `None` if malformed, checked
+/// byte by byte without regex or allocation.
+#[cfg_attr(feature = "uuid_regex", allow(dead_code))]
+fn uuid_digits(uuid: &str) -> Option<(u8, u8)> {
+    let bytes = uuid.as_bytes();
+    if bytes.len() != 36 {
+        return None;
+    }
+    let mut valid = true;
+    for (index, &byte) in bytes.iter().enumerate() {
+        valid &= if matches!(index, 8 | 13 | 18 | 23) { byte == b'-' } else { byte.is_ascii_hexdigit() };
+    }
+    let digit = |byte: u8| (byte as char).to_digit(16).unwrap() as u8;
+    valid.then(|| (digit(bytes[14]), digit(bytes[19])))
+}
+
+/// Version and variant digits of a uuid in the hyphenated form, `None` if malformed, matched by
+/// a regex as before the byte check of [`uuid_digits`].
+#[cfg(any(feature = "uuid_regex", feature = "bench"))]
+fn uuid_digits_regex(uuid: &str) -> Option<(u8, u8)> {
+    lazy_static! {
+        static ref REGEX: Regex =
+            Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-([0-9a-fA-F])[0-9a-fA-F]{3}-([0-9a-fA-F])[0-9a-fA-F]{3}-[0-9a-fA-F]{12}$").unwrap();
+    }
+    let captures = REGEX.captures(uuid)?;
+    let digit = |index| u8::from_str_radix(&captures[index], 16).unwrap();
+    Some((digit(1), digit(2)))
+}
+
+/// [`validate_uuid`] matching the uuid with a regex, to measure the byte check against it.
+#[cfg(feature = "bench")]
+pub(crate) fn validate_uuid_regex(uuid: &str) -> bool {
+    check_digits(uuid, uuid_digits_regex(uuid)) == Ok(UuidVersion::V5.number())
+}
+
+/// Check the hyphenated form and the variant of a uuid, the nil and max uuids excluded, and
+/// return its version digit.
+fn check_uuid(uuid: &str) -> Result<u8, UuidError> {
+    #[cfg(not(feature = "uuid_regex"))]
+    let digits = uuid_digits(uuid);
+    #[cfg(feature = "uuid_regex")]
+    let digits = uuid_digits_regex(uuid);
+    check_digits(uuid, digits)
+}
+
+fn check_digits(uuid: &str, digits: Option<(u8, u8)>) -> Result<u8, UuidError> {
+    let (version, variant) = digits.ok_or(UuidError::Malformed)?;
+    if uuid.bytes().all(|byte| matches!(byte, b'0' | b'-')) {
+        return Err(UuidError::Nil);
+    }
+    if uuid.bytes().all(|byte| matches!(byte, b'f' | b'F' | b'-')) {
+        return Err(UuidError::Max);
+    }
+    if !(0x8..=0xB).contains(&variant) {
+        return Err(UuidError::Variant);
+    }
+    Ok(version)
+}
+
+/// Hyphenated form of a uuid in the braced `{...}` or URN `urn:uuid:...` form, emitted e.g. by
+/// Windows and by XML documents, or the uuid itself.
+fn strip_uuid_form(uuid: &str) -> &str {
+    if let Some(braced) = uuid.strip_prefix('{').and_then(|uuid| uuid.strip_suffix('}')) {
+        return braced;
+    }
+    match uuid.get(..9) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("urn:uuid:") => &uuid[9..],
+        _ => uuid,
+    }
+}
+
+/// Validate a version-5 uuid [variant-1](https://en.wikipedia.org/wiki/Universally_unique_identifier#Variants),
+/// see [`validate_uuid_version`] for the other versions.
+///
+/// # Errors
+/// If the uuid is malformed, not of the variant 1 and the version 5, or the nil or max uuid, a
+/// [`ValidationError::Uuid`] telling why is returned.
 ///
 /// # Examples
 /// ``` ignore
-/// let mut result = validate_uuid("b267fe9e-6e37-5bed-a2c5-e44943802a91");
-/// assert!(result);
+/// assert!(validate_uuid("b267fe9e-6e37-5bed-a2c5-e44943802a91").is_ok());
 /// ```
-pub fn validate_uuid(uuid: &str) -> bool {
-    lazy_static! {
-        static ref REGEX: Regex =
-            Regex::new(r"^[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[5][0-9a-fA-F]{3}-[89abAB][0-9a-fA-F]{3}-[0-9a-fA-F]{12}$").unwrap();
+pub fn validate_uuid(uuid: &str) -> Result<(), ValidationError> {
+    validate_uuid_version(uuid, UuidVersion::V5)
+}
+
+/// Validate a variant-1 uuid of a version, e.g. a random v4 session id.
+///
+/// # Errors
+/// If the uuid is malformed, not of the variant 1 and the version, or the nil or max uuid.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_uuid_version("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04", UuidVersion::V4).is_ok());
+/// assert!(validate_uuid_version("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04", UuidVersion::V7).is_err());
+/// ```
+pub fn validate_uuid_version(uuid: &str, version: UuidVersion) -> Result<(), ValidationError> {
+    let actual = check_uuid(uuid)?;
+    if actual != version.number() {
+        return Err(UuidError::Version { expected: version, actual }.into());
+    }
+    Ok(())
+}
+
+/// Validate a variant-1 uuid of any version from 1 to 8, the nil and max uuids excluded.
+///
+/// # Errors
+/// If the uuid is malformed, not of the variant 1, of an unknown version, or the nil or max
+/// uuid.
+///
+/// # Examples
+/// ``` ignore
+/// assert!(validate_uuid_any("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").is_ok());
+/// ```
+pub fn validate_uuid_any(uuid: &str) -> Result<(), ValidationError> {
+    match check_uuid(uuid)? {
+        1..=8 => Ok(()),
+        _ => Err(UuidError::Malformed.into()),
+    }
+}
+
+/// Timestamp of a version-7 uuid in the hyphenated form, its first 48 bits in milliseconds since
+/// the Unix epoch, `None` if the uuid is invalid or of another version.
+///
+/// # Examples
+/// ``` ignore
+/// let created = uuid_v7_timestamp("017f22e2-79b0-7cc3-98c4-dc0c0c07398f").unwrap();
+/// assert_eq!(created, UNIX_EPOCH + Duration::from_millis(1645557742000));
+/// ```
+#[cfg(feature = "std")]
+pub fn uuid_v7_timestamp(uuid: &str) -> Option<SystemTime> {
+    if check_uuid(uuid) != Ok(UuidVersion::V7.number()) {
+        return None;
+    }
+    let milliseconds = u64::from_str_radix(&format!("{}{}", &uuid[..8], &uuid[9..13]), 16).ok()?;
+    UNIX_EPOCH.checked_add(Duration::from_millis(milliseconds))
+}
+
+/// Validate a version-7 uuid, e.g. an upload token, and with a maximum age, that its timestamp
+/// is at most that old and not in the future beyond [`UUID_V7_CLOCK_SKEW`].
+///
+/// # Errors
+/// If the uuid is invalid as by [`validate_uuid_version`], [`UuidError::Expired`] if older than
+/// the maximum age, or [`UuidError::Future`].
+///
+/// # Examples
+/// ``` ignore
+/// // tokens expire after an hour
+/// if let Err(ValidationError::Uuid(UuidError::Expired)) = validate_uuid_v7(&token, Some(Duration::from_secs(3600))) {
+///     println!("Expired token");
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn validate_uuid_v7(uuid: &str, max_age: Option<Duration>) -> Result<(), ValidationError> {
+    validate_uuid_version(uuid, UuidVersion::V7)?;
+    let Some(max_age) = max_age else {
+        return Ok(());
+    };
+    // Out of the range of SystemTime: from the far future
+    let timestamp = uuid_v7_timestamp(uuid).ok_or(UuidError::Future)?;
+    match SystemTime::now().duration_since(timestamp) {
+        Ok(age) if age > max_age => Err(UuidError::Expired.into()),
+        Err(e) if e.duration() > UUID_V7_CLOCK_SKEW => Err(UuidError::Future.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Parse a version-5 uuid, validated as by [`validate_uuid`], in one step.
+///
+/// The braced `{c70dc454-...}` and URN `urn:uuid:c70dc454-...` forms are accepted too.
+///
+/// # Errors
+/// If the uuid is malformed, or not of the variant 1 and the version 5.
+///
+/// # Examples
+/// ``` ignore
+/// match parse_uuid_v5(&input) {
+///     Ok(uuid) => println!("File {}", uuid),
+///     Err(e) => println!("{}", e),
+/// }
+/// ```
+pub fn parse_uuid_v5(uuid: &str) -> Result<Uuid, ValidationError> {
+    parse_uuid_version(uuid, UuidVersion::V5)
+}
+
+/// Parse a uuid of a version, validated as by [`validate_uuid_version`], in one step.
+///
+/// The braced `{c70dc454-...}` and URN `urn:uuid:c70dc454-...` forms are accepted too.
+///
+/// # Errors
+/// If the uuid is malformed, or not of the variant 1 and the version.
+pub fn parse_uuid_version(uuid: &str, version: UuidVersion) -> Result<Uuid, ValidationError> {
+    Ok(UuidValidator::builder().version(version).build().parse(uuid)?)
+}
+
+/// Normalize a uuid of any version from 1 to 8, in the hyphenated, braced or URN form, to the
+/// canonical lowercase hyphenated form.
+///
+/// # Errors
+/// If the uuid is malformed, not of the variant 1, of an unknown version, or the nil or max uuid.
+///
+/// # Examples
+/// ``` ignore
+/// let uuid = normalize_uuid("urn:uuid:C70DC454-1C7D-5C59-8FED-3A321E6A4A49").unwrap();
+/// assert_eq!(uuid, "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+/// ```
+pub fn normalize_uuid(uuid: &str) -> Result<String, ValidationError> {
+    let uuid = UuidValidator::builder().any_version().build().parse(uuid)?;
+    Ok(uuid.to_hyphenated().to_string())
+}
+
+/// Uuid validator configured through a [`UuidValidatorBuilder`], for the policies the
+/// validation functions don't cover, e.g. accepting the nil uuid as "no file".
+///
+/// # Examples
+/// ``` ignore
+/// let validator = UuidValidator::builder()
+///     .version(UuidVersion::V4)
+///     .allow_nil(true)
+///     .build();
+///
+/// match validator.parse(&input) {
+///     Ok(uuid) if uuid.is_nil() => println!("No session"),
+///     Ok(uuid) => println!("Session {}", uuid),
+///     Err(UuidError::Max) => println!("Sentinel value submitted"),
+///     Err(e) => println!("{}", e),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UuidValidator {
+    version: Option<UuidVersion>,
+    allow_nil: bool,
+    allow_max: bool,
+}
+
+/// Builder of a [`UuidValidator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UuidValidatorBuilder {
+    version: Option<UuidVersion>,
+    allow_nil: bool,
+    allow_max: bool,
+}
+
+impl Default for UuidValidatorBuilder {
+    fn default() -> Self {
+        UuidValidatorBuilder { version: Some(UuidVersion::V5), allow_nil: false, allow_max: false }
+    }
+}
+
+impl UuidValidatorBuilder {
+    /// Only accept the uuids of a version, by default 5.
+    pub fn version(mut self, version: UuidVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    /// Accept the uuids of any version from 1 to 8.
+    pub fn any_version(mut self) -> Self {
+        self.version = None;
+        self
+    }
+
+    /// Accept the nil uuid `00000000-0000-0000-0000-000000000000`, by default rejected with
+    /// [`UuidError::Nil`].
+    pub fn allow_nil(mut self, allow: bool) -> Self {
+        self.allow_nil = allow;
+        self
+    }
+
+    /// Accept the max uuid `ffffffff-ffff-ffff-ffff-ffffffffffff`, by default rejected with
+    /// [`UuidError::Max`].
+    pub fn allow_max(mut self, allow: bool) -> Self {
+        self.allow_max = allow;
+        self
+    }
+
+    /// Build the validator.
+    pub fn build(self) -> UuidValidator {
+        UuidValidator { version: self.version, allow_nil: self.allow_nil, allow_max: self.allow_max }
+    }
+}
+
+impl UuidValidator {
+    /// Create a builder accepting the version-5 uuids, without the nil and max uuids.
+    pub fn builder() -> UuidValidatorBuilder {
+        UuidValidatorBuilder::default()
+    }
+
+    /// Validate a uuid, see [`UuidValidator::parse`].
+    pub fn validate(&self, uuid: &str) -> bool {
+        self.parse(uuid).is_ok()
+    }
+
+    /// Parse a uuid in the hyphenated, braced `{c70dc454-...}` or URN `urn:uuid:c70dc454-...`
+    /// form.
+    ///
+    /// # Errors
+    /// If the uuid is malformed, not of the variant 1, of another version, or a sentinel not
+    /// allowed.
+    pub fn parse(&self, uuid: &str) -> Result<Uuid, UuidError> {
+        let uuid = strip_uuid_form(uuid);
+        self.accept(check_uuid(uuid), || Uuid::parse_str(uuid).map_err(|_| UuidError::Malformed))
+    }
+
+    /// Check the version and variant fields of a binary uuid, e.g. read from a database, without
+    /// formatting it.
+    ///
+    /// # Errors
+    /// If the uuid is not of the variant 1, of another version, or a sentinel not allowed.
+    pub fn parse_bytes(&self, bytes: &[u8; 16]) -> Result<Uuid, UuidError> {
+        self.accept(check_bytes(bytes), || Ok(Uuid::from_bytes(*bytes)))
+    }
+
+    /// Apply the version and sentinel policies to the version digit of a checked uuid.
+    fn accept<F>(&self, checked: Result<u8, UuidError>, uuid: F) -> Result<Uuid, UuidError>
+    where
+        F: FnOnce() -> Result<Uuid, UuidError>,
+    {
+        let actual = match checked {
+            Err(UuidError::Nil) if self.allow_nil => return Ok(Uuid::nil()),
+            Err(UuidError::Max) if self.allow_max => return Ok(Uuid::from_bytes([0xFF; 16])),
+            result => result?,
+        };
+        match self.version {
+            Some(expected) if actual != expected.number() => return Err(UuidError::Version { expected, actual }),
+            None if !(1..=8).contains(&actual) => return Err(UuidError::Malformed),
+            _ => {}
+        }
+        uuid()
+    }
+}
+
+/// Check the variant of a binary uuid, the nil and max uuids excluded, and return its version.
+fn check_bytes(bytes: &[u8; 16]) -> Result<u8, UuidError> {
+    if bytes.iter().all(|&byte| byte == 0) {
+        return Err(UuidError::Nil);
+    }
+    if bytes.iter().all(|&byte| byte == 0xFF) {
+        return Err(UuidError::Max);
+    }
+    if bytes[8] & 0xC0 != 0x80 {
+        return Err(UuidError::Variant);
+    }
+    Ok(bytes[6] >> 4)
+}
+
+/// Validate a binary version-5 uuid of the variant 1, as [`validate_uuid`] for its hyphenated
+/// form.
+///
+/// # Errors
+/// If the uuid is not of the variant 1 and the version 5, or the nil or max uuid.
+///
+/// # Examples
+/// ``` ignore
+/// let bytes: [u8; 16] = row.get("file_id");
+/// assert!(validate_uuid_bytes(&bytes).is_ok());
+/// ```
+pub fn validate_uuid_bytes(bytes: &[u8; 16]) -> Result<(), ValidationError> {
+    let actual = check_bytes(bytes)?;
+    if actual != UuidVersion::V5.number() {
+        return Err(UuidError::Version { expected: UuidVersion::V5, actual }.into());
     }
-    REGEX.is_match(uuid)
+    Ok(())
+}
+
+/// Validate a version-5 [`Uuid`] of the variant 1, as [`validate_uuid`] for its hyphenated form.
+///
+/// # Errors
+/// As [`validate_uuid_bytes`].
+pub fn validate_uuid_value(uuid: &Uuid) -> Result<(), ValidationError> {
+    validate_uuid_bytes(uuid.as_bytes())
 }
 
-/// Check that a version-5 uuid corresponds to a file.
+/// Check that a version-5 uuid corresponds to a file, generated by [`generate_uuid_v5`].
 ///
 /// # Examples
 /// ``` ignore
 /// let namespace = Uuid::NAMESPACE_OID;
-/// let uuid = Uuid::new_v5(&namespace, "my_content".as_bytes());
-/// let mut result = check_file_uuid(&namespace, "my_content".as_bytes(), &uuid);
+/// let uuid = generate_uuid_v5(&namespace, "my_content".as_bytes());
+/// let mut result = validate_file_uuid(&namespace, "my_content".as_bytes(), &uuid);
 /// assert!(result);
 /// ```
 pub fn validate_file_uuid(namespace: &Uuid, file: &[u8], uuid: &Uuid) -> bool {
-    Uuid::new_v5(namespace, file) == *uuid
+    generate_uuid_v5(namespace, file) == *uuid
+}
+
+/// Generate the version-5 uuid of a name in a namespace, checked by [`validate_file_uuid`] for
+/// the contents of a file. The name is hashed as given, without normalization: a path or url
+/// should be normalized first, e.g. with [`crate::dedup_key_for`].
+///
+/// # Examples
+/// ``` ignore
+/// let uuid = generate_uuid_v5(&namespace, b"users/42");
+/// ```
+pub fn generate_uuid_v5(namespace: &Uuid, name: &[u8]) -> Uuid {
+    Uuid::new_v5(namespace, name)
+}
+
+/// Incremental computation of a version-5 uuid, the SHA-1 hash of its namespace and name, for
+/// the names streamed by chunks.
+#[cfg(feature = "std")]
+struct UuidV5Hasher(Sha1);
+
+#[cfg(feature = "std")]
+impl UuidV5Hasher {
+    fn new(namespace: &Uuid) -> Self {
+        let mut hasher = Sha1::new();
+        hasher.update(namespace.as_bytes());
+        UuidV5Hasher(hasher)
+    }
+
+    /// Read a chunk into the hash, returning its length, 0 at the end of the reader.
+    fn read_chunk<R: Read>(&mut self, reader: &mut R, buffer: &mut [u8]) -> Result<usize, io::Error> {
+        loop {
+            match reader.read(buffer) {
+                Ok(length) => {
+                    self.0.update(&buffer[..length]);
+                    return Ok(length);
+                }
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    fn finalize(self) -> Uuid {
+        let mut bytes = [0; 16];
+        bytes.copy_from_slice(&self.0.digest().bytes()[..16]);
+        // Version 5 and variant 1
+        bytes[6] = (bytes[6] & 0x0F) | 0x50;
+        bytes[8] = (bytes[8] & 0x3F) | 0x80;
+        Uuid::from_bytes(bytes)
+    }
+}
+
+/// Check that a version-5 uuid corresponds to the contents of a reader, hashed by chunks so that
+/// large files, e.g. videos, are never loaded in memory. Same as [`validate_file_uuid`] otherwise.
+///
+/// # Errors
+/// If the reader failed.
+///
+/// # Examples
+/// ``` ignore
+/// let video = File::open("uploads/video.mp4")?;
+/// let result = validate_file_uuid_reader(&namespace, video, &uuid)?;
+/// ```
+#[cfg(feature = "std")]
+pub fn validate_file_uuid_reader<R: Read>(namespace: &Uuid, reader: R, uuid: &Uuid) -> Result<bool, ValidationError> {
+    Ok(hash_reader(namespace, reader)? == *uuid)
+}
+
+/// Version-5 uuid of the contents of a reader, hashed by chunks.
+#[cfg(feature = "std")]
+fn hash_reader<R: Read>(namespace: &Uuid, mut reader: R) -> Result<Uuid, io::Error> {
+    let mut hasher = UuidV5Hasher::new(namespace);
+    let mut buffer = vec![0; CHUNK_LENGTH];
+    while hasher.read_chunk(&mut reader, &mut buffer)? != 0 {}
+    Ok(hasher.finalize())
+}
+
+/// Check that a version-5 uuid corresponds to a file on disk, hashed by chunks as by
+/// [`validate_file_uuid_reader`].
+///
+/// # Errors
+/// If the file could not be opened or read.
+#[cfg(feature = "std")]
+pub fn validate_file_uuid_path<P: AsRef<Path>>(namespace: &Uuid, path: P, uuid: &Uuid) -> Result<bool, ValidationError> {
+    Ok(generate_file_uuid(namespace, path)? == *uuid)
 }
 
-#[cfg(test)]
+/// Generate the version-5 uuid of the contents of a file, hashed by chunks, the uuid checked by
+/// [`validate_file_uuid`] and [`validate_file_uuid_path`]. The path itself is not hashed, so a
+/// file keeps its uuid when renamed.
+///
+/// # Errors
+/// If the file could not be opened or read.
+///
+/// # Examples
+/// ``` ignore
+/// let uuid = generate_file_uuid(&namespace, "uploads/video.mp4")?;
+/// assert!(validate_file_uuid_path(&namespace, "uploads/video.mp4", &uuid)?);
+/// ```
+#[cfg(feature = "std")]
+pub fn generate_file_uuid<P: AsRef<Path>>(namespace: &Uuid, path: P) -> Result<Uuid, ValidationError> {
+    Ok(hash_reader(namespace, File::open(path)?)?)
+}
+
+/// Asynchronous [`validate_file_uuid_reader`], reading the chunks from an [`AsyncRead`], e.g. a
+/// file or a socket of the async runtime, without blocking its thread.
+///
+/// # Errors
+/// If the reader failed.
+///
+/// # Examples
+/// ``` ignore
+/// let result = validate_file_uuid_async(&namespace, upload.compat(), &uuid).await?;
+/// ```
+#[cfg(feature = "async")]
+pub async fn validate_file_uuid_async<R: AsyncRead + Unpin>(namespace: &Uuid, mut reader: R, uuid: &Uuid) -> Result<bool, ValidationError> {
+    let mut hasher = UuidV5Hasher::new(namespace);
+    let mut buffer = vec![0; CHUNK_LENGTH];
+    loop {
+        match poll_fn(|cx| Pin::new(&mut reader).poll_read(cx, &mut buffer)).await {
+            Ok(0) => break,
+            Ok(length) => hasher.0.update(&buffer[..length]),
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(hasher.finalize() == *uuid)
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
+    #[cfg(feature = "async")]
+    use std::future::Future;
+    #[cfg(feature = "async")]
+    use std::pin::{pin, Pin};
+    #[cfg(feature = "async")]
+    use std::task::{Context, Poll, Waker};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
     use uuid::Uuid;
-    use crate::{validate_file_uuid, validate_uuid};
+    use crate::{generate_file_uuid, generate_uuid_v5, normalize_uuid, parse_uuid_v5, parse_uuid_version, uuid_v7_timestamp, validate_file_uuid, validate_file_uuid_path, validate_file_uuid_reader, validate_uuid, validate_uuid_any, validate_uuid_bytes, validate_uuid_v7, validate_uuid_value, validate_uuid_version, UuidError, UuidValidator, UuidVersion, ValidationError};
 
     const FILE_CONTENT: &[u8] = "laCryptoCRigolo".as_bytes();
 
+    /// The uuid error of a failed validation.
+    fn uuid_error<T: std::fmt::Debug>(result: Result<T, ValidationError>) -> UuidError {
+        match result.unwrap_err() {
+            ValidationError::Uuid(error) => error,
+            error => panic!("{:?}", error),
+        }
+    }
+
     #[test]
     fn valid_uuids() {
-        assert!(validate_uuid("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"));
+        assert!(validate_uuid("c70dc454-1c7d-5c59-8fed-3a321e6a4a49").is_ok());
 
         // uuids should not be case sensitive
-        assert!(validate_uuid("C70DC454-1C7D-5C59-8fED-3A321E6A4A49"));
+        assert!(validate_uuid("C70DC454-1C7D-5C59-8fED-3A321E6A4A49").is_ok());
     }
 
     #[test]
     fn invalid_uuids() {
-        assert!(!validate_uuid(""));
+        assert!(validate_uuid("").is_err());
 
         // missing a char
-        assert!(!validate_uuid("70dc454-1c7d-5c59-8fed-3a321e6a4a49"));
-        assert!(!validate_uuid("c70dc454-c7d-5c59-8fed-3a321e6a4a49"));
-        assert!(!validate_uuid("c70dc454-1c7d-5c5-8fed-3a321e6a4a49"));
-        assert!(!validate_uuid("c70dc454-1c7d-5c59-8fd-3a321e6a4a49"));
-        assert!(!validate_uuid("c70dc454-1c7d-5c59-8fed-3a321e6a4a4"));
+        assert!(validate_uuid("70dc454-1c7d-5c59-8fed-3a321e6a4a49").is_err());
+        assert!(validate_uuid("c70dc454-c7d-5c59-8fed-3a321e6a4a49").is_err());
+        assert!(validate_uuid("c70dc454-1c7d-5c5-8fed-3a321e6a4a49").is_err());
+        assert!(validate_uuid("c70dc454-1c7d-5c59-8fd-3a321e6a4a49").is_err());
+        assert!(validate_uuid("c70dc454-1c7d-5c59-8fed-3a321e6a4a4").is_err());
 
         // invalid versions
-        assert!(!validate_uuid("c70dc454-1c7d-1c59-8fed-3a321e6a4a49")); // 1
-        assert!(!validate_uuid("c70dc454-1c7d-2c59-8fed-3a321e6a4a49")); // 2
-        assert!(!validate_uuid("c70dc454-1c7d-3c59-8fed-3a321e6a4a49")); // 3
-        assert!(!validate_uuid("c70dc454-1c7d-4c59-8fed-3a321e6a4a49")); // 4
+        assert!(validate_uuid("c70dc454-1c7d-1c59-8fed-3a321e6a4a49").is_err()); // 1
+        assert!(validate_uuid("c70dc454-1c7d-2c59-8fed-3a321e6a4a49").is_err()); // 2
+        assert!(validate_uuid("c70dc454-1c7d-3c59-8fed-3a321e6a4a49").is_err()); // 3
+        assert!(validate_uuid("c70dc454-1c7d-4c59-8fed-3a321e6a4a49").is_err()); // 4
+        assert_eq!(uuid_error(validate_uuid("c70dc454-1c7d-4c59-8fed-3a321e6a4a49")), UuidError::Version { expected: UuidVersion::V5, actual: 4 });
 
         // invalid variants
-        assert!(!validate_uuid("c70dc454-1c7d-5c59-0fed-3a321e6a4a49")); // 0
-        assert!(!validate_uuid("c70dc454-1c7d-5c59-cfed-3a321e6a4a49")); // 2
-        assert!(!validate_uuid("c70dc454-1c7d-5c59-ffed-3a321e6a4a49")); // 3
+        assert!(validate_uuid("c70dc454-1c7d-5c59-0fed-3a321e6a4a49").is_err()); // 0
+        assert!(validate_uuid("c70dc454-1c7d-5c59-cfed-3a321e6a4a49").is_err()); // 2
+        assert!(validate_uuid("c70dc454-1c7d-5c59-ffed-3a321e6a4a49").is_err()); // 3
 
         // invalid chars
-        assert!(!validate_uuid("c70xz454-1c7d-5c59-8fed-3a321e6a4a49"));
-        assert!(!validate_uuid("c70dc454.1c7d.5c59.8fed.3a321e6a4a49"));
+        assert!(validate_uuid("c70xz454-1c7d-5c59-8fed-3a321e6a4a49").is_err());
+        assert!(validate_uuid("c70dc454.1c7d.5c59.8fed.3a321e6a4a49").is_err());
+    }
+
+    #[test]
+    #[cfg(any(feature = "uuid_regex", feature = "bench"))]
+    fn regex_and_byte_checks() {
+        use super::{uuid_digits, uuid_digits_regex};
+
+        let mut uuids: Vec<String> = ["", "c70dc454-1c7d-5c59-8fed-3a321e6a4a49", "C70DC454-1C7D-5C59-8FED-3A321E6A4A49", "c70dc454-1c7d-5c59-8fed-3a321e6a4a4", "c70dc454-1c7d-5c59-8fed-3a321e6a4a490"]
+            .iter()
+            .map(|uuid| uuid.to_string())
+            .collect();
+        // every byte replaced by a hyphen, a letter out of the hexadecimal digits and a non-ascii char
+        let uuid = "c70dc454-1c7d-5c59-8fed-3a321e6a4a49";
+        for index in 0..uuid.len() {
+            for replacement in ["-", "g", "é", "0"] {
+                uuids.push(format!("{}{}{}", &uuid[..index], replacement, &uuid[index + 1..]));
+            }
+        }
+        for uuid in &uuids {
+            assert_eq!(uuid_digits(uuid), uuid_digits_regex(uuid), "{}", uuid);
+        }
+        assert_eq!(uuid_digits(uuid), Some((5, 8)));
+    }
+
+    #[test]
+    fn uuid_versions() {
+        assert!(validate_uuid_version("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04", UuidVersion::V4).is_ok());
+        assert!(validate_uuid_version("017F22E2-79B0-7CC3-98C4-DC0C0C07398F", UuidVersion::V7).is_ok());
+        assert!(validate_uuid_version("c70dc454-1c7d-5c59-8fed-3a321e6a4a49", UuidVersion::V5).is_ok());
+        assert!(validate_uuid_version("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04", UuidVersion::V7).is_err());
+        assert!(validate_uuid_version("9f0b8c2e-4d1a-4b7e-cc3f-2a6d5e8f1b04", UuidVersion::V4).is_err());
+        assert_eq!((UuidVersion::V1.number(), UuidVersion::V8.number()), (1, 8));
+
+        for version in 1..=8 {
+            assert!(validate_uuid_any(&format!("c70dc454-1c7d-{}c59-8fed-3a321e6a4a49", version)).is_ok());
+        }
+        assert!(validate_uuid_any("c70dc454-1c7d-9c59-8fed-3a321e6a4a49").is_err());
+        assert!(validate_uuid_any("00000000-0000-0000-0000-000000000000").is_err());
+        assert!(validate_uuid_any("ffffffff-ffff-ffff-ffff-ffffffffffff").is_err());
+        assert!(validate_uuid_any("c70dc454-1c7d-4c59-8fed-3a321e6a4a4").is_err());
+    }
+
+    #[test]
+    fn parsed_uuids() {
+        let uuid = parse_uuid_v5("C70DC454-1C7D-5C59-8FED-3A321E6A4A49").unwrap();
+        assert_eq!(uuid.to_hyphenated().to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        assert_eq!(uuid, Uuid::parse_str("c70dc454-1c7d-5c59-8fed-3a321e6a4a49").unwrap());
+        assert!(parse_uuid_version("017f22e2-79b0-7cc3-98c4-dc0c0c07398f", UuidVersion::V7).is_ok());
+
+        assert_eq!(uuid_error(parse_uuid_v5("c70dc4541c7d5c598fed3a321e6a4a49")), UuidError::Malformed);
+        assert_eq!(uuid_error(parse_uuid_v5("c70dc454-1c7d-5c59-cfed-3a321e6a4a49")), UuidError::Variant);
+        let error = uuid_error(parse_uuid_v5("c70dc454-1c7d-4c59-8fed-3a321e6a4a49"));
+        assert_eq!(error, UuidError::Version { expected: UuidVersion::V5, actual: 4 });
+        assert_eq!(error.to_string(), "Invalid uuid version 4, expected 5.");
+    }
+
+    #[test]
+    fn uuid_forms() {
+        let uuid = parse_uuid_v5("c70dc454-1c7d-5c59-8fed-3a321e6a4a49").unwrap();
+        assert_eq!(parse_uuid_v5("{c70dc454-1c7d-5c59-8fed-3a321e6a4a49}").unwrap(), uuid);
+        assert_eq!(parse_uuid_v5("urn:uuid:c70dc454-1c7d-5c59-8fed-3a321e6a4a49").unwrap(), uuid);
+        assert_eq!(parse_uuid_v5("URN:UUID:C70DC454-1C7D-5C59-8FED-3A321E6A4A49").unwrap(), uuid);
+        assert_eq!(normalize_uuid("{C70DC454-1C7D-5C59-8FED-3A321E6A4A49}").unwrap(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        assert_eq!(normalize_uuid("urn:uuid:017F22E2-79B0-7CC3-98C4-DC0C0C07398F").unwrap(), "017f22e2-79b0-7cc3-98c4-dc0c0c07398f");
+
+        // unbalanced or combined forms
+        assert_eq!(uuid_error(parse_uuid_v5("{c70dc454-1c7d-5c59-8fed-3a321e6a4a49")), UuidError::Malformed);
+        assert_eq!(uuid_error(parse_uuid_v5("c70dc454-1c7d-5c59-8fed-3a321e6a4a49}")), UuidError::Malformed);
+        assert_eq!(uuid_error(parse_uuid_v5("{urn:uuid:c70dc454-1c7d-5c59-8fed-3a321e6a4a49}")), UuidError::Malformed);
+        assert_eq!(uuid_error(normalize_uuid("urn:uuid:00000000-0000-0000-0000-000000000000")), UuidError::Nil);
+
+        // the validators only accept the hyphenated form
+        assert!(validate_uuid("{c70dc454-1c7d-5c59-8fed-3a321e6a4a49}").is_err());
+    }
+
+    #[test]
+    fn sentinel_uuids() {
+        const NIL: &str = "00000000-0000-0000-0000-000000000000";
+        const MAX: &str = "FFFFFFFF-ffff-ffff-ffff-ffffffffffff";
+        assert!(validate_uuid(NIL).is_err());
+        assert_eq!(uuid_error(parse_uuid_v5(NIL)), UuidError::Nil);
+        assert_eq!(uuid_error(parse_uuid_v5(&format!("{{{}}}", MAX))), UuidError::Max);
+        assert_eq!(UuidError::Max.to_string(), "The max uuid is not allowed.");
+
+        let validator = UuidValidator::builder().build();
+        assert!(validator.validate("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"));
+        assert!(!validator.validate(NIL) && !validator.validate(MAX));
+
+        let validator = UuidValidator::builder().version(UuidVersion::V4).allow_nil(true).build();
+        assert_eq!(validator.parse(&format!("urn:uuid:{}", NIL)), Ok(Uuid::nil()));
+        assert_eq!(validator.parse(MAX), Err(UuidError::Max));
+        assert_eq!(validator.parse("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"), Err(UuidError::Version { expected: UuidVersion::V4, actual: 5 }));
+
+        let validator = UuidValidator::builder().any_version().allow_max(true).build();
+        assert_eq!(validator.parse(MAX).unwrap().as_bytes(), &[0xFF; 16]);
+        assert_eq!(validator.parse(NIL), Err(UuidError::Nil));
+        assert!(validator.validate("017f22e2-79b0-7cc3-98c4-dc0c0c07398f"));
+        assert_eq!(validator.parse("017f22e2-79b0-0cc3-98c4-dc0c0c07398f"), Err(UuidError::Malformed));
+    }
+
+    /// Version-7 uuid of a time.
+    fn uuid_v7(time: SystemTime) -> String {
+        let milliseconds = time.duration_since(UNIX_EPOCH).unwrap().as_millis();
+        let hex = format!("{:012x}", milliseconds);
+        format!("{}-{}-7cc3-98c4-dc0c0c07398f", &hex[..8], &hex[8..])
+    }
+
+    #[test]
+    fn uuid_v7_timestamps() {
+        let uuid = "017f22e2-79b0-7cc3-98c4-dc0c0c07398f";
+        assert_eq!(uuid_v7_timestamp(uuid), Some(UNIX_EPOCH + Duration::from_millis(0x017f22e279b0)));
+        assert_eq!(uuid_v7_timestamp("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"), None);
+        assert!(validate_uuid_v7(uuid, None).is_ok());
+        assert!(matches!(validate_uuid_v7(uuid, Some(Duration::from_secs(3600))), Err(ValidationError::Uuid(UuidError::Expired))));
+        assert!(matches!(validate_uuid_v7("c70dc454-1c7d-5c59-8fed-3a321e6a4a49", None), Err(ValidationError::Uuid(UuidError::Version { actual: 5, .. }))));
+        assert!(matches!(validate_uuid_v7("017f22e2-79b0-7cc3-c8c4-dc0c0c07398f", None), Err(ValidationError::Uuid(UuidError::Variant))));
+
+        let hour = Duration::from_secs(3600);
+        let now = SystemTime::now();
+        assert!(validate_uuid_v7(&uuid_v7(now - Duration::from_secs(60)), Some(hour)).is_ok());
+        assert!(matches!(validate_uuid_v7(&uuid_v7(now - 2 * hour), Some(hour)), Err(ValidationError::Uuid(UuidError::Expired))));
+
+        // clocks out of sync, or a forged token
+        assert!(validate_uuid_v7(&uuid_v7(now + Duration::from_secs(10)), Some(hour)).is_ok());
+        assert!(matches!(validate_uuid_v7(&uuid_v7(now + hour), Some(hour)), Err(ValidationError::Uuid(UuidError::Future))));
+        assert!(validate_uuid_v7(&uuid_v7(now + hour), None).is_ok());
+    }
+
+    #[test]
+    fn binary_uuids() {
+        let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, FILE_CONTENT);
+        assert!(validate_uuid_value(&uuid).is_ok());
+        assert!(validate_uuid_bytes(uuid.as_bytes()).is_ok());
+        assert!(matches!(validate_uuid_value(&Uuid::nil()), Err(ValidationError::Uuid(UuidError::Nil))));
+        assert!(matches!(validate_uuid_bytes(&[0xFF; 16]), Err(ValidationError::Uuid(UuidError::Max))));
+
+        // the same results as their hyphenated forms
+        for text in ["c70dc454-1c7d-5c59-8fed-3a321e6a4a49", "c70dc454-1c7d-4c59-8fed-3a321e6a4a49", "c70dc454-1c7d-5c59-cfed-3a321e6a4a49", "c70dc454-1c7d-5c59-0fed-3a321e6a4a49"] {
+            let uuid = Uuid::parse_str(text).unwrap();
+            assert_eq!(validate_uuid_value(&uuid).map_err(|e| e.code()), validate_uuid(text).map_err(|e| e.code()), "{}", text);
+        }
+
+        let validator = UuidValidator::builder().version(UuidVersion::V4).allow_nil(true).build();
+        let v4 = Uuid::parse_str("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04").unwrap();
+        assert_eq!(validator.parse_bytes(v4.as_bytes()), Ok(v4));
+        assert_eq!(validator.parse_bytes(&[0; 16]), Ok(Uuid::nil()));
+        assert_eq!(validator.parse_bytes(&[0xFF; 16]), Err(UuidError::Max));
+        assert_eq!(validator.parse_bytes(uuid.as_bytes()), Err(UuidError::Version { expected: UuidVersion::V4, actual: 5 }));
     }
 
     #[test]
@@ -92,4 +840,87 @@ mod tests {
         assert!(!validate_file_uuid(&Uuid::NAMESPACE_OID, FILE_CONTENT,
                                     &Uuid::new_v5(&Uuid::NAMESPACE_DNS, FILE_CONTENT)));
     }
+
+    #[test]
+    fn streamed_uuid_to_file() {
+        let path = "test_files/valid_image.png";
+        let contents = std::fs::read(path).unwrap();
+        assert!(contents.len() > super::CHUNK_LENGTH / 4);
+        let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, &contents);
+
+        assert!(validate_file_uuid_path(&Uuid::NAMESPACE_OID, path, &uuid).unwrap());
+        assert!(validate_file_uuid_reader(&Uuid::NAMESPACE_OID, FILE_CONTENT, &Uuid::new_v5(&Uuid::NAMESPACE_OID, FILE_CONTENT)).unwrap());
+        assert!(validate_file_uuid_reader(&Uuid::NAMESPACE_OID, &b""[..], &Uuid::new_v5(&Uuid::NAMESPACE_OID, b"")).unwrap());
+        assert!(!validate_file_uuid_path(&Uuid::NAMESPACE_DNS, path, &uuid).unwrap());
+        assert!(validate_file_uuid_path(&Uuid::NAMESPACE_OID, "test_files/missing", &uuid).is_err());
+
+        // large contents over several chunks
+        let large: Vec<u8> = (0..3 * super::CHUNK_LENGTH + 7).map(|i| (i % 251) as u8).collect();
+        let large_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, &large);
+        assert!(validate_file_uuid_reader(&Uuid::NAMESPACE_URL, large.as_slice(), &large_uuid).unwrap());
+    }
+
+    /// Reader of a slice, pending before each read.
+    #[cfg(feature = "async")]
+    struct PendingReader<'a> {
+        contents: &'a [u8],
+        ready: bool,
+    }
+
+    #[cfg(feature = "async")]
+    impl futures_io::AsyncRead for PendingReader<'_> {
+        fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buffer: &mut [u8]) -> Poll<std::io::Result<usize>> {
+            self.ready = !self.ready;
+            if !self.ready {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+            let length = buffer.len().min(self.contents.len());
+            buffer[..length].copy_from_slice(&self.contents[..length]);
+            self.contents = &self.contents[length..];
+            Poll::Ready(Ok(length))
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "async")]
+    fn async_uuid_to_file() {
+        use crate::validate_file_uuid_async;
+
+        let large: Vec<u8> = (0..3 * super::CHUNK_LENGTH + 7).map(|i| (i % 251) as u8).collect();
+        let large_uuid = Uuid::new_v5(&Uuid::NAMESPACE_URL, &large);
+        let mut context = Context::from_waker(Waker::noop());
+
+        // polled to completion, pending before each of the 4 chunks and the end of the reader
+        let mut future = pin!(validate_file_uuid_async(&Uuid::NAMESPACE_URL, PendingReader { contents: &large, ready: true }, &large_uuid));
+        let mut polls = 1;
+        let result = loop {
+            match future.as_mut().poll(&mut context) {
+                Poll::Ready(result) => break result,
+                Poll::Pending => polls += 1,
+            }
+        };
+        assert!(result.unwrap());
+        assert_eq!(polls, 6);
+
+        // ready readers
+        let uuid = Uuid::new_v5(&Uuid::NAMESPACE_OID, FILE_CONTENT);
+        let future = pin!(validate_file_uuid_async(&Uuid::NAMESPACE_OID, FILE_CONTENT, &uuid));
+        assert!(matches!(future.poll(&mut context), Poll::Ready(Ok(true))));
+        let future = pin!(validate_file_uuid_async(&Uuid::NAMESPACE_DNS, FILE_CONTENT, &uuid));
+        assert!(matches!(future.poll(&mut context), Poll::Ready(Ok(false))));
+    }
+
+    #[test]
+    fn generated_uuids() {
+        assert_eq!(generate_uuid_v5(&Uuid::NAMESPACE_OID, FILE_CONTENT), Uuid::new_v5(&Uuid::NAMESPACE_OID, FILE_CONTENT));
+        assert_eq!(generate_uuid_v5(&Uuid::NAMESPACE_DNS, b""), Uuid::new_v5(&Uuid::NAMESPACE_DNS, b""));
+        assert!(validate_uuid(&generate_uuid_v5(&Uuid::NAMESPACE_URL, b"users/42").to_string()).is_ok());
+
+        let path = "test_files/valid_image.jpg";
+        let uuid = generate_file_uuid(&Uuid::NAMESPACE_OID, path).unwrap();
+        assert_eq!(uuid, generate_uuid_v5(&Uuid::NAMESPACE_OID, &std::fs::read(path).unwrap()));
+        assert!(validate_file_uuid_path(&Uuid::NAMESPACE_OID, path, &uuid).unwrap());
+        assert!(generate_file_uuid(&Uuid::NAMESPACE_OID, "test_files/missing").is_err());
+    }
 }
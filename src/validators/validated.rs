@@ -0,0 +1,420 @@
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+use std::fmt::{self, Display, Formatter};
+#[cfg(any(feature = "url", feature = "file", feature = "uuid"))]
+use std::ops::Deref;
+#[cfg(feature = "file")]
+use std::path::PathBuf;
+#[cfg(any(feature = "file", feature = "uuid"))]
+use std::path::Path;
+
+#[cfg(feature = "url")]
+use lazy_static::lazy_static;
+#[cfg(feature = "uuid")]
+use uuid::Uuid;
+
+#[cfg(feature = "file")]
+use super::validate_file::rejection_reason;
+use super::ValidationError;
+#[cfg(feature = "file")]
+use super::{validate_file, FileKind, ValidatedFile};
+#[cfg(feature = "uuid")]
+use super::{generate_file_uuid, UuidError, UuidValidator};
+#[cfg(feature = "url")]
+use super::{UrlError, UrlValidator};
+
+/// Validated type parsed from a string, e.g. a path parameter of a web request, see the
+/// extractors of the `axum` and `actix` features and the form fields of the `rocket` feature.
+pub trait ValidatedInput: Sized {
+    /// Parse and validate an input.
+    ///
+    /// # Errors
+    /// If the input is invalid.
+    fn parse_input(input: &str) -> Result<Self, ValidationError>;
+}
+
+/// Url accepted by a [`UrlValidator`], so that a function taking a `ValidUrl` instead of a `&str`
+/// cannot be given an unvalidated url.
+///
+/// # Examples
+/// ``` ignore
+/// fn redirect(url: &ValidUrl) { ... }
+///
+/// let url = ValidUrl::try_from("https://docs.rs/lazy_static")?;
+/// redirect(&url);
+/// ```
+#[cfg(feature = "url")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ValidUrl(String);
+
+#[cfg(feature = "url")]
+impl ValidUrl {
+    /// Validate an url with the default [`UrlValidator`], see [`crate::validate_url`].
+    ///
+    /// # Errors
+    /// If the url is invalid.
+    pub fn new(url: &str) -> Result<Self, UrlError> {
+        lazy_static! {
+            static ref VALIDATOR: UrlValidator = UrlValidator::builder().build().unwrap();
+        }
+        ValidUrl::with_validator(&VALIDATOR, url)
+    }
+
+    /// Validate an url with a configured validator, e.g. with a whitelist of hosts.
+    ///
+    /// # Errors
+    /// If the url is invalid.
+    pub fn with_validator(validator: &UrlValidator, url: &str) -> Result<Self, UrlError> {
+        validator.check(url)?;
+        Ok(ValidUrl(String::from(url)))
+    }
+
+    /// The url, as given.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The url, as given.
+    pub fn into_inner(self) -> String {
+        self.0
+    }
+}
+
+#[cfg(feature = "url")]
+impl Deref for ValidUrl {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "url")]
+impl AsRef<str> for ValidUrl {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(feature = "url")]
+impl TryFrom<&str> for ValidUrl {
+    type Error = UrlError;
+
+    fn try_from(url: &str) -> Result<Self, UrlError> {
+        ValidUrl::new(url)
+    }
+}
+
+#[cfg(feature = "url")]
+impl ValidatedInput for ValidUrl {
+    fn parse_input(input: &str) -> Result<Self, ValidationError> {
+        Ok(ValidUrl::try_from(input)?)
+    }
+}
+
+#[cfg(feature = "url")]
+impl Display for ValidUrl {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Validate a file on disk, accepting only one kind of file if given.
+#[cfg(feature = "file")]
+pub(crate) fn validate_kind(path: &Path, kind: Option<FileKind>, check_extension: bool) -> Result<ValidatedFile, ValidationError> {
+    let file = validate_file(path, check_extension)?;
+    if !file.is_valid() {
+        return Err(ValidationError::Rejected(rejection_reason(&file)));
+    }
+    if kind.is_some_and(|kind| file.kind != kind) {
+        return Err(ValidationError::Rejected(format!("The type {} is not allowed.", file.mime_type)));
+    }
+    Ok(file)
+}
+
+/// Validate an uploaded file from its contents, with its extension checked on its declared name
+/// if any.
+#[cfg(any(feature = "axum", feature = "rocket"))]
+pub(crate) fn validate_upload(contents: &[u8], declared_name: Option<&str>) -> Result<ValidatedFile, ValidationError> {
+    let file = super::validate_file_bytes(contents, declared_name, declared_name.is_some())?;
+    if file.is_valid() {
+        Ok(file)
+    } else {
+        Err(ValidationError::Rejected(rejection_reason(&file)))
+    }
+}
+
+/// Image file on disk validated by [`validate_file`] with its extension checked, so that a
+/// function taking an `ImageFile` cannot be given a video or an unvalidated path.
+///
+/// # Examples
+/// ``` ignore
+/// let image = ImageFile::try_from("myDir/myImage.png")?;
+/// println!("{} is a {}", image, image.file().mime_type);
+/// ```
+#[cfg(feature = "file")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageFile {
+    path: PathBuf,
+    file: ValidatedFile,
+}
+
+#[cfg(feature = "file")]
+impl ImageFile {
+    /// Validate an image file.
+    ///
+    /// # Errors
+    /// If the file could not be opened or read, its type is unknown, or it is not an image of a
+    /// matching extension ([`ValidationError::Rejected`]).
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ValidationError> {
+        let path = path.as_ref();
+        let file = validate_kind(path, Some(FileKind::Image), true)?;
+        Ok(ImageFile { path: path.to_path_buf(), file })
+    }
+
+    /// Details of the validation of the file.
+    pub fn file(&self) -> &ValidatedFile {
+        &self.file
+    }
+
+    /// The path of the file, as given.
+    pub fn into_inner(self) -> PathBuf {
+        self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl Deref for ImageFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl AsRef<Path> for ImageFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl TryFrom<&str> for ImageFile {
+    type Error = ValidationError;
+
+    fn try_from(path: &str) -> Result<Self, ValidationError> {
+        ImageFile::new(path)
+    }
+}
+
+#[cfg(feature = "file")]
+impl ValidatedInput for ImageFile {
+    fn parse_input(input: &str) -> Result<Self, ValidationError> {
+        ImageFile::try_from(input)
+    }
+}
+
+#[cfg(feature = "file")]
+impl Display for ImageFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+/// Video file on disk validated by [`validate_file`] with its extension checked, see
+/// [`ImageFile`].
+#[cfg(feature = "file")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoFile {
+    path: PathBuf,
+    file: ValidatedFile,
+}
+
+#[cfg(feature = "file")]
+impl VideoFile {
+    /// Validate a video file.
+    ///
+    /// # Errors
+    /// If the file could not be opened or read, its type is unknown, or it is not a video of a
+    /// matching extension ([`ValidationError::Rejected`]).
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self, ValidationError> {
+        let path = path.as_ref();
+        let file = validate_kind(path, Some(FileKind::Video), true)?;
+        Ok(VideoFile { path: path.to_path_buf(), file })
+    }
+
+    /// Details of the validation of the file.
+    pub fn file(&self) -> &ValidatedFile {
+        &self.file
+    }
+
+    /// The path of the file, as given.
+    pub fn into_inner(self) -> PathBuf {
+        self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl Deref for VideoFile {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl AsRef<Path> for VideoFile {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(feature = "file")]
+impl TryFrom<&str> for VideoFile {
+    type Error = ValidationError;
+
+    fn try_from(path: &str) -> Result<Self, ValidationError> {
+        VideoFile::new(path)
+    }
+}
+
+#[cfg(feature = "file")]
+impl ValidatedInput for VideoFile {
+    fn parse_input(input: &str) -> Result<Self, ValidationError> {
+        VideoFile::try_from(input)
+    }
+}
+
+#[cfg(feature = "file")]
+impl Display for VideoFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())
+    }
+}
+
+/// Version-5 uuid of a file, parsed by [`crate::parse_uuid_v5`] or generated from its contents.
+///
+/// # Examples
+/// ``` ignore
+/// let uuid = FileUuid::try_from("{C70DC454-1C7D-5C59-8FED-3A321E6A4A49}")?;
+/// assert_eq!(uuid.to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+/// ```
+#[cfg(feature = "uuid")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FileUuid(Uuid);
+
+#[cfg(feature = "uuid")]
+impl FileUuid {
+    /// Parse a version-5 uuid, see [`crate::parse_uuid_v5`].
+    ///
+    /// # Errors
+    /// If the uuid is invalid or not of the version 5.
+    pub fn new(uuid: &str) -> Result<Self, UuidError> {
+        UuidValidator::builder().build().parse(uuid).map(FileUuid)
+    }
+
+    /// Generate the uuid of a file in a namespace, see [`generate_file_uuid`].
+    ///
+    /// # Errors
+    /// If the file could not be opened or read.
+    pub fn generate<P: AsRef<Path>>(namespace: &Uuid, path: P) -> Result<Self, ValidationError> {
+        generate_file_uuid(namespace, path).map(FileUuid)
+    }
+
+    /// The uuid.
+    pub fn into_inner(self) -> Uuid {
+        self.0
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Deref for FileUuid {
+    type Target = Uuid;
+
+    fn deref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl AsRef<Uuid> for FileUuid {
+    fn as_ref(&self) -> &Uuid {
+        &self.0
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl TryFrom<&str> for FileUuid {
+    type Error = UuidError;
+
+    fn try_from(uuid: &str) -> Result<Self, UuidError> {
+        FileUuid::new(uuid)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl ValidatedInput for FileUuid {
+    fn parse_input(input: &str) -> Result<Self, ValidationError> {
+        Ok(FileUuid::try_from(input)?)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl Display for FileUuid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(all(test, feature = "url", feature = "file", feature = "uuid"))]
+mod tests {
+    use std::path::Path;
+
+    use uuid::Uuid;
+
+    use crate::{FileKind, FileUuid, ImageFile, UrlError, UrlValidator, UuidError, ValidUrl, ValidatedInput, ValidationError, VideoFile};
+
+    #[test]
+    fn valid_urls() {
+        let url = ValidUrl::try_from("https://docs.rs/lazy_static").unwrap();
+        assert_eq!(url.to_string(), "https://docs.rs/lazy_static");
+        assert!(url.starts_with("https://"));
+        assert_eq!(url.as_ref(), url.as_str());
+        assert_eq!(ValidUrl::try_from("https://docs .rs"), Err(UrlError::Invalid));
+
+        let validator = UrlValidator::builder().top_level_whitelist([".ch"]).build().unwrap();
+        assert!(ValidUrl::with_validator(&validator, "https://heig-vd.ch").is_ok());
+        assert!(ValidUrl::with_validator(&validator, "https://docs.rs").is_err());
+    }
+
+    #[test]
+    fn valid_files() {
+        let image = ImageFile::try_from("test_files/valid_image.png").unwrap();
+        assert_eq!(image.file().kind, FileKind::Image);
+        assert_eq!(image.extension().unwrap(), "png");
+        assert_eq!(image.to_string(), "test_files/valid_image.png");
+        let video = VideoFile::new(Path::new("test_files/valid_video.mov")).unwrap();
+        assert_eq!(video.file().kind, FileKind::Video);
+        assert_eq!(video.into_inner(), Path::new("test_files/valid_video.mov"));
+
+        let error = ImageFile::try_from("test_files/valid_video.mov").unwrap_err();
+        assert_eq!(error.to_string(), "The type video/quicktime is not allowed.");
+        assert!(matches!(VideoFile::try_from("test_files/valid_image.png"), Err(ValidationError::Rejected(_))));
+        assert!(matches!(ImageFile::try_from("test_files/invalid_ext_image_jpg.png"), Err(ValidationError::Rejected(_))));
+        assert!(matches!(ImageFile::try_from("test_files/missing.png"), Err(ValidationError::Io(_))));
+    }
+
+    #[test]
+    fn file_uuids() {
+        let uuid = FileUuid::try_from("{C70DC454-1C7D-5C59-8FED-3A321E6A4A49}").unwrap();
+        assert_eq!(uuid.to_string(), "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        assert_eq!(uuid.get_version_num(), 5);
+        assert!(matches!(FileUuid::try_from("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04"), Err(UuidError::Version { actual: 4, .. })));
+        assert!(matches!(FileUuid::parse_input("c70dc454"), Err(ValidationError::Uuid(UuidError::Malformed))));
+
+        let path = "test_files/valid_image.png";
+        let generated = FileUuid::generate(&Uuid::NAMESPACE_OID, path).unwrap();
+        assert_eq!(generated.into_inner(), Uuid::new_v5(&Uuid::NAMESPACE_OID, &std::fs::read(path).unwrap()));
+    }
+}
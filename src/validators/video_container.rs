@@ -0,0 +1,456 @@
+//! Structural validation of the common video containers, enabled with the `deep` feature.
+//!
+//! The boxes, chunks or elements of the container are walked to find a video track with its
+//! codec and the duration of the video, so that a garbage file behind the magic numbers of a
+//! video is rejected. The frames are not decoded.
+
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+use super::byte_reader::{Reader, Truncated};
+
+/// Container format of a video checked by the deep validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum VideoFormat {
+    Mp4,
+    QuickTime,
+    Avi,
+    WebM,
+    Matroska,
+}
+
+/// Container of a video checked by the deep validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VideoInfo {
+    /// Format of the container
+    pub format: VideoFormat,
+    /// Codec of the first video track as stored in the container, e.g. `avc1` in an MP4 file,
+    /// `H264` in an AVI file or `V_VP9` in a WebM file
+    pub codec: String,
+    /// Duration of the video, `None` if not stored in the container
+    pub duration: Option<Duration>,
+}
+
+/// Error of the deep validation of a video.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VideoError {
+    /// The container is malformed or truncated, for the given reason
+    Invalid(String),
+    /// The format of the container, given by its mime type, can't be checked
+    Unsupported(String),
+}
+
+impl Display for VideoError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            VideoError::Invalid(reason) => write!(f, "Invalid video: {}.", reason),
+            VideoError::Unsupported(mime_type) => write!(f, "Unsupported video format {}.", mime_type),
+        }
+    }
+}
+
+impl std::error::Error for VideoError {}
+
+impl From<Truncated> for VideoError {
+    fn from(_: Truncated) -> Self {
+        invalid("truncated file")
+    }
+}
+
+fn invalid(reason: &str) -> VideoError {
+    VideoError::Invalid(reason.to_string())
+}
+
+/// Parse the container of a video of the given mime type and find its first video track.
+pub(crate) fn parse_video(contents: &[u8], mime_type: &str) -> Result<VideoInfo, VideoError> {
+    match mime_type {
+        "video/mp4" | "video/x-m4v" => parse_iso_media(contents, VideoFormat::Mp4),
+        "video/quicktime" => parse_iso_media(contents, VideoFormat::QuickTime),
+        "video/x-msvideo" => parse_avi(contents),
+        "video/webm" | "video/x-matroska" => parse_matroska(contents),
+        _ => Err(VideoError::Unsupported(mime_type.to_string())),
+    }
+}
+
+/// Duration of a number of units of a time scale, in units per second.
+fn duration(units: u64, timescale: u64) -> Duration {
+    // The remainder is below the time scale, a u32, so the nanoseconds can't overflow
+    Duration::from_secs(units / timescale) + Duration::from_nanos(units % timescale * 1_000_000_000 / timescale)
+}
+
+/// Codec identifier from a four character code, without its padding.
+fn fourcc(code: &[u8]) -> Result<String, VideoError> {
+    let code = std::str::from_utf8(code)
+        .ok()
+        .filter(|code| code.chars().all(|c| c.is_ascii_graphic() || c == ' ' || c == '\0'))
+        .ok_or_else(|| invalid("bad codec identifier"))?;
+    Ok(code.trim_end_matches([' ', '\0']).to_string())
+}
+
+/// Type and contents of a box or a chunk of a container.
+type Chunk<'a> = (&'a [u8], &'a [u8]);
+
+/// Split the boxes of an ISO base media file (MP4, MOV) into their types and contents.
+fn iso_boxes(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, VideoError> {
+    let mut reader = Reader::new(bytes);
+    let mut boxes = Vec::new();
+    while reader.remaining() > 0 {
+        let size = reader.be_u32()? as u64;
+        let kind = reader.take(4)?;
+        let contents = match size {
+            // The box extends to the end of the file
+            0 => reader.rest(),
+            // The size is stored on 64 bits after the type
+            1 => {
+                let size = reader.be_u64()?.checked_sub(16).ok_or_else(|| invalid("bad box size"))?;
+                reader.take(usize::try_from(size).map_err(|_| Truncated)?)?
+            }
+            2..=7 => return Err(invalid("bad box size")),
+            _ => reader.take(size as usize - 8)?,
+        };
+        boxes.push((kind, contents));
+    }
+    Ok(boxes)
+}
+
+/// Contents of the first box of a type.
+fn find_box<'a>(boxes: &[Chunk<'a>], kind: &[u8]) -> Option<&'a [u8]> {
+    boxes.iter().find(|(box_kind, _)| *box_kind == kind).map(|&(_, contents)| contents)
+}
+
+/// Check the boxes of an MP4 or QuickTime file and the tracks of its movie box.
+fn parse_iso_media(bytes: &[u8], format: VideoFormat) -> Result<VideoInfo, VideoError> {
+    let boxes = iso_boxes(bytes)?;
+    let movie = iso_boxes(find_box(&boxes, b"moov").ok_or_else(|| invalid("missing movie box"))?)?;
+
+    // Movie header, with 64 bits times from version 1
+    let mut header = Reader::new(find_box(&movie, b"mvhd").ok_or_else(|| invalid("missing movie header"))?);
+    let (timescale, units) = if header.u8()? == 1 {
+        header.skip(3 + 16)?;
+        (header.be_u32()?, header.be_u64()?)
+    } else {
+        header.skip(3 + 8)?;
+        (header.be_u32()?, header.be_u32()? as u64)
+    };
+    if timescale == 0 {
+        return Err(invalid("bad movie header"));
+    }
+    // A duration of 0 or all ones is unknown, e.g. in fragmented files
+    let duration = (units != 0 && units != u64::MAX && units != u32::MAX as u64).then(|| duration(units, timescale as u64));
+
+    for (_, track) in movie.iter().filter(|(kind, _)| *kind == b"trak") {
+        let media = iso_boxes(find_box(&iso_boxes(track)?, b"mdia").ok_or_else(|| invalid("missing track media"))?)?;
+        // Version, flags and predefined field before the handler type
+        let handler = find_box(&media, b"hdlr").ok_or_else(|| invalid("missing track handler"))?;
+        if Reader::new(handler).take(12)?[8..] != *b"vide" {
+            continue;
+        }
+
+        let information = iso_boxes(find_box(&media, b"minf").ok_or_else(|| invalid("missing media information"))?)?;
+        let samples = iso_boxes(find_box(&information, b"stbl").ok_or_else(|| invalid("missing sample table"))?)?;
+        // Version, flags and entry count before the first sample description
+        let mut descriptions = Reader::new(find_box(&samples, b"stsd").ok_or_else(|| invalid("missing sample description"))?);
+        descriptions.skip(4)?;
+        if descriptions.be_u32()? == 0 {
+            return Err(invalid("missing sample description"));
+        }
+        descriptions.skip(4)?;
+        let codec = fourcc(descriptions.take(4)?)?;
+        return Ok(VideoInfo { format, codec, duration });
+    }
+    Err(invalid("no video track"))
+}
+
+/// Split the chunks of a RIFF list into their identifiers and contents.
+fn riff_chunks(bytes: &[u8]) -> Result<Vec<Chunk<'_>>, VideoError> {
+    let mut reader = Reader::new(bytes);
+    let mut chunks = Vec::new();
+    while reader.remaining() > 0 {
+        let id = reader.take(4)?;
+        let size = reader.le_u32()? as usize;
+        chunks.push((id, reader.take(size)?));
+        // The chunks are aligned on 16 bits
+        if size % 2 == 1 && reader.remaining() > 0 {
+            reader.skip(1)?;
+        }
+    }
+    Ok(chunks)
+}
+
+/// Contents of the first list of a type, after its type.
+fn find_list<'a>(chunks: &[Chunk<'a>], kind: &[u8]) -> Option<&'a [u8]> {
+    chunks
+        .iter()
+        .find(|(id, contents)| *id == b"LIST" && contents.starts_with(kind))
+        .map(|&(_, contents)| &contents[kind.len()..])
+}
+
+/// Check the RIFF chunks of an AVI file and the stream headers of its header list.
+fn parse_avi(bytes: &[u8]) -> Result<VideoInfo, VideoError> {
+    let mut reader = Reader::new(bytes);
+    if reader.take(4)? != b"RIFF" {
+        return Err(invalid("bad AVI signature"));
+    }
+    let riff_size = reader.le_u32()? as usize;
+    let mut riff = Reader::new(reader.take(riff_size)?);
+    if riff.take(4)? != b"AVI " {
+        return Err(invalid("bad AVI signature"));
+    }
+
+    let chunks = riff_chunks(riff.rest())?;
+    let header = riff_chunks(find_list(&chunks, b"hdrl").ok_or_else(|| invalid("missing AVI header list"))?)?;
+    if find_list(&chunks, b"movi").is_none() {
+        return Err(invalid("missing AVI movie data"));
+    }
+
+    for stream in header.iter().filter(|(id, contents)| *id == b"LIST" && contents.starts_with(b"strl")) {
+        let stream = riff_chunks(&stream.1[4..])?;
+        let mut stream_header = Reader::new(find_box(&stream, b"strh").ok_or_else(|| invalid("missing AVI stream header"))?);
+        if stream_header.take(4)? != b"vids" {
+            continue;
+        }
+        let handler = stream_header.take(4)?;
+        // Flags, priority, language and initial frames before the frame rate
+        stream_header.skip(12)?;
+        let (scale, rate) = (stream_header.le_u32()?, stream_header.le_u32()?);
+        stream_header.skip(4)?;
+        let length = stream_header.le_u32()?;
+
+        // The compression of the bitmap header is the codec, the handler may be blank
+        let compression = match find_box(&stream, b"strf") {
+            Some(format) => {
+                let mut format = Reader::new(format);
+                format.skip(16)?;
+                Some(format.take(4)?)
+            }
+            None => None,
+        };
+        let codec = match compression {
+            Some([0, 0, 0, 0]) => String::from("RGB"),
+            Some(compression) => fourcc(compression)?,
+            None => fourcc(handler)?,
+        };
+        if codec.is_empty() {
+            return Err(invalid("bad codec identifier"));
+        }
+
+        let duration = (rate != 0 && length != 0).then(|| duration(length as u64 * scale as u64, rate as u64));
+        return Ok(VideoInfo { format: VideoFormat::Avi, codec, duration });
+    }
+    Err(invalid("no video track"))
+}
+
+const EBML_HEADER: u32 = 0x1A45_DFA3;
+const EBML_DOC_TYPE: u32 = 0x4282;
+const SEGMENT: u32 = 0x1853_8067;
+const SEGMENT_INFO: u32 = 0x1549_A966;
+const TIMESTAMP_SCALE: u32 = 0x2A_D7B1;
+const DURATION: u32 = 0x4489;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const VIDEO_TRACK: u64 = 1;
+
+/// Read the identifier and the size of an EBML element, `None` if the size is unknown.
+fn ebml_element_header(reader: &mut Reader) -> Result<(u32, Option<u64>), VideoError> {
+    // The identifier keeps its length marker, on 1 to 4 bytes
+    let first = reader.u8()?;
+    let length = first.leading_zeros() as usize + 1;
+    if length > 4 {
+        return Err(invalid("bad EBML element"));
+    }
+    let id = reader.take(length - 1)?.iter().fold(first as u32, |id, &byte| (id << 8) | byte as u32);
+
+    // The size drops its length marker, on 1 to 8 bytes
+    let first = reader.u8()?;
+    let length = first.leading_zeros() as usize + 1;
+    if length > 8 {
+        return Err(invalid("bad EBML element"));
+    }
+    let size = reader.take(length - 1)?.iter().fold(first as u64 & (0xFF >> length), |size, &byte| (size << 8) | byte as u64);
+    let unknown = (1u64 << (7 * length)) - 1;
+    Ok((id, (size != unknown).then_some(size)))
+}
+
+/// Split the EBML elements of a master element into their identifiers and contents.
+fn ebml_elements(bytes: &[u8]) -> Result<Vec<(u32, &[u8])>, VideoError> {
+    let mut reader = Reader::new(bytes);
+    let mut elements = Vec::new();
+    while reader.remaining() > 0 {
+        let (id, size) = ebml_element_header(&mut reader)?;
+        let size = size.ok_or_else(|| invalid("bad EBML element size"))?;
+        elements.push((id, reader.take(usize::try_from(size).map_err(|_| Truncated)?)?));
+    }
+    Ok(elements)
+}
+
+/// Contents of the first EBML element of an identifier.
+fn find_element<'a>(elements: &[(u32, &'a [u8])], id: u32) -> Option<&'a [u8]> {
+    elements.iter().find(|(element_id, _)| *element_id == id).map(|&(_, contents)| contents)
+}
+
+/// Value of an EBML unsigned integer element.
+fn ebml_uint(contents: &[u8]) -> Result<u64, VideoError> {
+    if contents.len() > 8 {
+        return Err(invalid("bad EBML integer"));
+    }
+    Ok(contents.iter().fold(0, |value, &byte| (value << 8) | byte as u64))
+}
+
+/// Value of an EBML float element.
+fn ebml_float(contents: &[u8]) -> Result<f64, VideoError> {
+    match contents.len() {
+        0 => Ok(0.0),
+        4 => Ok(f32::from_be_bytes(contents.try_into().unwrap()) as f64),
+        8 => Ok(f64::from_be_bytes(contents.try_into().unwrap())),
+        _ => Err(invalid("bad EBML float")),
+    }
+}
+
+/// Check the EBML header and the segment information and tracks of a WebM or Matroska file.
+fn parse_matroska(bytes: &[u8]) -> Result<VideoInfo, VideoError> {
+    let mut reader = Reader::new(bytes);
+    let (id, size) = ebml_element_header(&mut reader)?;
+    if id != EBML_HEADER {
+        return Err(invalid("bad EBML signature"));
+    }
+    let header = ebml_elements(reader.take(size.ok_or_else(|| invalid("bad EBML element size"))? as usize)?)?;
+    let format = match find_element(&header, EBML_DOC_TYPE) {
+        Some(b"webm") => VideoFormat::WebM,
+        Some(b"matroska") => VideoFormat::Matroska,
+        _ => return Err(invalid("bad EBML document type")),
+    };
+
+    let (id, size) = ebml_element_header(&mut reader)?;
+    if id != SEGMENT {
+        return Err(invalid("missing Matroska segment"));
+    }
+    // A segment of unknown size, e.g. from a live stream, extends to the end of the file
+    let segment = match size {
+        Some(size) => reader.take(usize::try_from(size).map_err(|_| Truncated)?)?,
+        None => reader.rest(),
+    };
+
+    // The clusters of unknown size can't be skipped, and follow the information and the tracks
+    let mut reader = Reader::new(segment);
+    let (mut information, mut tracks) = (None, None);
+    while reader.remaining() > 0 {
+        let (id, size) = ebml_element_header(&mut reader)?;
+        let Some(size) = size else { break };
+        let contents = reader.take(usize::try_from(size).map_err(|_| Truncated)?)?;
+        match id {
+            SEGMENT_INFO if information.is_none() => information = Some(contents),
+            TRACKS if tracks.is_none() => tracks = Some(contents),
+            _ => {}
+        }
+    }
+
+    let information = ebml_elements(information.ok_or_else(|| invalid("missing segment information"))?)?;
+    let timestamp_scale = find_element(&information, TIMESTAMP_SCALE).map_or(Ok(1_000_000), ebml_uint)?;
+    let duration = match find_element(&information, DURATION) {
+        Some(contents) => {
+            let seconds = ebml_float(contents)? * timestamp_scale as f64 / 1e9;
+            Some(Duration::try_from_secs_f64(seconds).map_err(|_| invalid("bad duration"))?)
+        }
+        None => None,
+    };
+
+    let tracks = ebml_elements(tracks.ok_or_else(|| invalid("missing tracks"))?)?;
+    for (_, track) in tracks.iter().filter(|(id, _)| *id == TRACK_ENTRY) {
+        let track = ebml_elements(track)?;
+        if find_element(&track, TRACK_TYPE).map(ebml_uint).transpose()? != Some(VIDEO_TRACK) {
+            continue;
+        }
+        let codec = find_element(&track, CODEC_ID)
+            .and_then(|codec| std::str::from_utf8(codec).ok())
+            .map(|codec| codec.trim_end_matches('\0'))
+            .filter(|codec| !codec.is_empty() && codec.chars().all(|c| c.is_ascii_graphic()))
+            .ok_or_else(|| invalid("bad codec identifier"))?;
+        return Ok(VideoInfo { format, codec: codec.to_string(), duration });
+    }
+    Err(invalid("no video track"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{parse_video, VideoError, VideoFormat, VideoInfo};
+
+    const TEST_DIR: &str = "test_files";
+
+    fn iso_box(kind: &[u8], contents: &[u8]) -> Vec<u8> {
+        [&(contents.len() as u32 + 8).to_be_bytes(), kind, contents].concat()
+    }
+
+    /// MP4 file with a track of the given handler type and codec, and a duration of 2.5 seconds.
+    fn mp4(handler: &[u8], codec: &[u8]) -> Vec<u8> {
+        let header = [&[0u8; 12][..], &1000u32.to_be_bytes(), &2500u32.to_be_bytes(), &[0; 80]].concat();
+        let descriptions = [&[0u8, 0, 0, 0, 0, 0, 0, 1][..], &iso_box(codec, &[0; 8])].concat();
+        let samples = iso_box(b"stbl", &iso_box(b"stsd", &descriptions));
+        let media = [iso_box(b"hdlr", &[&[0u8; 8][..], handler, &[0; 13]].concat()), iso_box(b"minf", &samples)].concat();
+        let movie = [iso_box(b"mvhd", &header), iso_box(b"trak", &iso_box(b"mdia", &media))].concat();
+        [iso_box(b"ftyp", b"isom\x00\x00\x02\x00isommp41"), iso_box(b"moov", &movie), iso_box(b"mdat", b"frames")].concat()
+    }
+
+    fn ebml(id: &[u8], contents: &[u8]) -> Vec<u8> {
+        [id, &[0x80 | contents.len() as u8], contents].concat()
+    }
+
+    /// WebM file with a track of the given type and codec, and a duration of 1.5 seconds.
+    fn webm(track_type: u8, codec: &[u8]) -> Vec<u8> {
+        let header = ebml(b"\x1a\x45\xdf\xa3", &ebml(b"\x42\x82", b"webm"));
+        let information = ebml(b"\x15\x49\xa9\x66", &ebml(b"\x44\x89", &1500f32.to_be_bytes()));
+        let track = ebml(b"\xae", &[ebml(b"\x83", &[track_type]), ebml(b"\x86", codec)].concat());
+        let segment = [information, ebml(b"\x16\x54\xae\x6b", &track)].concat();
+        // Segment of unknown size
+        [header, b"\x18\x53\x80\x67\x01\xff\xff\xff\xff\xff\xff\xff".to_vec(), segment].concat()
+    }
+
+    #[test]
+    fn valid_videos() {
+        let video = std::fs::read(format!("{}/valid_video.mov", TEST_DIR)).unwrap();
+        let info = parse_video(&video, "video/quicktime").unwrap();
+        assert_eq!(info, VideoInfo { format: VideoFormat::QuickTime, codec: String::from("avc1"), duration: Some(Duration::from_millis(30571)) });
+
+        let video = std::fs::read(format!("{}/valid_video.avi", TEST_DIR)).unwrap();
+        let info = parse_video(&video, "video/x-msvideo").unwrap();
+        assert_eq!((info.format, info.codec.as_str()), (VideoFormat::Avi, "H264"));
+        assert_eq!(info.duration, Some(Duration::from_secs(901) / 30));
+
+        let info = parse_video(&mp4(b"vide", b"avc1"), "video/mp4").unwrap();
+        assert_eq!(info, VideoInfo { format: VideoFormat::Mp4, codec: String::from("avc1"), duration: Some(Duration::from_millis(2500)) });
+        let info = parse_video(&webm(1, b"V_VP9"), "video/webm").unwrap();
+        assert_eq!(info, VideoInfo { format: VideoFormat::WebM, codec: String::from("V_VP9"), duration: Some(Duration::from_millis(1500)) });
+    }
+
+    #[test]
+    fn truncated_videos() {
+        for (name, mime_type) in [("valid_video.mov", "video/quicktime"), ("valid_video.avi", "video/x-msvideo")] {
+            let video = std::fs::read(format!("{}/{}", TEST_DIR, name)).unwrap();
+            for length in [4, video.len() / 2, video.len() - 1] {
+                assert_eq!(parse_video(&video[..length], mime_type).unwrap_err().to_string(), "Invalid video: truncated file.");
+            }
+        }
+        let video = webm(1, b"V_VP9");
+        assert!(parse_video(&video[..video.len() - 1], "video/webm").is_err());
+    }
+
+    #[test]
+    fn crafted_videos() {
+        // magic numbers followed by garbage
+        let video = b"\x00\x00\x00\x18ftypmp42\x00\x00\x00\x00mp42isomgarbage garbage garbage";
+        assert!(parse_video(video, "video/mp4").is_err());
+        assert_eq!(parse_video(b"\x00\x00\x00\x14ftypmp42\x00\x00\x00\x00mp42", "video/mp4").unwrap_err().to_string(), "Invalid video: missing movie box.");
+        assert!(parse_video(b"RIFF\x04\x00\x00\x00AVI garbage", "video/x-msvideo").is_err());
+        assert!(parse_video(b"\x1a\x45\xdf\xa3garbage", "video/webm").is_err());
+
+        // no video track
+        assert_eq!(parse_video(&mp4(b"soun", b"mp4a"), "video/mp4").unwrap_err().to_string(), "Invalid video: no video track.");
+        assert_eq!(parse_video(&webm(2, b"A_OPUS"), "video/webm").unwrap_err().to_string(), "Invalid video: no video track.");
+        assert_eq!(parse_video(&mp4(b"vide", b"\x01\x02\x03\x04"), "video/mp4").unwrap_err().to_string(), "Invalid video: bad codec identifier.");
+
+        assert_eq!(parse_video(b"FLV\x01", "video/x-flv").unwrap_err(), VideoError::Unsupported(String::from("video/x-flv")));
+    }
+}
@@ -0,0 +1,88 @@
+use wasm_bindgen::prelude::*;
+
+use super::validate_file::rejection_reason;
+use super::{validate_file_bytes, validate_url, validate_url_with_whitelist, validate_uuid};
+
+// The exports of the validators to JavaScript, running the same rules in the browser before an
+// upload as on the server after it. The errors are thrown as JavaScript errors. The module is
+// built with `cargo rustc --release --target wasm32-unknown-unknown --features wasm
+// --crate-type cdylib`.
+
+/// Validate an url, accepting only the top level domains of the whitelist if given, see
+/// [`validate_url`].
+///
+/// ``` js
+/// validateUrl("https://heig-vd.ch", [".ch", ".com"]);
+/// ```
+#[wasm_bindgen(js_name = validateUrl)]
+pub fn wasm_validate_url(url: &str, top_level_whitelist: Option<Vec<String>>) -> Result<bool, JsError> {
+    Ok(match top_level_whitelist {
+        None => validate_url(url, None)?,
+        Some(whitelist) => validate_url_with_whitelist(url, whitelist)?,
+    })
+}
+
+/// Validate a version-5 uuid, see [`validate_uuid`].
+#[wasm_bindgen(js_name = validateUuid)]
+pub fn wasm_validate_uuid(uuid: &str) -> bool {
+    validate_uuid(uuid).is_ok()
+}
+
+/// Result of [`wasm_validate_file_bytes`].
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileCheck {
+    /// Whether the file is valid
+    pub valid: bool,
+    /// Kind of the file, e.g. `image`
+    pub kind: String,
+    /// Mime type detected from the contents, e.g. `image/png`
+    #[wasm_bindgen(js_name = mimeType)]
+    pub mime_type: String,
+    /// Extension matching the contents, e.g. `png`
+    pub extension: String,
+    /// Reason of the rejection of an invalid file
+    pub reason: Option<String>,
+}
+
+/// Validate a file from its contents, e.g. the `Uint8Array` of a selected file, see
+/// [`validate_file_bytes`].
+///
+/// ``` js
+/// const check = validateFileBytes(new Uint8Array(await file.arrayBuffer()), file.name, true);
+/// if (!check.valid) alert(check.reason);
+/// ```
+#[wasm_bindgen(js_name = validateFileBytes)]
+pub fn wasm_validate_file_bytes(contents: &[u8], declared_name: Option<String>, check_extension: bool) -> Result<FileCheck, JsError> {
+    let file = validate_file_bytes(contents, declared_name.as_deref(), check_extension)?;
+    Ok(FileCheck {
+        valid: file.is_valid(),
+        kind: format!("{:?}", file.kind).to_lowercase(),
+        mime_type: String::from(file.mime_type),
+        extension: String::from(file.extension),
+        reason: (!file.is_valid()).then(|| rejection_reason(&file)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wasm_validate_file_bytes, wasm_validate_url, wasm_validate_uuid, FileCheck};
+
+    // The errors are JavaScript errors, only the valid calls run outside of a browser
+
+    #[test]
+    fn exported_validators() {
+        assert!(wasm_validate_url("https://heig-vd.ch", Some(vec![String::from(".ch")])).unwrap());
+        assert!(!wasm_validate_url("https://docs.rs", Some(vec![String::from(".ch")])).unwrap());
+        assert!(wasm_validate_url("https://docs.rs", None).unwrap());
+        assert!(wasm_validate_uuid("c70dc454-1c7d-5c59-8fed-3a321e6a4a49"));
+        assert!(!wasm_validate_uuid("9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04"));
+
+        let image = std::fs::read("test_files/valid_image.png").unwrap();
+        let check = wasm_validate_file_bytes(&image, Some(String::from("image.png")), true).unwrap();
+        assert_eq!(check, FileCheck { valid: true, kind: String::from("image"), mime_type: String::from("image/png"), extension: String::from("png"), reason: None });
+        let check = wasm_validate_file_bytes(&image, Some(String::from("image.jpg")), true).unwrap();
+        assert!(!check.valid);
+        assert_eq!(check.reason.as_deref(), Some("The extension doesn't match the type image/png."));
+    }
+}
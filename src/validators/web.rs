@@ -0,0 +1,248 @@
+use std::ops::Deref;
+
+#[cfg(feature = "axum")]
+use super::ValidatedFile;
+use super::{ValidatedInput, ValidationReport};
+
+/// Path parameter of a web request validated by its type, e.g. `ValidatedPath<FileUuid>`,
+/// extracted by the `axum` and `actix` features. An invalid parameter is rejected with a
+/// `400 Bad Request` and the [`ValidationReport`] of the parameter, under the path `path`, as
+/// JSON.
+///
+/// # Examples
+/// ``` ignore
+/// async fn file(ValidatedPath(id): ValidatedPath<FileUuid>) -> String {
+///     format!("sec.upload/files/{}", id)
+/// }
+///
+/// let app = Router::new().route("/files/{id}", get(file));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedPath<T>(pub T);
+
+impl<T> ValidatedPath<T> {
+    /// The validated parameter.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedPath<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Validate a path parameter, with the report of its error.
+fn parse_path<T: ValidatedInput>(value: &str) -> Result<ValidatedPath<T>, ValidationReport> {
+    let mut report = ValidationReport::new();
+    let value = report.check("path", T::parse_input(value));
+    report.into_result()?;
+    Ok(ValidatedPath(value.expect("the report is empty")))
+}
+
+/// File of a multipart upload, validated from its contents and declared name, see
+/// [`crate::validate_file_bytes`].
+#[cfg(feature = "axum")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedFile {
+    /// Name of the field of the part
+    pub name: String,
+    /// Name of the file declared by the client, untrusted
+    pub file_name: String,
+    /// Contents of the file
+    pub contents: Vec<u8>,
+    /// Details of the validation of the file
+    pub file: ValidatedFile,
+}
+
+/// Multipart upload whose every file is validated, extracted by the `axum` feature. The parts
+/// without a file name are kept as text fields. An upload with an invalid file is rejected with
+/// a `400 Bad Request` and the [`ValidationReport`] of every invalid file, under the name of its
+/// field, as JSON.
+///
+/// # Examples
+/// ``` ignore
+/// async fn upload(upload: ValidatedMultipart) -> String {
+///     format!("{} files uploaded", upload.files.len())
+/// }
+/// ```
+#[cfg(feature = "axum")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidatedMultipart {
+    /// The valid files
+    pub files: Vec<UploadedFile>,
+    /// The text fields, by name
+    pub fields: Vec<(String, String)>,
+}
+
+#[cfg(feature = "axum")]
+mod axum_extractors {
+    use axum::extract::{FromRequest, FromRequestParts, Multipart, Path, Request};
+    use axum::http::request::Parts;
+    use axum::http::StatusCode;
+    use axum::response::{IntoResponse, Response};
+    use axum::Json;
+
+    use super::{parse_path, UploadedFile, ValidatedInput, ValidatedMultipart, ValidatedPath, ValidationReport};
+    use crate::validators::validated::validate_upload;
+
+    impl IntoResponse for ValidationReport {
+        fn into_response(self) -> Response {
+            (StatusCode::BAD_REQUEST, Json(self)).into_response()
+        }
+    }
+
+    impl<T: ValidatedInput, S: Send + Sync> FromRequestParts<S> for ValidatedPath<T> {
+        type Rejection = Response;
+
+        async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Response> {
+            let Path(value) = Path::<String>::from_request_parts(parts, state).await.map_err(IntoResponse::into_response)?;
+            parse_path(&value).map_err(IntoResponse::into_response)
+        }
+    }
+
+    impl<S: Send + Sync> FromRequest<S> for ValidatedMultipart {
+        type Rejection = Response;
+
+        async fn from_request(request: Request, state: &S) -> Result<Self, Response> {
+            let mut multipart = Multipart::from_request(request, state).await.map_err(IntoResponse::into_response)?;
+            let mut upload = ValidatedMultipart::default();
+            let mut report = ValidationReport::new();
+            while let Some(field) = multipart.next_field().await.map_err(IntoResponse::into_response)? {
+                let name = String::from(field.name().unwrap_or_default());
+                match field.file_name().map(String::from) {
+                    None => {
+                        let text = field.text().await.map_err(IntoResponse::into_response)?;
+                        upload.fields.push((name, text));
+                    }
+                    Some(file_name) => {
+                        let contents = field.bytes().await.map_err(IntoResponse::into_response)?.to_vec();
+                        if let Some(file) = report.check(&name, validate_upload(&contents, Some(&file_name))) {
+                            upload.files.push(UploadedFile { name, file_name, contents, file });
+                        }
+                    }
+                }
+            }
+            report.into_result().map_err(IntoResponse::into_response)?;
+            Ok(upload)
+        }
+    }
+}
+
+#[cfg(feature = "actix")]
+mod actix_extractors {
+    use std::future::Future;
+    use std::pin::Pin;
+
+    use actix_web::dev::Payload;
+    use actix_web::http::StatusCode;
+    use actix_web::{web, FromRequest, HttpRequest, HttpResponse, ResponseError};
+
+    use super::{parse_path, ValidatedInput, ValidatedPath, ValidationReport};
+
+    impl ResponseError for ValidationReport {
+        fn status_code(&self) -> StatusCode {
+            StatusCode::BAD_REQUEST
+        }
+
+        fn error_response(&self) -> HttpResponse {
+            HttpResponse::BadRequest().json(self)
+        }
+    }
+
+    impl<T: ValidatedInput + 'static> FromRequest for ValidatedPath<T> {
+        type Error = actix_web::Error;
+        type Future = Pin<Box<dyn Future<Output = Result<Self, actix_web::Error>>>>;
+
+        fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+            let path = web::Path::<String>::from_request(request, payload);
+            Box::pin(async move {
+                let value = path.await?.into_inner();
+                Ok(parse_path(&value)?)
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "axum")]
+    use std::future::Future;
+    #[cfg(feature = "axum")]
+    use std::pin::pin;
+    #[cfg(feature = "axum")]
+    use std::task::{Context, Poll, Waker};
+
+    /// Run a future which never waits, the requests being in memory.
+    #[cfg(feature = "axum")]
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        let mut context = Context::from_waker(Waker::noop());
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                return output;
+            }
+        }
+    }
+
+    #[cfg(feature = "axum")]
+    #[test]
+    fn axum_extractors() {
+        use axum::body::{to_bytes, Body};
+        use axum::http::{Request, StatusCode};
+        use axum::routing::{get, post};
+        use axum::Router;
+        use tower::ServiceExt;
+
+        use crate::{FileUuid, ValidatedMultipart, ValidatedPath};
+
+        let app = Router::new()
+            .route("/files/{id}", get(|ValidatedPath(id): ValidatedPath<FileUuid>| async move { id.to_string() }))
+            .route("/upload", post(|upload: ValidatedMultipart| async move { format!("{} {}", upload.files[0].file.mime_type, upload.fields[0].1) }));
+        let call = |request: Request<Body>| {
+            let response = block_on(app.clone().oneshot(request)).unwrap();
+            let status = response.status();
+            (status, String::from_utf8(block_on(to_bytes(response.into_body(), usize::MAX)).unwrap().to_vec()).unwrap())
+        };
+
+        let request = Request::get("/files/%7BC70DC454-1C7D-5C59-8FED-3A321E6A4A49%7D").body(Body::empty()).unwrap();
+        assert_eq!(call(request), (StatusCode::OK, String::from("c70dc454-1c7d-5c59-8fed-3a321e6a4a49")));
+        let request = Request::get("/files/c70dc454").body(Body::empty()).unwrap();
+        assert_eq!(call(request), (StatusCode::BAD_REQUEST, String::from(r#"{"errors":[{"path":"path","code":"invalid_uuid","message":"Invalid uuid."}]}"#)));
+
+        let multipart = |file_name: &str, contents: &[u8]| {
+            let mut body = b"--XYZ\r\nContent-Disposition: form-data; name=\"title\"\r\n\r\nholidays\r\n".to_vec();
+            body.extend_from_slice(format!("--XYZ\r\nContent-Disposition: form-data; name=\"image\"; filename=\"{}\"\r\n\r\n", file_name).as_bytes());
+            body.extend_from_slice(contents);
+            body.extend_from_slice(b"\r\n--XYZ--\r\n");
+            Request::post("/upload").header("content-type", "multipart/form-data; boundary=XYZ").body(Body::from(body)).unwrap()
+        };
+        let image = std::fs::read("test_files/valid_image.png").unwrap();
+        assert_eq!(call(multipart("image.png", &image)), (StatusCode::OK, String::from("image/png holidays")));
+        let (status, body) = call(multipart("image.jpg", &image));
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(body.starts_with(r#"{"errors":[{"path":"image","code":"rejected_file","#));
+    }
+
+    #[cfg(feature = "actix")]
+    #[actix_web::test]
+    async fn actix_extractors() {
+        use actix_web::http::StatusCode;
+        use actix_web::{test, web, App};
+
+        use crate::{FileUuid, ValidatedPath};
+
+        let app = test::init_service(App::new().route("/files/{id}", web::get().to(|ValidatedPath(id): ValidatedPath<FileUuid>| async move { id.to_string() }))).await;
+
+        let request = test::TestRequest::get().uri("/files/c70dc454-1c7d-5c59-8fed-3a321e6a4a49").to_request();
+        assert_eq!(test::call_and_read_body(&app, request).await, "c70dc454-1c7d-5c59-8fed-3a321e6a4a49");
+        let request = test::TestRequest::get().uri("/files/9f0b8c2e-4d1a-4b7e-9c3f-2a6d5e8f1b04").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = test::read_body(response).await;
+        assert_eq!(body, r#"{"errors":[{"path":"path","code":"uuid_version","message":"Invalid uuid version 4, expected 5."}]}"#);
+    }
+}